@@ -0,0 +1,87 @@
+#[macro_use]
+extern crate criterion;
+extern crate algo_hft;
+extern crate rsrl;
+
+use algo_hft::{
+    agents::{build_trader, training::trader::train_trader_once, Basis},
+    env::{Env, observation::DriftEstimator},
+};
+use criterion::Criterion;
+use rsrl::{
+    domains::Domain,
+    fa::{basis::Projector, Composable},
+    geometry::Vector,
+    policies::Policy,
+};
+
+fn env_step(c: &mut Criterion) {
+    c.bench_function("env_step", |b| {
+        let mut env = Env::default();
+
+        b.iter(|| {
+            if env.is_terminal() {
+                env = Env::default();
+            }
+
+            env.step([0.5, 0.5])
+        })
+    });
+}
+
+fn env_step_precomputed(c: &mut Criterion) {
+    c.bench_function("env_step_precomputed", |b| {
+        let mut env = Env::default().with_precomputed_draws(200);
+
+        b.iter(|| {
+            if env.is_terminal() {
+                env = Env::default().with_precomputed_draws(200);
+            }
+
+            env.step([0.5, 0.5])
+        })
+    });
+}
+
+fn embed_state(c: &mut Criterion) {
+    c.bench_function("embed_state", |b| {
+        // Bypass the memoised projection used by the agents:
+        let basis = Basis::from_space(3, Env::default().state_space()).with_constant();
+        let state = Vector::from_vec(vec![0.5, 2.0]);
+
+        b.iter(|| basis.project(&state))
+    });
+}
+
+fn embed_state_augmented(c: &mut Criterion) {
+    c.bench_function("embed_state_augmented", |b| {
+        // Time, inventory, drift estimate and volatility: 255 monomials.
+        let env = Env::default()
+            .with_drift_estimator(DriftEstimator::new(0.1, 5.0))
+            .with_volatility_observation(4.0);
+        let basis = Basis::from_space(3, env.state_space()).with_constant();
+        let state = Vector::from_vec(vec![0.5, 2.0, 0.3, 2.0]);
+
+        b.iter(|| basis.project(&state))
+    });
+}
+
+fn policy_sample(c: &mut Criterion) {
+    c.bench_function("policy_sample", |b| {
+        let mut trader = build_trader(Env::default().state_space(), 0.01, 0.000001).unwrap();
+        let state = Vector::from_vec(vec![0.5, 2.0]);
+
+        b.iter(|| trader.policy.sample(&state))
+    });
+}
+
+fn training_episode(c: &mut Criterion) {
+    c.bench_function("training_episode", |b| {
+        let mut trader = build_trader(Env::default().state_space(), 0.01, 0.000001).unwrap();
+
+        b.iter(|| train_trader_once(Env::default(), &mut trader))
+    });
+}
+
+criterion_group!(benches, env_step, env_step_precomputed, embed_state, embed_state_augmented, policy_sample, training_episode);
+criterion_main!(benches);
@@ -0,0 +1,35 @@
+#ifndef ALGO_HFT_H
+#define ALGO_HFT_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define ALGO_HFT_OK 0
+#define ALGO_HFT_NULL_POINTER -1
+#define ALGO_HFT_PANIC -2
+#define ALGO_HFT_BAD_STATE -3
+
+typedef struct algo_hft_trader algo_hft_trader;
+
+/* Load a serialised trader; returns NULL on failure. */
+algo_hft_trader *algo_hft_trader_load(const char *path);
+
+/* Write the greedy (ask, bid) offsets for `state` into `quotes[0..2]`;
+ * returns ALGO_HFT_BAD_STATE if `state_len` is not the trader's state
+ * dimension. */
+int algo_hft_trader_quotes(algo_hft_trader *trader,
+                           const double *state,
+                           size_t state_len,
+                           double *quotes);
+
+/* Release a trader returned by algo_hft_trader_load. */
+void algo_hft_trader_free(algo_hft_trader *trader);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -5,7 +5,7 @@ use crate::{
 };
 
 /// Fixed uniform basis projector.
-#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
 pub struct Constant {
     n_features: usize,
     value: f64,
@@ -67,6 +67,7 @@ impl<I: ?Sized> Projector<I> for Indices {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::ActivationT;
     use quickcheck::quickcheck;
 
     #[test]
@@ -103,7 +104,7 @@ mod tests {
             match Constant::new(length, value).project(&input) {
                 Features::Sparse(_) => false,
                 Features::Dense(activations) => {
-                    activations.len() == length && activations.into_iter().all(|&v| v == value)
+                    activations.len() == length && activations.into_iter().all(|&v| v == value as ActivationT)
                 },
             }
         }
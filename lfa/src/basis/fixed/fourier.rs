@@ -77,19 +77,17 @@ impl Projector<[f64]> for Fourier {
             .map(|(i, v)| (v - self.limits[i].0) / (self.limits[i].1 - self.limits[i].0))
             .collect::<Vec<f64>>();
 
-        Features::Dense(
-            self.coefficients
-                .iter()
-                .map(|cfs| {
-                    let cx = scaled_state
-                        .iter()
-                        .zip(cfs)
-                        .fold(0.0, |acc, (v, c)| acc + *c * v);
-
-                    ((PI * cx).cos() + 1.0) / 2.0
-                })
-                .collect(),
-        )
+        self.coefficients
+            .iter()
+            .map(|cfs| {
+                let cx = scaled_state
+                    .iter()
+                    .zip(cfs)
+                    .fold(0.0, |acc, (v, c)| acc + *c * v);
+
+                ((PI * cx).cos() + 1.0) / 2.0
+            })
+            .collect()
     }
 }
 
@@ -84,7 +84,7 @@ impl<I, K: Kernel<I>> Space for KernelProjector<I, K> {
 
 impl<I, K: Kernel<I>> Projector<I> for KernelProjector<I, K> {
     fn project(&self, input: &I) -> Features {
-        Features::Dense(self.prototypes.iter().map(|p| p.kernel(input)).collect())
+        self.prototypes.iter().map(|p| p.kernel(input)).collect()
     }
 }
 
@@ -41,7 +41,7 @@ mod cpfk;
 /// assert_eq!(p.project(&vec![0.75]), vec![0.5, 0.25].into());
 /// assert_eq!(p.project(&vec![1.00]), vec![1.0, 1.0].into());
 /// ```
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub struct Polynomial {
     pub order: u8,
     pub limits: Vec<(f64, f64)>,
@@ -92,25 +92,31 @@ impl Space for Polynomial {
 
 impl Projector<[f64]> for Polynomial {
     fn project(&self, input: &[f64]) -> Features {
-        let scaled_state = input
-            .iter()
-            .enumerate()
-            .map(|(i, v)| (v - self.limits[i].0) / (self.limits[i].1 - self.limits[i].0))
-            .map(|v| 2.0 * v - 1.0)
-            .collect::<Vec<f64>>();
+        // Tabulate the powers 0..=order of each scaled input once by repeated
+        // multiplication, so that each monomial is a product of lookups
+        // rather than one call to powi per dimension and feature.
+        let n_powers = self.order as usize + 1;
+        let mut powers = Vec::with_capacity(input.len() * n_powers);
+
+        for (v, &(lb, ub)) in input.iter().zip(self.limits.iter()) {
+            let x = 2.0 * ((v - lb) / (ub - lb)) - 1.0;
+            let mut p = 1.0;
+
+            for _ in 0..n_powers {
+                powers.push(p);
+                p *= x;
+            }
+        }
 
-        Features::Dense(
-            self.exponents
-                .iter()
-                .map(|exps| {
-                    scaled_state
-                        .iter()
-                        .zip(exps)
-                        .map(|(v, e)| v.powi(*e))
-                        .product()
-                })
-                .collect(),
-        )
+        self.exponents
+            .iter()
+            .map(|exps| {
+                exps.iter()
+                    .enumerate()
+                    .map(|(i, e)| powers[i * n_powers + *e as usize])
+                    .product::<f64>()
+            })
+            .collect()
     }
 }
 
@@ -199,13 +205,39 @@ impl Projector<[f64]> for Chebyshev {
             .map(|v| 2.0 * v - 1.0)
             .collect::<Vec<f64>>();
 
-        Features::Dense(
-            self.polynomials
-                .iter()
-                .map(|ps| scaled_state.iter().zip(ps).map(|(v, f)| f(*v)).product())
-                .collect(),
-        )
+        self.polynomials
+            .iter()
+            .map(|ps| scaled_state.iter().zip(ps).map(|(v, f)| f(*v)).product::<f64>())
+            .collect()
     }
 }
 
 impl_array_proxies!(Chebyshev; f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_matches_naive() {
+        let p = Polynomial::new(3, vec![(0.0, 1.0), (-5.0, 5.0), (-1.0, 1.0), (0.0, 2.0)]);
+        let input = vec![0.3, -2.5, 0.7, 1.9];
+
+        let scaled: Vec<f64> = input
+            .iter()
+            .zip(p.limits.iter())
+            .map(|(v, &(lb, ub))| 2.0 * ((v - lb) / (ub - lb)) - 1.0)
+            .collect();
+        let naive: Vec<f64> = p.exponents
+            .iter()
+            .map(|exps| scaled.iter().zip(exps).map(|(v, e)| v.powi(*e)).product())
+            .collect();
+
+        let features = p.project_expanded(&input);
+
+        assert_eq!(features.len(), naive.len());
+        for (f, n) in features.iter().zip(naive.iter()) {
+            assert!((f - n).abs() < 1e-12);
+        }
+    }
+}
@@ -70,6 +70,7 @@ impl<I: ?Sized, D: Distribution<f64>> Projector<I> for Random<D> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::ActivationT;
     use quickcheck::{quickcheck, TestResult};
 
     #[test]
@@ -136,7 +137,7 @@ mod tests {
                     Features::Sparse(_) => TestResult::failed(),
                     Features::Dense(activations) => TestResult::from_bool(
                         activations.len() == length
-                            && activations.into_iter().all(|&v| v >= lb && v < ub),
+                            && activations.into_iter().all(|&v| v >= lb as ActivationT && v < ub as ActivationT),
                     ),
                 }
             }
@@ -1,7 +1,7 @@
 use crate::{
     basis::Projector,
     core::*,
-    geometry::{Card, Space, Vector},
+    geometry::{Card, Space},
 };
 use itertools::Itertools;
 use std::{
@@ -128,7 +128,7 @@ impl<P: Space> IFDD<P> {
     }
 
     #[inline]
-    fn discover_dense(&mut self, phi: Vector<f64>, error: f64) -> Vec<CandidateFeature> {
+    fn discover_dense(&mut self, phi: DenseT, error: f64) -> Vec<CandidateFeature> {
         (0..phi.len())
             .filter(|&i| phi[i].abs() < 1e-7)
             .combinations(2)
@@ -1,7 +1,7 @@
 use crate::{
     composition::Composable,
-    core::{DenseT, Features},
-    geometry::Space,
+    core::Features,
+    geometry::{Space, Vector},
 };
 
 /// Trait for basis projectors.
@@ -39,7 +39,7 @@ pub trait Projector<I: ?Sized>: Space<Value = Features> {
     ///     vec![1.0, 1.0].into(),
     /// );
     /// ```
-    fn project_expanded(&self, input: &I) -> DenseT { self.project(input).expanded(self.dim()) }
+    fn project_expanded(&self, input: &I) -> Vector<f64> { self.project(input).expanded(self.dim()) }
 }
 
 // #[macro_export]
@@ -22,7 +22,7 @@ impl<T: Space> Space for Negate<T> {
 
 impl<I: ?Sized, P: Projector<I>> Projector<I> for Negate<P> {
     fn project(&self, input: &I) -> Features {
-        Features::Dense(-self.0.project_expanded(input))
+        Features::from(-self.0.project_expanded(input))
     }
 }
 
@@ -63,7 +63,7 @@ impl<I: ?Sized, P1: Projector<I>, P2: Projector<I>> Projector<I> for Sum<P1, P2>
                 let p1_activations = p1.expanded(self.0.dim());
                 let p2_activations = p2.expanded(self.1.dim());
 
-                Features::Dense(p1_activations + p2_activations)
+                Features::from(p1_activations + p2_activations)
             },
         }
     }
@@ -93,7 +93,7 @@ impl<I: ?Sized, P: Projector<I>> Projector<I> for Reciprocal<P> {
 
         match p {
             Features::Sparse(_) => p,
-            Features::Dense(activations) => Features::Dense(1.0 / activations),
+            Features::Dense(activations) => Features::Dense(activations.mapv(|a| 1.0 / a)),
         }
     }
 }
@@ -147,7 +147,7 @@ impl<I: ?Sized, P1: Projector<I>, P2: Projector<I>> Projector<I> for Product<P1,
                 let p1_activations = p1.expanded(self.p1.dim());
                 let p2_activations = p2.expanded(self.p2.dim());
 
-                Features::Dense(p1_activations * p2_activations)
+                Features::from(p1_activations * p2_activations)
             },
         }
     }
@@ -0,0 +1,203 @@
+use crate::{
+    basis::Projector,
+    core::Features,
+    geometry::{Card, Space, Vector},
+};
+use serde::{Deserialize, Deserializer};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::VecDeque,
+    sync::{atomic::{AtomicUsize, Ordering}, Mutex},
+};
+
+// Both states of a transition, for up to two agents acting in the same loop.
+const CAPACITY: usize = 4;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Projectors deserialised so far, with the identifiers of their caches.
+static INTERNED: Mutex<Vec<(Box<dyn Any + Send>, usize)>> = Mutex::new(Vec::new());
+
+struct Entry {
+    id: usize,
+    input: Vector<f64>,
+    output: Features,
+}
+
+thread_local! {
+    static CACHE: RefCell<VecDeque<Entry>> = RefCell::new(VecDeque::with_capacity(CAPACITY));
+}
+
+fn next_id() -> usize { NEXT_ID.fetch_add(1, Ordering::Relaxed) }
+
+// Copy `src` into `dst`, reusing the allocation of `dst` where possible.
+fn copy_features(dst: &mut Features, src: &Features) {
+    match (dst, src) {
+        (Features::Dense(ref mut d), &Features::Dense(ref s)) if d.len() == s.len() => d.assign(s),
+        (dst, src) => *dst = src.clone(),
+    }
+}
+
+/// Memoise the most recent outputs of a `Projector` instance.
+///
+/// Outputs are cached per thread and shared by all clones of an instance, so
+/// that several approximators built on one projector embed each input only
+/// once between them. Deserialised instances of equal projectors share their
+/// outputs in the same way. This is only valid for projectors whose output
+/// depends solely on the input; adaptive bases such as `IFDD` should not be
+/// wrapped.
+#[derive(Clone, Serialize, Debug)]
+#[serde(transparent)]
+pub struct Memoise<P> {
+    projector: P,
+
+    #[serde(skip)]
+    id: usize,
+}
+
+impl<P> Memoise<P> {
+    pub fn new(projector: P) -> Self {
+        Memoise {
+            projector: projector,
+            id: next_id(),
+        }
+    }
+
+    /// Return a reference to the underlying projector.
+    pub fn projector(&self) -> &P { &self.projector }
+}
+
+impl<P: PartialEq + Clone + Send + 'static> Memoise<P> {
+    // Wrap `projector`, sharing the cache of any equal projector interned
+    // before.
+    fn interned(projector: P) -> Self {
+        let mut interned = INTERNED.lock().unwrap();
+
+        let id = interned.iter()
+            .find(|&&(ref p, _)| p.downcast_ref::<P>().map_or(false, |p| *p == projector))
+            .map(|&(_, id)| id);
+
+        let id = id.unwrap_or_else(|| {
+            let id = next_id();
+
+            interned.push((Box::new(projector.clone()), id));
+
+            id
+        });
+
+        Memoise { projector, id, }
+    }
+}
+
+impl<'de, P> Deserialize<'de> for Memoise<P>
+where
+    P: Deserialize<'de> + PartialEq + Clone + Send + 'static,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        P::deserialize(deserializer).map(Memoise::interned)
+    }
+}
+
+impl<P: Space> Space for Memoise<P> {
+    type Value = Features;
+
+    fn dim(&self) -> usize { self.projector.dim() }
+
+    fn card(&self) -> Card { self.projector.card() }
+}
+
+impl<P: Projector<Vector<f64>>> Projector<Vector<f64>> for Memoise<P> {
+    fn project(&self, input: &Vector<f64>) -> Features {
+        let hit = CACHE.with(|cache| {
+            cache.borrow().iter()
+                .find(|e| e.id == self.id && e.input == *input)
+                .map(|e| e.output.clone())
+        });
+
+        if let Some(output) = hit {
+            return output;
+        }
+
+        // The cache is not borrowed while projecting, in case the projector
+        // memoises its own components:
+        let output = self.projector.project(input);
+
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+
+            if cache.len() < CAPACITY {
+                cache.push_back(Entry { id: self.id, input: input.clone(), output: output.clone(), });
+            } else {
+                // Recycle the buffers of the oldest entry:
+                let mut entry = cache.pop_front().unwrap();
+
+                entry.id = self.id;
+
+                if entry.input.len() == input.len() {
+                    entry.input.assign(input);
+                } else {
+                    entry.input = input.clone();
+                }
+
+                copy_features(&mut entry.output, &output);
+                cache.push_back(entry);
+            }
+        });
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basis::fixed::{Constant, Polynomial};
+
+    #[test]
+    fn test_memoise_equivalence() {
+        let p = Polynomial::new(3, vec![(0.0, 1.0), (-1.0, 1.0)]);
+        let m = Memoise::new(p.clone());
+
+        for input in &[[0.0, 0.0], [0.0, 0.0], [0.5, -0.25], [1.0, 1.0], [0.5, -0.25]] {
+            let input = Vector::from_vec(input.to_vec());
+
+            assert_eq!(m.project(&input), p.project(&input));
+        }
+    }
+
+    #[test]
+    fn test_memoise_clone_shares_cache() {
+        let m1 = Memoise::new(Constant::ones(2));
+        let m2 = m1.clone();
+        let m3 = Memoise::new(Constant::zeros(2));
+
+        assert_eq!(m1.id, m2.id);
+        assert_ne!(m1.id, m3.id);
+
+        let input = Vector::from_vec(vec![0.5]);
+
+        assert_eq!(m1.project(&input), Features::from(vec![1.0, 1.0]));
+        assert_eq!(m3.project(&input), Features::from(vec![0.0, 0.0]));
+        assert_eq!(m2.project(&input), Features::from(vec![1.0, 1.0]));
+    }
+
+    #[test]
+    fn test_memoise_interned() {
+        let p = Polynomial::new(2, vec![(0.0, 1.0)]);
+        let m1: Memoise<Polynomial> = Memoise::interned(p.clone());
+        let m2: Memoise<Polynomial> = Memoise::interned(p.clone());
+        let m3: Memoise<Polynomial> = Memoise::interned(Polynomial::new(3, vec![(0.0, 1.0)]));
+
+        assert_eq!(m1.id, m2.id);
+        assert_ne!(m1.id, m3.id);
+        assert_ne!(m1.id, Memoise::new(p).id);
+    }
+
+    #[test]
+    fn test_memoise_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<Memoise<Polynomial>>();
+    }
+}
@@ -11,6 +11,7 @@ import_all!(arithmetic);
 import_all!(scaling);
 import_all!(shifting);
 import_all!(normalisation);
+import_all!(memoisation);
 
 /// Trait for composition of any support LFA types.
 pub trait Composable: Sized {
@@ -64,6 +65,9 @@ pub trait Composable: Sized {
     /// Return the original `Projector` with all activations normalised in _L∞_.
     fn normalise_linf(self) -> LinfNormalise<Self> { LinfNormalise::new(self) }
 
+    /// Return the original `Projector` with its most recent outputs `Memoise`d.
+    fn memoise(self) -> Memoise<Self> { Memoise::new(self) }
+
     /// Return the a `Stack` of this `Projector` with a single constant feature term.
     fn with_constant(self) -> Stack<Self, Constant> {
         self.stack(Constant::ones(1))
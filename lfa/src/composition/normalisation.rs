@@ -31,7 +31,7 @@ impl<I: ?Sized, P: Projector<I>> Projector<I> for L1Normalise<P> {
         let phi = self.0.project_expanded(input);
         let z = l1(phi.as_slice().unwrap());
 
-        Features::Dense(phi / z)
+        Features::from(phi / z)
     }
 }
 
@@ -58,7 +58,7 @@ impl<I: ?Sized, P: Projector<I>> Projector<I> for L2Normalise<P> {
         let phi = self.0.project_expanded(input);
         let z = l2(phi.as_slice().unwrap());
 
-        Features::Dense(phi / z)
+        Features::from(phi / z)
     }
 }
 
@@ -85,7 +85,7 @@ impl<I: ?Sized, P: Projector<I>> Projector<I> for LpNormalise<P> {
         let phi = self.0.project_expanded(input);
         let z = lp(phi.as_slice().unwrap(), self.1);
 
-        Features::Dense(phi / z)
+        Features::from(phi / z)
     }
 }
 
@@ -112,7 +112,7 @@ impl<I: ?Sized, P: Projector<I>> Projector<I> for LinfNormalise<P> {
         let phi = self.0.project_expanded(input);
         let z = linf(phi.as_slice().unwrap());
 
-        Features::Dense(phi / z)
+        Features::from(phi / z)
     }
 }
 
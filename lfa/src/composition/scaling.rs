@@ -30,7 +30,7 @@ impl<P: Space> Space for Scale<P> {
 
 impl<I: ?Sized, P: Projector<I>> Projector<I> for Scale<P> {
     fn project(&self, input: &I) -> Features {
-        Features::Dense(self.scale * self.projector.project_expanded(input))
+        Features::from(self.scale * self.projector.project_expanded(input))
     }
 }
 
@@ -30,7 +30,7 @@ impl<P: Space> Space for Shift<P> {
 
 impl<I: ?Sized, P: Projector<I>> Projector<I> for Shift<P> {
     fn project(&self, input: &I) -> Features {
-        Features::Dense(self.offset + self.projector.project_expanded(input))
+        Features::from(self.offset + self.projector.project_expanded(input))
     }
 }
 
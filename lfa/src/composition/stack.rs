@@ -5,7 +5,7 @@ use crate::{
 };
 
 /// Stack the output of two `Projector` instances.
-#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
 pub struct Stack<P1, P2> {
     p1: P1,
     p2: P2,
@@ -13,6 +13,9 @@ pub struct Stack<P1, P2> {
 
 impl<P1, P2> Stack<P1, P2> {
     pub fn new(p1: P1, p2: P2) -> Self { Stack { p1, p2 } }
+
+    /// Return references to the two stacked projectors.
+    pub fn projectors(&self) -> (&P1, &P2) { (&self.p1, &self.p2) }
 }
 
 impl<P1: Space, P2: Space> Space for Stack<P1, P2> {
@@ -6,18 +6,25 @@ use crate::{
 /// An interface for types parameterised by a matrix of weights.
 pub trait Parameterised {
     /// Return a clone of the weights.
-    fn weights(&self) -> Matrix<f64> { self.weights_view().to_owned() }
+    fn weights(&self) -> Matrix<f64>;
 
+    /// Return the dimensions of the weight matrix.
+    fn weights_dim(&self) -> (usize, usize) {
+        self.weights().dim()
+    }
+}
+
+/// An interface for types whose weights are stored in a single matrix, and
+/// can hence be viewed in place.
+///
+/// Types with several separately stored weight matrices, such as policies
+/// with one approximator per parameter, only implement `Parameterised`.
+pub trait WeightsView: Parameterised {
     /// Return a read-only view of the weights.
     fn weights_view(&self) -> MatrixView<f64>;
 
     /// Return a mutable view of the weights.
     fn weights_view_mut(&mut self) -> MatrixViewMut<f64>;
-
-    /// Return the dimensions of the weight matrix.
-    fn weights_dim(&self) -> (usize, usize) {
-        self.weights_view().dim()
-    }
 }
 
 pub trait Embedding<I: ?Sized> {
@@ -8,6 +8,21 @@ pub type EvaluationResult<T> = Result<T, EvaluationError>;
 #[derive(Debug)]
 pub enum UpdateError {
     Failed,
+
+    /// The update (or the features it applies to) contained NaN or infinite
+    /// values, and was skipped to avoid corrupting the weights.
+    NonFinite,
 }
 
 pub type UpdateResult<T> = Result<T, UpdateError>;
+
+pub(crate) fn check_finite<'a, I>(values: I) -> UpdateResult<()>
+where
+    I: IntoIterator<Item = &'a f64>,
+{
+    if values.into_iter().all(|v| v.is_finite()) {
+        Ok(())
+    } else {
+        Err(UpdateError::NonFinite)
+    }
+}
@@ -104,6 +104,15 @@ impl Features {
         })
     }
 
+    /// Return true if none of the activations are NaN or infinite.
+    pub fn is_finite(&self) -> bool {
+        apply_to_features!(self => activations, {
+            activations.iter().all(|v| v.is_finite())
+        }; _indices, {
+            true
+        })
+    }
+
     /// Remove one feature entry from the features, if present.
     ///
     /// For the `Features::Dense` variant, the feature is set to zero, and for
@@ -153,7 +162,7 @@ impl Features {
     }
 
     pub(crate) fn dot_dense(activations: &DenseT, weights: &VectorView<f64>) -> f64 {
-        activations.dot(weights)
+        dot_dense(activations, weights)
     }
 
     pub(crate) fn dot_sparse(indices: &SparseT, weights: &VectorView<f64>) -> f64 {
@@ -190,7 +199,7 @@ impl Features {
             .collect()
     }
 
-    /// Expand the features and convert it into a raw, dense vector.
+    /// Expand the features and convert it into a raw, dense `f64` vector.
     ///
     /// ```
     /// use lfa::basis::Features;
@@ -200,15 +209,17 @@ impl Features {
     ///     vec![1.0, 1.0, 1.0, 0.0, 1.0].into()
     /// );
     /// ```
-    pub fn expanded(&self, dim: usize) -> DenseT {
+    pub fn expanded(&self, dim: usize) -> Vector<f64> {
         apply_to_features!(self => activations, {
+            let activations = widened(activations);
+
             if activations.len() != dim {
-                let mut activations = activations.to_vec();
+                let mut activations = activations.into_raw_vec();
                 activations.resize(dim, 0.0);
 
-                DenseT::from_vec(activations)
+                Vector::from_vec(activations)
             } else {
-                activations.to_owned()
+                activations
             }
         }; indices, {
             let mut phi = Vector::zeros((dim,));
@@ -243,7 +254,7 @@ impl Features {
                 let mut all_activations = f1.expanded(d1).to_vec();
                 all_activations.extend_from_slice(f2.expanded(d2).as_slice().unwrap());
 
-                Features::Dense(all_activations.into())
+                Features::from(Vector::from_vec(all_activations))
             },
         }
     }
@@ -304,9 +315,9 @@ impl Add<Features> for Features {
 }
 
 impl Index<usize> for Features {
-    type Output = f64;
+    type Output = ActivationT;
 
-    fn index(&self, idx: usize) -> &f64 {
+    fn index(&self, idx: usize) -> &ActivationT {
         apply_to_features!(self => activations, {
             activations.index(idx)
         }; indices, {
@@ -348,6 +359,23 @@ impl FromIterator<ActivationT> for Features {
     }
 }
 
+#[cfg(feature = "f32")]
+impl From<Vector<f64>> for Features {
+    fn from(activations: Vector<f64>) -> Features { DenseFeatures(narrow(&activations)) }
+}
+
+#[cfg(feature = "f32")]
+impl From<Vec<f64>> for Features {
+    fn from(activations: Vec<f64>) -> Features { Features::from(Vector::from_vec(activations)) }
+}
+
+#[cfg(feature = "f32")]
+impl FromIterator<f64> for Features {
+    fn from_iter<I: IntoIterator<Item = f64>>(iter: I) -> Self {
+        DenseFeatures(iter.into_iter().map(|a| a as f32).collect())
+    }
+}
+
 impl From<SparseT> for Features {
     fn from(indices: SparseT) -> Features { SparseFeatures(indices) }
 }
@@ -1,10 +1,77 @@
 use crate::geometry::Vector;
+use ndarray::{ArrayBase, Data, DataMut, Ix1};
 use std::collections::BTreeSet;
+#[cfg(feature = "f32")]
+use ndarray::Zip;
 
+/// Storage type of dense feature activations.
+///
+/// Enabling the `f32` feature halves the size of dense feature vectors. All
+/// weights and evaluator outputs remain in double precision: activations are
+/// read in single precision and accumulated in double precision, without
+/// converting the feature vector first.
+#[cfg(not(feature = "f32"))]
 pub type ActivationT = f64;
+#[cfg(feature = "f32")]
+pub type ActivationT = f32;
+
 pub type IndexT = usize;
 
 pub type IndexSet = BTreeSet<IndexT>;
 
 pub type DenseT = Vector<ActivationT>;
 pub type SparseT = IndexSet;
+
+/// Dot product of dense activations with a vector of weights.
+#[cfg(not(feature = "f32"))]
+#[inline]
+pub(crate) fn dot_dense<S>(activations: &DenseT, weights: &ArrayBase<S, Ix1>) -> f64
+where
+    S: Data<Elem = f64>,
+{
+    activations.dot(weights)
+}
+
+/// Dot product of dense activations with a vector of weights.
+#[cfg(feature = "f32")]
+#[inline]
+pub(crate) fn dot_dense<S>(activations: &DenseT, weights: &ArrayBase<S, Ix1>) -> f64
+where
+    S: Data<Elem = f64>,
+{
+    activations.iter().zip(weights.iter()).fold(0.0, |acc, (&a, &w)| acc + f64::from(a) * w)
+}
+
+/// Add `alpha` times the dense activations to a vector of weights.
+#[cfg(not(feature = "f32"))]
+#[inline]
+pub(crate) fn scaled_add_dense<S>(weights: &mut ArrayBase<S, Ix1>, alpha: f64, activations: &DenseT)
+where
+    S: DataMut<Elem = f64>,
+{
+    weights.scaled_add(alpha, activations)
+}
+
+/// Add `alpha` times the dense activations to a vector of weights.
+#[cfg(feature = "f32")]
+#[inline]
+pub(crate) fn scaled_add_dense<S>(weights: &mut ArrayBase<S, Ix1>, alpha: f64, activations: &DenseT)
+where
+    S: DataMut<Elem = f64>,
+{
+    Zip::from(weights).and(activations).apply(|w, &a| *w += alpha * f64::from(a))
+}
+
+/// Copy the dense activations into a new double precision vector.
+#[cfg(not(feature = "f32"))]
+#[inline]
+pub(crate) fn widened(activations: &DenseT) -> Vector<f64> { activations.to_owned() }
+
+/// Copy the dense activations into a new double precision vector.
+#[cfg(feature = "f32")]
+#[inline]
+pub(crate) fn widened(activations: &DenseT) -> Vector<f64> { activations.mapv(f64::from) }
+
+#[cfg(feature = "f32")]
+#[inline]
+pub(crate) fn narrow(activations: &Vector<f64>) -> DenseT { activations.mapv(|a| a as f32) }
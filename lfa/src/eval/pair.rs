@@ -21,6 +21,10 @@ impl PairFunction {
 
 impl Parameterised for PairFunction {
     fn weights(&self) -> Matrix<f64> { self.weights.clone() }
+    fn weights_dim(&self) -> (usize, usize) { self.weights.dim() }
+}
+
+impl WeightsView for PairFunction {
     fn weights_view(&self) -> MatrixView<f64> { self.weights.view() }
     fn weights_view_mut(&mut self) -> MatrixViewMut<f64> { self.weights.view_mut() }
 }
@@ -33,8 +37,8 @@ impl Approximator for PairFunction {
     fn evaluate(&self, features: &Features) -> EvaluationResult<Self::Output> {
         apply_to_features!(features => activations, {
             Ok([
-                self.weights.column(0).dot(activations),
-                self.weights.column(1).dot(activations),
+                dot_dense(activations, &self.weights.column(0)),
+                dot_dense(activations, &self.weights.column(1)),
             ])
         }; indices, {
             Ok(indices.iter().fold([0.0; 2], |acc, idx| [
@@ -57,6 +61,9 @@ impl Approximator for PairFunction {
     }
 
     fn update_grad(&mut self, grad: &Matrix<f64>, update: Self::Output) -> UpdateResult<()> {
+        check_finite(grad)?;
+        check_finite(&update)?;
+
         Ok({
             self.weights.column_mut(0).scaled_add(update[0], &grad.column(0));
             self.weights.column_mut(1).scaled_add(update[1], &grad.column(1));
@@ -64,10 +71,16 @@ impl Approximator for PairFunction {
     }
 
     fn update(&mut self, features: &Features, errors: Self::Output) -> UpdateResult<()> {
+        if !features.is_finite() {
+            return Err(UpdateError::NonFinite);
+        }
+
+        check_finite(&errors)?;
+
         apply_to_features!(features => activations, {
             Ok({
-                self.weights.column_mut(0).scaled_add(errors[0], activations);
-                self.weights.column_mut(1).scaled_add(errors[1], activations);
+                scaled_add_dense(&mut self.weights.column_mut(0), errors[0], activations);
+                scaled_add_dense(&mut self.weights.column_mut(1), errors[1], activations);
             })
         }; indices, {
             let z = indices.len() as f64;
@@ -21,6 +21,12 @@ impl ScalarFunction {
 }
 
 impl Parameterised for ScalarFunction {
+    fn weights(&self) -> Matrix<f64> { self.weights_view().to_owned() }
+
+    fn weights_dim(&self) -> (usize, usize) { (self.weights.len(), 1) }
+}
+
+impl WeightsView for ScalarFunction {
     fn weights_view(&self) -> MatrixView<f64> {
         let n_rows = self.weights.len();
 
@@ -41,7 +47,7 @@ impl Approximator for ScalarFunction {
 
     fn evaluate(&self, features: &Features) -> EvaluationResult<Self::Output> {
         apply_to_features!(features => activations, {
-            Ok(activations.dot(&self.weights))
+            Ok(Features::dot_dense(activations, &self.weights.view()))
         }; indices, {
             Ok(Features::dot_sparse(indices, &self.weights.view()))
         })
@@ -52,12 +58,21 @@ impl Approximator for ScalarFunction {
     }
 
     fn update_grad(&mut self, grad: &Matrix<f64>, update: Self::Output) -> UpdateResult<()> {
+        check_finite(grad)?;
+        check_finite(Some(&update))?;
+
         Ok({ self.weights.scaled_add(update, &grad.column(0)) })
     }
 
     fn update(&mut self, features: &Features, error: Self::Output) -> UpdateResult<()> {
+        if !features.is_finite() {
+            return Err(UpdateError::NonFinite);
+        }
+
+        check_finite(Some(&error))?;
+
         apply_to_features!(features => activations, {
-            Ok(self.weights.scaled_add(error, activations))
+            Ok(scaled_add_dense(&mut self.weights, error, activations))
         }; indices, {
             Ok({
                 let scaled_error = error / indices.len() as f64;
@@ -116,4 +131,16 @@ mod tests {
 
         assert!((out - 50.0).abs() < 1e-6);
     }
+
+    #[test]
+    fn test_non_finite_update_skipped() {
+        let projector = Fourier::new(3, vec![(0.0, 10.0)]).normalise_l2();
+        let mut evaluator = ScalarFunction::zeros(projector.dim());
+
+        let features = projector.project(&vec![5.0]);
+
+        assert!(evaluator.update(&features, ::std::f64::NAN).is_err());
+        assert!(evaluator.update(&vec![::std::f64::INFINITY, 0.0, 0.0].into(), 1.0).is_err());
+        assert!(evaluator.weights.iter().all(|w| *w == 0.0));
+    }
 }
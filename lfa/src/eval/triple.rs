@@ -21,6 +21,10 @@ impl TripleFunction {
 
 impl Parameterised for TripleFunction {
     fn weights(&self) -> Matrix<f64> { self.weights.clone() }
+    fn weights_dim(&self) -> (usize, usize) { self.weights.dim() }
+}
+
+impl WeightsView for TripleFunction {
     fn weights_view(&self) -> MatrixView<f64> { self.weights.view() }
     fn weights_view_mut(&mut self) -> MatrixViewMut<f64> { self.weights.view_mut() }
 }
@@ -33,9 +37,9 @@ impl Approximator for TripleFunction {
     fn evaluate(&self, features: &Features) -> EvaluationResult<Self::Output> {
         apply_to_features!(features => activations, {
             Ok([
-                self.weights.column(0).dot(activations),
-                self.weights.column(1).dot(activations),
-                self.weights.column(2).dot(activations),
+                dot_dense(activations, &self.weights.column(0)),
+                dot_dense(activations, &self.weights.column(1)),
+                dot_dense(activations, &self.weights.column(2)),
             ])
         }; indices, {
             Ok(indices.iter().fold([0.0; 3], |acc, idx| [
@@ -60,6 +64,9 @@ impl Approximator for TripleFunction {
     }
 
     fn update_grad(&mut self, grad: &Matrix<f64>, update: Self::Output) -> UpdateResult<()> {
+        check_finite(grad)?;
+        check_finite(&update)?;
+
         Ok({
             self.weights.column_mut(0).scaled_add(update[0], &grad.column(0));
             self.weights.column_mut(1).scaled_add(update[1], &grad.column(1));
@@ -68,11 +75,17 @@ impl Approximator for TripleFunction {
     }
 
     fn update(&mut self, features: &Features, errors: Self::Output) -> UpdateResult<()> {
+        if !features.is_finite() {
+            return Err(UpdateError::NonFinite);
+        }
+
+        check_finite(&errors)?;
+
         apply_to_features!(features => activations, {
             Ok({
-                self.weights.column_mut(0).scaled_add(errors[0], activations);
-                self.weights.column_mut(1).scaled_add(errors[1], activations);
-                self.weights.column_mut(2).scaled_add(errors[2], activations);
+                scaled_add_dense(&mut self.weights.column_mut(0), errors[0], activations);
+                scaled_add_dense(&mut self.weights.column_mut(1), errors[1], activations);
+                scaled_add_dense(&mut self.weights.column_mut(2), errors[2], activations);
             })
         }; indices, {
             let z = indices.len() as f64;
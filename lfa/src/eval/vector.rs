@@ -23,6 +23,10 @@ impl VectorFunction {
 
 impl Parameterised for VectorFunction {
     fn weights(&self) -> Matrix<f64> { self.weights.clone() }
+    fn weights_dim(&self) -> (usize, usize) { self.weights.dim() }
+}
+
+impl WeightsView for VectorFunction {
     fn weights_view(&self) -> MatrixView<f64> { self.weights.view() }
     fn weights_view_mut(&mut self) -> MatrixViewMut<f64> { self.weights.view_mut() }
 }
@@ -34,7 +38,7 @@ impl Approximator for VectorFunction {
 
     fn evaluate(&self, features: &Features) -> EvaluationResult<Self::Output> {
         apply_to_features!(features => activations, {
-            Ok(activations.dot(&self.weights))
+            Ok(self.weights.gencolumns().into_iter().map(|col| dot_dense(activations, &col)).collect())
         }; indices, {
             Ok(self.weights.gencolumns().into_iter().map(|col| {
                 Features::dot_sparse(indices, &col)
@@ -56,15 +60,24 @@ impl Approximator for VectorFunction {
     }
 
     fn update_grad(&mut self, grad: &Matrix<f64>, update: Self::Output) -> UpdateResult<()> {
+        check_finite(grad)?;
+        check_finite(&update)?;
+
         Ok(update.into_iter().enumerate().for_each(|(c, &e)| {
             self.weights.column_mut(c).scaled_add(e, &grad.column(c));
         }))
     }
 
     fn update(&mut self, features: &Features, errors: Self::Output) -> UpdateResult<()> {
+        if !features.is_finite() {
+            return Err(UpdateError::NonFinite);
+        }
+
+        check_finite(&errors)?;
+
         apply_to_features!(features => activations, {
             Ok(for (c, &e) in errors.into_iter().enumerate() {
-                self.weights.column_mut(c).scaled_add(e, activations);
+                scaled_add_dense(&mut self.weights.column_mut(c), e, activations);
             })
         }; indices, {
             Ok(for (c, &e) in errors.into_iter().enumerate() {
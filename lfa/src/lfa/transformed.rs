@@ -48,6 +48,10 @@ impl<P: Space, T> TransformedLFA<P, VectorFunction, T> {
 impl<P, E: Parameterised, T> Parameterised for TransformedLFA<P, E, T> {
     fn weights(&self) -> Matrix<f64> { self.evaluator.weights() }
 
+    fn weights_dim(&self) -> (usize, usize) { self.evaluator.weights_dim() }
+}
+
+impl<P, E: WeightsView, T> WeightsView for TransformedLFA<P, E, T> {
     fn weights_view(&self) -> MatrixView<f64> { self.evaluator.weights_view() }
 
     fn weights_view_mut(&mut self) -> MatrixViewMut<f64> { self.evaluator.weights_view_mut() }
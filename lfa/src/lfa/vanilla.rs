@@ -49,11 +49,13 @@ impl<P: Space> LFA<P, VectorFunction> {
 impl<P, E: Parameterised> Parameterised for LFA<P, E> {
     fn weights(&self) -> Matrix<f64> { self.evaluator.weights() }
 
+    fn weights_dim(&self) -> (usize, usize) { self.evaluator.weights_dim() }
+}
+
+impl<P, E: WeightsView> WeightsView for LFA<P, E> {
     fn weights_view(&self) -> MatrixView<f64> { self.evaluator.weights_view() }
 
     fn weights_view_mut(&mut self) -> MatrixViewMut<f64> { self.evaluator.weights_view_mut() }
-
-    fn weights_dim(&self) -> (usize, usize) { self.evaluator.weights_dim() }
 }
 
 impl<I: ?Sized, P, E> Embedding<I> for LFA<P, E>
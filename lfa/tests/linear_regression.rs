@@ -5,7 +5,7 @@ use rand::{distributions::Uniform, Rng, thread_rng};
 use self::lfa::{
     basis::fixed::Polynomial,
     composition::Composable,
-    core::{Parameterised, Approximator, Embedding},
+    core::{Parameterised, Approximator, Embedding, WeightsView},
     LFA,
 };
 
@@ -53,6 +53,13 @@ where
         self.target_policy.handle_terminal();
         self.behaviour_policy.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.target_policy.reseed(stream.seed(0));
+        self.behaviour_policy.reseed(stream.seed(1));
+    }
 }
 
 impl<S, C, PT, PB> OnlineLearner<S, PT::Action> for CACLA<C, PT, PB>
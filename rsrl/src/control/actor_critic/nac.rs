@@ -35,6 +35,10 @@ where
         self.critic.handle_terminal();
         self.policy.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        self.policy.reseed(seed);
+    }
 }
 
 impl<S, C, P> OnlineLearner<S, P::Action> for NAC<C, P>
@@ -34,6 +34,10 @@ where
         self.critic.handle_terminal();
         self.policy.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        self.policy.reseed(seed);
+    }
 }
 
 impl<C, P> QAC<C, P> {
@@ -1,20 +1,181 @@
 use crate::core::*;
 use crate::fa::Parameterised;
-use crate::domains::Transition;
-use crate::policies::{Policy, ParameterisedPolicy, DifferentiablePolicy};
-use std::{
-    marker::PhantomData,
-    ops::AddAssign,
-};
+use crate::domains::{Transition, Truncation};
+use crate::policies::{Policy, ParameterisedPolicy, DifferentiablePolicy, KLDivergence};
+use crate::prediction::td::TD;
+use std::marker::PhantomData;
+
+/// Critic and actor learning rates on two timescales.
+///
+/// The actor's rate is the critic's scaled by `ratio`, typically much less
+/// than one, and follows the same schedule so that both decay jointly while
+/// their ratio stays fixed.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct TwoTimescale {
+    pub critic: Parameter,
+    pub ratio: f64,
+}
+
+impl TwoTimescale {
+    pub fn new<T: Into<Parameter>>(critic: T, ratio: f64) -> TwoTimescale {
+        TwoTimescale { critic: critic.into(), ratio, }
+    }
+
+    pub fn critic(&self) -> Parameter { self.critic }
+
+    pub fn actor(&self) -> Parameter { self.critic.scaled(self.ratio) }
+}
+
+/// Rule by which an actor applies its policy updates.
+///
+/// `()` applies every update as it is, `TrustRegion` bounds the divergence of
+/// each one, and `Option` applies its rule only if there is one.
+pub trait PolicyUpdate<S, P>: Algorithm {
+    /// Apply `update` to `policy`, as computed at state `s`.
+    fn apply<F: FnOnce(&mut P)>(&mut self, policy: &mut P, s: &S, update: F);
+}
+
+impl<S, P> PolicyUpdate<S, P> for () {
+    fn apply<F: FnOnce(&mut P)>(&mut self, policy: &mut P, _: &S, update: F) { update(policy) }
+}
+
+impl<S, P, R: PolicyUpdate<S, P>> PolicyUpdate<S, P> for Option<R> {
+    fn apply<F: FnOnce(&mut P)>(&mut self, policy: &mut P, s: &S, update: F) {
+        match self {
+            Some(rule) => rule.apply(policy, s, update),
+            None => update(policy),
+        }
+    }
+}
+
+/// Bound on the KL divergence induced by a single policy update.
+///
+/// Updates whose divergence at the visited state exceeds `max_kl` are
+/// shrunk, assuming the divergence grows quadratically in the step size, so
+/// that they land on the boundary of the trust region.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct TrustRegion<P> {
+    pub max_kl: f64,
+
+    /// Number of updates that have been rescaled.
+    pub n_triggered: usize,
+
+    // Copy of the policy that updates are measured against, taken once per
+    // episode and brought up to date with the policy's weights before each
+    // update, rather than cloning the policy every time.
+    #[serde(skip)]
+    reference: Option<P>,
+}
+
+impl<P> TrustRegion<P> {
+    pub fn new(max_kl: f64) -> TrustRegion<P> {
+        TrustRegion { max_kl, n_triggered: 0, reference: None, }
+    }
+
+    /// Return the number of rescaled updates since the last call.
+    pub fn take_triggered(&mut self) -> usize {
+        ::std::mem::replace(&mut self.n_triggered, 0)
+    }
+}
+
+impl<P> Algorithm for TrustRegion<P> {
+    fn handle_terminal(&mut self) { self.reference = None; }
+}
+
+impl<S, P> PolicyUpdate<S, P> for TrustRegion<P>
+where
+    P: ParameterisedPolicy<S> + KLDivergence<S> + Clone,
+{
+    fn apply<F: FnOnce(&mut P)>(&mut self, policy: &mut P, s: &S, update: F) {
+        let weights = policy.weights();
+        let reference = self.reference.get_or_insert_with(|| policy.clone());
+
+        // The weights may also have changed outside of the learner, e.g. when
+        // set by a derivative-free search:
+        reference.update_raw(&weights - &reference.weights());
+
+        update(policy);
+
+        let kl = reference.kl_divergence(policy, s);
+
+        if kl > self.max_kl {
+            let step = policy.weights() - &weights;
+
+            policy.update_raw(step * ((self.max_kl / kl).sqrt() - 1.0));
+
+            self.n_triggered += 1;
+        }
+    }
+}
+
+/// Running normalisation of rewards by the standard deviation of the
+/// discounted return.
+///
+/// Keeps TD targets on a comparable scale when the magnitude of rewards varies
+/// widely, e.g. between small per-step terms and large terminal penalties.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct RewardScale {
+    ret: f64,
+
+    n: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl RewardScale {
+    const MIN_STDDEV: f64 = 1e-4;
+
+    pub fn new() -> RewardScale { RewardScale::default() }
+
+    /// Current estimate of the standard deviation of the discounted return.
+    pub fn stddev(&self) -> f64 {
+        if self.n < 2 {
+            1.0
+        } else {
+            (self.m2 / (self.n - 1) as f64).sqrt().max(Self::MIN_STDDEV)
+        }
+    }
+
+    /// Update the statistics with a reward and return its normalised value.
+    pub fn normalise(&mut self, reward: f64, gamma: f64, terminal: bool) -> f64 {
+        self.ret = gamma * self.ret + reward;
+
+        self.n += 1;
+
+        let delta = self.ret - self.mean;
+        self.mean += delta / self.n as f64;
+        self.m2 += delta * (self.ret - self.mean);
+
+        if terminal {
+            self.ret = 0.0;
+        }
+
+        reward / self.stddev()
+    }
+
+    fn transition<S: Clone, A: Clone>(&mut self, t: &Transition<S, A>, gamma: f64) -> Transition<S, A> {
+        let reward = self.normalise(t.reward, gamma, t.terminated());
+
+        t.clone().replace_reward(reward)
+    }
+}
 
 /// TD-error actor-critic.
+///
+/// Policy updates are applied through the rule `R`, by default as they are.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct TDAC<C, P> {
+pub struct TDAC<C, P, R = ()> {
     pub critic: C,
     pub policy: P,
 
     pub alpha: Parameter,
     pub gamma: Parameter,
+
+    pub truncation: Truncation,
+
+    pub trust_region: R,
+    pub reward_scale: Option<RewardScale>,
 }
 
 impl<C, P> TDAC<C, P> {
@@ -29,14 +190,61 @@ impl<C, P> TDAC<C, P> {
 
             alpha: alpha.into(),
             gamma: gamma.into(),
+
+            truncation: Truncation::default(),
+
+            trust_region: (),
+            reward_scale: None,
+        }
+    }
+
+    /// Bound the KL divergence of each policy update by `max_kl`, if any.
+    pub fn with_trust_region(self, max_kl: Option<f64>) -> TDAC<C, P, Option<TrustRegion<P>>> {
+        TDAC {
+            critic: self.critic,
+            policy: self.policy,
+
+            alpha: self.alpha,
+            gamma: self.gamma,
+
+            truncation: self.truncation,
+
+            trust_region: max_kl.map(TrustRegion::new),
+            reward_scale: self.reward_scale,
         }
     }
 }
 
-impl<C, P> Algorithm for TDAC<C, P>
+impl<C, P, R> TDAC<C, P, R> {
+    /// Set the treatment of episodes that end by truncation by the actor's
+    /// TD error; the critic is configured separately.
+    pub fn with_truncation(self, truncation: Truncation) -> Self {
+        TDAC { truncation, ..self }
+    }
+
+    /// Normalise rewards by a running estimate of the return's scale.
+    pub fn with_reward_normalisation(self) -> Self {
+        TDAC { reward_scale: Some(RewardScale::new()), ..self }
+    }
+}
+
+impl<V, P, R> TDAC<TD<V>, P, R> {
+    /// Set the critic and actor learning rates jointly.
+    pub fn with_timescales(mut self, lrs: TwoTimescale) -> Self {
+        self.critic.alpha = lrs.critic();
+        self.alpha = lrs.actor();
+        self
+    }
+
+    /// Current ratio of the actor's learning rate to the critic's.
+    pub fn timescale_ratio(&self) -> f64 { self.alpha.value() / self.critic.alpha.value() }
+}
+
+impl<C, P, R> Algorithm for TDAC<C, P, R>
 where
     C: Algorithm,
     P: Algorithm,
+    R: Algorithm,
 {
     fn handle_terminal(&mut self) {
         self.alpha = self.alpha.step();
@@ -44,30 +252,43 @@ where
 
         self.critic.handle_terminal();
         self.policy.handle_terminal();
+        self.trust_region.handle_terminal();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.policy.reseed(seed);
     }
 }
 
-impl<S, C, P> OnlineLearner<S, P::Action> for TDAC<C, P>
+impl<S: Clone, C, P, R> OnlineLearner<S, P::Action> for TDAC<C, P, R>
 where
     C: OnlineLearner<S, P::Action> + ValuePredictor<S>,
     P: ParameterisedPolicy<S>,
     P::Action: Clone,
+    R: PolicyUpdate<S, P>,
 {
     fn handle_transition(&mut self, t: &Transition<S, P::Action>) {
+        let gamma = self.gamma.value();
+        let normalised = self.reward_scale.as_mut().map(|rs| rs.transition(t, gamma));
+        let t = normalised.as_ref().unwrap_or(t);
+
         let s = t.from.state();
         let v = self.critic.predict_v(s);
-        let td_error = if t.terminated() {
-            t.reward - v
+        let td_error = if t.bootstraps(self.truncation) {
+            t.reward + self.gamma * t.discount * self.predict_v(t.to.state()) - v
         } else {
-            t.reward + self.gamma * self.predict_v(t.to.state()) - v
+            t.reward - v
         };
 
         self.critic.handle_transition(t);
-        self.policy.update(s, t.action.clone(), self.alpha * td_error);
+
+        let error = self.alpha * td_error;
+
+        self.trust_region.apply(&mut self.policy, s, |p| p.update(s, t.action.clone(), error));
     }
 }
 
-impl<S, C, P> ValuePredictor<S> for TDAC<C, P>
+impl<S, C, P, R> ValuePredictor<S> for TDAC<C, P, R>
 where
     C: ValuePredictor<S>,
 {
@@ -76,7 +297,7 @@ where
     }
 }
 
-impl<S, C, P> ActionValuePredictor<S, P::Action> for TDAC<C, P>
+impl<S, C, P, R> ActionValuePredictor<S, P::Action> for TDAC<C, P, R>
 where
     C: ActionValuePredictor<S, P::Action>,
     P: Policy<S>,
@@ -90,7 +311,7 @@ where
     }
 }
 
-impl<S, C, P> Controller<S, P::Action> for TDAC<C, P>
+impl<S, C, P, R> Controller<S, P::Action> for TDAC<C, P, R>
 where
     P: Policy<S>,
 {
@@ -104,16 +325,24 @@ where
 }
 
 /// TD-error actor-critic (with eligibility traces).
+///
+/// Policy updates are applied through the rule `R`, by default as they are.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct TDACLambda<C, P> {
+pub struct TDACLambda<C, P, R = ()> {
     pub critic: C,
     pub policy: P,
 
     trace: Matrix<f64>,
+    pub trace_type: TraceType,
 
     pub alpha: Parameter,
     pub gamma: Parameter,
     pub lambda: Parameter,
+
+    pub truncation: Truncation,
+
+    pub trust_region: R,
+    pub reward_scale: Option<RewardScale>,
 }
 
 impl<C, P: Parameterised> TDACLambda<C, P> {
@@ -130,57 +359,130 @@ impl<C, P: Parameterised> TDACLambda<C, P> {
             policy,
 
             trace,
+            trace_type: TraceType::Accumulating,
 
             alpha: alpha.into(),
             gamma: gamma.into(),
             lambda: lambda.into(),
+
+            truncation: Truncation::default(),
+
+            trust_region: (),
+            reward_scale: None,
         }
     }
+
+    /// Bound the KL divergence of each policy update by `max_kl`, if any.
+    pub fn with_trust_region(self, max_kl: Option<f64>) -> TDACLambda<C, P, Option<TrustRegion<P>>> {
+        TDACLambda {
+            critic: self.critic,
+            policy: self.policy,
+
+            trace: self.trace,
+            trace_type: self.trace_type,
+
+            alpha: self.alpha,
+            gamma: self.gamma,
+            lambda: self.lambda,
+
+            truncation: self.truncation,
+
+            trust_region: max_kl.map(TrustRegion::new),
+            reward_scale: self.reward_scale,
+        }
+    }
+}
+
+impl<C, P, R> TDACLambda<C, P, R> {
+    /// Use traces of the given type over the policy's gradients; dutch traces
+    /// are applied at the actor's learning rate.
+    pub fn with_trace_type(self, trace_type: TraceType) -> Self {
+        TDACLambda { trace_type, ..self }
+    }
+
+    /// Set the treatment of episodes that end by truncation by the actor's
+    /// TD error; the critic is configured separately.
+    pub fn with_truncation(self, truncation: Truncation) -> Self {
+        TDACLambda { truncation, ..self }
+    }
+
+    /// Normalise rewards by a running estimate of the return's scale.
+    pub fn with_reward_normalisation(self) -> Self {
+        TDACLambda { reward_scale: Some(RewardScale::new()), ..self }
+    }
+}
+
+impl<V, P, R> TDACLambda<TD<V>, P, R> {
+    /// Set the critic and actor learning rates jointly.
+    pub fn with_timescales(mut self, lrs: TwoTimescale) -> Self {
+        self.critic.alpha = lrs.critic();
+        self.alpha = lrs.actor();
+        self
+    }
+
+    /// Current ratio of the actor's learning rate to the critic's.
+    pub fn timescale_ratio(&self) -> f64 { self.alpha.value() / self.critic.alpha.value() }
 }
 
-impl<C, P> Algorithm for TDACLambda<C, P>
+impl<C, P, R> Algorithm for TDACLambda<C, P, R>
 where
     C: Algorithm,
     P: Algorithm,
+    R: Algorithm,
 {
     fn handle_terminal(&mut self) {
         self.alpha = self.alpha.step();
         self.gamma = self.gamma.step();
-        self.lambda = self.gamma.step();
+        self.lambda = self.lambda.step();
 
         self.trace.fill(0.0);
 
         self.critic.handle_terminal();
         self.policy.handle_terminal();
+        self.trust_region.handle_terminal();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.policy.reseed(seed);
     }
 }
 
-impl<S, C, P> OnlineLearner<S, P::Action> for TDACLambda<C, P>
+impl<S: Clone, C, P, R> OnlineLearner<S, P::Action> for TDACLambda<C, P, R>
 where
     C: OnlineLearner<S, P::Action> + ValuePredictor<S>,
     P: ParameterisedPolicy<S> + DifferentiablePolicy<S>,
     P::Action: Clone,
+    R: PolicyUpdate<S, P>,
 {
     fn handle_transition(&mut self, t: &Transition<S, P::Action>) {
+        let gamma = self.gamma.value();
+        let normalised = self.reward_scale.as_mut().map(|rs| rs.transition(t, gamma));
+        let t = normalised.as_ref().unwrap_or(t);
+
         let s = t.from.state();
         let v = self.critic.predict_v(s);
-        let td_error = if t.terminated() {
-            t.reward - v
+        let td_error = if t.bootstraps(self.truncation) {
+            t.reward + self.gamma * t.discount * self.predict_v(t.to.state()) - v
         } else {
-            t.reward + self.gamma * self.predict_v(t.to.state()) - v
+            t.reward - v
         };
 
         let gl_policy = self.policy.grad_log(s, t.action.clone());
 
         self.trace *= self.gamma * self.lambda;
-        self.trace.add_assign(&gl_policy);
+        self.trace_type.apply(&mut self.trace, &gl_policy, self.alpha.value());
 
         self.critic.handle_transition(t);
-        self.policy.update_raw(self.trace.clone() * (self.alpha.value() * td_error));
+
+        let update = self.trace.clone() * (self.alpha.value() * td_error);
+
+        self.trace *= t.discount;
+
+        self.trust_region.apply(&mut self.policy, s, |p| p.update_raw(update));
     }
 }
 
-impl<S, C, P> ValuePredictor<S> for TDACLambda<C, P>
+impl<S, C, P, R> ValuePredictor<S> for TDACLambda<C, P, R>
 where
     C: ValuePredictor<S>,
 {
@@ -189,7 +491,7 @@ where
     }
 }
 
-impl<S, C, P> ActionValuePredictor<S, P::Action> for TDACLambda<C, P>
+impl<S, C, P, R> ActionValuePredictor<S, P::Action> for TDACLambda<C, P, R>
 where
     C: ActionValuePredictor<S, P::Action>,
     P: Policy<S>,
@@ -203,7 +505,7 @@ where
     }
 }
 
-impl<S, C, P> Controller<S, P::Action> for TDACLambda<C, P>
+impl<S, C, P, R> Controller<S, P::Action> for TDACLambda<C, P, R>
 where
     P: Policy<S>,
 {
@@ -215,3 +517,80 @@ where
         self.policy.sample(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::{Algorithm, OnlineLearner, Parameter},
+        domains::{Observation, Transition},
+        fa::{Composable, Parameterised, LFA, basis::fixed::Polynomial},
+        policies::{KLDivergence, PreferenceSoftmax},
+        prediction::td::TD,
+    };
+    use super::{TDAC, TDACLambda};
+
+    macro_rules! policy {
+        () => {
+            PreferenceSoftmax::standard(LFA::vector(Polynomial::new(1, vec![(0.0, 1.0)]).with_constant(), 3))
+        };
+    }
+
+    fn transition(reward: f64) -> Transition<Vec<f64>, usize> {
+        Transition {
+            from: Observation::Full(vec![0.5]),
+            action: 1,
+            reward,
+            to: Observation::Full(vec![0.5]),
+            discount: 1.0,
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn test_trust_region() {
+        let critic = TD::new(LFA::scalar(Polynomial::new(1, vec![(0.0, 1.0)]).with_constant()), 0.1, 0.9);
+        let initial = policy!();
+
+        let mut unconstrained = TDAC::new(critic.clone(), initial.clone(), 1.0, 0.9);
+        let mut constrained = TDAC::new(critic, initial.clone(), 1.0, 0.9).with_trust_region(Some(1e-3));
+
+        unconstrained.handle_transition(&transition(1.0));
+        constrained.handle_transition(&transition(1.0));
+
+        let kl = initial.kl_divergence(&unconstrained.policy, &vec![0.5]);
+        let scale = (1e-3 / kl).sqrt();
+
+        assert!(kl > 1e-3);
+        assert_eq!(constrained.trust_region.as_mut().unwrap().take_triggered(), 1);
+
+        let step = unconstrained.policy.weights() - initial.weights();
+        let constrained_step = constrained.policy.weights() - initial.weights();
+
+        assert!(step.iter().zip(constrained_step.iter()).all(|(s, c)| (scale * s - c).abs() < 1e-10));
+    }
+
+    #[test]
+    fn test_trust_region_inactive() {
+        let critic = TD::new(LFA::scalar(Polynomial::new(1, vec![(0.0, 1.0)]).with_constant()), 0.1, 0.9);
+        let mut agent = TDAC::new(critic, policy!(), 1e-3, 0.9).with_trust_region(Some(1.0));
+
+        agent.handle_transition(&transition(1.0));
+        agent.handle_terminal();
+        agent.handle_transition(&transition(1.0));
+
+        assert_eq!(agent.trust_region.as_mut().unwrap().take_triggered(), 0);
+    }
+
+    #[test]
+    fn test_lambda_schedule() {
+        let mut lambda = Parameter::exponential(0.9, 0.1, 0.5);
+        let mut agent = TDACLambda::new((), policy!(), 0.1, 0.99, lambda);
+
+        for _ in 0..5 {
+            lambda = lambda.step();
+            agent.handle_terminal();
+
+            assert_eq!(agent.lambda.value(), lambda.value());
+        }
+    }
+}
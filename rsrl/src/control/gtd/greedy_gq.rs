@@ -2,17 +2,20 @@ use crate::core::*;
 use crate::domains::Transition;
 use crate::fa::*;
 use crate::geometry::{MatrixView, MatrixViewMut};
-use crate::policies::{Greedy, Policy, FinitePolicy};
+use crate::policies::{Greedy, LinkedPolicy, Policy, FinitePolicy};
+use serde::{Deserialize, Deserializer};
 
 /// Greedy GQ control algorithm.
 ///
 /// Maei, Hamid R., et al. "Toward off-policy learning control with function
 /// approximation." Proceedings of the 27th International Conference on Machine
 /// Learning (ICML-10). 2010.
+#[derive(Serialize)]
 pub struct GreedyGQ<Q, W, PB> {
     pub fa_q: Q,
     pub fa_w: W,
 
+    #[serde(skip_serializing)]
     pub target_policy: Greedy<Q>,
     pub behaviour_policy: PB,
 
@@ -51,12 +54,47 @@ impl<Q, W, PB> GreedyGQ<Shared<Q>, W, PB> {
     }
 }
 
-impl<Q, W, PB> Algorithm for GreedyGQ<Q, W, PB> {
+/// The greedy target policy is rebuilt from, and the behaviour policy linked
+/// back to, the deserialised Q-function, so that both follow its updates.
+impl<'de, Q, W, PB> Deserialize<'de> for GreedyGQ<Shared<Q>, W, PB>
+where
+    Q: Clone + Deserialize<'de>,
+    W: Deserialize<'de>,
+    PB: LinkedPolicy<Q> + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "GreedyGQ")]
+        struct Fields<Q, W, PB> {
+            fa_q: Q,
+            fa_w: W,
+            behaviour_policy: PB,
+            alpha: Parameter,
+            beta: Parameter,
+            gamma: Parameter,
+        }
+
+        let mut f = Fields::<Q, W, PB>::deserialize(deserializer)?;
+
+        f.behaviour_policy.link(f.fa_q.clone());
+
+        Ok(GreedyGQ::new(f.fa_q, f.fa_w, f.behaviour_policy, f.alpha, f.beta, f.gamma))
+    }
+}
+
+impl<Q, W, PB: Algorithm> Algorithm for GreedyGQ<Q, W, PB> {
     fn handle_terminal(&mut self) {
         self.alpha = self.alpha.step();
         self.beta = self.beta.step();
         self.gamma = self.gamma.step();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.behaviour_policy.reseed(stream.seed(0));
+        self.target_policy.reseed(stream.seed(1));
+    }
 }
 
 impl<S, Q, W, PB> OnlineLearner<S, PB::Action> for GreedyGQ<Q, W, PB>
@@ -101,7 +139,7 @@ where
                 self.alpha * self.beta * (residual - estimate)
             ).ok();
             self.fa_q.update_index(
-                &Features::Dense(update_q),
+                &Features::from(update_q),
                 t.action,
                 self.alpha.value()
             ).ok();
@@ -147,16 +185,4 @@ where
     }
 }
 
-impl<Q: Parameterised, W, PB> Parameterised for GreedyGQ<Q, W, PB> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa_q.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa_q.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa_q.weights_view_mut()
-    }
-}
+impl_parameterised!(GreedyGQ<Q, W, PB> => fa_q: Q);
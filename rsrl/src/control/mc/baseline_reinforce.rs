@@ -42,6 +42,10 @@ where
         self.policy.handle_terminal();
         self.baseline.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        self.policy.reseed(seed);
+    }
 }
 
 impl<S, B, P> BatchLearner<S, P::Action> for BaselineREINFORCE<B, P>
@@ -73,16 +77,4 @@ impl<S, B, P: ParameterisedPolicy<S>> Controller<S, P::Action> for BaselineREINF
     fn sample_behaviour(&mut self, s: &S) -> P::Action { self.policy.sample(s) }
 }
 
-impl<B, P: Parameterised> Parameterised for BaselineREINFORCE<B, P> {
-    fn weights(&self) -> Matrix<f64> {
-        self.policy.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.policy.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.policy.weights_view_mut()
-    }
-}
+impl_parameterised!(BaselineREINFORCE<B, P> => policy: P);
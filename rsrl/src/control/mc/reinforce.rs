@@ -35,6 +35,10 @@ impl<P: Algorithm> Algorithm for REINFORCE<P> {
 
         self.policy.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        self.policy.reseed(seed);
+    }
 }
 
 impl<S, P> BatchLearner<S, P::Action> for REINFORCE<P>
@@ -64,16 +68,4 @@ impl<S, P: ParameterisedPolicy<S>> Controller<S, P::Action> for REINFORCE<P> {
     fn sample_behaviour(&mut self, s: &S) -> P::Action { self.policy.sample(s) }
 }
 
-impl<P: Parameterised> Parameterised for REINFORCE<P> {
-    fn weights(&self) -> Matrix<f64> {
-        self.policy.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.policy.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.policy.weights_view_mut()
-    }
-}
+impl_parameterised!(REINFORCE<P> => policy: P);
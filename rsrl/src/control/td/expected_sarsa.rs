@@ -20,6 +20,8 @@ pub struct ExpectedSARSA<Q, P> {
 
     pub alpha: Parameter,
     pub gamma: Parameter,
+
+    pub loss: Loss,
 }
 
 impl<Q, P> ExpectedSARSA<Q, P> {
@@ -34,8 +36,15 @@ impl<Q, P> ExpectedSARSA<Q, P> {
 
             alpha: alpha.into(),
             gamma: gamma.into(),
+
+            loss: Loss::default(),
         }
     }
+
+    /// Set the loss minimised by the updates.
+    pub fn with_loss(self, loss: Loss) -> Self {
+        ExpectedSARSA { loss, ..self }
+    }
 }
 
 impl<Q, P: Algorithm> Algorithm for ExpectedSARSA<Q, P> {
@@ -45,6 +54,10 @@ impl<Q, P: Algorithm> Algorithm for ExpectedSARSA<Q, P> {
 
         self.policy.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        self.policy.reseed(seed);
+    }
 }
 
 impl<S, Q, P> OnlineLearner<S, P::Action> for ExpectedSARSA<Q, P>
@@ -61,12 +74,12 @@ where
             let ns = t.to.state();
             let exp_nv = self.predict_v(ns);
 
-            t.reward + self.gamma * exp_nv - qsa
+            t.reward + self.gamma * t.discount * exp_nv - qsa
         };
 
         self.q_func.update_index(
             &self.q_func.embed(s),
-            t.action, self.alpha * residual
+            t.action, self.alpha * self.loss.gradient(residual)
         ).ok();
     }
 }
@@ -101,16 +114,4 @@ where
     }
 }
 
-impl<Q: Parameterised, P> Parameterised for ExpectedSARSA<Q, P> {
-    fn weights(&self) -> Matrix<f64> {
-        self.q_func.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.q_func.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.q_func.weights_view_mut()
-    }
-}
+impl_parameterised!(ExpectedSARSA<Q, P> => q_func: Q);
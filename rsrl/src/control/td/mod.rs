@@ -1,6 +1,7 @@
 // Off-policy:
 import_all!(q_learning);
 import_all!(q_lambda);
+import_all!(peng_q_lambda);
 import_all!(q_sigma);
 import_all!(pal);
 
@@ -2,17 +2,20 @@ use crate::core::*;
 use crate::domains::Transition;
 use crate::fa::{Parameterised, QFunction};
 use crate::geometry::{MatrixView, MatrixViewMut};
-use crate::policies::{Greedy, Policy};
+use crate::policies::{Greedy, LinkedPolicy, Policy};
+use serde::{Deserialize, Deserializer};
 
 /// Persistent Advantage Learning
 ///
 /// # References
 /// - Bellemare, Marc G., et al. "Increasing the Action Gap: New Operators for
 /// Reinforcement Learning." AAAI. 2016.
+#[derive(Serialize)]
 pub struct PAL<Q, P> {
     pub q_func: Q,
 
     pub policy: P,
+    #[serde(skip_serializing)]
     pub target: Greedy<Q>,
 
     pub alpha: Parameter,
@@ -39,6 +42,31 @@ impl<Q, P> PAL<Shared<Q>, P> {
     }
 }
 
+/// The greedy target policy is rebuilt from, and the behaviour policy linked
+/// back to, the deserialised Q-function, so that both follow its updates.
+impl<'de, Q, P> Deserialize<'de> for PAL<Shared<Q>, P>
+where
+    Q: Clone + Deserialize<'de>,
+    P: LinkedPolicy<Q> + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "PAL")]
+        struct Fields<Q, P> {
+            q_func: Q,
+            policy: P,
+            alpha: Parameter,
+            gamma: Parameter,
+        }
+
+        let mut f = Fields::<Q, P>::deserialize(deserializer)?;
+
+        f.policy.link(f.q_func.clone());
+
+        Ok(PAL::new(f.q_func, f.policy, f.alpha, f.gamma))
+    }
+}
+
 impl<Q, P: Algorithm> Algorithm for PAL<Q, P> {
     fn handle_terminal(&mut self) {
         self.alpha = self.alpha.step();
@@ -47,6 +75,13 @@ impl<Q, P: Algorithm> Algorithm for PAL<Q, P> {
         self.policy.handle_terminal();
         self.target.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.policy.reseed(stream.seed(0));
+        self.target.reseed(stream.seed(1));
+    }
 }
 
 impl<S, Q, P> OnlineLearner<S, P::Action> for PAL<Q, P>
@@ -68,7 +103,7 @@ where
             let a_star = self.sample_target(s);
             let na_star = self.sample_target(ns);
 
-            let td_error = t.reward + self.gamma * nqs[a_star] - qs[t.action];
+            let td_error = t.reward + self.gamma * t.discount * nqs[a_star] - qs[t.action];
             let al_error = td_error - self.alpha * (qs[a_star] - qs[t.action]);
 
             al_error.max(td_error - self.alpha * (nqs[na_star] - nqs[t.action]))
@@ -114,16 +149,4 @@ where
     }
 }
 
-impl<Q: Parameterised, P> Parameterised for PAL<Q, P> {
-    fn weights(&self) -> Matrix<f64> {
-        self.q_func.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.q_func.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.q_func.weights_view_mut()
-    }
-}
+impl_parameterised!(PAL<Q, P> => q_func: Q);
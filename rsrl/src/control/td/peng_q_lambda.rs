@@ -0,0 +1,168 @@
+use crate::core::*;
+use crate::domains::Transition;
+use crate::fa::{Approximator, Parameterised, Features, QFunction};
+use crate::geometry::{MatrixView, MatrixViewMut};
+use crate::policies::{Greedy, Policy};
+
+/// Peng's Q-learning with eligibility traces.
+///
+/// Unlike Watkins' variant (`QLambda`), traces are never cut after
+/// exploratory actions: earlier state-action pairs are updated towards the
+/// greedy value of each successive state, and only the latest pair uses the
+/// one-step Q-learning error. Each action has its own trace over the
+/// features, initialised from the given template.
+///
+/// # References
+/// - Peng, J., Williams, R. J. (1996). Incremental multi-step Q-learning.
+/// Machine Learning, 22:283–290.
+pub struct PengQLambda<F, P> {
+    pub fa_theta: F,
+
+    pub policy: P,
+    pub target: Greedy<F>,
+
+    pub alpha: Parameter,
+    pub gamma: Parameter,
+
+    template: Trace,
+    traces: Vec<Trace>,
+}
+
+impl<F, P> PengQLambda<Shared<F>, P> {
+    pub fn new<T1, T2>(
+        fa_theta: F,
+        policy: P,
+        trace: Trace,
+        alpha: T1,
+        gamma: T2,
+    ) -> Self
+    where
+        T1: Into<Parameter>,
+        T2: Into<Parameter>,
+    {
+        let fa_theta = make_shared(fa_theta);
+
+        PengQLambda {
+            fa_theta: fa_theta.clone(),
+
+            policy,
+            target: Greedy::new(fa_theta),
+
+            alpha: alpha.into(),
+            gamma: gamma.into(),
+
+            template: trace,
+            traces: vec![],
+        }
+    }
+}
+
+impl<F, P: Algorithm> Algorithm for PengQLambda<F, P> {
+    fn handle_terminal(&mut self) {
+        self.alpha = self.alpha.step();
+        self.gamma = self.gamma.step();
+
+        self.policy.handle_terminal();
+        self.target.handle_terminal();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.policy.reseed(stream.seed(0));
+        self.target.reseed(stream.seed(1));
+    }
+}
+
+fn max(values: &Vector<f64>) -> f64 {
+    values.iter().cloned().fold(::std::f64::NEG_INFINITY, f64::max)
+}
+
+impl<S, F, P> OnlineLearner<S, P::Action> for PengQLambda<F, P>
+where
+    F: QFunction<S>,
+    P: Policy<S, Action = usize>,
+{
+    fn handle_transition(&mut self, t: &Transition<S, usize>) {
+        if self.traces.len() != self.fa_theta.n_outputs() {
+            self.traces = vec![self.template.clone(); self.fa_theta.n_outputs()];
+        }
+
+        let phi_s = self.fa_theta.embed(t.from.state());
+        let qs = self.fa_theta.evaluate(&phi_s).unwrap();
+
+        let nv = if t.terminated() {
+            0.0
+        } else {
+            max(&self.fa_theta.evaluate(&self.fa_theta.embed(t.to.state())).unwrap())
+        };
+
+        let td_error = t.reward + self.gamma * t.discount * nv - max(&qs);
+        let td_error_current = t.reward + self.gamma * t.discount * nv - qs[t.action];
+
+        // Update earlier state-action pairs towards the greedy values:
+        let decay_rate = self.template.lambda.value() * self.gamma.value();
+
+        for (a, trace) in self.traces.iter_mut().enumerate() {
+            trace.decay(decay_rate);
+
+            self.fa_theta.update_index(
+                &Features::from(trace.get()),
+                a,
+                self.alpha * td_error,
+            ).ok();
+        }
+
+        // Update the latest pair with the one-step error:
+        let phi_s = phi_s.expanded(self.fa_theta.n_features());
+
+        self.fa_theta.update_index(
+            &Features::from(phi_s.clone()),
+            t.action,
+            self.alpha * td_error_current,
+        ).ok();
+        self.traces[t.action].update(&phi_s);
+
+        let discount = if t.terminated() { 0.0 } else { t.discount };
+
+        self.traces.iter_mut().for_each(|trace| trace.decay(discount));
+    }
+}
+
+impl<S, F, P> Controller<S, P::Action> for PengQLambda<F, P>
+where
+    F: QFunction<S>,
+    P: Policy<S, Action = usize>,
+{
+    fn sample_target(&mut self, s: &S) -> usize { self.target.sample(s) }
+
+    fn sample_behaviour(&mut self, s: &S) -> usize { self.policy.sample(s) }
+}
+
+impl<S, F, P> ValuePredictor<S> for PengQLambda<F, P>
+where
+    F: QFunction<S>,
+    P: Policy<S, Action = usize>,
+{
+    fn predict_v(&mut self, s: &S) -> f64 {
+        let a = self.target.sample(s);
+
+        self.predict_qsa(s, a)
+    }
+}
+
+impl<S, F, P> ActionValuePredictor<S, P::Action> for PengQLambda<F, P>
+where
+    F: QFunction<S>,
+    P: Policy<S, Action = usize>,
+{
+    fn predict_qs(&mut self, s: &S) -> Vector<f64> {
+        self.fa_theta.evaluate(&self.fa_theta.embed(s)).unwrap()
+    }
+
+    fn predict_qsa(&mut self, s: &S, a: usize) -> f64 {
+        self.fa_theta.evaluate_index(&self.fa_theta.embed(s), a).unwrap()
+    }
+}
+
+impl_parameterised!(PengQLambda<F, P> => fa_theta: F);
@@ -6,6 +6,9 @@ use crate::policies::{Greedy, Policy};
 
 /// Watkins' Q-learning with eligibility traces.
 ///
+/// Traces are cut whenever the behaviour policy takes a non-greedy action;
+/// see `PengQLambda` for a variant which keeps them.
+///
 /// # References
 /// - Watkins, C. J. C. H. (1989). Learning from Delayed Rewards. Ph.D. thesis,
 /// Cambridge University.
@@ -20,6 +23,8 @@ pub struct QLambda<F, P> {
     pub alpha: Parameter,
     pub gamma: Parameter,
 
+    pub loss: Loss,
+
     trace: Trace,
 }
 
@@ -46,9 +51,16 @@ impl<F, P> QLambda<Shared<F>, P> {
             alpha: alpha.into(),
             gamma: gamma.into(),
 
+            loss: Loss::default(),
+
             trace,
         }
     }
+
+    /// Set the loss minimised by the updates.
+    pub fn with_loss(self, loss: Loss) -> Self {
+        QLambda { loss, ..self }
+    }
 }
 
 impl<F, P: Algorithm> Algorithm for QLambda<F, P> {
@@ -59,6 +71,13 @@ impl<F, P: Algorithm> Algorithm for QLambda<F, P> {
         self.policy.handle_terminal();
         self.target.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.policy.reseed(stream.seed(0));
+        self.target.reseed(stream.seed(1));
+    }
 }
 
 impl<S, F, P> OnlineLearner<S, P::Action> for QLambda<F, P>
@@ -94,13 +113,15 @@ where
             let na = self.target.sample(&ns);
             let nqsna = self.fa_theta.evaluate_index(&phi_ns, na).unwrap();
 
-            t.reward + self.gamma * nqsna - qsa
+            self.trace.decay(t.discount);
+
+            t.reward + self.gamma * t.discount * nqsna - qsa
         };
 
         self.fa_theta.update_index(
-            &Features::Dense(z),
+            &Features::from(z),
             t.action,
-            self.alpha * residual,
+            self.alpha * self.loss.gradient(residual),
         ).ok();
     }
 }
@@ -141,16 +162,4 @@ where
     }
 }
 
-impl<F: Parameterised, P> Parameterised for QLambda<F, P> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa_theta.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa_theta.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        unimplemented!()
-    }
-}
+impl_parameterised!(QLambda<F, P> => fa_theta: F);
@@ -2,7 +2,8 @@ use crate::core::*;
 use crate::domains::Transition;
 use crate::fa::{Parameterised, QFunction};
 use crate::geometry::{MatrixView, MatrixViewMut};
-use crate::policies::{Greedy, Policy, FinitePolicy};
+use crate::policies::{Greedy, LinkedPolicy, Policy, FinitePolicy};
+use serde::{Deserialize, Deserializer};
 use std::marker::PhantomData;
 
 /// Watkins' Q-learning.
@@ -12,14 +13,18 @@ use std::marker::PhantomData;
 /// Cambridge University.
 /// - Watkins, C. J. C. H., Dayan, P. (1992). Q-learning. Machine Learning,
 /// 8:279–292.
+#[derive(Serialize)]
 pub struct QLearning<Q, P> {
     pub q_func: Q,
 
     pub policy: P,
+    #[serde(skip_serializing)]
     pub target: Greedy<Q>,
 
     pub alpha: Parameter,
     pub gamma: Parameter,
+
+    pub loss: Loss,
 }
 
 impl<Q, P> QLearning<Shared<Q>, P> {
@@ -38,8 +43,41 @@ impl<Q, P> QLearning<Shared<Q>, P> {
 
             alpha: alpha.into(),
             gamma: gamma.into(),
+
+            loss: Loss::default(),
         }
     }
+
+    /// Set the loss minimised by the updates.
+    pub fn with_loss(self, loss: Loss) -> Self {
+        QLearning { loss, ..self }
+    }
+}
+
+/// The greedy target policy is rebuilt from, and the behaviour policy linked
+/// back to, the deserialised Q-function, so that both follow its updates.
+impl<'de, Q, P> Deserialize<'de> for QLearning<Shared<Q>, P>
+where
+    Q: Clone + Deserialize<'de>,
+    P: LinkedPolicy<Q> + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "QLearning")]
+        struct Fields<Q, P> {
+            q_func: Q,
+            policy: P,
+            alpha: Parameter,
+            gamma: Parameter,
+            loss: Loss,
+        }
+
+        let mut f = Fields::<Q, P>::deserialize(deserializer)?;
+
+        f.policy.link(f.q_func.clone());
+
+        Ok(QLearning::new(f.q_func, f.policy, f.alpha, f.gamma).with_loss(f.loss))
+    }
 }
 
 impl<Q, P: Algorithm> Algorithm for QLearning<Q, P> {
@@ -49,6 +87,13 @@ impl<Q, P: Algorithm> Algorithm for QLearning<Q, P> {
 
         self.policy.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.policy.reseed(stream.seed(0));
+        self.target.reseed(stream.seed(1));
+    }
 }
 
 impl<S, Q, P> OnlineLearner<S, P::Action> for QLearning<Q, P>
@@ -66,12 +111,12 @@ where
             let na = self.sample_target(&ns);
             let nqsna = self.predict_qsa(&ns, na);
 
-            t.reward + self.gamma * nqsna - qsa
+            t.reward + self.gamma * t.discount * nqsna - qsa
         };
 
         self.q_func.update_index(
             &self.q_func.embed(s),
-            t.action, self.alpha * residual
+            t.action, self.alpha * self.loss.gradient(residual)
         ).ok();
     }
 }
@@ -112,16 +157,41 @@ where
     }
 }
 
-impl<Q: Parameterised, P> Parameterised for QLearning<Q, P> {
-    fn weights(&self) -> Matrix<f64> {
-        self.q_func.weights()
-    }
+impl_parameterised!(QLearning<Q, P> => q_func: Q);
 
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.q_func.weights_view()
+#[cfg(all(test, feature = "logging"))]
+mod tests {
+    use crate::{
+        core::{make_shared, Controller},
+        fa::{Composable, Embedding, VectorApproximator, LFA, basis::fixed::Polynomial},
+        policies::{EpsilonGreedy, Greedy, Policy, Random, TieBreak},
+    };
+    use serde::{de::DeserializeOwned, Serialize};
+    use super::QLearning;
+
+    fn round_trip<T: Serialize + DeserializeOwned>(t: &T) -> T {
+        serde_json::from_str(&serde_json::to_string(t).unwrap()).unwrap()
     }
 
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.q_func.weights_view_mut()
+    #[test]
+    fn test_policies_follow_q_after_deserialisation() {
+        let q_func = make_shared(LFA::vector(Polynomial::new(1, vec![(0.0, 1.0)]).with_constant(), 2));
+        let policy = EpsilonGreedy::new(
+            Greedy::new(q_func.clone()).with_tie_break(TieBreak::LowestIndex),
+            Random::new(2),
+            0.0,
+        );
+
+        let mut agent = round_trip(&QLearning::new(q_func, policy, 0.1, 1.0));
+        let s = vec![0.5];
+
+        assert_eq!(agent.policy.mpa(&s), 0);
+
+        let phi = agent.q_func.embed(&s);
+        agent.q_func.update_index(&phi, 1, 1.0).unwrap();
+
+        assert_eq!(agent.policy.mpa(&s), 1);
+        assert_eq!(agent.sample_behaviour(&s), 1);
+        assert_eq!(agent.sample_target(&s), 1);
     }
 }
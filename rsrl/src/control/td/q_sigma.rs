@@ -122,6 +122,13 @@ impl<S, Q, P: Algorithm> Algorithm for QSigma<S, Q, P> {
         self.policy.handle_terminal();
         self.target.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.policy.reseed(stream.seed(0));
+        self.target.reseed(stream.seed(1));
+    }
 }
 
 impl<S, Q, P> OnlineLearner<S, P::Action> for QSigma<S, Q, P>
@@ -220,16 +227,4 @@ where
     }
 }
 
-impl<S, Q: Parameterised, P> Parameterised for QSigma<S, Q, P> {
-    fn weights(&self) -> Matrix<f64> {
-        self.q_func.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.q_func.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.q_func.weights_view_mut()
-    }
-}
+impl_parameterised!(QSigma<S, Q, P> => q_func: Q);
@@ -2,7 +2,8 @@ use crate::core::*;
 use crate::domains::Transition;
 use crate::fa::{Parameterised, QFunction};
 use crate::geometry::{MatrixView, MatrixViewMut};
-use crate::policies::{Policy, FinitePolicy};
+use crate::policies::{LinkedPolicy, Policy, FinitePolicy};
+use serde::{Deserialize, Deserializer};
 use std::marker::PhantomData;
 
 /// On-policy variant of Watkins' Q-learning (aka "modified Q-learning").
@@ -12,12 +13,15 @@ use std::marker::PhantomData;
 /// thesis, Cambridge University.
 /// - Singh, S. P., Sutton, R. S. (1996). Reinforcement learning with replacing
 /// eligibility traces. Machine Learning 22:123–158.
+#[derive(Serialize)]
 pub struct SARSA<Q, P> {
     pub q_func: Q,
     pub policy: P,
 
     pub alpha: Parameter,
     pub gamma: Parameter,
+
+    pub loss: Loss,
 }
 
 impl<Q, P> SARSA<Q, P> {
@@ -32,8 +36,41 @@ impl<Q, P> SARSA<Q, P> {
 
             alpha: alpha.into(),
             gamma: gamma.into(),
+
+            loss: Loss::default(),
         }
     }
+
+    /// Set the loss minimised by the updates.
+    pub fn with_loss(self, loss: Loss) -> Self {
+        SARSA { loss, ..self }
+    }
+}
+
+/// The policy is linked back to the deserialised Q-function, so that it
+/// follows its updates.
+impl<'de, Q, P> Deserialize<'de> for SARSA<Q, P>
+where
+    Q: Clone + Deserialize<'de>,
+    P: LinkedPolicy<Q> + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "SARSA")]
+        struct Fields<Q, P> {
+            q_func: Q,
+            policy: P,
+            alpha: Parameter,
+            gamma: Parameter,
+            loss: Loss,
+        }
+
+        let mut f = Fields::<Q, P>::deserialize(deserializer)?;
+
+        f.policy.link(f.q_func.clone());
+
+        Ok(SARSA::new(f.q_func, f.policy, f.alpha, f.gamma).with_loss(f.loss))
+    }
 }
 
 impl<Q, P: Algorithm> Algorithm for SARSA<Q, P> {
@@ -43,6 +80,10 @@ impl<Q, P: Algorithm> Algorithm for SARSA<Q, P> {
 
         self.policy.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        self.policy.reseed(seed);
+    }
 }
 
 impl<S, Q, P> OnlineLearner<S, P::Action> for SARSA<Q, P>
@@ -60,12 +101,12 @@ where
             let na = self.policy.sample(ns);
             let nqsna = self.q_func.evaluate_index(&self.q_func.embed(ns), na).unwrap();
 
-            t.reward + self.gamma * nqsna - qsa
+            t.reward + self.gamma * t.discount * nqsna - qsa
         };
 
         self.q_func.update_index(
             &self.q_func.embed(s),
-            t.action, self.alpha * residual
+            t.action, self.alpha * self.loss.gradient(residual)
         ).ok();
     }
 }
@@ -104,16 +145,4 @@ where
     }
 }
 
-impl<Q: Parameterised, P> Parameterised for SARSA<Q, P> {
-    fn weights(&self) -> Matrix<f64> {
-        self.q_func.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.q_func.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.q_func.weights_view_mut()
-    }
-}
+impl_parameterised!(SARSA<Q, P> => q_func: Q);
@@ -19,6 +19,8 @@ pub struct SARSALambda<F, P> {
     pub alpha: Parameter,
     pub gamma: Parameter,
 
+    pub loss: Loss,
+
     trace: Trace,
 }
 
@@ -41,10 +43,17 @@ impl<F, P> SARSALambda<F, P> {
             alpha: alpha.into(),
             gamma: gamma.into(),
 
+            loss: Loss::default(),
+
             trace,
         }
     }
 
+    /// Set the loss minimised by the updates.
+    pub fn with_loss(self, loss: Loss) -> Self {
+        SARSALambda { loss, ..self }
+    }
+
     #[inline(always)]
     fn update_trace(&mut self, phi: Vector<f64>) {
         let decay_rate = self.trace.lambda.value() * self.gamma.value();
@@ -61,6 +70,10 @@ impl<F, P: Algorithm> Algorithm for SARSALambda<F, P> {
 
         self.policy.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        self.policy.reseed(seed);
+    }
 }
 
 impl<S, Q, P> OnlineLearner<S, P::Action> for SARSALambda<Q, P>
@@ -87,13 +100,15 @@ where
             let na = self.policy.sample(ns);
             let nqsna = self.fa_theta.evaluate_index(&self.fa_theta.embed(ns), na).unwrap();
 
-            t.reward + self.gamma * nqsna - qsa
+            self.trace.decay(t.discount);
+
+            t.reward + self.gamma * t.discount * nqsna - qsa
         };
 
         self.fa_theta.update_index(
-            &Features::Dense(z),
+            &Features::from(z),
             t.action,
-            self.alpha * residual,
+            self.alpha * self.loss.gradient(residual),
         ).ok();
     }
 }
@@ -132,16 +147,4 @@ where
     }
 }
 
-impl<F: Parameterised, P> Parameterised for SARSALambda<F, P> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa_theta.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa_theta.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa_theta.weights_view_mut()
-    }
-}
+impl_parameterised!(SARSALambda<F, P> => fa_theta: F);
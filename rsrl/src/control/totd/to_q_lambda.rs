@@ -6,6 +6,8 @@ use crate::policies::{Greedy, Policy};
 
 /// True online variant of the Q(lambda) algorithm.
 ///
+/// As with `TOSARSALambda`, the algorithm is derived for a dutch trace.
+///
 /// # References
 /// - [Van Seijen, H., Mahmood, A. R., Pilarski, P. M., Machado, M. C., &
 /// Sutton, R. S. (2016). True online temporal-difference learning. Journal of
@@ -56,21 +58,24 @@ impl<F, P> TOQLambda<F, P> {
     #[inline(always)]
     fn update_traces(&mut self, phi: Vector<f64>, decay_rate: f64) {
         let update_rate = self.trace.lambda.value() * self.gamma.value();
-        let trace_update = (
-            1.0 -
-            self.alpha.value() * update_rate * self.trace.get().dot(&phi)
-        ) * phi;
 
         self.trace.decay(decay_rate*update_rate);
-        self.trace.update(&trace_update);
+        self.trace.update_with_rate(&phi, self.alpha.value());
     }
 }
 
-impl<F, P> Algorithm for TOQLambda<F, P> {
+impl<F, P: Algorithm> Algorithm for TOQLambda<F, P> {
     fn handle_terminal(&mut self) {
         self.alpha = self.alpha.step();
         self.gamma = self.gamma.step();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.policy.reseed(stream.seed(0));
+        self.target.reseed(stream.seed(1));
+    }
 }
 
 impl<S, F, P> OnlineLearner<S, P::Action> for TOQLambda<F, P>
@@ -109,11 +114,13 @@ where
 
             self.q_old = nqsna;
 
-            t.reward + self.gamma * nqsna - q_old
+            self.trace.decay(t.discount);
+
+            t.reward + self.gamma * t.discount * nqsna - q_old
         };
 
         self.q_func.update_index(
-            &Features::Dense(z), t.action,
+            &Features::from(z), t.action,
             self.alpha * residual,
         ).ok();
 
@@ -164,16 +171,4 @@ where
     }
 }
 
-impl<F: Parameterised, P> Parameterised for TOQLambda<F, P> {
-    fn weights(&self) -> Matrix<f64> {
-        self.q_func.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.q_func.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.q_func.weights_view_mut()
-    }
-}
+impl_parameterised!(TOQLambda<F, P> => q_func: F);
@@ -2,14 +2,20 @@ use crate::core::*;
 use crate::domains::Transition;
 use crate::fa::{Approximator, Parameterised, Features, QFunction};
 use crate::geometry::{MatrixView, MatrixViewMut};
-use crate::policies::{Policy, FinitePolicy};
+use crate::policies::{LinkedPolicy, Policy, FinitePolicy};
+use serde::{Deserialize, Deserializer};
 
 /// True online variant of the SARSA(lambda) algorithm.
 ///
+/// The algorithm is derived for a dutch trace (`Trace::dutch`); accumulating
+/// and replacing traces are accepted but lose the exact equivalence with the
+/// online lambda-return.
+///
 /// # References
 /// - [Van Seijen, H., Mahmood, A. R., Pilarski, P. M., Machado, M. C., &
 /// Sutton, R. S. (2016). True online temporal-difference learning. Journal of
 /// Machine Learning Research, 17(145), 1-40.](https://arxiv.org/pdf/1512.04087.pdf)
+#[derive(Serialize)]
 pub struct TOSARSALambda<F, P> {
     pub q_func: F,
     pub policy: P,
@@ -47,13 +53,38 @@ impl<F, P> TOSARSALambda<F, P> {
 
     #[inline(always)]
     fn update_traces(&mut self, phi: Vector<f64>, decay_rate: f64) {
-        let trace_update = (
-            1.0 -
-            self.alpha.value() * decay_rate * self.trace.get().dot(&phi)
-        ) * phi;
-
         self.trace.decay(decay_rate);
-        self.trace.update(&trace_update);
+        self.trace.update_with_rate(&phi, self.alpha.value());
+    }
+}
+
+/// The policy is linked back to the deserialised Q-function, so that it
+/// follows its updates.
+impl<'de, F, P> Deserialize<'de> for TOSARSALambda<F, P>
+where
+    F: Clone + Deserialize<'de>,
+    P: LinkedPolicy<F> + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "TOSARSALambda")]
+        struct Fields<F, P> {
+            q_func: F,
+            policy: P,
+            alpha: Parameter,
+            gamma: Parameter,
+            trace: Trace,
+            q_old: f64,
+        }
+
+        let mut f = Fields::<F, P>::deserialize(deserializer)?;
+
+        f.policy.link(f.q_func.clone());
+
+        Ok(TOSARSALambda {
+            q_old: f.q_old,
+            ..TOSARSALambda::new(f.q_func, f.policy, f.trace, f.alpha, f.gamma)
+        })
     }
 }
 
@@ -98,11 +129,13 @@ where
 
             self.q_old = nqsna;
 
-            t.reward + self.gamma * nqsna - q_old
+            self.trace.decay(t.discount);
+
+            t.reward + self.gamma * t.discount * nqsna - q_old
         };
 
         self.q_func.update_index(
-            &Features::Dense(z),
+            &Features::from(z),
             t.action,
             self.alpha * residual,
         ).ok();
@@ -152,16 +185,4 @@ where
     }
 }
 
-impl<F: Parameterised, P> Parameterised for TOSARSALambda<F, P> {
-    fn weights(&self) -> Matrix<f64> {
-        self.q_func.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.q_func.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.q_func.weights_view_mut()
-    }
-}
+impl_parameterised!(TOSARSALambda<F, P> => q_func: F);
@@ -1,11 +1,33 @@
 #![allow(unused_variables)]
-use crate::core::Shared;
+use crate::core::{Shared, SyncShared};
 use crate::domains::{Transition, Observation::Terminal};
 use crate::geometry::Vector;
 
 pub trait Algorithm {
     /// Perform housekeeping after terminal state observation.
     fn handle_terminal(&mut self) {}
+
+    /// Reset any internal random number generators from the given seed.
+    ///
+    /// Deterministic algorithms may ignore this; composite algorithms should
+    /// derive distinct seeds for their components via a `SeedStream`.
+    fn reseed(&mut self, seed: u64) {}
+}
+
+impl Algorithm for () {}
+
+impl<T: Algorithm> Algorithm for Option<T> {
+    fn handle_terminal(&mut self) {
+        if let Some(algorithm) = self {
+            algorithm.handle_terminal();
+        }
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        if let Some(algorithm) = self {
+            algorithm.reseed(seed);
+        }
+    }
 }
 
 pub trait OnlineLearner<S, A>: Algorithm {
@@ -48,51 +70,62 @@ pub trait ActionValuePredictor<S, A>: ValuePredictor<S> {
     }
 }
 
-// Shared<T> impls:
-impl<T: Algorithm> Algorithm for Shared<T> {
-    fn handle_terminal(&mut self) {
-        self.borrow_mut().handle_terminal()
-    }
-}
-
-impl<S, A, T: OnlineLearner<S, A>> OnlineLearner<S, A> for Shared<T> {
-    fn handle_transition(&mut self, transition: &Transition<S, A>) {
-        self.borrow_mut().handle_transition(transition)
-    }
-
-    fn handle_sequence(&mut self, sequence: &[Transition<S, A>]) {
-        self.borrow_mut().handle_sequence(sequence)
-    }
+// Shared<T> and SyncShared<T> impls:
+macro_rules! impl_shared_algorithms {
+    ($shared:ident) => {
+        impl<T: Algorithm> Algorithm for $shared<T> {
+            fn handle_terminal(&mut self) {
+                self.borrow_mut().handle_terminal()
+            }
+
+            fn reseed(&mut self, seed: u64) {
+                self.borrow_mut().reseed(seed)
+            }
+        }
+
+        impl<S, A, T: OnlineLearner<S, A>> OnlineLearner<S, A> for $shared<T> {
+            fn handle_transition(&mut self, transition: &Transition<S, A>) {
+                self.borrow_mut().handle_transition(transition)
+            }
+
+            fn handle_sequence(&mut self, sequence: &[Transition<S, A>]) {
+                self.borrow_mut().handle_sequence(sequence)
+            }
+        }
+
+        impl<S, A, T: BatchLearner<S, A>> BatchLearner<S, A> for $shared<T> {
+            fn handle_batch(&mut self, batch: &[Transition<S, A>]) {
+                self.borrow_mut().handle_batch(batch)
+            }
+        }
+
+        impl<S, A, T: Controller<S, A>> Controller<S, A> for $shared<T> {
+            fn sample_target(&mut self, s: &S) -> A {
+                self.borrow_mut().sample_target(s)
+            }
+
+            fn sample_behaviour(&mut self, s: &S) -> A {
+                self.borrow_mut().sample_behaviour(s)
+            }
+        }
+
+        impl<S, T: ValuePredictor<S>> ValuePredictor<S> for $shared<T> {
+            fn predict_v(&mut self, s: &S) -> f64 {
+                self.borrow_mut().predict_v(s)
+            }
+        }
+
+        impl<S, A, T: ActionValuePredictor<S, A>> ActionValuePredictor<S, A> for $shared<T> {
+            fn predict_qsa(&mut self, s: &S, a: A) -> f64 {
+                self.borrow_mut().predict_qsa(s, a)
+            }
+
+            fn predict_qs(&mut self, s: &S) -> Vector<f64> {
+                self.borrow_mut().predict_qs(s)
+            }
+        }
+    };
 }
 
-impl<S, A, T: BatchLearner<S, A>> BatchLearner<S, A> for Shared<T> {
-    fn handle_batch(&mut self, batch: &[Transition<S, A>]) {
-        self.borrow_mut().handle_batch(batch)
-    }
-}
-
-impl<S, A, T: Controller<S, A>> Controller<S, A> for Shared<T> {
-    fn sample_target(&mut self, s: &S) -> A {
-        self.borrow_mut().sample_target(s)
-    }
-
-    fn sample_behaviour(&mut self, s: &S) -> A {
-        self.borrow_mut().sample_behaviour(s)
-    }
-}
-
-impl<S, T: ValuePredictor<S>> ValuePredictor<S> for Shared<T> {
-    fn predict_v(&mut self, s: &S) -> f64 {
-        self.borrow_mut().predict_v(s)
-    }
-}
-
-impl<S, A, T: ActionValuePredictor<S, A>> ActionValuePredictor<S, A> for Shared<T> {
-    fn predict_qsa(&mut self, s: &S, a: A) -> f64 {
-        self.borrow_mut().predict_qsa(s, a)
-    }
-
-    fn predict_qs(&mut self, s: &S) -> Vector<f64> {
-        self.borrow_mut().predict_qs(s)
-    }
-}
+impl_shared_algorithms!(Shared);
+impl_shared_algorithms!(SyncShared);
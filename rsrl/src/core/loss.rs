@@ -0,0 +1,69 @@
+//! Losses on the TD error.
+use std::str::FromStr;
+
+/// Loss minimised by a TD update, which determines how the update scales with
+/// the TD error.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Loss {
+    /// Half the squared error; updates are proportional to the TD error.
+    Squared,
+
+    /// Huber (smooth-L1) loss, quadratic within `delta` of zero and linear
+    /// beyond, so that updates are proportional to the TD error clipped to
+    /// [-delta, delta]. Outsized errors then move the weights no further
+    /// than an error of `delta` would.
+    Huber { delta: f64 },
+}
+
+impl Loss {
+    pub fn huber(delta: f64) -> Loss { Loss::Huber { delta, } }
+
+    /// Negative gradient of the loss with respect to the prediction, i.e. the
+    /// error by which the learning rate is multiplied.
+    pub fn gradient(&self, error: f64) -> f64 {
+        match *self {
+            Loss::Squared => error,
+            Loss::Huber { delta } => error.max(-delta).min(delta),
+        }
+    }
+}
+
+impl Default for Loss {
+    fn default() -> Loss { Loss::Squared }
+}
+
+impl FromStr for Loss {
+    type Err = String;
+
+    /// Parse either `squared` or `huber:delta`.
+    fn from_str(s: &str) -> Result<Loss, String> {
+        let mut parts = s.splitn(2, ':');
+
+        match (parts.next().unwrap().trim(), parts.next()) {
+            ("squared", None) => Ok(Loss::Squared),
+            ("huber", Some(delta)) => match delta.trim().parse::<f64>() {
+                Ok(delta) if delta > 0.0 => Ok(Loss::huber(delta)),
+                _ => Err(format!("Loss `{}` requires a positive delta.", s)),
+            },
+            _ => Err(format!("Loss `{}` must be `squared` or `huber:delta`.", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Loss;
+
+    #[test]
+    fn test_huber_gradient() {
+        let loss = Loss::huber(1.0);
+
+        assert_eq!(loss.gradient(0.5), 0.5);
+        assert_eq!(loss.gradient(-20.0), -1.0);
+        assert_eq!(loss.gradient(3.0), 1.0);
+        assert_eq!(Loss::Squared.gradient(-20.0), -20.0);
+
+        assert_eq!("huber:2.5".parse::<Loss>(), Ok(Loss::huber(2.5)));
+        assert!("huber:-1".parse::<Loss>().is_err());
+    }
+}
@@ -1,8 +1,10 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     cell::{RefCell, Ref, RefMut},
     fmt,
-    ops::{Deref, DerefMut},
+    ops::Deref,
     rc::Rc,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
 
 pub fn make_shared<T>(t: T) -> Shared<T> { Shared(Rc::new(RefCell::new(t))) }
@@ -53,3 +55,115 @@ impl<T> Clone for Shared<T> {
         Shared(self.0.clone())
     }
 }
+
+/// Serialises the shared value itself. Note that handles are not tracked, so
+/// each deserialised `Shared` owns a fresh copy; algorithms that hold several
+/// handles to the same value must re-link them on deserialisation.
+impl<T: Serialize> Serialize for Shared<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.borrow().serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Shared<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Shared<T>, D::Error> {
+        T::deserialize(deserializer).map(make_shared)
+    }
+}
+
+pub fn make_sync_shared<T>(t: T) -> SyncShared<T> { SyncShared(Arc::new(RwLock::new(t))) }
+
+/// Thread-safe variant of `Shared`, backed by an `Arc<RwLock<T>>`.
+///
+/// `SyncShared<T>` is `Send + Sync` whenever `T: Send + Sync`, so learners
+/// can be shared with worker threads. The value is only ever reached through
+/// a guard, or a closure run while the lock is held; unlike `Shared`, there
+/// is no `Deref`, and so no views of the weights that outlive the lock, which
+/// rules out implementing `Parameterised` for it. Note that, unlike
+/// `RefCell`, the lock is not re-entrant: holding a guard from `borrow_mut`
+/// while calling `borrow` on the same thread will deadlock.
+pub struct SyncShared<T>(Arc<RwLock<T>>);
+
+impl<T> SyncShared<T> {
+    pub fn new(t: T) -> SyncShared<T> {
+        make_sync_shared(t)
+    }
+
+    pub fn borrow(&self) -> RwLockReadGuard<T> {
+        self.0.read().unwrap()
+    }
+
+    pub fn borrow_mut(&self) -> RwLockWriteGuard<T> {
+        self.0.write().unwrap()
+    }
+
+    /// Apply `f` to the value while holding the read lock.
+    pub fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.borrow())
+    }
+
+    /// Apply `f` to the value while holding the write lock.
+    pub fn write<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.borrow_mut())
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for SyncShared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", *self.borrow())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SyncShared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", *self.borrow())
+    }
+}
+
+impl<T> Clone for SyncShared<T> {
+    fn clone(&self) -> SyncShared<T> {
+        SyncShared(self.0.clone())
+    }
+}
+
+impl<T: Serialize> Serialize for SyncShared<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.borrow().serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SyncShared<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SyncShared<T>, D::Error> {
+        T::deserialize(deserializer).map(make_sync_shared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SyncShared;
+    use std::thread;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_sync_shared_is_send_sync() {
+        assert_send_sync::<SyncShared<Vec<f64>>>();
+    }
+
+    #[test]
+    fn test_sync_shared_concurrent_writes() {
+        let shared = SyncShared::new(0usize);
+
+        let handles: Vec<_> = (0..4).map(|_| {
+            let shared = shared.clone();
+
+            thread::spawn(move || for _ in 0..1000 { shared.write(|x| *x += 1); })
+        }).collect();
+
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        assert_eq!(shared.read(|x| *x), 4000);
+    }
+}
@@ -2,6 +2,9 @@ pub use crate::geometry::{Matrix, Vector};
 
 import_all!(trace);
 import_all!(memory);
+import_all!(loss);
 import_all!(parameter);
+import_all!(seeding);
+#[cfg(feature = "logging")]
 import_all!(experiment);
 import_all!(algorithms);
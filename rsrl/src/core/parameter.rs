@@ -107,6 +107,37 @@ impl Parameter {
 
     pub fn to_fixed(self) -> Parameter { Parameter::Fixed(self.value()) }
 
+    /// Scale the parameter by a constant `factor`, keeping its schedule.
+    pub fn scaled(self, factor: f64) -> Parameter {
+        match self {
+            Parameter::Fixed(v) => Parameter::Fixed(v * factor),
+            Parameter::Exponential { init, floor, tau, count } => Parameter::Exponential {
+                init: init * factor,
+                floor: floor * factor,
+                tau,
+                count,
+            },
+            Parameter::Polynomial { init, floor, tau, count } => Parameter::Polynomial {
+                init: init * factor,
+                floor: floor * factor,
+                tau,
+                count,
+            },
+            Parameter::Boyan { init, floor, n0, count } => Parameter::Boyan {
+                init: init * factor,
+                floor: floor * factor,
+                n0,
+                count,
+            },
+            Parameter::GHC { init, floor, tau, count } => Parameter::GHC {
+                init: init * factor,
+                floor: floor * factor,
+                tau,
+                count,
+            },
+        }
+    }
+
     pub fn step(self) -> Parameter {
         match self {
             Parameter::Fixed(_) => self,
@@ -346,4 +377,19 @@ mod tests {
         p = p.step().step().step().back().back();
         assert!((p.value() - 0.9).abs() < 1e-7);
     }
+
+    #[test]
+    fn test_scaled() {
+        let mut base = Parameter::exponential(1.0, 0.5, 0.9);
+        let mut p = base.scaled(0.1);
+
+        assert!((p.value() - 0.1).abs() < 1e-7);
+
+        for _ in 0..1000 {
+            base = base.step();
+            p = p.step();
+
+            assert!((p.value() - 0.1 * base.value()).abs() < 1e-7);
+        }
+    }
 }
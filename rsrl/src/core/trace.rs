@@ -1,11 +1,60 @@
 //! Eligibility trace types
 use crate::core::{Parameter, Vector};
+use ndarray::{Array, Dimension};
+use std::str::FromStr;
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TraceType {
     Accumulating,
+
+    /// Accumulating, with each element clipped to [-1, 1].
     Replacing,
+
+    /// Accumulating, less the part of the activation already covered by the
+    /// trace at the learning rate of the weights it is applied to. Bounded
+    /// for dense features where accumulating traces can grow without limit.
+    ///
+    /// See van Seijen, H., et al. (2016). True online temporal-difference
+    /// learning. JMLR, 17(145), 1-40.
+    Dutch,
+}
+
+impl TraceType {
+    /// Add `activation` to an `eligibility` that has already been decayed.
+    ///
+    /// Only dutch traces depend on the learning rate `alpha`; with `alpha` of
+    /// zero they coincide with accumulating traces.
+    pub fn apply<D: Dimension>(&self, eligibility: &mut Array<f64, D>, activation: &Array<f64, D>, alpha: f64) {
+        match self {
+            TraceType::Accumulating => *eligibility += activation,
+            TraceType::Replacing => {
+                eligibility.zip_mut_with(activation, |val, &a| {
+                    *val = f64::max(-1.0, f64::min(1.0, *val + a));
+                });
+            },
+            TraceType::Dutch => {
+                let overlap: f64 = eligibility.iter().zip(activation.iter()).map(|(e, a)| e * a).sum();
+
+                eligibility.scaled_add(1.0 - alpha * overlap, activation);
+            },
+        }
+    }
+}
+
+impl FromStr for TraceType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TraceType, String> {
+        match s.trim() {
+            "accumulating" => Ok(TraceType::Accumulating),
+            "replacing" => Ok(TraceType::Replacing),
+            "dutch" => Ok(TraceType::Dutch),
+            _ => Err(format!("Trace type `{}` must be one of accumulating, replacing or dutch.", s)),
+        }
+    }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Trace {
     pub trace_type: TraceType,
 
@@ -31,25 +80,28 @@ impl Trace {
         Trace::new(TraceType::Replacing, lambda, activation)
     }
 
+    pub fn dutch<T: Into<Parameter>>(lambda: T, activation: usize) -> Trace {
+        Trace::new(TraceType::Dutch, lambda, activation)
+    }
+
     pub fn get(&self) -> Vector { self.eligibility.clone() }
 
     pub fn decay(&mut self, rate: f64) { self.eligibility *= rate; }
 
-    pub fn update(&mut self, activation: &Vector) {
-        match self.trace_type {
-            TraceType::Accumulating => self.eligibility += activation,
-            TraceType::Replacing => {
-                self.eligibility.zip_mut_with(activation, |val, &a| {
-                    *val = f64::max(-1.0, f64::min(1.0, *val + a));
-                });
-            },
-        }
+    /// Add `activation` to the trace; dutch traces are treated as
+    /// accumulating, see `update_with_rate`.
+    pub fn update(&mut self, activation: &Vector) { self.update_with_rate(activation, 0.0); }
+
+    /// Add `activation` to the trace, which is applied to weights with
+    /// learning rate `alpha`.
+    pub fn update_with_rate(&mut self, activation: &Vector, alpha: f64) {
+        self.trace_type.apply(&mut self.eligibility, activation, alpha);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Trace;
+    use super::{Trace, TraceType};
     use ndarray::arr1;
 
     #[test]
@@ -93,4 +145,23 @@ mod tests {
         trace.update(&arr1(&[1.0f64; 10]));
         assert_eq!(trace.get(), arr1(&[1.0f64; 10]));
     }
+
+    #[test]
+    fn test_dutch() {
+        let mut trace = Trace::dutch(0.5, 2);
+
+        trace.update_with_rate(&arr1(&[1.0, 0.0]), 0.5);
+        assert_eq!(trace.get(), arr1(&[1.0, 0.0]));
+
+        let l = trace.lambda.value();
+        trace.decay(l);
+        assert_eq!(trace.get(), arr1(&[0.5, 0.0]));
+
+        // Overlap of 0.5 with the activation, so it's added with weight 0.75:
+        trace.update_with_rate(&arr1(&[1.0, 1.0]), 0.5);
+        assert_eq!(trace.get(), arr1(&[1.25, 0.75]));
+
+        assert_eq!("dutch".parse::<TraceType>(), Ok(TraceType::Dutch));
+        assert!("eligible".parse::<TraceType>().is_err());
+    }
 }
@@ -143,6 +143,8 @@ impl Domain for Acrobat {
             action,
             reward,
             to,
+            discount: 1.0,
+            truncated: false,
         }
     }
 
@@ -119,6 +119,8 @@ impl Domain for CartPole {
             action,
             reward,
             to,
+            discount: 1.0,
+            truncated: false,
         }
     }
 
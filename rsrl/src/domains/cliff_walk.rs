@@ -60,6 +60,8 @@ impl Domain for CliffWalk {
             action,
             reward,
             to,
+            discount: 1.0,
+            truncated: false,
         }
     }
 
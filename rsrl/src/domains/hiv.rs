@@ -151,6 +151,8 @@ impl Domain for HIVTreatment {
             action,
             reward,
             to,
+            discount: 1.0,
+            truncated: false,
         }
     }
 
@@ -33,6 +33,17 @@ impl<S> Observation<S> {
         }
     }
 
+    /// Apply a closure to the state values, preserving the observation type.
+    pub fn map<T>(self, f: impl FnOnce(S) -> T) -> Observation<T> {
+        use self::Observation::*;
+
+        match self {
+            Full(state) => Full(f(state)),
+            Partial(state) => Partial(f(state)),
+            Terminal(state) => Terminal(f(state)),
+        }
+    }
+
     /// Returns true if the state was fully observed, otherwise false.
     pub fn is_full(&self) -> bool {
         match self {
@@ -58,6 +69,21 @@ impl<S> Observation<S> {
     }
 }
 
+/// Treatment of episodes that end by truncation, e.g. at a time limit, rather
+/// than by reaching a state with no future.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Truncation {
+    /// Value the final state as zero, as for a true termination.
+    Terminate,
+
+    /// Bootstrap from the estimated value of the final state.
+    Bootstrap,
+}
+
+impl Default for Truncation {
+    fn default() -> Truncation { Truncation::Terminate }
+}
+
 /// Container class for data associated with a domain transition.
 #[derive(Clone, Copy, Debug)]
 pub struct Transition<S, A> {
@@ -72,6 +98,18 @@ pub struct Transition<S, A> {
 
     /// State transitioned _to_, `s'`.
     pub to: Observation<S>,
+
+    /// State-dependent discount, gamma(s'), applied to the value of `to` on
+    /// top of the learner's own discount factor.
+    ///
+    /// One unless the domain terminates softly, e.g. with a random horizon,
+    /// in which case it is the probability of continuing past `to`; the
+    /// terminal flag is reserved for certain termination.
+    pub discount: f64,
+
+    /// True if `to` is terminal only because the episode was cut short, e.g.
+    /// by a fixed horizon; see `Truncation`.
+    pub truncated: bool,
 }
 
 impl<S, A> Transition<S, A> {
@@ -88,6 +126,18 @@ impl<S, A> Transition<S, A> {
     /// Returns true if the transition ends in a terminal state.
     pub fn terminated(&self) -> bool { self.to.is_terminal() }
 
+    /// Returns true if the value of `to` should be bootstrapped from, given
+    /// the learner's treatment of truncated episodes.
+    pub fn bootstraps(&self, truncation: Truncation) -> bool {
+        !self.terminated() || (self.truncated && truncation == Truncation::Bootstrap)
+    }
+
+    /// Replace the state-dependent discount of this transition and return a
+    /// new instance.
+    pub fn with_discount(self, discount: f64) -> Transition<S, A> {
+        Transition { discount, ..self }
+    }
+
     /// Replace the action associated with this transition and return a new
     /// instance.
     pub fn replace_action<T>(self, action: T) -> Transition<S, T> {
@@ -96,6 +146,8 @@ impl<S, A> Transition<S, A> {
             action: action,
             reward: self.reward,
             to: self.to,
+            discount: self.discount,
+            truncated: self.truncated,
         }
     }
 
@@ -104,21 +156,11 @@ impl<S, A> Transition<S, A> {
     pub fn drop_action(self) -> Transition<S, ()> { self.replace_action(()) }
 
     pub fn replace_reward(self, r: f64) -> Transition<S, A> {
-        Transition {
-            from: self.from,
-            action: self.action,
-            reward: r,
-            to: self.to,
-        }
+        Transition { reward: r, ..self }
     }
 
     pub fn negate_reward(self) -> Transition<S, A> {
-        Transition {
-            from: self.from,
-            action: self.action,
-            reward: -self.reward,
-            to: self.to,
-        }
+        Transition { reward: -self.reward, ..self }
     }
 }
 
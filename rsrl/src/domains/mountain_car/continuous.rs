@@ -78,6 +78,8 @@ impl Domain for ContinuousMountainCar {
             action,
             reward,
             to,
+            discount: 1.0,
+            truncated: false,
         }
     }
 
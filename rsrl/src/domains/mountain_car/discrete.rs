@@ -96,6 +96,8 @@ impl Domain for MountainCar {
             action,
             reward,
             to,
+            discount: 1.0,
+            truncated: false,
         }
     }
 
@@ -102,6 +102,8 @@ impl Domain for OpenAIGym {
             action: a,
             reward: self.last_reward,
             to: to,
+            discount: 1.0,
+            truncated: false,
         }
     }
 
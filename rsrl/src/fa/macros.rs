@@ -7,6 +7,12 @@ macro_rules! impl_newtype_fa {
                 self.0.weights()
             }
 
+            fn weights_dim(&self) -> (usize, usize) {
+                self.0.weights_dim()
+            }
+        }
+
+        impl<F: Approximator<Output = $output> + WeightsView> WeightsView for $type<F> {
             fn weights_view(&self) -> MatrixView<f64> {
                 self.0.weights_view()
             }
@@ -54,6 +60,12 @@ macro_rules! impl_newtype_fa {
                 self.$inner.weights()
             }
 
+            fn weights_dim(&self) -> (usize, usize) {
+                self.$inner.weights_dim()
+            }
+        }
+
+        impl<F: Approximator<Output = $output> + WeightsView> WeightsView for $type<F> {
             fn weights_view(&self) -> MatrixView<f64> {
                 self.$inner.weights_view()
             }
@@ -88,3 +100,67 @@ macro_rules! impl_newtype_fa {
         }
     }
 }
+
+/// Implement `Parameterised` for a learner or policy by delegating to its
+/// weighted components.
+///
+/// With a single field, all methods forward to that field, and `WeightsView`
+/// is implemented too whenever the field implements it. With multiple fields,
+/// the weights of each head are stacked row-wise in the order given; only
+/// `Parameterised` is implemented in this case, since the heads are stored
+/// separately and cannot be viewed as one matrix.
+///
+/// ```ignore
+/// impl_parameterised!(QLearning<Q, P> => q_func: Q);
+/// impl_parameterised!(Beta<F> => alpha: F, beta: F);
+/// ```
+macro_rules! impl_parameterised {
+    ($type:ident<$($gen:ident),+> => $field:ident: $ftype:ident) => {
+        impl<$($gen),+> $crate::fa::Parameterised for $type<$($gen),+>
+        where
+            $ftype: $crate::fa::Parameterised,
+        {
+            fn weights(&self) -> $crate::geometry::Matrix<f64> {
+                $crate::fa::Parameterised::weights(&self.$field)
+            }
+
+            fn weights_dim(&self) -> (usize, usize) {
+                $crate::fa::Parameterised::weights_dim(&self.$field)
+            }
+        }
+
+        impl<$($gen),+> $crate::fa::WeightsView for $type<$($gen),+>
+        where
+            $ftype: $crate::fa::WeightsView,
+        {
+            fn weights_view(&self) -> $crate::geometry::MatrixView<f64> {
+                $crate::fa::WeightsView::weights_view(&self.$field)
+            }
+
+            fn weights_view_mut(&mut self) -> $crate::geometry::MatrixViewMut<f64> {
+                $crate::fa::WeightsView::weights_view_mut(&mut self.$field)
+            }
+        }
+    };
+    ($type:ident<$($gen:ident),+> => $field:ident: $ftype:ident, $($fields:ident: $ftypes:ident),+) => {
+        impl<$($gen),+> $crate::fa::Parameterised for $type<$($gen),+>
+        where
+            $ftype: $crate::fa::Parameterised,
+            $($ftypes: $crate::fa::Parameterised),+
+        {
+            fn weights(&self) -> $crate::geometry::Matrix<f64> {
+                stack![
+                    ::ndarray::Axis(0),
+                    $crate::fa::Parameterised::weights(&self.$field),
+                    $($crate::fa::Parameterised::weights(&self.$fields)),+
+                ]
+            }
+
+            fn weights_dim(&self) -> (usize, usize) {
+                let (rows, cols) = $crate::fa::Parameterised::weights_dim(&self.$field);
+
+                (rows $(+ $crate::fa::Parameterised::weights_dim(&self.$fields).0)+, cols)
+            }
+        }
+    };
+}
@@ -1,7 +1,7 @@
 use crate::{
     core::{make_shared, Shared},
     fa::{Approximator, Embedding, Parameterised, EvaluationResult, Features, QFunction, UpdateResult},
-    geometry::{Matrix, MatrixView, MatrixViewMut, Vector},
+    geometry::{Matrix, Vector},
 };
 use std::marker::PhantomData;
 
@@ -26,15 +26,11 @@ impl MockQ {
 impl Embedding<Vector<f64>> for MockQ {
     fn n_features(&self) -> usize { unimplemented!() }
 
-    fn embed(&self, s: &Vector<f64>) -> Features { Features::Dense(s.clone()) }
+    fn embed(&self, s: &Vector<f64>) -> Features { Features::from(s.clone()) }
 }
 
 impl Parameterised for MockQ {
-    fn weights_view(&self) -> MatrixView<f64> {
-        unimplemented!()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
+    fn weights(&self) -> Matrix<f64> {
         unimplemented!()
     }
 }
@@ -54,7 +50,7 @@ impl Approximator for MockQ {
             Some(ref out) => out.clone(),
             None => match f {
                 Features::Sparse(_) => unimplemented!(),
-                Features::Dense(ref out) => out.clone(),
+                Features::Dense(ref out) => f.expanded(out.len()),
             },
         })
     }
@@ -1,6 +1,6 @@
 //! Function approximation and value function representation module.
 use crate::{
-    core::Shared,
+    core::{Shared, SyncShared},
     geometry::{Vector, Matrix, MatrixView, MatrixViewMut},
 };
 
@@ -42,7 +42,7 @@ pub trait QFunction<S: ?Sized>: Embedding<S> + VectorApproximator {
 
 impl<S: ?Sized, T: Embedding<S> + VectorApproximator> QFunction<S> for T {}
 
-// Shared<T> impls:
+// Shared<T> and SyncShared<T> impls:
 impl<S: ?Sized, T: Embedding<S>> Embedding<S> for Shared<T> {
     fn n_features(&self) -> usize {
         self.borrow().n_features()
@@ -53,6 +53,19 @@ impl<S: ?Sized, T: Embedding<S>> Embedding<S> for Shared<T> {
     }
 }
 
+impl<S: ?Sized, T: Embedding<S>> Embedding<S> for SyncShared<T> {
+    fn n_features(&self) -> usize {
+        self.borrow().n_features()
+    }
+
+    fn embed(&self, s: &S) -> Features {
+        self.borrow().embed(s)
+    }
+}
+
+// Views of the weights borrow from the shared value itself, so only the
+// single-threaded `Shared` is an approximator in its own right; a
+// `SyncShared` approximator is used through its guards instead.
 impl<T: Approximator> Approximator for Shared<T> {
     type Output = T::Output;
 
@@ -78,6 +91,10 @@ impl<T: Approximator> Approximator for Shared<T> {
 impl<T: Parameterised> Parameterised for Shared<T> {
     fn weights(&self) -> Matrix<f64> { self.borrow().weights() }
 
+    fn weights_dim(&self) -> (usize, usize) { self.borrow().weights_dim() }
+}
+
+impl<T: WeightsView> WeightsView for Shared<T> {
     fn weights_view(&self) -> MatrixView<f64> {
         unsafe { self.as_ptr().as_ref().unwrap().weights_view() }
     }
@@ -85,6 +102,4 @@ impl<T: Parameterised> Parameterised for Shared<T> {
     fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
         unsafe { self.as_ptr().as_mut().unwrap().weights_view_mut() }
     }
-
-    fn weights_dim(&self) -> (usize, usize) { self.borrow().weights_dim() }
 }
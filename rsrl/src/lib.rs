@@ -2,17 +2,24 @@
 
 #[macro_use]
 extern crate ndarray;
+#[cfg(feature = "linalg")]
 extern crate ndarray_linalg;
-extern crate rand;
 
+#[cfg(feature = "logging")]
 #[macro_use]
 extern crate slog;
+#[cfg(feature = "logging")]
 extern crate slog_async;
+#[cfg(feature = "logging")]
+extern crate slog_json;
+#[cfg(feature = "logging")]
 extern crate slog_term;
 
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[cfg(feature = "logging")]
+extern crate serde_json;
 
 extern crate elementwise;
 pub extern crate spaces as geometry;
@@ -22,7 +29,9 @@ mod utils;
 
 pub(crate) mod consts;
 pub mod core;
+#[cfg(feature = "logging")]
 pub mod logging;
+pub mod rand;
 pub mod domains;
 
 #[macro_use] pub mod fa;
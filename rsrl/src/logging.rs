@@ -2,8 +2,16 @@
 
 use slog::*;
 use slog_async;
+use slog_json;
 use slog_term;
-use std::{fmt::Debug, fs::File};
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    fs::File,
+    io::{self, BufWriter, Write},
+    str::FromStr,
+    sync::Mutex,
+};
 
 pub fn stdout() -> Fuse<slog_async::Async> {
     let decorator = slog_term::TermDecorator::new().build();
@@ -25,6 +33,16 @@ pub fn file(file: File) -> Fuse<slog_async::Async> {
         .fuse()
 }
 
+/// Newline-delimited JSON records, including the timestamp, level and message.
+pub fn json(file: File) -> Fuse<slog_async::Async> {
+    let drain = slog_json::Json::new(file).add_default_keys().build().fuse();
+
+    slog_async::Async::new(drain)
+        .overflow_strategy(slog_async::OverflowStrategy::Block)
+        .build()
+        .fuse()
+}
+
 pub fn combine<D1, D2>(drain1: D1, drain2: D2) -> Fuse<Duplicate<D1, D2>>
 where
     D1: Drain,
@@ -42,3 +60,271 @@ where D: SendSyncUnwindSafeDrain<Err = Never, Ok = ()>
         + SendSyncRefUnwindSafeDrain<Ok = (), Err = Never> {
     Logger::root(drain, o!())
 }
+
+/// Minimum level of records to log, overridable per module.
+///
+/// Parsed from a comma-separated list of a default level and `module=level`
+/// overrides, e.g. `info,rsrl::control=debug`. The override with the longest
+/// matching module prefix applies.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Levels {
+    pub default: Level,
+    pub modules: Vec<(String, Level)>,
+}
+
+impl Levels {
+    pub fn new(default: Level) -> Levels { Levels { default, modules: vec![], } }
+
+    pub fn level(&self, module: &str) -> Level {
+        self.modules.iter()
+            .filter(|(prefix, _)| module.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |&(_, level)| level)
+    }
+}
+
+impl Default for Levels {
+    fn default() -> Levels { Levels::new(Level::Info) }
+}
+
+impl FromStr for Levels {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Levels, String> {
+        let parse_level = |l: &str| {
+            Level::from_str(l.trim()).map_err(|_| format!("Unknown log level `{}` in `{}`.", l, s))
+        };
+
+        let mut levels = Levels::default();
+
+        for part in s.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let mut kv = part.splitn(2, '=');
+            let key = kv.next().unwrap();
+
+            match kv.next() {
+                Some(level) => levels.modules.push((key.trim().to_owned(), parse_level(level)?)),
+                None => levels.default = parse_level(key)?,
+            }
+        }
+
+        Ok(levels)
+    }
+}
+
+/// Drain that discards records below the level configured for their module.
+pub struct LevelsFilter<D> {
+    drain: D,
+    levels: Levels,
+}
+
+impl<D: Drain<Ok = ()>> Drain for LevelsFilter<D> {
+    type Ok = ();
+    type Err = D::Err;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> std::result::Result<(), D::Err> {
+        if record.level().is_at_least(self.levels.level(record.module())) {
+            self.drain.log(record, values)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub fn filter<D: Drain<Ok = ()>>(drain: D, levels: Levels) -> LevelsFilter<D> {
+    LevelsFilter { drain, levels, }
+}
+
+/// Root logger writing to the terminal and, if a path is given, JSON records
+/// to that file, keeping only records at or above the configured levels.
+pub fn configured(levels: Levels, json_path: Option<&str>) -> io::Result<Logger> {
+    Ok(match json_path {
+        Some(path) => {
+            let drain = combine(stdout(), json(File::create(path)?)).ignore_res();
+
+            root(filter(drain, levels).fuse())
+        },
+        None => root(filter(stdout(), levels).fuse()),
+    })
+}
+
+/// Tag of the records emitted by `metric!`.
+pub const METRIC_TAG: &str = "metric";
+
+/// Emit a point of the scalar series `name`; see `metric!`.
+pub fn emit_metric(logger: &Logger, name: &str, value: f64) {
+    info!(logger, #"metric", "{}", name; "value" => value);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SeriesFormat {
+    /// Rows of `series,step,value`, with a header.
+    Csv,
+
+    /// Newline-delimited objects with `series`, `step` and `value` fields.
+    Json,
+}
+
+#[derive(Serialize)]
+struct Point {
+    series: String,
+    step: u64,
+    value: f64,
+}
+
+struct SeriesBuffer {
+    points: Vec<Point>,
+    steps: HashMap<String, u64>,
+}
+
+/// Buffered file of scalar series, indexed by the number of points emitted
+/// to each series so far. Flushed when full and when dropped.
+pub struct SeriesSink {
+    format: SeriesFormat,
+    capacity: usize,
+
+    buffer: Mutex<SeriesBuffer>,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl SeriesSink {
+    pub fn create(file: File, format: SeriesFormat, capacity: usize) -> io::Result<SeriesSink> {
+        let mut writer = BufWriter::new(file);
+
+        if format == SeriesFormat::Csv {
+            writeln!(writer, "series,step,value")?;
+        }
+
+        Ok(SeriesSink {
+            format,
+            capacity,
+
+            buffer: Mutex::new(SeriesBuffer { points: vec![], steps: HashMap::new(), }),
+            writer: Mutex::new(writer),
+        })
+    }
+
+    pub fn push(&self, series: String, value: f64) -> io::Result<()> {
+        let is_full = {
+            let mut buffer = self.buffer.lock().unwrap();
+            let step = {
+                let step = buffer.steps.entry(series.clone()).or_insert(0);
+
+                *step += 1;
+                *step - 1
+            };
+
+            buffer.points.push(Point { series, step, value, });
+            buffer.points.len() >= self.capacity
+        };
+
+        if is_full { self.flush() } else { Ok(()) }
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        let points = ::std::mem::replace(&mut self.buffer.lock().unwrap().points, vec![]);
+        let mut writer = self.writer.lock().unwrap();
+
+        for p in points {
+            match self.format {
+                SeriesFormat::Csv => writeln!(writer, "{},{},{}", p.series, p.step, p.value)?,
+                SeriesFormat::Json => {
+                    serde_json::to_writer(&mut *writer, &p).map_err(io::Error::from)?;
+                    writeln!(writer)?;
+                },
+            }
+        }
+
+        writer.flush()
+    }
+}
+
+impl Drop for SeriesSink {
+    fn drop(&mut self) { self.flush().ok(); }
+}
+
+// Extracts the value of a metric record.
+struct ValueCapture(Option<f64>);
+
+impl Serializer for ValueCapture {
+    fn emit_arguments(&mut self, _: Key, _: &fmt::Arguments) -> slog::Result { Ok(()) }
+
+    fn emit_f64(&mut self, key: Key, value: f64) -> slog::Result {
+        if key == "value" {
+            self.0 = Some(value);
+        }
+
+        Ok(())
+    }
+}
+
+/// Drain that diverts records emitted by `metric!` to a `SeriesSink`,
+/// passing all other records through.
+pub struct MetricsDrain<D> {
+    drain: D,
+    sink: SeriesSink,
+}
+
+impl<D: Drain<Ok = ()>> Drain for MetricsDrain<D> {
+    type Ok = ();
+    type Err = D::Err;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> std::result::Result<(), D::Err> {
+        if record.tag() != METRIC_TAG {
+            return self.drain.log(record, values);
+        }
+
+        let mut capture = ValueCapture(None);
+
+        record.kv().serialize(record, &mut capture).ok();
+
+        if let Some(value) = capture.0 {
+            self.sink.push(record.msg().to_string(), value).ok();
+        }
+
+        Ok(())
+    }
+}
+
+pub fn with_metrics<D: Drain<Ok = ()>>(drain: D, sink: SeriesSink) -> MetricsDrain<D> {
+    MetricsDrain { drain, sink, }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{with_metrics, Levels, SeriesFormat, SeriesSink};
+    use slog::{Discard, Drain, Level, Logger};
+    use std::{env, fs::{self, File}};
+
+    #[test]
+    fn test_levels_parse() {
+        let levels: Levels = "warn,rsrl::control=debug,rsrl::control::td=trace".parse().unwrap();
+
+        assert_eq!(levels.default, Level::Warning);
+        assert_eq!(levels.level("algo_hft::agents"), Level::Warning);
+        assert_eq!(levels.level("rsrl::control::actor_critic"), Level::Debug);
+        assert_eq!(levels.level("rsrl::control::td::q_learning"), Level::Trace);
+
+        assert!("verbose".parse::<Levels>().is_err());
+    }
+
+    #[test]
+    fn test_metrics_csv() {
+        let path = env::temp_dir().join(format!("rsrl_metrics_{}.csv", ::std::process::id()));
+
+        {
+            let sink = SeriesSink::create(File::create(&path).unwrap(), SeriesFormat::Csv, 2).unwrap();
+            let logger = Logger::root(with_metrics(Discard, sink).fuse(), o!());
+
+            metric!(logger, "td_error", 0.5);
+            metric!(logger, "trace_norm", 2);
+            metric!(logger, "td_error", -1.0);
+            info!(logger, "not a metric"; "value" => 3.0);
+        }
+
+        let contents = fs::read_to_string(&path).unwrap();
+
+        fs::remove_file(&path).ok();
+
+        assert_eq!(contents, "series,step,value\ntd_error,0,0.5\ntrace_norm,0,2\ntd_error,1,-1\n");
+    }
+}
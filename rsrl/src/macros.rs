@@ -29,3 +29,15 @@ macro_rules! import_all {
         pub use self::$module::*;
     };
 }
+
+/// Emit a point of a named scalar series, e.g. `metric!(logger, "td_error", value)`.
+///
+/// Points are logged as records tagged with `logging::METRIC_TAG`, which
+/// `logging::with_metrics` diverts to a buffered CSV or JSON file.
+#[cfg(feature = "logging")]
+#[macro_export]
+macro_rules! metric {
+    ($logger:expr, $name:expr, $value:expr) => {
+        $crate::logging::emit_metric(&$logger, $name, ($value) as f64)
+    };
+}
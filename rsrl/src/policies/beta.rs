@@ -4,10 +4,10 @@ use crate::{
     core::{Algorithm, Parameter},
     fa::{Approximator, Embedding, Features, Parameterised, VFunction},
     geometry::{Vector, Matrix, MatrixView, MatrixViewMut},
-    policies::{DifferentiablePolicy, ParameterisedPolicy, Policy},
+    policies::{DifferentiablePolicy, Entropy, KLDivergence, ParameterisedPolicy, Policy, SkippedUpdates},
 };
 use ndarray::Axis;
-use rand::{thread_rng, rngs::{ThreadRng}};
+use crate::rand::{from_entropy, seeded, SmallRng};
 use rstat::{
     Distribution, ContinuousDistribution,
     core::Modes,
@@ -17,6 +17,11 @@ use serde::de::{self, Deserialize, Deserializer, Visitor, SeqAccess, MapAccess};
 use std::{fmt::{self, Debug}, marker::PhantomData, ops::AddAssign};
 
 const MIN_TOL: f64 = 1.0;
+const MAX_TOL: f64 = 1e6;
+
+/// Offset a raw approximation by `MIN_TOL`, clamping negative, NaN and
+/// infinite values into the valid range.
+fn bounded(raw: f64) -> f64 { (raw + MIN_TOL).max(MIN_TOL).min(MAX_TOL) }
 
 #[derive(Clone, Debug, Serialize)]
 pub struct Beta<F> {
@@ -24,7 +29,10 @@ pub struct Beta<F> {
     beta: F,
 
     #[serde(skip_serializing)]
-    rng: ThreadRng,
+    n_skipped: usize,
+
+    #[serde(skip_serializing)]
+    rng: SmallRng,
 }
 
 impl<F> Beta<F> {
@@ -32,7 +40,9 @@ impl<F> Beta<F> {
         Beta {
             alpha, beta,
 
-            rng: thread_rng(),
+            n_skipped: 0,
+
+            rng: from_entropy(),
         }
     }
 
@@ -40,14 +50,14 @@ impl<F> Beta<F> {
     pub fn alpha<S>(&self, s: &S) -> f64
         where F: VFunction<S>,
     {
-        self.alpha.evaluate(&self.alpha.embed(s)).unwrap() + MIN_TOL
+        bounded(self.alpha.evaluate(&self.alpha.embed(s)).unwrap())
     }
 
     #[inline]
     pub fn beta<S>(&self, s: &S) -> f64
         where F: VFunction<S>,
     {
-        self.beta.evaluate(&self.beta.embed(s)).unwrap() + MIN_TOL
+        bounded(self.beta.evaluate(&self.beta.embed(s)).unwrap())
     }
 
     #[inline]
@@ -75,7 +85,11 @@ impl<F> Beta<F> {
     }
 }
 
-impl<F> Algorithm for Beta<F> {}
+impl<F> Algorithm for Beta<F> {
+    fn reseed(&mut self, seed: u64) {
+        self.rng = seeded(seed);
+    }
+}
 
 impl<S, F: VFunction<S>> Policy<S> for Beta<F> {
     type Action = f64;
@@ -99,11 +113,11 @@ impl<S, F: VFunction<S>> Policy<S> for Beta<F> {
 impl<S, F: VFunction<S> + Parameterised> DifferentiablePolicy<S> for Beta<F> {
     fn grad_log(&self, input: &S, a: f64) -> Matrix<f64> {
         let phi_alpha = self.alpha.embed(input);
-        let val_alpha = self.alpha.evaluate(&phi_alpha).unwrap() + MIN_TOL;
+        let val_alpha = bounded(self.alpha.evaluate(&phi_alpha).unwrap());
         let jac_alpha = self.alpha.jacobian(&phi_alpha);
 
         let phi_beta = self.beta.embed(input);
-        let val_beta = self.beta.evaluate(&phi_beta).unwrap() + MIN_TOL;
+        let val_beta = bounded(self.beta.evaluate(&phi_beta).unwrap());
         let jac_beta = self.beta.jacobian(&phi_beta);
 
         let [gl_alpha, gl_beta] = self.gl_partial(val_alpha, val_beta, a);
@@ -112,36 +126,62 @@ impl<S, F: VFunction<S> + Parameterised> DifferentiablePolicy<S> for Beta<F> {
     }
 }
 
-impl<F: Parameterised> Parameterised for Beta<F> {
-    fn weights(&self) -> Matrix<f64> {
-        stack![Axis(0), self.alpha.weights(), self.beta.weights()]
-    }
+impl<S, F: VFunction<S>> KLDivergence<S> for Beta<F> {
+    fn kl_divergence(&self, other: &Self, input: &S) -> f64 {
+        use special_fun::FloatSpecial;
 
-    fn weights_view(&self) -> MatrixView<f64> {
-        unimplemented!()
-    }
+        let (a1, b1) = (self.alpha(input), self.beta(input));
+        let (a2, b2) = (other.alpha(input), other.beta(input));
 
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        unimplemented!()
+        a2.logbeta(b2) - a1.logbeta(b1)
+            + (a1 - a2) * a1.digamma()
+            + (b1 - b2) * b1.digamma()
+            + (a2 - a1 + b2 - b1) * (a1 + b1).digamma()
     }
+}
 
-    fn weights_dim(&self) -> (usize, usize) {
-        (self.alpha.weights_dim().0 + self.beta.weights_dim().0, 1)
+impl<S, F: VFunction<S>> Entropy<S> for Beta<F> {
+    fn entropy(&mut self, input: &S) -> f64 {
+        use special_fun::FloatSpecial;
+
+        let (a, b) = (self.alpha(input), self.beta(input));
+
+        a.logbeta(b) - (a - 1.0) * a.digamma() - (b - 1.0) * b.digamma() + (a + b - 2.0) * (a + b).digamma()
     }
 }
 
+impl<F> SkippedUpdates for Beta<F> {
+    fn take_skipped(&mut self) -> usize { ::std::mem::replace(&mut self.n_skipped, 0) }
+}
+
+impl_parameterised!(Beta<F> => alpha: F, beta: F);
+
 impl<S, F: VFunction<S> + Parameterised> ParameterisedPolicy<S> for Beta<F> {
     fn update(&mut self, input: &S, a: f64, error: f64) {
         let phi_alpha = self.alpha.embed(input);
-        let val_alpha = self.alpha.evaluate(&phi_alpha).unwrap() + MIN_TOL;
+        let val_alpha = bounded(self.alpha.evaluate(&phi_alpha).unwrap());
 
         let phi_beta = self.beta.embed(input);
-        let val_beta = self.beta.evaluate(&phi_beta).unwrap() + MIN_TOL;
+        let val_beta = bounded(self.beta.evaluate(&phi_beta).unwrap());
 
         let [gl_alpha, gl_beta] = self.gl_partial(val_alpha, val_beta, a);
 
-        self.alpha.update(&phi_alpha, gl_alpha * error).ok();
-        self.beta.update(&phi_beta, gl_beta * error).ok();
+        let (update_alpha, update_beta) = (gl_alpha * error, gl_beta * error);
+
+        // Apply both updates or neither, so that a pathological step cannot
+        // move one parameter without the other.
+        if !(update_alpha.is_finite() && update_beta.is_finite()) {
+            self.n_skipped += 1;
+
+            return;
+        }
+
+        let alpha_result = self.alpha.update(&phi_alpha, update_alpha);
+        let beta_result = self.beta.update(&phi_beta, update_beta);
+
+        if alpha_result.is_err() || beta_result.is_err() {
+            self.n_skipped += 1;
+        }
     }
 
     fn update_raw(&mut self, errors: Matrix<f64>) {
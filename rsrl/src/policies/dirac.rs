@@ -1,6 +1,6 @@
 use crate::{
     core::*,
-    fa::{Approximator, VFunction, Parameterised, Embedding, Features, IntoVector},
+    fa::{Approximator, VFunction, Parameterised, WeightsView, Embedding, Features, IntoVector},
     geometry::{MatrixView, MatrixViewMut},
     policies::{
         DifferentiablePolicy,
@@ -12,6 +12,7 @@ use elementwise::arithmetic::{ElementwiseSub, ElementwiseMul};
 use ndarray::Axis;
 use std::ops::AddAssign;
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Dirac<F> {
     pub fa: F,
 }
@@ -22,19 +23,7 @@ impl<F> Dirac<F> {
     }
 }
 
-impl<F: Parameterised> Parameterised for Dirac<F> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa.weights_view_mut()
-    }
-}
+impl_parameterised!(Dirac<F> => fa: F);
 
 impl<F> Algorithm for Dirac<F> {}
 
@@ -76,7 +65,7 @@ where
 
 impl<S, F> ParameterisedPolicy<S> for Dirac<F>
 where
-    F: Approximator + Embedding<S> + Parameterised,
+    F: Approximator + Embedding<S> + WeightsView,
     F::Output: PartialEq + ElementwiseSub + ElementwiseMul<f64>,
 {
     fn update(&mut self, input: &S, a: F::Output, error: f64) {
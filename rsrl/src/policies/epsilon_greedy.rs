@@ -1,13 +1,16 @@
-use super::{FinitePolicy, Greedy, Policy, Random};
+use super::{FinitePolicy, Greedy, LinkedPolicy, Policy, Random, TieBreak};
 use crate::{core::*, domains::Transition, fa::QFunction};
-use rand::{rngs::ThreadRng, thread_rng, Rng};
+use crate::rand::{from_entropy, seeded, Rng, SmallRng};
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EpsilonGreedy<Q> {
     greedy: Greedy<Q>,
     random: Random,
 
     epsilon: Parameter,
-    rng: ThreadRng,
+
+    #[serde(skip, default = "crate::rand::from_entropy")]
+    rng: SmallRng,
 }
 
 impl<Q> EpsilonGreedy<Q> {
@@ -17,7 +20,7 @@ impl<Q> EpsilonGreedy<Q> {
             random,
 
             epsilon: epsilon.into(),
-            rng: thread_rng(),
+            rng: from_entropy(),
         }
     }
 
@@ -29,6 +32,14 @@ impl<Q> EpsilonGreedy<Q> {
 
         EpsilonGreedy::new(greedy, random, epsilon)
     }
+
+    pub fn with_tie_break(self, tie_break: TieBreak) -> Self {
+        EpsilonGreedy { greedy: self.greedy.with_tie_break(tie_break), ..self }
+    }
+
+    /// Current exploration rate, which follows the schedule of the `Parameter`
+    /// it was built from and steps at the end of each episode.
+    pub fn epsilon(&self) -> f64 { self.epsilon.value() }
 }
 
 impl<Q> Algorithm for EpsilonGreedy<Q> {
@@ -38,6 +49,19 @@ impl<Q> Algorithm for EpsilonGreedy<Q> {
         self.greedy.handle_terminal();
         self.random.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.rng = seeded(stream.seed(0));
+
+        self.greedy.reseed(stream.seed(1));
+        self.random.reseed(stream.seed(2));
+    }
+}
+
+impl<Q> LinkedPolicy<Q> for EpsilonGreedy<Q> {
+    fn link(&mut self, q_func: Q) { self.greedy.link(q_func); }
 }
 
 impl<S, Q: QFunction<S>> Policy<S> for EpsilonGreedy<Q> {
@@ -1,8 +1,9 @@
 use crate::{
-    fa::{self, Approximator, Embedding, Parameterised, EvaluationResult, Features, UpdateResult},
+    fa::{self, Approximator, Embedding, Parameterised, WeightsView, EvaluationResult, Features, UpdateResult},
     geometry::{Matrix, MatrixView, MatrixViewMut, Vector},
-    utils::pinv,
 };
+#[cfg(feature = "linalg")]
+use crate::utils::pinv;
 use ndarray::Axis;
 use std::ops::MulAssign;
 
@@ -11,7 +12,7 @@ pub trait Mean<I, S>: Approximator + Embedding<I> {
 
     fn grad_log(&self, input: &I, a: &Self::Output, stddev: S) -> Matrix<f64>;
 
-    fn update_mean(&mut self, input: &I, a: &Self::Output, stddev: S, error: f64);
+    fn update_mean(&mut self, input: &I, a: &Self::Output, stddev: S, error: f64) -> UpdateResult<()>;
 }
 
 // Scalar:
@@ -33,11 +34,11 @@ impl<I, F: Approximator<Output = f64> + Embedding<I>> Mean<I, f64> for Scalar<F>
         self.0.jacobian(&phi) * gl_partial
     }
 
-    fn update_mean(&mut self, input: &I, a: &f64, stddev: f64, error: f64) {
+    fn update_mean(&mut self, input: &I, a: &f64, stddev: f64, error: f64) -> UpdateResult<()> {
         let phi = self.0.embed(input);
         let mean = self.evaluate(&phi).unwrap();
 
-        self.update(&phi, (a - mean) / stddev / stddev * error).ok();
+        self.update(&phi, (a - mean) / stddev / stddev * error)
     }
 }
 
@@ -67,14 +68,14 @@ impl<I, F: Approximator<Output = [f64; 2]> + Embedding<I>> Mean<I, f64> for Pair
         g
     }
 
-    fn update_mean(&mut self, input: &I, actions: &[f64; 2], stddev: f64, error: f64) {
+    fn update_mean(&mut self, input: &I, actions: &[f64; 2], stddev: f64, error: f64) -> UpdateResult<()> {
         let phi = self.0.embed(input);
         let means = self.evaluate(&phi).unwrap();
 
         self.update(&phi, [
             (actions[0] - means[0]) / stddev / stddev * error,
             (actions[1] - means[1]) / stddev / stddev * error
-        ]).ok();
+        ])
     }
 }
 
@@ -98,14 +99,14 @@ impl<I, F: Approximator<Output = [f64; 2]> + Embedding<I>> Mean<I, [f64; 2]> for
         g
     }
 
-    fn update_mean(&mut self, input: &I, actions: &[f64; 2], stddev: [f64; 2], error: f64) {
+    fn update_mean(&mut self, input: &I, actions: &[f64; 2], stddev: [f64; 2], error: f64) -> UpdateResult<()> {
         let phi = self.0.embed(input);
         let means = self.evaluate(&phi).unwrap();
 
         self.update(&phi, [
             (actions[0] - means[0]) / stddev[0] / stddev[0] * error,
             (actions[1] - means[1]) / stddev[1] / stddev[1] * error
-        ]).ok();
+        ])
     }
 }
 
@@ -115,6 +116,7 @@ pub struct Multi<F: Approximator<Output = Vector<f64>>>(pub F);
 
 impl_newtype_fa!(Multi.0 => Vector<f64>);
 
+#[cfg(feature = "linalg")]
 impl<F: Approximator<Output = Vector<f64>>> Multi<F> {
     fn gl_fmv_partial(&self, phi: &Features, actions: &Vector<f64>, sigma: Matrix<f64>) -> Matrix<f64> {
         let means = self.evaluate(&phi).unwrap();
@@ -150,13 +152,13 @@ impl<I, F: Approximator<Output = Vector<f64>> + Embedding<I>> Mean<I, f64> for M
         jacobian * gl_partial.t()
     }
 
-    fn update_mean(&mut self, input: &I, actions: &Vector<f64>, stddev: f64, error: f64) {
+    fn update_mean(&mut self, input: &I, actions: &Vector<f64>, stddev: f64, error: f64) -> UpdateResult<()> {
         let phi = self.0.embed(input);
         let means = self.evaluate(&phi).unwrap();
 
         let gl_partial_scaled = (means - actions).mapv_into(|v| -v / stddev / stddev * error);
 
-        self.update(&phi, gl_partial_scaled).ok();
+        self.update(&phi, gl_partial_scaled)
     }
 }
 
@@ -185,7 +187,7 @@ impl<I, F: Approximator<Output = Vector<f64>> + Embedding<I>> Mean<I, Vector<f64
         jacobian * gl_partial.t()
     }
 
-    fn update_mean(&mut self, input: &I, actions: &Vector<f64>, stddev: Vector<f64>, error: f64) {
+    fn update_mean(&mut self, input: &I, actions: &Vector<f64>, stddev: Vector<f64>, error: f64) -> UpdateResult<()> {
         let phi = self.0.embed(input);
         let means = self.evaluate(&phi).unwrap();
 
@@ -195,10 +197,11 @@ impl<I, F: Approximator<Output = Vector<f64>> + Embedding<I>> Mean<I, Vector<f64
             .map(|(m, (a, s))| (a - m) / s / s * error)
             .collect();
 
-        self.update(&phi, gl_partial_scaled).ok();
+        self.update(&phi, gl_partial_scaled)
     }
 }
 
+#[cfg(feature = "linalg")]
 impl<I, F: Approximator<Output = Vector<f64>> + Embedding<I>> Mean<I, Matrix<f64>> for Multi<F> {
     fn mean(&self, input: &I) -> Self::Output {
         self.0.evaluate(&self.0.embed(input)).unwrap()
@@ -217,10 +220,10 @@ impl<I, F: Approximator<Output = Vector<f64>> + Embedding<I>> Mean<I, Matrix<f64
         jacobian * gl_partial.t()
     }
 
-    fn update_mean(&mut self, input: &I, actions: &Vector<f64>, sigma: Matrix<f64>, error: f64) {
+    fn update_mean(&mut self, input: &I, actions: &Vector<f64>, sigma: Matrix<f64>, error: f64) -> UpdateResult<()> {
         let phi = self.embed(input);
         let gl_partial = self.gl_fmv_partial(&phi, actions, sigma).index_axis_move(Axis(1), 0);
 
-        self.update(&phi, gl_partial * error).ok();
+        self.update(&phi, gl_partial * error)
     }
 }
@@ -1,11 +1,11 @@
 use crate::{
     core::{Algorithm, Parameter},
-    fa::{Approximator, Embedding, Features, Parameterised, VFunction},
+    fa::{Approximator, Embedding, Features, Parameterised, VFunction, WeightsView},
     geometry::{Space, Matrix, MatrixView, MatrixViewMut, Vector, continuous::Interval},
-    policies::{DifferentiablePolicy, ParameterisedPolicy, Policy},
+    policies::{DifferentiablePolicy, Entropy, KLDivergence, ParameterisedPolicy, Policy, SkippedUpdates},
 };
 use ndarray::Axis;
-use rand::{thread_rng, rngs::{ThreadRng}};
+use crate::rand::{from_entropy, seeded, SmallRng};
 use rstat::{
     Distribution, ContinuousDistribution,
     univariate::continuous::Normal,
@@ -23,11 +23,14 @@ import_all!(dbuilder);
 
 #[derive(Clone, Debug, Serialize)]
 pub struct Gaussian<M, S> {
-    mean: M,
-    stddev: S,
+    pub mean: M,
+    pub stddev: S,
 
     #[serde(skip_serializing)]
-    rng: ThreadRng,
+    n_skipped: usize,
+
+    #[serde(skip_serializing)]
+    rng: SmallRng,
 }
 
 impl<M, S> Gaussian<M, S> {
@@ -35,7 +38,9 @@ impl<M, S> Gaussian<M, S> {
         Gaussian {
             mean, stddev,
 
-            rng: thread_rng(),
+            n_skipped: 0,
+
+            rng: from_entropy(),
         }
     }
 }
@@ -60,7 +65,11 @@ impl<M, S> Gaussian<M, S> {
     }
 }
 
-impl<M, S> Algorithm for Gaussian<M, S> {}
+impl<M, S> Algorithm for Gaussian<M, S> {
+    fn reseed(&mut self, seed: u64) {
+        self.rng = seeded(seed);
+    }
+}
 
 impl<I, M, S> Policy<I> for Gaussian<M, S>
 where
@@ -107,36 +116,40 @@ where
     }
 }
 
-impl<M, S> Parameterised for Gaussian<M, S>
+impl<I, M, S> KLDivergence<I> for Gaussian<M, S>
 where
-    M: Parameterised,
-    S: Parameterised,
+    M: Mean<I, f64> + Approximator<Output = f64>,
+    S: StdDev<I, f64> + Approximator<Output = f64>,
 {
-    fn weights(&self) -> Matrix<f64> {
-        unimplemented!()
-    }
+    fn kl_divergence(&self, other: &Self, input: &I) -> f64 {
+        let (m1, s1) = (self.mean(input), self.stddev(input));
+        let (m2, s2) = (other.mean(input), other.stddev(input));
 
-    fn weights_view(&self) -> MatrixView<f64> {
-        unimplemented!()
+        (s2 / s1).ln() + (s1 * s1 + (m1 - m2).powi(2)) / (2.0 * s2 * s2) - 0.5
     }
+}
 
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        unimplemented!()
+impl<I, M, S> Entropy<I> for Gaussian<M, S>
+where
+    M: Mean<I, f64> + Approximator<Output = f64>,
+    S: StdDev<I, f64> + Approximator<Output = f64>,
+{
+    fn entropy(&mut self, input: &I) -> f64 {
+        0.5 * (1.0 + (2.0 * ::std::f64::consts::PI).ln()) + self.stddev(input).ln()
     }
+}
 
-    fn weights_dim(&self) -> (usize, usize) {
-        let dim_mean = self.mean.weights_dim();
-        let dim_stddev = self.stddev.weights_dim();
-
-        (dim_mean.0 + dim_stddev.0, dim_mean.1)
-    }
+impl<M, S> SkippedUpdates for Gaussian<M, S> {
+    fn take_skipped(&mut self) -> usize { ::std::mem::replace(&mut self.n_skipped, 0) }
 }
 
+impl_parameterised!(Gaussian<M, S> => mean: M, stddev: S);
+
 impl<I, M, S> ParameterisedPolicy<I> for Gaussian<M, S>
 where
-    M: Mean<I, <S as Approximator>::Output> + Parameterised,
+    M: Mean<I, <S as Approximator>::Output> + WeightsView,
     M::Output: Clone + Debug,
-    S: StdDev<I, <M as Approximator>::Output> + Parameterised,
+    S: StdDev<I, <M as Approximator>::Output> + WeightsView,
     S::Output: Clone + Debug,
     GB: DistBuilder<M::Output, S::Output>,
     GBSupport<M::Output, S::Output>: Space<Value = M::Output>
@@ -145,11 +158,21 @@ where
         let mean = self.mean(input);
         let stddev = self.stddev(input);
 
-        self.mean.update_mean(input, &a, stddev, error);
-        self.stddev.update_stddev(input, &a, mean, error);
+        let mean_result = self.mean.update_mean(input, &a, stddev, error);
+        let stddev_result = self.stddev.update_stddev(input, &a, mean, error);
+
+        if mean_result.is_err() || stddev_result.is_err() {
+            self.n_skipped += 1;
+        }
     }
 
     fn update_raw(&mut self, errors: Matrix<f64>) {
+        if !errors.iter().all(|e| e.is_finite()) {
+            self.n_skipped += 1;
+
+            return;
+        }
+
         let mf = self.mean.n_features();
         let sf = self.stddev.n_features();
 
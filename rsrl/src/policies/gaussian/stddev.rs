@@ -1,11 +1,16 @@
 use crate::{
-    fa::{Approximator, Embedding, Parameterised, EvaluationResult, Features, UpdateResult},
+    fa::{Approximator, Embedding, Parameterised, WeightsView, EvaluationResult, Features, UpdateResult},
     geometry::{Matrix, MatrixView, MatrixViewMut, Vector},
 };
 use ndarray::Axis;
 use std::ops::MulAssign;
 
 const MIN_STDDEV: f64 = 0.05;
+const MAX_STDDEV: f64 = 1e6;
+
+/// Offset a raw approximation by `MIN_STDDEV`, clamping negative, NaN and
+/// infinite values into the valid range.
+fn bounded(raw: f64) -> f64 { (raw + MIN_STDDEV).max(MIN_STDDEV).min(MAX_STDDEV) }
 
 fn gl_from_mv(a: f64, mean: f64, stddev: f64) -> f64 {
     let diff_sq = (a - mean).powi(2);
@@ -18,7 +23,7 @@ pub trait StdDev<I, M>: Approximator + Embedding<I> {
 
     fn grad_log(&self, input: &I, a: &M, mean: M) -> Matrix<f64>;
 
-    fn update_stddev(&mut self, input: &I, a: &M, mean: M, error: f64);
+    fn update_stddev(&mut self, input: &I, a: &M, mean: M, error: f64) -> UpdateResult<()>;
 }
 
 // Constant:
@@ -29,7 +34,9 @@ impl<V: Clone> Parameterised for Constant<V> {
     fn weights(&self) -> Matrix<f64> {
         Matrix::zeros((0, 0))
     }
+}
 
+impl<V: Clone> WeightsView for Constant<V> {
     fn weights_view(&self) -> MatrixView<f64> {
         MatrixView::from_shape((0, 0), &[]).unwrap()
     }
@@ -113,7 +120,7 @@ where
         Matrix::default((0, 0))
     }
 
-    fn update_stddev(&mut self, _: &I, _: &M, _: M, _: f64) {}
+    fn update_stddev(&mut self, _: &I, _: &M, _: M, _: f64) -> UpdateResult<()> { Ok(()) }
 }
 
 // Scalar:
@@ -124,22 +131,22 @@ impl_newtype_fa!(Scalar.0 => f64);
 
 impl<I, F: Approximator<Output = f64> + Embedding<I>> StdDev<I, f64> for Scalar<F> {
     fn stddev(&self, input: &I) -> Self::Output {
-        self.0.evaluate(&self.0.embed(input)).unwrap() + MIN_STDDEV
+        bounded(self.0.evaluate(&self.0.embed(input)).unwrap())
     }
 
     fn grad_log(&self, input: &I, a: &f64, mean: f64) -> Matrix<f64> {
         let phi = self.embed(input);
-        let stddev = self.evaluate(&phi).unwrap() + MIN_STDDEV;
+        let stddev = bounded(self.evaluate(&phi).unwrap());
         let gl_partial = gl_from_mv(*a, mean, stddev);
 
         (phi.expanded(self.0.n_features()) * gl_partial).insert_axis(Axis(1))
     }
 
-    fn update_stddev(&mut self, input: &I, a: &f64, mean: f64, error: f64) {
+    fn update_stddev(&mut self, input: &I, a: &f64, mean: f64, error: f64) -> UpdateResult<()> {
         let phi = self.embed(input);
-        let stddev = self.evaluate(&phi).unwrap() + MIN_STDDEV;
+        let stddev = bounded(self.evaluate(&phi).unwrap());
 
-        self.update(&phi, gl_from_mv(*a, mean, stddev) * error).ok();
+        self.update(&phi, gl_from_mv(*a, mean, stddev) * error)
     }
 }
 
@@ -153,7 +160,7 @@ impl<I, F: Approximator<Output = [f64; 2]> + Embedding<I>> StdDev<I, [f64; 2]> f
     fn stddev(&self, input: &I) -> Self::Output {
         let raw = self.0.evaluate(&self.0.embed(input)).unwrap();
 
-        [raw[0] + MIN_STDDEV, raw[1] + MIN_STDDEV]
+        [bounded(raw[0]), bounded(raw[1])]
     }
 
     fn grad_log(&self, input: &I, a: &[f64; 2], mean: [f64; 2]) -> Matrix<f64> {
@@ -163,21 +170,21 @@ impl<I, F: Approximator<Output = [f64; 2]> + Embedding<I>> StdDev<I, [f64; 2]> f
         let n_features = self.0.n_features();
         let phi = phi.expanded(n_features);
 
-        let gl_partial_0 = gl_from_mv(a[0], mean[0], stddev[0] + MIN_STDDEV);
-        let gl_partial_1 = gl_from_mv(a[1], mean[1], stddev[1] + MIN_STDDEV);
+        let gl_partial_0 = gl_from_mv(a[0], mean[0], bounded(stddev[0]));
+        let gl_partial_1 = gl_from_mv(a[1], mean[1], bounded(stddev[1]));
 
         Vector::from_iter(
             phi.iter().map(|v| v * gl_partial_0).chain(phi.iter().map(|v| v * gl_partial_1))
         ).into_shape((2, n_features)).unwrap().reversed_axes()
     }
 
-    fn update_stddev(&mut self, input: &I, a: &[f64; 2], mean: [f64; 2], error: f64) {
+    fn update_stddev(&mut self, input: &I, a: &[f64; 2], mean: [f64; 2], error: f64) -> UpdateResult<()> {
         let phi = self.embed(input);
         let stddev = self.evaluate(&phi).unwrap();
 
         self.update(&phi, [
-            gl_from_mv(a[0], mean[0], stddev[0] + MIN_STDDEV) * error,
-            gl_from_mv(a[1], mean[1], stddev[1] + MIN_STDDEV) * error
-        ]).ok();
+            gl_from_mv(a[0], mean[0], bounded(stddev[0])) * error,
+            gl_from_mv(a[1], mean[1], bounded(stddev[1])) * error
+        ])
     }
 }
@@ -1,27 +1,84 @@
 use crate::{
     core::*,
     fa::QFunction,
-    policies::{FinitePolicy, Policy},
-    utils::{argmax_choose, argmaxima},
+    policies::{FinitePolicy, LinkedPolicy, Policy},
+    rand::{argmax_choose, from_entropy, seeded, SmallRng},
+    utils::argmaxima,
 };
-use rand::thread_rng;
+use std::str::FromStr;
+
+/// Rule by which a greedy policy chooses between actions of equal value.
+///
+/// Values within a small tolerance of the maximum are considered tied, except
+/// by `First`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TieBreak {
+    /// The first action attaining the exact maximum.
+    First,
+
+    /// Uniformly at random among the tied actions.
+    Random,
+
+    /// The tied action with the lowest index.
+    LowestIndex,
+}
+
+impl Default for TieBreak {
+    fn default() -> TieBreak { TieBreak::Random }
+}
 
-pub struct Greedy<Q>(Q);
+impl FromStr for TieBreak {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TieBreak, String> {
+        match s.trim() {
+            "first" => Ok(TieBreak::First),
+            "random" => Ok(TieBreak::Random),
+            "lowest_index" => Ok(TieBreak::LowestIndex),
+            _ => Err(format!("Tie-break `{}` must be one of first, random or lowest_index.", s)),
+        }
+    }
+}
+
+/// Greedy policy over a Q-function.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Greedy<Q>(
+    Q,
+    TieBreak,
+    #[serde(skip, default = "crate::rand::from_entropy")] SmallRng,
+);
 
 impl<Q> Greedy<Q> {
-    pub fn new(q_func: Q) -> Self { Greedy(q_func) }
+    pub fn new(q_func: Q) -> Self { Greedy(q_func, TieBreak::default(), from_entropy()) }
+
+    pub fn with_tie_break(self, tie_break: TieBreak) -> Self { Greedy(self.0, tie_break, self.2) }
+
+    pub fn tie_break(&self) -> TieBreak { self.1 }
+
+    fn choose(&mut self, qs: &[f64]) -> usize {
+        match self.1 {
+            TieBreak::First => qs.iter().enumerate().fold(0, |best, (i, &q)| if q > qs[best] { i } else { best }),
+            TieBreak::Random => argmax_choose(&mut self.2, qs).1,
+            TieBreak::LowestIndex => argmaxima(qs).1[0],
+        }
+    }
 }
 
-impl<Q> Algorithm for Greedy<Q> {}
+impl<Q> Algorithm for Greedy<Q> {
+    fn reseed(&mut self, seed: u64) { self.2 = seeded(seed); }
+}
+
+impl<Q> LinkedPolicy<Q> for Greedy<Q> {
+    fn link(&mut self, q_func: Q) { self.0 = q_func; }
+}
 
 impl<S, Q: QFunction<S>> Policy<S> for Greedy<Q> {
     type Action = usize;
 
     fn mpa(&mut self, s: &S) -> usize {
-        self.0
-            .evaluate(&self.0.embed(s))
-            .map(|qs| argmax_choose(&mut thread_rng(), qs.as_slice().unwrap()).1)
-            .unwrap()
+        let qs = self.0.evaluate(&self.0.embed(s)).unwrap();
+
+        self.choose(qs.as_slice().unwrap())
     }
 
     fn probability(&mut self, s: &S, a: usize) -> f64 { self.probabilities(s)[a] }
@@ -31,27 +88,29 @@ impl<S, Q: QFunction<S>> FinitePolicy<S> for Greedy<Q> {
     fn n_actions(&self) -> usize { self.0.n_outputs() }
 
     fn probabilities(&mut self, s: &S) -> Vector<f64> {
-        self.0
-            .evaluate(&self.0.embed(s))
-            .map(|qs| {
-                let mut ps = vec![0.0; qs.len()];
+        let qs = self.0.evaluate(&self.0.embed(s)).unwrap();
+        let qs = qs.as_slice().unwrap();
+        let mut ps = vec![0.0; qs.len()];
 
-                let (_, maxima) = argmaxima(qs.as_slice().unwrap());
+        match self.1 {
+            TieBreak::Random => {
+                let (_, maxima) = argmaxima(qs);
 
                 let p = 1.0 / maxima.len() as f64;
                 for i in maxima {
                     ps[i] = p;
                 }
+            },
+            _ => ps[self.choose(qs)] = 1.0,
+        }
 
-                ps.into()
-            })
-            .unwrap()
+        ps.into()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{FinitePolicy, Greedy, Policy};
+    use super::{FinitePolicy, Greedy, Policy, TieBreak};
     use crate::{fa::mocking::MockQ, geometry::Vector};
 
     #[test]
@@ -124,4 +183,17 @@ mod tests {
             Vector::from_vec(vec![0.25, 0.25, 0.25, 0.25])
         );
     }
+
+    #[test]
+    fn test_tie_break() {
+        let mut p = Greedy::new(MockQ::new_shared(None)).with_tie_break(TieBreak::LowestIndex);
+
+        assert_eq!(p.sample(&vec![1.0, 2.0, 2.0 + 1e-9].into()), 1);
+        assert_eq!(p.probabilities(&vec![1.0, 2.0, 2.0].into()), Vector::from_vec(vec![0.0, 1.0, 0.0]));
+
+        let mut p = p.with_tie_break(TieBreak::First);
+
+        assert_eq!(p.sample(&vec![1.0, 2.0, 2.0 + 1e-9].into()), 2);
+        assert_eq!(p.sample(&vec![2.0, 1.0, 2.0].into()), 0);
+    }
 }
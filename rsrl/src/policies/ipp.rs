@@ -1,12 +1,8 @@
 use crate::core::*;
 use crate::fa::Parameterised;
-use crate::geometry::{Space, MatrixView, MatrixViewMut};
-use crate::policies::{FinitePolicy, DifferentiablePolicy, ParameterisedPolicy, Policy};
-use rand::{
-    distributions::{Distribution, Normal},
-    rngs::ThreadRng,
-    Rng,
-    thread_rng,
+use crate::geometry::Space;
+use crate::policies::{
+    FinitePolicy, DifferentiablePolicy, Entropy, KLDivergence, ParameterisedPolicy, Policy, SkippedUpdates,
 };
 use ndarray::Axis;
 use std::ops::Add;
@@ -24,6 +20,13 @@ impl<P1: Algorithm, P2: Algorithm> Algorithm for IPP<P1, P2> {
         self.0.handle_terminal();
         self.1.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.0.reseed(stream.seed(0));
+        self.1.reseed(stream.seed(1));
+    }
 }
 
 impl<S, P1, P2> Policy<S> for IPP<P1, P2>
@@ -56,24 +59,38 @@ where
     }
 }
 
-impl<P1: Parameterised, P2: Parameterised> Parameterised for IPP<P1, P2> {
-    fn weights(&self) -> Matrix<f64> {
-        stack![Axis(1), self.0.weights(), self.1.weights()]
+impl<S, P1, P2> KLDivergence<S> for IPP<P1, P2>
+where
+    P1: KLDivergence<S>,
+    P2: KLDivergence<S>,
+{
+    fn kl_divergence(&self, other: &Self, s: &S) -> f64 {
+        self.0.kl_divergence(&other.0, s) + self.1.kl_divergence(&other.1, s)
     }
+}
 
-    fn weights_view(&self) -> MatrixView<f64> {
-        unimplemented!()
-    }
+impl<S, P1, P2> Entropy<S> for IPP<P1, P2>
+where
+    P1: Entropy<S>,
+    P2: Entropy<S>,
+{
+    fn entropy(&mut self, s: &S) -> f64 { self.0.entropy(s) + self.1.entropy(s) }
+}
+
+impl<P1: SkippedUpdates, P2: SkippedUpdates> SkippedUpdates for IPP<P1, P2> {
+    fn take_skipped(&mut self) -> usize { self.0.take_skipped() + self.1.take_skipped() }
+}
 
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        unimplemented!()
+impl<P1: Parameterised, P2: Parameterised> Parameterised for IPP<P1, P2> {
+    fn weights(&self) -> Matrix<f64> {
+        stack![Axis(0), self.0.weights(), self.1.weights()]
     }
 
     fn weights_dim(&self) -> (usize, usize) {
         let d0 = self.0.weights_dim();
-        let d1 = self.0.weights_dim();
+        let d1 = self.1.weights_dim();
 
-        (d0.0 + d1.0, d0.1 + d1.1)
+        (d0.0 + d1.0, d0.1)
     }
 }
 
@@ -87,7 +104,10 @@ where
         self.1.update(input, a.1, error);
     }
 
-    fn update_raw(&mut self, _errors: Matrix<f64>) {
-        unimplemented!()
+    fn update_raw(&mut self, errors: Matrix<f64>) {
+        let n0 = self.0.weights_dim().0;
+
+        self.0.update_raw(errors.slice(s![0..n0, ..]).to_owned());
+        self.1.update_raw(errors.slice(s![n0.., ..]).to_owned());
     }
 }
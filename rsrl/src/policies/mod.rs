@@ -13,7 +13,6 @@
 //! is placed on a single action _u'_ for any given state _x_. For continuous
 //! policies, this can be seen as a dirac delta distribution, _δ(u' - u)_.
 use crate::{core::*, domains::Transition, fa::Parameterised};
-use rand::{seq::SliceRandom, thread_rng, Rng};
 
 pub mod gaussian;
 
@@ -21,6 +20,7 @@ import_all!(random);
 import_all!(greedy);
 import_all!(epsilon_greedy);
 import_all!(softmax);
+import_all!(preferences);
 import_all!(dirac);
 import_all!(beta);
 import_all!(gamma);
@@ -28,30 +28,8 @@ import_all!(gamma);
 
 import_all!(ipp);
 import_all!(perturbation);
-
-#[allow(dead_code)]
-#[inline]
-pub(self) fn sample_probs(probabilities: &[f64]) -> usize {
-    sample_probs_with_rng(&mut thread_rng(), probabilities)
-}
-
-#[inline]
-pub(self) fn sample_probs_with_rng(rng: &mut impl Rng, probabilities: &[f64]) -> usize {
-    let r = rng.gen::<f64>();
-
-    match probabilities
-        .into_iter()
-        .scan(0.0, |state, &p| {
-            *state = *state + p;
-
-            Some(*state)
-        })
-        .position(|p| p > r)
-    {
-        Some(index) => index,
-        None => probabilities.len() - 1,
-    }
-}
+import_all!(parameter_noise);
+import_all!(squashed);
 
 /// Policy trait for functions that define a probability distribution over
 /// actions.
@@ -84,6 +62,38 @@ pub trait DifferentiablePolicy<S>: Policy<S> {
     fn grad_log(&self, state: &S, a: Self::Action) -> Matrix<f64>;
 }
 
+/// Trait for policies whose divergence from another policy of the same form
+/// can be evaluated at a given state.
+pub trait KLDivergence<S>: Policy<S> {
+    /// Compute _KL(π(·|s) || π'(·|s))_, where _π_ is `self` and _π'_ is `other`.
+    fn kl_divergence(&self, other: &Self, state: &S) -> f64;
+}
+
+/// Trait for policies whose (differential) entropy can be evaluated at a
+/// given state.
+pub trait Entropy<S>: Policy<S> {
+    /// Compute _H(π(·|s))_ for the given `state`.
+    fn entropy(&mut self, state: &S) -> f64;
+}
+
+/// Trait for policies that skip updates with NaN or infinite values rather
+/// than applying them to their weights.
+pub trait SkippedUpdates {
+    /// Return the number of updates skipped since the last call, and reset
+    /// the count.
+    fn take_skipped(&mut self) -> usize;
+}
+
+/// Trait for policies that act on a Q-function shared with a learner.
+///
+/// Shared handles are deserialised as independent copies, so a learner must
+/// point its policy back at its own Q-function after loading, or the policy
+/// would follow a Q-function that is no longer updated.
+pub trait LinkedPolicy<Q> {
+    /// Replace the policy's Q-function with `q_func`.
+    fn link(&mut self, q_func: Q);
+}
+
 /// Trait for policies that are parameterised by a vector of weights.
 pub trait ParameterisedPolicy<S>: Policy<S> + Parameterised {
     /// Update the weights in the direction of an error for a given state and
@@ -94,39 +104,47 @@ pub trait ParameterisedPolicy<S>: Policy<S> + Parameterised {
     fn update_raw(&mut self, errors: Matrix<f64>);
 }
 
-// Shared<T> impls:
-impl<S, T: Policy<S>> Policy<S> for Shared<T> {
-    type Action = T::Action;
-
-    fn sample(&mut self, state: &S) -> Self::Action {
-        self.borrow_mut().sample(state)
-    }
-
-    fn mpa(&mut self, s: &S) -> Self::Action {
-        self.borrow_mut().mpa(s)
-    }
-
-    fn probability(&mut self, state: &S, a: Self::Action) -> f64 {
-        self.borrow_mut().probability(state, a)
-    }
+// Shared<T> and SyncShared<T> impls:
+macro_rules! impl_shared_policies {
+    ($shared:ident) => {
+        impl<S, T: Policy<S>> Policy<S> for $shared<T> {
+            type Action = T::Action;
+
+            fn sample(&mut self, state: &S) -> Self::Action {
+                self.borrow_mut().sample(state)
+            }
+
+            fn mpa(&mut self, s: &S) -> Self::Action {
+                self.borrow_mut().mpa(s)
+            }
+
+            fn probability(&mut self, state: &S, a: Self::Action) -> f64 {
+                self.borrow_mut().probability(state, a)
+            }
+        }
+
+        impl<S, T: FinitePolicy<S>> FinitePolicy<S> for $shared<T> {
+            fn n_actions(&self) -> usize {
+                self.borrow().n_actions()
+            }
+
+            fn probabilities(&mut self, state: &S) -> Vector<f64> {
+                self.borrow_mut().probabilities(state)
+            }
+        }
+
+        impl<S, T: DifferentiablePolicy<S>> DifferentiablePolicy<S> for $shared<T> {
+            fn grad_log(&self, state: &S, a: Self::Action) -> Matrix<f64> {
+                self.borrow().grad_log(state, a)
+            }
+        }
+    };
 }
 
-impl<S, T: FinitePolicy<S>> FinitePolicy<S> for Shared<T> {
-    fn n_actions(&self) -> usize {
-        self.borrow().n_actions()
-    }
-
-    fn probabilities(&mut self, state: &S) -> Vector<f64> {
-        self.borrow_mut().probabilities(state)
-    }
-}
-
-impl<S, T: DifferentiablePolicy<S>> DifferentiablePolicy<S> for Shared<T> {
-    fn grad_log(&self, state: &S, a: Self::Action) -> Matrix<f64> {
-        self.borrow().grad_log(state, a)
-    }
-}
+impl_shared_policies!(Shared);
+impl_shared_policies!(SyncShared);
 
+// Parameterised policies need views of the weights; see `SyncShared`.
 impl<S, T: ParameterisedPolicy<S>> ParameterisedPolicy<S> for Shared<T> {
     fn update(&mut self, state: &S, a: Self::Action, error: f64) {
         self.borrow_mut().update(state, a, error)
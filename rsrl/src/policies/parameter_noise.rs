@@ -0,0 +1,232 @@
+use crate::core::*;
+use crate::fa::Parameterised;
+use crate::policies::{DifferentiablePolicy, Entropy, KLDivergence, ParameterisedPolicy, Policy, SkippedUpdates};
+use crate::rand::{from_entropy, seeded, Rng, SmallRng};
+use rand::distributions::StandardNormal;
+
+/// Distance between two actions, used to calibrate parameter-space noise.
+pub trait ActionDistance {
+    fn distance(&self, other: &Self) -> f64;
+}
+
+impl ActionDistance for f64 {
+    fn distance(&self, other: &f64) -> f64 { (self - other).abs() }
+}
+
+impl ActionDistance for usize {
+    fn distance(&self, other: &usize) -> f64 {
+        if self == other { 0.0 } else { 1.0 }
+    }
+}
+
+impl<A: ActionDistance, B: ActionDistance> ActionDistance for (A, B) {
+    fn distance(&self, other: &(A, B)) -> f64 {
+        self.0.distance(&other.0).hypot(self.1.distance(&other.1))
+    }
+}
+
+impl ActionDistance for Vector<f64> {
+    fn distance(&self, other: &Vector<f64>) -> f64 {
+        self.iter().zip(other.iter()).fold(0.0, |acc, (x, y)| acc + (x - y).powi(2)).sqrt()
+    }
+}
+
+/// Parameter-space exploration (Plappert et al., 2018).
+///
+/// Actions are sampled deterministically from a copy of the base policy whose
+/// weights are perturbed by isotropic Gaussian noise, redrawn at the end of
+/// each episode. The noise scale is adapted so that the mean distance between
+/// the perturbed and unperturbed actions tracks `target_distance`. Learning
+/// updates are applied to the unperturbed policy only.
+///
+/// A zero `stddev` disables the perturbation, in which case actions are
+/// sampled from the base policy itself.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ParameterNoise<P> {
+    pub policy: P,
+    perturbed: P,
+    stale: bool,
+
+    /// Standard deviation of the noise added to each weight.
+    pub stddev: f64,
+
+    /// Mean action distance to maintain between the perturbed and base
+    /// policies.
+    pub target_distance: f64,
+
+    /// Factor (> 1) by which `stddev` is scaled up or down after each episode.
+    pub adaptation_rate: f64,
+
+    distance_sum: f64,
+    n_samples: usize,
+
+    #[serde(skip, default = "crate::rand::from_entropy")]
+    rng: SmallRng,
+}
+
+impl<P: Clone> ParameterNoise<P> {
+    pub fn new(policy: P, stddev: f64, target_distance: f64, adaptation_rate: f64) -> Self {
+        ParameterNoise {
+            perturbed: policy.clone(),
+            policy,
+            stale: true,
+
+            stddev,
+            target_distance,
+            adaptation_rate,
+
+            distance_sum: 0.0,
+            n_samples: 0,
+
+            rng: from_entropy(),
+        }
+    }
+
+    /// Wrapper that samples from the base policy without perturbing it.
+    pub fn disabled(policy: P) -> Self { ParameterNoise::new(policy, 0.0, 0.0, 1.0) }
+}
+
+impl<P> ParameterNoise<P> {
+    pub fn is_enabled(&self) -> bool { self.stddev > 0.0 }
+
+    /// Mean action distance between the perturbed and base policies over the
+    /// current episode.
+    pub fn mean_distance(&self) -> Option<f64> {
+        if self.n_samples > 0 {
+            Some(self.distance_sum / self.n_samples as f64)
+        } else {
+            None
+        }
+    }
+
+    fn adapt(&mut self) {
+        if let Some(d) = self.mean_distance() {
+            if d > self.target_distance {
+                self.stddev /= self.adaptation_rate;
+            } else {
+                self.stddev *= self.adaptation_rate;
+            }
+        }
+
+        self.distance_sum = 0.0;
+        self.n_samples = 0;
+    }
+
+    fn perturb<S>(&mut self)
+    where
+        P: ParameterisedPolicy<S> + Clone,
+    {
+        let stddev = self.stddev;
+        let rng = &mut self.rng;
+        let noise = Matrix::from_shape_fn(self.policy.weights_dim(), |_| {
+            stddev * rng.sample(StandardNormal)
+        });
+
+        self.perturbed = self.policy.clone();
+        self.perturbed.update_raw(noise);
+        self.stale = false;
+    }
+}
+
+impl<P: Algorithm> Algorithm for ParameterNoise<P> {
+    fn handle_terminal(&mut self) {
+        self.policy.handle_terminal();
+
+        if self.is_enabled() {
+            self.adapt();
+        }
+
+        self.stale = true;
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.rng = seeded(stream.seed(0));
+        self.policy.reseed(stream.seed(1));
+        self.stale = true;
+    }
+}
+
+impl<S, P> Policy<S> for ParameterNoise<P>
+where
+    P: ParameterisedPolicy<S> + Clone,
+    P::Action: ActionDistance,
+{
+    type Action = P::Action;
+
+    fn sample(&mut self, s: &S) -> P::Action {
+        if !self.is_enabled() {
+            return self.policy.sample(s);
+        }
+
+        if self.stale {
+            self.perturb::<S>();
+        }
+
+        let a = self.perturbed.mpa(s);
+
+        self.distance_sum += a.distance(&self.policy.mpa(s));
+        self.n_samples += 1;
+
+        a
+    }
+
+    fn mpa(&mut self, s: &S) -> P::Action { self.policy.mpa(s) }
+
+    fn probability(&mut self, s: &S, a: P::Action) -> f64 { self.policy.probability(s, a) }
+}
+
+impl<S, P> DifferentiablePolicy<S> for ParameterNoise<P>
+where
+    P: ParameterisedPolicy<S> + DifferentiablePolicy<S> + Clone,
+    P::Action: ActionDistance,
+{
+    fn grad_log(&self, s: &S, a: P::Action) -> Matrix<f64> { self.policy.grad_log(s, a) }
+}
+
+impl<S, P> KLDivergence<S> for ParameterNoise<P>
+where
+    P: ParameterisedPolicy<S> + KLDivergence<S> + Clone,
+    P::Action: ActionDistance,
+{
+    fn kl_divergence(&self, other: &Self, s: &S) -> f64 { self.policy.kl_divergence(&other.policy, s) }
+}
+
+impl<S, P> Entropy<S> for ParameterNoise<P>
+where
+    P: ParameterisedPolicy<S> + Entropy<S> + Clone,
+    P::Action: ActionDistance,
+{
+    fn entropy(&mut self, s: &S) -> f64 { self.policy.entropy(s) }
+}
+
+impl<P: SkippedUpdates> SkippedUpdates for ParameterNoise<P> {
+    fn take_skipped(&mut self) -> usize { self.policy.take_skipped() }
+}
+
+impl_parameterised!(ParameterNoise<P> => policy: P);
+
+impl<S, P> ParameterisedPolicy<S> for ParameterNoise<P>
+where
+    P: ParameterisedPolicy<S> + Clone,
+    P::Action: ActionDistance,
+{
+    fn update(&mut self, s: &S, a: P::Action, error: f64) { self.policy.update(s, a, error) }
+
+    fn update_raw(&mut self, errors: Matrix<f64>) { self.policy.update_raw(errors) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_distance() {
+        assert_eq!(1.5f64.distance(&-0.5), 2.0);
+        assert_eq!(3usize.distance(&3), 0.0);
+        assert_eq!(3usize.distance(&4), 1.0);
+        assert_eq!((0.0f64, 0.0f64).distance(&(3.0, 4.0)), 5.0);
+        assert_eq!(Vector::from_vec(vec![1.0, 1.0]).distance(&Vector::from_vec(vec![1.0, 2.0])), 1.0);
+    }
+}
@@ -1,15 +1,18 @@
 use crate::core::*;
 use crate::geometry::Space;
 use crate::policies::{FinitePolicy, Policy};
+use crate::rand::{from_entropy, Rng, SmallRng};
 use rand::{
     distributions::{Distribution, Normal},
-    rngs::ThreadRng,
-    Rng,
-    thread_rng,
+    SeedableRng,
 };
 use std::ops::Add;
 
-pub struct PerturbedPolicy<P, D, R = ThreadRng> {
+/// Policy whose sampled actions are perturbed by additive noise.
+///
+/// The noise generator `R` must be seedable so that `reseed` can make the
+/// perturbations reproducible alongside those of the base policy.
+pub struct PerturbedPolicy<P, D, R = SmallRng> {
     pub base_policy: P,
     pub noise_dist: D,
 
@@ -18,13 +21,13 @@ pub struct PerturbedPolicy<P, D, R = ThreadRng> {
 
 impl<P, D> PerturbedPolicy<P, D> {
     pub fn new(base_policy: P, noise_dist: D) -> Self {
-        PerturbedPolicy::with_rng(base_policy, noise_dist, thread_rng())
+        PerturbedPolicy::with_rng(base_policy, noise_dist, from_entropy())
     }
 }
 
 impl<P> PerturbedPolicy<P, Normal> {
     pub fn normal(base_policy: P, std_dev: f64) -> Self {
-        PerturbedPolicy::with_rng(base_policy, Normal::new(0.0, std_dev), thread_rng())
+        PerturbedPolicy::with_rng(base_policy, Normal::new(0.0, std_dev), from_entropy())
     }
 }
 
@@ -38,17 +41,24 @@ impl<P, D, R> PerturbedPolicy<P, D, R> {
     }
 }
 
-impl<P: Algorithm, D, R> Algorithm for PerturbedPolicy<P, D, R> {
+impl<P: Algorithm, D, R: SeedableRng> Algorithm for PerturbedPolicy<P, D, R> {
     fn handle_terminal(&mut self) {
         self.base_policy.handle_terminal();
     }
+
+    fn reseed(&mut self, seed: u64) {
+        let stream = SeedStream::new(seed);
+
+        self.rng = R::seed_from_u64(stream.seed(0));
+        self.base_policy.reseed(stream.seed(1));
+    }
 }
 
 impl<S, P, D, R> Policy<S> for PerturbedPolicy<P, D, R>
 where
     P: Policy<S>,
     D: Distribution<P::Action>,
-    R: Rng,
+    R: Rng + SeedableRng,
     P::Action: Add<P::Action, Output = P::Action>,
 {
     type Action = P::Action;
@@ -69,7 +79,7 @@ impl<S, P, D, R> FinitePolicy<S> for PerturbedPolicy<P, D, R>
 where
     P: FinitePolicy<S>,
     D: Distribution<P::Action>,
-    R: Rng,
+    R: Rng + SeedableRng,
 {
     fn n_actions(&self) -> usize {
         self.base_policy.n_actions()
@@ -0,0 +1,257 @@
+use crate::{
+    core::*,
+    fa::{Approximator, Embedding, Parameterised, VectorApproximator, WeightsView},
+    geometry::{MatrixView, MatrixViewMut},
+    policies::{
+        DifferentiablePolicy,
+        FinitePolicy,
+        KLDivergence,
+        ParameterisedPolicy,
+        Policy,
+    },
+    rand::{argmax_choose, from_entropy, sample_probs, seeded, SmallRng},
+};
+use ndarray::Axis;
+use std::{f64, ops::AddAssign};
+
+/// Softmax of `preferences / tau`, shifted by the largest preference so that
+/// large preferences or small temperatures do not overflow.
+fn softmax(preferences: &Vector<f64>, tau: f64) -> Vector<f64> {
+    let max = preferences.fold(f64::NEG_INFINITY, |acc, &h| acc.max(h));
+    let exps = preferences.mapv(|h| ((h - max) / tau).exp());
+    let z = exps.sum();
+
+    exps / z
+}
+
+/// Softmax policy over action preferences learned by the actor itself.
+///
+/// Unlike `Softmax`, whose values are typically estimates of _Q(s, a)_
+/// shared with a critic, the preferences _h(s, a)_ are free parameters of the
+/// policy, one linear function of the features per action, and are adjusted
+/// only through policy gradient updates. Actions are then chosen with
+/// probability _π(a|s) ∝ exp(h(s, a) / τ)_, where the temperature _τ_ follows
+/// its `Parameter` schedule across episodes.
+///
+/// # References
+/// - Sutton, R. S., Barto, A. G. (2018). Reinforcement Learning: An
+/// Introduction (2nd ed.), section 13.1. MIT Press.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PreferenceSoftmax<F> {
+    preferences: F,
+    tau: Parameter,
+
+    #[serde(skip, default = "crate::rand::from_entropy")]
+    rng: SmallRng,
+}
+
+impl<F> PreferenceSoftmax<F> {
+    pub fn new<T: Into<Parameter>>(preferences: F, tau: T) -> Self {
+        let tau: Parameter = tau.into();
+
+        if tau.value() < 1e-7 {
+            panic!("Tau parameter in PreferenceSoftmax must be positive.");
+        }
+
+        PreferenceSoftmax {
+            preferences,
+            tau,
+            rng: from_entropy(),
+        }
+    }
+
+    pub fn standard(preferences: F) -> Self {
+        Self::new(preferences, 1.0)
+    }
+
+    /// Return the current temperature.
+    pub fn temperature(&self) -> f64 { self.tau.value() }
+
+    /// Return the preference for each action at a given `state`.
+    pub fn preferences<S>(&self, state: &S) -> Vector<f64>
+    where
+        F: Embedding<S> + VectorApproximator,
+    {
+        self.preferences.evaluate(&self.preferences.embed(state)).unwrap()
+    }
+}
+
+impl<F> Algorithm for PreferenceSoftmax<F> {
+    fn handle_terminal(&mut self) { self.tau = self.tau.step(); }
+
+    fn reseed(&mut self, seed: u64) { self.rng = seeded(seed); }
+}
+
+impl<S, F: Embedding<S> + VectorApproximator> Policy<S> for PreferenceSoftmax<F> {
+    type Action = usize;
+
+    fn sample(&mut self, s: &S) -> usize {
+        let ps = self.probabilities(s);
+
+        sample_probs(&mut self.rng, ps.as_slice().unwrap())
+    }
+
+    fn mpa(&mut self, s: &S) -> usize {
+        let hs = self.preferences(s);
+
+        argmax_choose(&mut self.rng, hs.as_slice().unwrap()).1
+    }
+
+    fn probability(&mut self, s: &S, a: usize) -> f64 { self.probabilities(s)[a] }
+}
+
+impl<S, F: Embedding<S> + VectorApproximator> FinitePolicy<S> for PreferenceSoftmax<F> {
+    fn n_actions(&self) -> usize { self.preferences.n_outputs() }
+
+    fn probabilities(&mut self, s: &S) -> Vector<f64> {
+        softmax(&self.preferences(s), self.tau.value())
+    }
+}
+
+impl<S, F: Embedding<S> + VectorApproximator> DifferentiablePolicy<S> for PreferenceSoftmax<F> {
+    fn grad_log(&self, input: &S, a: usize) -> Matrix<f64> {
+        let tau = self.tau.value();
+        let phi = self.preferences.embed(input);
+        let probabilities = softmax(&self.preferences.evaluate(&phi).unwrap(), tau);
+
+        // (N x 1)
+        let phi = phi.expanded(self.preferences.n_features()) / tau;
+
+        // (N x 1) . (1 x A) => (N x A)
+        let mut grad_log = phi
+            .clone()
+            .insert_axis(Axis(1))
+            .dot(&-probabilities.insert_axis(Axis(0)));
+
+        grad_log.column_mut(a).add_assign(&phi);
+
+        grad_log
+    }
+}
+
+impl<S, F: Embedding<S> + VectorApproximator> KLDivergence<S> for PreferenceSoftmax<F> {
+    fn kl_divergence(&self, other: &Self, input: &S) -> f64 {
+        let ps = softmax(&self.preferences(input), self.tau.value());
+        let qs = softmax(&other.preferences(input), other.tau.value());
+
+        ps.iter()
+            .zip(qs.iter())
+            .filter(|&(&p, _)| p > 0.0)
+            .map(|(&p, &q)| p * (p / q).ln())
+            .sum()
+    }
+}
+
+impl_parameterised!(PreferenceSoftmax<F> => preferences: F);
+
+impl<S, F> ParameterisedPolicy<S> for PreferenceSoftmax<F>
+where
+    F: Embedding<S> + VectorApproximator + WeightsView,
+{
+    fn update(&mut self, input: &S, a: usize, error: f64) {
+        let grad_log = self.grad_log(input, a);
+
+        self.weights_view_mut().scaled_add(error, &grad_log);
+    }
+
+    fn update_raw(&mut self, errors: Matrix<f64>) {
+        self.weights_view_mut().add_assign(&errors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Algorithm,
+        DifferentiablePolicy,
+        FinitePolicy,
+        KLDivergence,
+        Matrix,
+        Parameter,
+        ParameterisedPolicy,
+        Policy,
+        PreferenceSoftmax,
+    };
+    use crate::fa::{Composable, LFA, basis::fixed::Polynomial};
+
+    macro_rules! policy {
+        ($tau:expr) => {
+            PreferenceSoftmax::new(
+                LFA::vector(Polynomial::new(1, vec![(0.0, 1.0)]).with_constant(), 3),
+                $tau,
+            )
+        };
+    }
+
+    #[test]
+    fn test_uniform_initially() {
+        let mut p = policy!(1.0);
+        let ps = p.probabilities(&vec![0.5]);
+
+        assert!(ps.iter().all(|&x| (x - 1.0 / 3.0).abs() < 1e-10));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut p = policy!(0.5);
+
+        p.update(&vec![0.5], 1, 1.0);
+        p.update(&vec![0.5], 2, -1.0);
+
+        let ps = p.probabilities(&vec![0.5]);
+
+        assert!(ps[1] > ps[0] && ps[0] > ps[2]);
+        assert!((ps.sum() - 1.0).abs() < 1e-10);
+        assert_eq!(p.mpa(&vec![0.5]), 1);
+    }
+
+    #[test]
+    fn test_grad_log() {
+        let mut p = policy!(0.5);
+
+        p.update(&vec![0.5], 0, 0.3);
+        p.update(&vec![0.5], 2, -0.2);
+
+        let grad = p.grad_log(&vec![0.5], 2);
+        let log_p = p.probability(&vec![0.5], 2).ln();
+
+        // Finite-difference check against the first preference weight of
+        // each action:
+        for a in 0..3 {
+            let mut step = Matrix::zeros(grad.dim());
+            step[[0, a]] = 1e-6;
+
+            let mut q = p.clone();
+            <_ as ParameterisedPolicy<Vec<f64>>>::update_raw(&mut q, step);
+
+            let numerical = (q.probability(&vec![0.5], 2).ln() - log_p) / 1e-6;
+
+            assert!((numerical - grad[[0, a]]).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_kl_divergence() {
+        let p = policy!(1.0);
+        let mut q = p.clone();
+
+        assert_eq!(p.kl_divergence(&q, &vec![0.5]), 0.0);
+
+        q.update(&vec![0.5], 0, 1.0);
+
+        assert!(p.kl_divergence(&q, &vec![0.5]) > 0.0);
+    }
+
+    #[test]
+    fn test_terminal() {
+        let mut tau = Parameter::exponential(10.0, 1.0, 0.9);
+        let mut p = PreferenceSoftmax::new(LFA::vector(Polynomial::new(1, vec![(0.0, 1.0)]), 2), tau);
+
+        for _ in 0..10 {
+            tau = tau.step();
+            p.handle_terminal();
+
+            assert_eq!(tau.value(), p.temperature());
+        }
+    }
+}
@@ -1,28 +1,36 @@
 use crate::{
     core::*,
     geometry::Space,
-    policies::{FinitePolicy, Policy},
-};
-use rand::{
-    distributions::{Distribution, Uniform},
-    rngs::ThreadRng,
-    thread_rng,
+    policies::{FinitePolicy, LinkedPolicy, Policy},
 };
+use crate::rand::{from_entropy, seeded, SmallRng};
+use rand::distributions::{Distribution, Uniform};
 
 // TODO: Generalise the random policy to work on any `Space`. This won't be hard
 // at all, just use T: Into<Space>. Just make sure that you add all the relevant
 // From implementations for the different spaces in the `spaces` crate; i.e.
 // From<usize> for Ordinal etc etc...
 
-pub struct Random(usize, ThreadRng);
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Random(
+    usize,
+    #[serde(skip, default = "crate::rand::from_entropy")] SmallRng,
+);
 
 impl Random {
-    pub fn new(n_actions: usize) -> Self { Random(n_actions, thread_rng()) }
+    pub fn new(n_actions: usize) -> Self { Random(n_actions, from_entropy()) }
 
     pub fn from_space<S: Space>(space: S) -> Self { Self::new(space.dim()) }
 }
 
-impl Algorithm for Random {}
+impl Algorithm for Random {
+    fn reseed(&mut self, seed: u64) { self.1 = seeded(seed); }
+}
+
+/// Random actions do not depend on any Q-function, so there is nothing to link.
+impl<Q> LinkedPolicy<Q> for Random {
+    fn link(&mut self, _: Q) {}
+}
 
 impl<S> Policy<S> for Random {
     type Action = usize;
@@ -40,7 +48,7 @@ impl<S> FinitePolicy<S> for Random {
 
 #[cfg(test)]
 mod tests {
-    use super::{FinitePolicy, Policy, Random};
+    use super::{Algorithm, FinitePolicy, Policy, Random};
     use crate::geometry::Vector;
 
     #[test]
@@ -80,4 +88,17 @@ mod tests {
             .probabilities(&[0.0, 0.0, 0.0, 0.0, 1.0])
             .all_close(&Vector::from_vec(vec![0.2; 5]), 1e-6));
     }
+
+    #[test]
+    fn test_reseed() {
+        let mut p1 = Random::new(10);
+        let mut p2 = Random::new(10);
+
+        p1.reseed(42);
+        p2.reseed(42);
+
+        for _ in 0..100 {
+            assert_eq!(p1.sample(&()), p2.sample(&()));
+        }
+    }
 }
@@ -1,19 +1,18 @@
 use crate::{
     core::*,
     domains::Transition,
-    fa::{Approximator, Parameterised, QFunction},
+    fa::{Approximator, Parameterised, QFunction, WeightsView},
     geometry::{MatrixView, MatrixViewMut},
     policies::{
-        sample_probs_with_rng,
         DifferentiablePolicy,
         ParameterisedPolicy,
         FinitePolicy,
+        LinkedPolicy,
         Policy
     },
-    utils::argmax_choose,
+    rand::{argmax_choose, from_entropy, sample_probs, seeded, SmallRng},
 };
 use ndarray::Axis;
-use rand::{rngs::ThreadRng, thread_rng};
 use std::{f64, ops::AddAssign};
 
 fn probabilities_from_values<'a>(values: impl Iterator<Item = &'a f64>, tau: f64) -> Vector<f64> {
@@ -33,10 +32,13 @@ fn probabilities_from_values<'a>(values: impl Iterator<Item = &'a f64>, tau: f64
 
 pub type Gibbs<F> = Softmax<F>;
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Softmax<F> {
     fa: F,
     tau: Parameter,
-    rng: ThreadRng,
+
+    #[serde(skip, default = "crate::rand::from_entropy")]
+    rng: SmallRng,
 }
 
 impl<F> Softmax<F> {
@@ -50,7 +52,7 @@ impl<F> Softmax<F> {
         Softmax {
             fa,
             tau: tau.into(),
-            rng: thread_rng(),
+            rng: from_entropy(),
         }
     }
 
@@ -61,6 +63,12 @@ impl<F> Softmax<F> {
 
 impl<F> Algorithm for Softmax<F> {
     fn handle_terminal(&mut self) { self.tau = self.tau.step(); }
+
+    fn reseed(&mut self, seed: u64) { self.rng = seeded(seed); }
+}
+
+impl<F> LinkedPolicy<F> for Softmax<F> {
+    fn link(&mut self, fa: F) { self.fa = fa; }
 }
 
 impl<S, F: QFunction<S>> Policy<S> for Softmax<F> {
@@ -69,7 +77,7 @@ impl<S, F: QFunction<S>> Policy<S> for Softmax<F> {
     fn sample(&mut self, s: &S) -> usize {
         let ps = self.probabilities(s);
 
-        sample_probs_with_rng(&mut self.rng, ps.as_slice().unwrap())
+        sample_probs(&mut self.rng, ps.as_slice().unwrap())
     }
 
     fn mpa(&mut self, s: &S) -> usize {
@@ -113,21 +121,9 @@ impl<S, F: QFunction<S>> DifferentiablePolicy<S> for Softmax<F> {
     }
 }
 
-impl<F: Parameterised> Parameterised for Softmax<F> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa.weights_view_mut()
-    }
-}
+impl_parameterised!(Softmax<F> => fa: F);
 
-impl<S, F: QFunction<S> + Parameterised> ParameterisedPolicy<S> for Softmax<F> {
+impl<S, F: QFunction<S> + WeightsView> ParameterisedPolicy<S> for Softmax<F> {
     fn update(&mut self, input: &S, a: usize, error: f64) {
         let grad_log = self.grad_log(input, a);
 
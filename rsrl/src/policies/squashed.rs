@@ -0,0 +1,202 @@
+use crate::core::*;
+use crate::policies::{
+    DifferentiablePolicy, Entropy, KLDivergence, ParameterisedPolicy, Policy, SkippedUpdates,
+};
+
+// Keeps the inverse of the squashing finite at the boundary of the interval.
+const BOUNDARY_TOL: f64 = 1e-6;
+
+fn atanh(x: f64) -> f64 {
+    let x = x.max(BOUNDARY_TOL - 1.0).min(1.0 - BOUNDARY_TOL);
+
+    0.5 * ((1.0 + x) / (1.0 - x)).ln()
+}
+
+/// Scalar policy whose actions are squashed into an interval _(lb, ub)_ by
+/// _a = c + r tanh((u - c) / r)_, where _u_ is the action of a base policy
+/// (e.g. a Gaussian), _c_ is the centre of the interval and _r_ its radius.
+///
+/// The map is close to the identity near the centre of the interval and
+/// saturates smoothly towards its bounds. Since it is a fixed bijection,
+/// gradients and updates are those of the base policy at the unsquashed
+/// action, the density picks up the Jacobian of the map, and the KL
+/// divergence is unchanged. Without bounds, actions are passed through.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Squashed<P> {
+    pub policy: P,
+    pub bounds: Option<(f64, f64)>,
+}
+
+impl<P> Squashed<P> {
+    /// Squash actions into _(-1, 1)_.
+    pub fn new(policy: P) -> Self { Squashed::bounded(policy, -1.0, 1.0) }
+
+    pub fn bounded(policy: P, lb: f64, ub: f64) -> Self {
+        Squashed { policy, bounds: Some((lb, ub)), }
+    }
+
+    pub fn unbounded(policy: P) -> Self {
+        Squashed { policy, bounds: None, }
+    }
+
+    fn squash(&self, u: f64) -> f64 {
+        match self.bounds {
+            Some((lb, ub)) => {
+                let (c, r) = ((lb + ub) / 2.0, (ub - lb) / 2.0);
+
+                c + r * ((u - c) / r).tanh()
+            },
+            None => u,
+        }
+    }
+
+    fn unsquash(&self, a: f64) -> f64 {
+        match self.bounds {
+            Some((lb, ub)) => {
+                let (c, r) = ((lb + ub) / 2.0, (ub - lb) / 2.0);
+
+                c + r * atanh((a - c) / r)
+            },
+            None => a,
+        }
+    }
+
+    /// Derivative of the squashed action wrt the unsquashed action.
+    fn jacobian(&self, u: f64) -> f64 {
+        match self.bounds {
+            Some((lb, ub)) => {
+                let (c, r) = ((lb + ub) / 2.0, (ub - lb) / 2.0);
+
+                1.0 - ((u - c) / r).tanh().powi(2)
+            },
+            None => 1.0,
+        }
+    }
+
+    /// Log of the Jacobian, which stays finite where the squashing saturates.
+    fn log_jacobian(&self, u: f64) -> f64 {
+        match self.bounds {
+            Some((lb, ub)) => {
+                let (c, r) = ((lb + ub) / 2.0, (ub - lb) / 2.0);
+                let x = ((u - c) / r).abs();
+
+                // log(sech^2(x)) = 2 (log 2 - x - log(1 + exp(-2x))):
+                2.0 * (2.0f64.ln() - x - (-2.0 * x).exp().ln_1p())
+            },
+            None => 0.0,
+        }
+    }
+}
+
+impl<P: Algorithm> Algorithm for Squashed<P> {
+    fn handle_terminal(&mut self) { self.policy.handle_terminal(); }
+
+    fn reseed(&mut self, seed: u64) { self.policy.reseed(seed); }
+}
+
+impl<S, P: Policy<S, Action = f64>> Policy<S> for Squashed<P> {
+    type Action = f64;
+
+    fn sample(&mut self, s: &S) -> f64 {
+        let u = self.policy.sample(s);
+
+        self.squash(u)
+    }
+
+    fn mpa(&mut self, s: &S) -> f64 {
+        let u = self.policy.mpa(s);
+
+        self.squash(u)
+    }
+
+    fn probability(&mut self, s: &S, a: f64) -> f64 {
+        let u = self.unsquash(a);
+
+        self.policy.probability(s, u) / self.jacobian(u)
+    }
+}
+
+impl<S, P: DifferentiablePolicy<S, Action = f64>> DifferentiablePolicy<S> for Squashed<P> {
+    fn grad_log(&self, s: &S, a: f64) -> Matrix<f64> { self.policy.grad_log(s, self.unsquash(a)) }
+}
+
+impl<S, P: KLDivergence<S, Action = f64>> KLDivergence<S> for Squashed<P> {
+    fn kl_divergence(&self, other: &Self, s: &S) -> f64 { self.policy.kl_divergence(&other.policy, s) }
+}
+
+impl<S, P: Entropy<S, Action = f64>> Entropy<S> for Squashed<P> {
+    /// Entropy of the base policy plus the expected log-Jacobian of the
+    /// squashing, the latter approximated at the base policy's mode; exact
+    /// without bounds.
+    fn entropy(&mut self, s: &S) -> f64 {
+        let u = self.policy.mpa(s);
+
+        self.policy.entropy(s) + self.log_jacobian(u)
+    }
+}
+
+impl<P: SkippedUpdates> SkippedUpdates for Squashed<P> {
+    fn take_skipped(&mut self) -> usize { self.policy.take_skipped() }
+}
+
+impl_parameterised!(Squashed<P> => policy: P);
+
+impl<S, P: ParameterisedPolicy<S, Action = f64>> ParameterisedPolicy<S> for Squashed<P> {
+    fn update(&mut self, s: &S, a: f64, error: f64) {
+        let u = self.unsquash(a);
+
+        self.policy.update(s, u, error)
+    }
+
+    fn update_raw(&mut self, errors: Matrix<f64>) { self.policy.update_raw(errors) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fa::{basis::fixed::Constant, LFA},
+        geometry::Matrix,
+        policies::{Dirac, Policy, ParameterisedPolicy},
+    };
+    use super::Squashed;
+
+    const STATE: f64 = 0.0;
+
+    #[test]
+    fn test_unsquash() {
+        let pi = Squashed::bounded((), 0.5, 3.0);
+
+        for &u in &[-3.0f64, 0.0, 1.0, 1.75, 2.5, 10.0] {
+            let a = pi.squash(u);
+
+            assert!(a > 0.5 && a < 3.0);
+            assert!((pi.unsquash(a) - u).abs() < 1e-6);
+        }
+
+        assert!(pi.unsquash(0.5).is_finite());
+        assert!(pi.unsquash(3.0).is_finite());
+        assert_eq!(Squashed::unbounded(()).squash(10.0), 10.0);
+    }
+
+    #[test]
+    fn test_log_jacobian() {
+        let pi = Squashed::bounded((), 0.5, 3.0);
+
+        for &u in &[-3.0f64, 0.0, 1.0, 1.75, 2.5, 10.0] {
+            assert!((pi.log_jacobian(u) - pi.jacobian(u).ln()).abs() < 1e-9);
+        }
+
+        assert!(pi.log_jacobian(1e3).is_finite());
+        assert_eq!(Squashed::unbounded(()).log_jacobian(10.0), 0.0);
+    }
+
+    #[test]
+    fn test_squashed_dirac() {
+        let mut pi = Squashed::new(Dirac::new(LFA::scalar(Constant::ones(1))));
+
+        <_ as ParameterisedPolicy<f64>>::update_raw(&mut pi, Matrix::from_elem((1, 1), 0.5));
+
+        assert!((pi.mpa(&STATE) - 0.5f64.tanh()).abs() < 1e-9);
+        assert!((pi.sample(&STATE) - 0.5f64.tanh()).abs() < 1e-9);
+    }
+}
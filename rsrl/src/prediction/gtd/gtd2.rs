@@ -3,6 +3,10 @@ use crate::domains::Transition;
 use crate::fa::{Approximator, Parameterised, Features, VFunction};
 use crate::geometry::{Space, MatrixView, MatrixViewMut};
 
+/// Gradient TD2 (Sutton et al., 2009). The secondary weights `fa_w` track the
+/// expected TD error with step size `beta`, scheduled independently of the
+/// primary step size `alpha`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GTD2<F> {
     pub fa_theta: F,
     pub fa_w: F,
@@ -43,7 +47,7 @@ impl<F: Parameterised> GTD2<F> {
 impl<F> Algorithm for GTD2<F> {
     fn handle_terminal(&mut self) {
         self.alpha = self.alpha.step();
-        self.beta = self.alpha.step();
+        self.beta = self.beta.step();
         self.gamma = self.gamma.step();
     }
 }
@@ -61,12 +65,16 @@ impl<S, A, F: VFunction<S>> OnlineLearner<S, A> for GTD2<F> {
             t.reward + self.gamma * self.fa_theta.evaluate(&phi_ns).unwrap() - v
         };
 
-        self.fa_w.update(&phi_s, self.beta * (td_error - td_estimate)).unwrap();
+        self.fa_w.update(&phi_s, self.beta * (td_error - td_estimate)).ok();
 
         let dim = self.fa_theta.n_features();
-        let pd = phi_s.expanded(dim) - self.gamma.value() * phi_ns.expanded(dim);
+        let pd = if t.terminated() {
+            phi_s.expanded(dim)
+        } else {
+            phi_s.expanded(dim) - self.gamma.value() * phi_ns.expanded(dim)
+        };
 
-        self.fa_theta.update(&Features::Dense(pd), self.alpha * td_estimate).ok();
+        self.fa_theta.update(&Features::from(pd), self.alpha * td_estimate).ok();
     }
 }
 
@@ -78,16 +86,4 @@ impl<S, F: VFunction<S>> ValuePredictor<S> for GTD2<F> {
 
 impl<S, A, F: VFunction<S>> ActionValuePredictor<S, A> for GTD2<F> {}
 
-impl<F: Parameterised> Parameterised for GTD2<F> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa_theta.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa_theta.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa_theta.weights_view_mut()
-    }
-}
+impl_parameterised!(GTD2<F> => fa_theta: F);
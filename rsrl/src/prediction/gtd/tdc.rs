@@ -3,6 +3,9 @@ use crate::domains::Transition;
 use crate::fa::{Approximator, Parameterised, Features, VFunction};
 use crate::geometry::{Space, MatrixView, MatrixViewMut};
 
+/// Linear TD with gradient correction (Sutton et al., 2009); see `GTD2` for
+/// the roles of `alpha` and `beta`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TDC<F> {
     pub fa_theta: F,
     pub fa_w: F,
@@ -43,7 +46,7 @@ impl<F: Parameterised> TDC<F> {
 impl<F> Algorithm for TDC<F> {
     fn handle_terminal(&mut self) {
         self.alpha = self.alpha.step();
-        self.beta = self.alpha.step();
+        self.beta = self.beta.step();
         self.gamma = self.gamma.step();
     }
 }
@@ -64,11 +67,14 @@ impl<S, A, F: VFunction<S>> OnlineLearner<S, A> for TDC<F> {
         self.fa_w.update(&phi_s, self.beta * (td_error - td_estimate)).ok();
 
         let dim = self.fa_theta.n_features();
-        let phi =
+        let phi = if t.terminated() {
+            td_error * phi_s.expanded(dim)
+        } else {
             td_error * phi_s.expanded(dim) -
-            td_estimate * self.gamma.value() * phi_ns.expanded(dim);
+                td_estimate * self.gamma.value() * phi_ns.expanded(dim)
+        };
 
-        self.fa_theta.update(&Features::Dense(phi), self.alpha.value()).ok();
+        self.fa_theta.update(&Features::from(phi), self.alpha.value()).ok();
     }
 }
 
@@ -80,16 +86,4 @@ impl<S, F: VFunction<S>> ValuePredictor<S> for TDC<F> {
 
 impl<S, A, F: VFunction<S>> ActionValuePredictor<S, A> for TDC<F> {}
 
-impl<F: Parameterised> Parameterised for TDC<F> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa_theta.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa_theta.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa_theta.weights_view_mut()
-    }
-}
+impl_parameterised!(TDC<F> => fa_theta: F);
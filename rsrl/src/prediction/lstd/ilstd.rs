@@ -1,7 +1,7 @@
 use crate::{
     core::*,
     domains::Transition,
-    fa::{Embedding, VFunction, Parameterised},
+    fa::{Embedding, VFunction, Parameterised, WeightsView},
     geometry::{Space, Matrix, MatrixView, MatrixViewMut},
     utils::argmaxima,
 };
@@ -39,7 +39,7 @@ impl<F: Parameterised> iLSTD<F> {
     }
 }
 
-impl<F: Parameterised> iLSTD<F> {
+impl<F: WeightsView> iLSTD<F> {
     fn solve(&mut self) {
         let mut w = self.fa_theta.weights_view_mut();
         let alpha = self.alpha.value();
@@ -66,7 +66,7 @@ impl<F> Algorithm for iLSTD<F> {
     }
 }
 
-impl<S, A, F: Embedding<S> + Parameterised> OnlineLearner<S, A> for iLSTD<F> {
+impl<S, A, F: Embedding<S> + WeightsView> OnlineLearner<S, A> for iLSTD<F> {
     fn handle_transition(&mut self, t: &Transition<S, A>) {
         // (D x 1)
         let phi_s = self.fa_theta.embed(t.from.state()).expanded(self.a.rows());
@@ -100,16 +100,4 @@ impl<S, F: VFunction<S>> ValuePredictor<S> for iLSTD<F> {
 
 impl<S, A, F: VFunction<S>> ActionValuePredictor<S, A> for iLSTD<F> {}
 
-impl<F: Parameterised> Parameterised for iLSTD<F> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa_theta.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa_theta.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa_theta.weights_view_mut()
-    }
-}
+impl_parameterised!(iLSTD<F> => fa_theta: F);
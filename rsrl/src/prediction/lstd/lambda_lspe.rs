@@ -1,7 +1,7 @@
 use crate::{
     core::*,
     domains::Transition,
-    fa::{Approximator, VFunction, Parameterised},
+    fa::{Approximator, VFunction, Parameterised, WeightsView},
     geometry::{Space, Matrix, MatrixView, MatrixViewMut},
     utils::{argmaxima, pinv},
 };
@@ -44,7 +44,7 @@ impl<F: Parameterised> LambdaLSPE<F> {
     }
 }
 
-impl<F: Parameterised> LambdaLSPE<F> {
+impl<F: WeightsView> LambdaLSPE<F> {
     fn solve(&mut self) {
         // First try the clean approach otherwise solve via SVD:
         if let Ok(theta) = self.a.solve(&self.b).or_else(|_| {
@@ -70,7 +70,7 @@ impl<M> Algorithm for LambdaLSPE<M> {
     }
 }
 
-impl<S, A, F: VFunction<S> + Parameterised> BatchLearner<S, A> for LambdaLSPE<F> {
+impl<S, A, F: VFunction<S> + WeightsView> BatchLearner<S, A> for LambdaLSPE<F> {
     fn handle_batch(&mut self, batch: &[Transition<S, A>]) {
         batch.into_iter().rev().for_each(|ref t| {
             let phi_s = self.fa_theta.embed(t.from.state());
@@ -107,16 +107,4 @@ impl<S, F: VFunction<S>> ValuePredictor<S> for LambdaLSPE<F> {
 
 impl<S, A, F: VFunction<S>> ActionValuePredictor<S, A> for LambdaLSPE<F> {}
 
-impl<F: Parameterised> Parameterised for LambdaLSPE<F> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa_theta.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa_theta.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa_theta.weights_view_mut()
-    }
-}
+impl_parameterised!(LambdaLSPE<F> => fa_theta: F);
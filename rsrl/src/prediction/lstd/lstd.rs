@@ -1,7 +1,7 @@
 use crate::{
     core::*,
     domains::Transition,
-    fa::{Approximator, VFunction, Parameterised},
+    fa::{Approximator, VFunction, Parameterised, WeightsView},
     geometry::{Space, Matrix, MatrixView, MatrixViewMut},
     utils::{argmaxima, pinv},
 };
@@ -32,7 +32,7 @@ impl<F: Parameterised> LSTD<F> {
     }
 }
 
-impl<F: Parameterised> LSTD<F> {
+impl<F: WeightsView> LSTD<F> {
     pub fn solve(&mut self) {
         let mut w = self.fa_theta.weights_view_mut();
 
@@ -52,7 +52,7 @@ impl<F> Algorithm for LSTD<F> {
     }
 }
 
-impl<S, A, F: VFunction<S> + Parameterised> BatchLearner<S, A> for LSTD<F> {
+impl<S, A, F: VFunction<S> + WeightsView> BatchLearner<S, A> for LSTD<F> {
     fn handle_batch(&mut self, ts: &[Transition<S, A>]) {
         ts.into_iter().for_each(|ref t| {
             let phi_s = self.fa_theta
@@ -84,16 +84,4 @@ impl<S, F: VFunction<S>> ValuePredictor<S> for LSTD<F> {
 
 impl<S, A, F: VFunction<S>> ActionValuePredictor<S, A> for LSTD<F> {}
 
-impl<F: Parameterised> Parameterised for LSTD<F> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa_theta.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa_theta.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa_theta.weights_view_mut()
-    }
-}
+impl_parameterised!(LSTD<F> => fa_theta: F);
@@ -1,7 +1,7 @@
 use crate::{
     core::*,
     domains::Transition,
-    fa::{Approximator, VFunction, Parameterised},
+    fa::{Approximator, VFunction, Parameterised, WeightsView},
     geometry::{Space, Matrix, MatrixView, MatrixViewMut},
     utils::{argmaxima, pinv},
 };
@@ -47,7 +47,7 @@ impl<F> LSTDLambda<F> {
     }
 }
 
-impl<F: Parameterised> LSTDLambda<F> {
+impl<F: WeightsView> LSTDLambda<F> {
     pub fn solve(&mut self) {
         let mut w = self.fa_theta.weights_view_mut();
 
@@ -67,7 +67,7 @@ impl<F> Algorithm for LSTDLambda<F> {
     }
 }
 
-impl<S, A, F: VFunction<S> + Parameterised> BatchLearner<S, A> for LSTDLambda<F> {
+impl<S, A, F: VFunction<S> + WeightsView> BatchLearner<S, A> for LSTDLambda<F> {
     fn handle_batch(&mut self, ts: &[Transition<S, A>]) {
         ts.into_iter().for_each(|t| {
             let phi_s = self.fa_theta
@@ -104,16 +104,4 @@ impl<S, F: VFunction<S>> ValuePredictor<S> for LSTDLambda<F> {
 
 impl<S, A, F: VFunction<S>> ActionValuePredictor<S, A> for LSTDLambda<F> {}
 
-impl<F: Parameterised> Parameterised for LSTDLambda<F> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa_theta.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa_theta.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa_theta.weights_view_mut()
-    }
-}
+impl_parameterised!(LSTDLambda<F> => fa_theta: F);
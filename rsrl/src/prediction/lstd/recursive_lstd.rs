@@ -73,7 +73,7 @@ impl<S, A, F: VFunction<S>> OnlineLearner<S, A> for RecursiveLSTD<F> {
         let vg = v.dot(&g);
 
         self.c_mat.scaled_add(-1.0 / a, &vg);
-        self.fa_theta.update(&Features::Dense(
+        self.fa_theta.update(&Features::from(
             v.index_axis_move(Axis(1), 0)
         ), residual / a).ok();
     }
@@ -87,16 +87,4 @@ impl<S, F: VFunction<S>> ValuePredictor<S> for RecursiveLSTD<F> {
 
 impl<S, A, F: VFunction<S>> ActionValuePredictor<S, A> for RecursiveLSTD<F> {}
 
-impl<F: Parameterised> Parameterised for RecursiveLSTD<F> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa_theta.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa_theta.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa_theta.weights_view_mut()
-    }
-}
+impl_parameterised!(RecursiveLSTD<F> => fa_theta: F);
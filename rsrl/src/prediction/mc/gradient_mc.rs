@@ -55,16 +55,4 @@ impl<S, V: VFunction<S>> ValuePredictor<S> for GradientMC<V> {
 
 impl<S, A, V: VFunction<S>> ActionValuePredictor<S, A> for GradientMC<V> {}
 
-impl<V: Parameterised> Parameterised for GradientMC<V> {
-    fn weights(&self) -> Matrix<f64> {
-        self.v_func.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.v_func.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.v_func.weights_view_mut()
-    }
-}
+impl_parameterised!(GradientMC<V> => v_func: V);
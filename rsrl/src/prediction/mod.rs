@@ -2,6 +2,7 @@
 pub use crate::core::{ActionValuePredictor, ValuePredictor};
 
 pub mod gtd;
+#[cfg(feature = "linalg")]
 pub mod lstd;
 pub mod mc;
 pub mod td;
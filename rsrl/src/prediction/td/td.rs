@@ -1,5 +1,5 @@
 use crate::core::*;
-use crate::domains::Transition;
+use crate::domains::{Transition, Truncation};
 use crate::fa::{Parameterised, Approximator, VFunction};
 use crate::geometry::{Matrix, MatrixView, MatrixViewMut};
 
@@ -9,6 +9,9 @@ pub struct TD<V> {
 
     pub alpha: Parameter,
     pub gamma: Parameter,
+
+    pub truncation: Truncation,
+    pub loss: Loss,
 }
 
 impl<V> TD<V> {
@@ -22,8 +25,21 @@ impl<V> TD<V> {
 
             alpha: alpha.into(),
             gamma: gamma.into(),
+
+            truncation: Truncation::default(),
+            loss: Loss::default(),
         }
     }
+
+    /// Set the treatment of episodes that end by truncation.
+    pub fn with_truncation(self, truncation: Truncation) -> Self {
+        TD { truncation, ..self }
+    }
+
+    /// Set the loss minimised by the updates.
+    pub fn with_loss(self, loss: Loss) -> Self {
+        TD { loss, ..self }
+    }
 }
 
 impl<V> Algorithm for TD<V> {
@@ -34,17 +50,26 @@ impl<V> Algorithm for TD<V> {
 }
 
 impl<S, A, V: VFunction<S>> OnlineLearner<S, A> for TD<V> {
-    fn handle_transition(&mut self, t: &Transition<S, A>) {
+    fn handle_transition(&mut self, t: &Transition<S, A>) { self.handle_weighted_transition(t, 1.0) }
+}
+
+impl<V> TD<V> {
+    /// Update on a transition with the step size scaled by `weight`, e.g. an
+    /// importance weight for a transition generated by another policy.
+    pub fn handle_weighted_transition<S, A>(&mut self, t: &Transition<S, A>, weight: f64)
+    where
+        V: VFunction<S>,
+    {
         let phi_s = self.v_func.embed(t.from.state());
         let v = self.v_func.evaluate(&phi_s).unwrap();
 
-        let td_error = if t.terminated() {
-            t.reward - v
+        let td_error = if t.bootstraps(self.truncation) {
+            t.reward + self.gamma * t.discount * self.predict_v(t.to.state()) - v
         } else {
-            t.reward + self.gamma * self.predict_v(t.to.state()) - v
+            t.reward - v
         };
 
-        self.v_func.update(&phi_s, self.alpha * td_error).ok();
+        self.v_func.update(&phi_s, self.alpha * (weight * self.loss.gradient(td_error))).ok();
     }
 }
 
@@ -56,16 +81,4 @@ impl<S, V: VFunction<S>> ValuePredictor<S> for TD<V> {
 
 impl<S, A, V: VFunction<S>> ActionValuePredictor<S, A> for TD<V> {}
 
-impl<V: Parameterised> Parameterised for TD<V> {
-    fn weights(&self) -> Matrix<f64> {
-        self.v_func.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.v_func.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.v_func.weights_view_mut()
-    }
-}
+impl_parameterised!(TD<V> => v_func: V);
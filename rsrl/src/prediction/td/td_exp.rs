@@ -62,16 +62,4 @@ impl<S, V: VFunction<S>> ValuePredictor<S> for ExponentialTD<S, V> {
 
 impl<S, A, V: VFunction<S>> ActionValuePredictor<S, A> for ExponentialTD<S, V> {}
 
-impl<S, V: Parameterised> Parameterised for ExponentialTD<S, V> {
-    fn weights(&self) -> Matrix<f64> {
-        self.v_func.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.v_func.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.v_func.weights_view_mut()
-    }
-}
+impl_parameterised!(ExponentialTD<S, V> => v_func: V);
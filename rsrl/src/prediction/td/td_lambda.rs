@@ -9,6 +9,8 @@ pub struct TDLambda<F> {
     pub alpha: Parameter,
     pub gamma: Parameter,
 
+    pub loss: Loss,
+
     trace: Trace,
 }
 
@@ -29,9 +31,16 @@ impl<F> TDLambda<F> {
             alpha: alpha.into(),
             gamma: gamma.into(),
 
+            loss: Loss::default(),
+
             trace,
         }
     }
+
+    /// Set the loss minimised by the updates.
+    pub fn with_loss(self, loss: Loss) -> Self {
+        TDLambda { loss, ..self }
+    }
 }
 
 impl<F> Algorithm for TDLambda<F> {
@@ -57,10 +66,13 @@ impl<S, A, F: VFunction<S>> OnlineLearner<S, A> for TDLambda<F> {
 
             t.reward - v
         } else {
-            t.reward + self.gamma * self.predict_v(t.to.state()) - v
+            // Carry gamma(s') over to the next decay of the trace:
+            self.trace.decay(t.discount);
+
+            t.reward + self.gamma * t.discount * self.predict_v(t.to.state()) - v
         };
 
-        self.fa_theta.update(&Features::Dense(z), self.alpha * td_error).ok();
+        self.fa_theta.update(&Features::from(z), self.alpha * self.loss.gradient(td_error)).ok();
     }
 }
 
@@ -72,16 +84,4 @@ impl<S, F: VFunction<S>> ValuePredictor<S> for TDLambda<F> {
 
 impl<S, A, F: VFunction<S>> ActionValuePredictor<S, A> for TDLambda<F> {}
 
-impl<F: Parameterised> Parameterised for TDLambda<F> {
-    fn weights(&self) -> Matrix<f64> {
-        self.fa_theta.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.fa_theta.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.fa_theta.weights_view_mut()
-    }
-}
+impl_parameterised!(TDLambda<F> => fa_theta: F);
@@ -72,16 +72,4 @@ impl<S, J, V: VFunction<S>> ValuePredictor<S> for VarianceTD<J, V> {
 
 impl<S, A, J: VFunction<S>, V: VFunction<S>> ActionValuePredictor<S, A> for VarianceTD<J, V> {}
 
-impl<J, V: Parameterised> Parameterised for VarianceTD<J, V> {
-    fn weights(&self) -> Matrix<f64> {
-        self.variance_estimator.weights()
-    }
-
-    fn weights_view(&self) -> MatrixView<f64> {
-        self.variance_estimator.weights_view()
-    }
-
-    fn weights_view_mut(&mut self) -> MatrixViewMut<f64> {
-        self.variance_estimator.weights_view_mut()
-    }
-}
+impl_parameterised!(VarianceTD<J, V> => variance_estimator: V);
@@ -0,0 +1,82 @@
+//! Random number generation shared by policies and domains.
+//!
+//! All randomness in the crate is drawn from generators created here: from
+//! entropy until a component is reseeded, and deterministically afterwards.
+//! Composite components give each of their parts a generator derived from
+//! their own seed, so that reseeding the whole reproduces every part.
+use crate::{core::SeedStream, utils::argmaxima};
+use ::rand::{seq::SliceRandom, FromEntropy, SeedableRng};
+
+pub use ::rand::{rngs::SmallRng, Rng};
+
+/// Generator seeded from the operating system, for components that have not
+/// been seeded explicitly.
+pub fn from_entropy() -> SmallRng { SmallRng::from_entropy() }
+
+/// Generator whose output depends only on `seed`.
+pub fn seeded(seed: u64) -> SmallRng { SmallRng::seed_from_u64(seed) }
+
+/// Generator of the `index`-th component of something seeded with `seed`,
+/// independent of the generators of the other components.
+pub fn derived(seed: u64, index: u64) -> SmallRng { seeded(SeedStream::new(seed).seed(index)) }
+
+/// Return the maximum of `values` and its index, breaking ties uniformly at
+/// random.
+pub fn argmax_choose(rng: &mut impl Rng, values: &[f64]) -> (f64, usize) {
+    let (value, maxima) = argmaxima(values);
+
+    let maximum = if maxima.len() == 1 {
+        maxima[0]
+    } else {
+        *maxima
+            .choose(rng)
+            .expect("No valid maxima to choose from in `argmax_choose`.")
+    };
+
+    (value, maximum)
+}
+
+/// Sample an index from a categorical distribution with the given
+/// `probabilities`.
+pub fn sample_probs(rng: &mut impl Rng, probabilities: &[f64]) -> usize {
+    let r = rng.gen::<f64>();
+
+    match probabilities
+        .into_iter()
+        .scan(0.0, |state, &p| {
+            *state = *state + p;
+
+            Some(*state)
+        })
+        .position(|p| p > r)
+    {
+        Some(index) => index,
+        None => probabilities.len() - 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{argmax_choose, derived, sample_probs, seeded, Rng};
+
+    #[test]
+    fn test_reproducible() {
+        let draws = |seed| -> Vec<u64> { (0..10).map(|_| seeded(seed).gen()).collect() };
+
+        assert_eq!(draws(1), draws(1));
+        assert_ne!(seeded(1).gen::<u64>(), derived(1, 0).gen::<u64>());
+        assert_ne!(derived(1, 0).gen::<u64>(), derived(1, 1).gen::<u64>());
+    }
+
+    #[test]
+    fn test_samplers() {
+        let mut rng = seeded(0);
+
+        assert_eq!(sample_probs(&mut rng, &[0.0, 1.0, 0.0]), 1);
+        assert_eq!(argmax_choose(&mut rng, &[1.0, 3.0, 2.0]), (3.0, 1));
+
+        for _ in 0..10 {
+            assert_ne!(argmax_choose(&mut rng, &[1.0, 0.0, 1.0]).1, 1);
+        }
+    }
+}
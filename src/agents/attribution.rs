@@ -0,0 +1,114 @@
+//! Attribution of an episode's PnL to spread capture, adverse selection and
+//! inventory carry, for comparing strategies replayed on identical scenarios.
+//!
+//! Each step's change in the mark-to-market value _wealth + inv * price_
+//! splits exactly into the edge of its fills relative to the midprice and
+//! the carry of the inventory held over the price move. The part of the
+//! carry due to the price moving against each fill within `horizon` steps
+//! is reported separately as adverse selection.
+use crate::env::{Env, dynamics::{price::PriceDynamics, execution::ExecutionDynamics}};
+use rsrl::domains::Domain;
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Attribution {
+    pub pnl: f64,
+
+    pub spread_capture: f64,
+    pub adverse_selection: f64,
+    pub inventory_carry: f64,
+
+    pub n_fills: usize,
+}
+
+/// Executions in a single step of an episode.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Fill {
+    pub step: usize,
+
+    pub ask: bool,
+    pub bid: bool,
+
+    /// Net change in inventory over the step.
+    pub quantity: f64,
+}
+
+/// Run an episode of `env` with the given quotes, attributing its PnL.
+pub fn replay<P, E, F>(mut env: Env<P, E>, mut quotes: F, horizon: usize) -> (Attribution, Vec<Fill>)
+where
+    P: PriceDynamics,
+    E: ExecutionDynamics,
+    F: FnMut(&Env<P, E>) -> [f64; 2],
+{
+    let mut attribution = Attribution::default();
+    let mut fills = vec![];
+    let mut prices = vec![env.dynamics.price];
+    let mut value = env.wealth + env.inv * env.dynamics.price;
+
+    loop {
+        let inv = env.inv;
+        let price = env.dynamics.price;
+        let a = quotes(&env);
+        let t = env.step(a);
+
+        let new_value = env.wealth + env.inv * env.dynamics.price;
+        let carry = inv * (env.dynamics.price - price);
+
+        attribution.spread_capture += new_value - value - carry;
+        attribution.inventory_carry += carry;
+
+        if env.ask_executed || env.bid_executed {
+            let new_inv = if t.terminated() { env.inv_terminal } else { env.inv };
+
+            fills.push(Fill {
+                step: prices.len() - 1,
+
+                ask: env.ask_executed,
+                bid: env.bid_executed,
+
+                quantity: new_inv - inv,
+            });
+        }
+
+        prices.push(env.dynamics.price);
+        value = new_value;
+
+        if t.terminated() {
+            break;
+        }
+    }
+
+    // Fills execute after the price moves within a step, i.e. at the price
+    // recorded after that step:
+    let last = prices.len() - 1;
+
+    attribution.adverse_selection = fills.iter().map(|f| {
+        f.quantity * (prices[(f.step + 1 + horizon).min(last)] - prices[f.step + 1])
+    }).sum();
+    attribution.inventory_carry -= attribution.adverse_selection;
+
+    attribution.pnl = value;
+    attribution.n_fills = fills.len();
+
+    (attribution, fills)
+}
+
+/// Number of fills of two replays of a scenario that occur on the same step
+/// and side of the book.
+pub fn aligned_fills(a: &[Fill], b: &[Fill]) -> usize {
+    let (mut i, mut j, mut n) = (0, 0, 0);
+
+    while i < a.len() && j < b.len() {
+        if a[i].step < b[j].step {
+            i += 1;
+        } else if a[i].step > b[j].step {
+            j += 1;
+        } else {
+            n += (a[i].ask && b[j].ask) as usize + (a[i].bid && b[j].bid) as usize;
+
+            i += 1;
+            j += 1;
+        }
+    }
+
+    n
+}
@@ -0,0 +1,177 @@
+//! Chunked, compressed checkpoints.
+//!
+//! A plain checkpoint is a single bincode value, which must be read in full
+//! even when only the policy is wanted, and which grows with the dense
+//! weights of large approximators. Here an agent is instead split into named
+//! sections, each serialised and zstd-compressed separately, behind a
+//! versioned header:
+//!
+//! ```text
+//! magic  version  (header  payload)*
+//! ```
+//!
+//! where each section header records the name and compressed length of its
+//! payload, so that a reader can skip straight to the sections it needs.
+use bincode::{deserialize_from, serialize_into};
+use crate::{
+    agents::{Critic, Spread, Trader, RP},
+    error::{Error, Result},
+};
+use rsrl::{
+    control::actor_critic::{RewardScale, TrustRegion, TDAC},
+    core::Parameter,
+    domains::Truncation,
+    policies::{IPP, ParameterNoise},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+};
+use zstd;
+
+/// Format version written by this build.
+pub const VERSION: u32 = 1;
+
+const MAGIC: [u8; 4] = *b"AHFT";
+
+/// Compression level passed to zstd; favours speed over size.
+const LEVEL: i32 = 3;
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    magic: [u8; 4],
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SectionHeader {
+    name: String,
+    length: u64,
+}
+
+/// Writer of the sections of one checkpoint, in order.
+pub struct CheckpointWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> CheckpointWriter<W> {
+    pub fn new(mut writer: W) -> Result<CheckpointWriter<W>> {
+        serialize_into(&mut writer, &Envelope { magic: MAGIC, version: VERSION, })?;
+
+        Ok(CheckpointWriter { writer, })
+    }
+
+    /// Compress and append `value` as the section `name`.
+    pub fn section<T: Serialize>(&mut self, name: &str, value: &T) -> Result<()> {
+        let mut encoder = zstd::Encoder::new(vec![], LEVEL)?;
+
+        serialize_into(&mut encoder, value)?;
+
+        let payload = encoder.finish()?;
+
+        serialize_into(&mut self.writer, &SectionHeader { name: name.to_owned(), length: payload.len() as u64, })?;
+        self.writer.write_all(&payload)?;
+
+        Ok(())
+    }
+
+    /// Flush the checkpoint, returning the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+/// Reader of a checkpoint, holding only the offsets of its sections until
+/// they are requested.
+pub struct CheckpointReader<R: Read + Seek> {
+    reader: R,
+
+    // Offset and compressed length of each section's payload.
+    sections: HashMap<String, (u64, u64)>,
+}
+
+impl<R: Read + Seek> CheckpointReader<R> {
+    pub fn new(mut reader: R) -> Result<CheckpointReader<R>> {
+        let envelope: Envelope = deserialize_from(&mut reader)?;
+
+        if envelope.magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a compressed checkpoint.").into());
+        }
+
+        if envelope.version != VERSION {
+            return Err(Error::CheckpointVersion { expected: VERSION, found: envelope.version, });
+        }
+
+        let mut offset = reader.seek(SeekFrom::Current(0))?;
+        let end = reader.seek(SeekFrom::End(0))?;
+
+        reader.seek(SeekFrom::Start(offset))?;
+
+        let mut sections = HashMap::new();
+
+        while offset < end {
+            let header: SectionHeader = deserialize_from(&mut reader)?;
+
+            offset = reader.seek(SeekFrom::Current(0))?;
+            sections.insert(header.name, (offset, header.length));
+            offset = reader.seek(SeekFrom::Current(header.length as i64))?;
+        }
+
+        Ok(CheckpointReader { reader, sections, })
+    }
+
+    /// Names of the sections in the checkpoint, in no particular order.
+    pub fn sections(&self) -> impl Iterator<Item = &str> { self.sections.keys().map(|s| s.as_str()) }
+
+    /// Decompress and deserialise the section `name` alone.
+    pub fn section<T: DeserializeOwned>(&mut self, name: &str) -> Result<T> {
+        let (offset, length) = *self.sections.get(name).ok_or_else(|| io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Checkpoint has no `{}` section.", name),
+        ))?;
+
+        self.reader.seek(SeekFrom::Start(offset))?;
+
+        let decoder = zstd::Decoder::new((&mut self.reader).take(length))?;
+
+        Ok(deserialize_from(decoder)?)
+    }
+}
+
+pub type TraderPolicy = ParameterNoise<IPP<RP, Spread>>;
+
+type LearnerParameters = (Parameter, Parameter, Truncation, Option<TrustRegion<TraderPolicy>>, Option<RewardScale>);
+
+/// Write a trader to `path` as a compressed checkpoint with `critic`,
+/// `policy` and `learner` sections.
+pub fn save_trader_compressed(agent: &Trader, path: String) -> Result<()> {
+    let mut writer = CheckpointWriter::new(BufWriter::new(File::create(path)?))?;
+    let learner: LearnerParameters =
+        (agent.alpha, agent.gamma, agent.truncation, agent.trust_region.clone(), agent.reward_scale);
+
+    writer.section("critic", &agent.critic)?;
+    writer.section("policy", &agent.policy)?;
+    writer.section("learner", &learner)?;
+    writer.finish().map(|_| ())
+}
+
+/// Read a trader written by `save_trader_compressed` from `path`.
+pub fn load_trader_compressed(path: String) -> Result<Trader> {
+    let mut reader = CheckpointReader::new(BufReader::new(File::open(path)?))?;
+
+    let critic: Critic = reader.section("critic")?;
+    let policy: TraderPolicy = reader.section("policy")?;
+    let (alpha, gamma, truncation, trust_region, reward_scale): LearnerParameters = reader.section("learner")?;
+
+    Ok(TDAC { critic, policy, alpha, gamma, truncation, trust_region, reward_scale, })
+}
+
+/// Read only the policy of a trader written by `save_trader_compressed`,
+/// e.g. for evaluation, skipping its critic.
+pub fn load_trader_policy(path: String) -> Result<TraderPolicy> {
+    CheckpointReader::new(BufReader::new(File::open(path)?))?.section("policy")
+}
@@ -0,0 +1,166 @@
+//! Compression of trained traders into smaller inference artifacts, e.g. for
+//! the FFI and WASM bindings.
+//!
+//! Every head of a trader is a linear function of the same polynomial basis
+//! (plus a constant), optionally passed through a softplus. Within the limits
+//! of the basis each polynomial term lies in [-1, 1], so zeroing a set of
+//! weights changes the output of a head by at most the sum of their absolute
+//! values; the softplus is 1-Lipschitz and preserves the bound. Terms whose
+//! weights are zero in every head are then dropped from the basis entirely.
+use crate::{
+    agents::{Basis, Projection, Trader},
+    error::{Error, Result},
+};
+use rsrl::{
+    fa::{basis::fixed::Polynomial, Composable},
+    geometry::Vector,
+    policies::ParameterNoise,
+};
+
+/// Bounds on the change in the output of each head over the state space.
+///
+/// The spread heads are bounded before squashing, which can only shrink the
+/// change in the quoted spread.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct ErrorBounds {
+    pub critic: f64,
+    pub rp_mean: f64,
+    pub rp_stddev: f64,
+    pub spread_mean: f64,
+    pub spread_stddev: f64,
+}
+
+impl ErrorBounds {
+    pub fn max(&self) -> f64 {
+        [self.rp_mean, self.rp_stddev, self.spread_mean, self.spread_stddev]
+            .iter()
+            .fold(self.critic, |acc, &b| acc.max(b))
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CompressionReport {
+    /// Number of weights across all heads before compression.
+    pub n_weights: usize,
+
+    /// Number of weights set to zero.
+    pub n_pruned: usize,
+
+    /// Number of polynomial terms in the basis before compression.
+    pub n_terms: usize,
+
+    /// Number of polynomial terms removed from the basis.
+    pub n_terms_removed: usize,
+
+    pub error_bounds: ErrorBounds,
+}
+
+/// Zero the smallest weights of `weights` while their total magnitude stays
+/// within `tolerance`, returning the number zeroed and their total magnitude.
+fn prune(weights: &mut Vector<f64>, tolerance: f64) -> (usize, f64) {
+    let mut order: Vec<usize> = (0..weights.len()).filter(|&i| weights[i] != 0.0).collect();
+
+    order.sort_by(|&i, &j| weights[i].abs().total_cmp(&weights[j].abs()));
+
+    let mut total = 0.0;
+    let mut n_pruned = 0;
+
+    for i in order {
+        let magnitude = weights[i].abs();
+
+        if total + magnitude > tolerance {
+            break;
+        }
+
+        total += magnitude;
+        n_pruned += 1;
+        weights[i] = 0.0;
+    }
+
+    (n_pruned, total)
+}
+
+/// Basis and weights of each head of `trader`, in the field order of
+/// `ErrorBounds`.
+fn heads(trader: &mut Trader) -> [(&mut Projection, &mut Vector<f64>); 5] {
+    let ipp = &mut trader.policy.policy;
+    let critic = &mut trader.critic.v_func;
+    let rp = &mut ipp.0;
+    let spread = &mut ipp.1.policy;
+
+    [
+        (&mut critic.projector, &mut critic.evaluator.weights),
+        (&mut rp.mean.0.projector, &mut rp.mean.0.evaluator.weights),
+        (&mut rp.stddev.0.projector, &mut rp.stddev.0.evaluator.weights),
+        (&mut spread.mean.0.projector, &mut spread.mean.0.evaluator.weights),
+        (&mut spread.stddev.0.projector, &mut spread.stddev.0.evaluator.weights),
+    ]
+}
+
+/// Prune the weights of each head of `trader` by up to `tolerance` in total
+/// magnitude, and remove the basis terms that no head uses any more.
+///
+/// The outputs of every head change by at most `tolerance` at any state
+/// within the limits of the basis; the exact bounds are reported. Parameter
+/// noise is restarted from the compressed policy with the same settings.
+pub fn compress_trader(trader: &Trader, tolerance: f64) -> Result<(Trader, CompressionReport)> {
+    if !(tolerance >= 0.0) {
+        return Err(Error::InvalidParameter { name: "tolerance", value: tolerance, });
+    }
+
+    let mut trader = trader.clone();
+    let basis: Basis = trader.critic.v_func.projector.projector().projectors().0.clone();
+    let n_terms = basis.exponents.len();
+
+    let mut n_weights = 0;
+    let mut n_pruned = 0;
+    let mut bounds = [0.0; 5];
+    let mut used = vec![false; n_terms];
+
+    for (k, (_, weights)) in heads(&mut trader).iter_mut().enumerate() {
+        let (n, bound) = prune(weights, tolerance);
+
+        n_weights += weights.len();
+        n_pruned += n;
+        bounds[k] = bound;
+
+        for (j, u) in used.iter_mut().enumerate() {
+            *u = *u || weights[j] != 0.0;
+        }
+    }
+
+    // The constant term follows the polynomial terms and is always kept:
+    let kept: Vec<usize> = (0..n_terms).filter(|&j| used[j]).chain(Some(n_terms)).collect();
+    let exponents = kept[..kept.len() - 1].iter().map(|&j| basis.exponents[j].clone()).collect();
+    let projection: Projection = Polynomial { exponents, ..basis }.with_constant().memoise();
+
+    for (projector, weights) in heads(&mut trader).iter_mut() {
+        **weights = kept.iter().map(|&j| weights[j]).collect();
+        **projector = projection.clone();
+    }
+
+    let noise = &trader.policy;
+
+    trader.policy = ParameterNoise::new(
+        noise.policy.clone(),
+        noise.stddev,
+        noise.target_distance,
+        noise.adaptation_rate,
+    );
+
+    let report = CompressionReport {
+        n_weights,
+        n_pruned,
+        n_terms,
+        n_terms_removed: n_terms + 1 - kept.len(),
+        error_bounds: ErrorBounds {
+            critic: bounds[0],
+            rp_mean: bounds[1],
+            rp_stddev: bounds[2],
+            spread_mean: bounds[3],
+            spread_stddev: bounds[4],
+        },
+    };
+
+    Ok((trader, report))
+}
@@ -0,0 +1,88 @@
+//! Comparison of trader snapshots, e.g. between two checkpoints of a run or
+//! before and after adversarial fine-tuning.
+use crate::{
+    agents::Trader,
+    error::{Error, Result},
+};
+use rsrl::{
+    core::ValuePredictor,
+    fa::Parameterised,
+    geometry::{continuous::Interval, product::LinearSpace, Matrix, Vector},
+    policies::Policy,
+};
+
+#[derive(Debug, Serialize)]
+pub struct Record {
+    pub critic_distance: f64,
+    pub critic_distance_relative: f64,
+
+    pub policy_distance: f64,
+    pub policy_distance_relative: f64,
+
+    pub rp_divergence_max: f64,
+    pub spread_divergence_max: f64,
+    pub value_divergence_max: f64,
+}
+
+/// Euclidean distance between two weight matrices, both in absolute terms
+/// and relative to the norm of `from`.
+fn distance(from: &Matrix<f64>, to: &Matrix<f64>) -> Result<(f64, f64)> {
+    if from.dim() != to.dim() {
+        return Err(Error::IncompatibleShapes { expected: from.dim(), found: to.dim() });
+    }
+
+    let norm = from.iter().fold(0.0, |acc, w| acc + w * w).sqrt();
+    let dist = from.iter().zip(to.iter()).fold(0.0, |acc, (x, y)| acc + (x - y).powi(2)).sqrt();
+
+    Ok((dist, dist / norm.max(1e-12)))
+}
+
+/// Regular grid over the state space, with `density` points per dimension
+/// placed at the centres of equally sized partitions.
+pub fn state_grid(state_space: LinearSpace<Interval>, density: usize) -> Vec<Vector<f64>> {
+    state_space.partitioned(density).centres().into_iter().fold(vec![vec![]], |grid, centres| {
+        grid.into_iter().flat_map(|point| centres.iter().map(move |&c| {
+            let mut point = point.clone();
+            point.push(c);
+            point
+        })).collect()
+    }).into_iter().map(Vector::from_vec).collect()
+}
+
+/// Compare trader `b` against trader `a` over the given set of states.
+///
+/// Action divergences are measured between the most probable actions of the
+/// two policies, i.e. the mean reservation price offset and spread.
+pub fn diff_traders(a: &mut Trader, b: &mut Trader, grid: &[Vector<f64>]) -> Result<Record> {
+    let (critic_distance, critic_distance_relative) =
+        distance(&a.critic.weights(), &b.critic.weights())?;
+    let (policy_distance, policy_distance_relative) =
+        distance(&a.policy.weights(), &b.policy.weights())?;
+
+    let mut rp_divergence_max: f64 = 0.0;
+    let mut spread_divergence_max: f64 = 0.0;
+    let mut value_divergence_max: f64 = 0.0;
+
+    for s in grid {
+        let (rp_a, sp_a) = a.policy.mpa(s);
+        let (rp_b, sp_b) = b.policy.mpa(s);
+
+        rp_divergence_max = rp_divergence_max.max((rp_a - rp_b).abs());
+        spread_divergence_max = spread_divergence_max.max((sp_a - sp_b).abs());
+        value_divergence_max = value_divergence_max.max(
+            (a.critic.predict_v(s) - b.critic.predict_v(s)).abs()
+        );
+    }
+
+    Ok(Record {
+        critic_distance,
+        critic_distance_relative,
+
+        policy_distance,
+        policy_distance_relative,
+
+        rp_divergence_max,
+        spread_divergence_max,
+        value_divergence_max,
+    })
+}
@@ -0,0 +1,217 @@
+//! Parameterisations of the adversary's policy over the normalised drift.
+//!
+//! Both parameterisations act on _[-1, 1]_, which is scaled to the drift of
+//! the price process by the training loops.
+use crate::agents::Projection;
+use rsrl::{
+    core::Algorithm,
+    fa::{Parameterised, TransformedLFA, LFA, transforms::Softplus},
+    geometry::{Matrix, Vector},
+    policies::{
+        gaussian::{self, Gaussian},
+        Beta,
+        DifferentiablePolicy,
+        Entropy,
+        KLDivergence,
+        ParameterisedPolicy,
+        Policy,
+        SkippedUpdates,
+        Squashed,
+    },
+};
+use std::str::FromStr;
+
+pub type BetaDrift = Beta<
+    TransformedLFA<
+        Projection,
+        lfa::eval::ScalarFunction,
+        Softplus,
+    >,
+>;
+
+pub type TanhGaussianDrift = Squashed<Gaussian<
+    gaussian::mean::Scalar<LFA<
+        Projection,
+        lfa::eval::ScalarFunction,
+    >>,
+    gaussian::stddev::Scalar<TransformedLFA<
+        Projection,
+        lfa::eval::ScalarFunction,
+        Softplus,
+    >>,
+>>;
+
+/// Choice of distribution for the adversary's policy.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DriftParameterisation {
+    /// Beta distribution, rescaled from _[0, 1]_. Both shape parameters are
+    /// bounded below by one, so the density cannot concentrate on the
+    /// endpoints.
+    Beta,
+
+    /// Gaussian squashed by _tanh_.
+    TanhGaussian,
+}
+
+impl Default for DriftParameterisation {
+    fn default() -> DriftParameterisation { DriftParameterisation::Beta }
+}
+
+impl FromStr for DriftParameterisation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DriftParameterisation, String> {
+        match s {
+            "beta" => Ok(DriftParameterisation::Beta),
+            "tanh_gaussian" => Ok(DriftParameterisation::TanhGaussian),
+            _ => Err(format!("Unknown drift policy `{}`; expected beta or tanh_gaussian.", s)),
+        }
+    }
+}
+
+/// Adversary policy over the normalised drift in _[-1, 1]_.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Drift {
+    Beta(BetaDrift),
+    TanhGaussian(TanhGaussianDrift),
+}
+
+impl Drift {
+    pub fn build(parameterisation: DriftParameterisation, basis: Projection) -> Drift {
+        match parameterisation {
+            DriftParameterisation::Beta => Drift::Beta(Beta::new(
+                TransformedLFA::scalar(basis.clone(), Softplus),
+                TransformedLFA::scalar(basis, Softplus),
+            )),
+            DriftParameterisation::TanhGaussian => Drift::TanhGaussian(Squashed::new(Gaussian::new(
+                gaussian::mean::Scalar(LFA::scalar(basis.clone())),
+                gaussian::stddev::Scalar(TransformedLFA::scalar(basis, Softplus)),
+            ))),
+        }
+    }
+
+    pub fn parameterisation(&self) -> DriftParameterisation {
+        match self {
+            Drift::Beta(_) => DriftParameterisation::Beta,
+            Drift::TanhGaussian(_) => DriftParameterisation::TanhGaussian,
+        }
+    }
+}
+
+// The Beta policy acts on [0, 1]:
+fn to_unit(a: f64) -> f64 { (a + 1.0) / 2.0 }
+
+fn from_unit(b: f64) -> f64 { 2.0 * b - 1.0 }
+
+impl Algorithm for Drift {
+    fn handle_terminal(&mut self) {
+        match self {
+            Drift::Beta(p) => p.handle_terminal(),
+            Drift::TanhGaussian(p) => p.handle_terminal(),
+        }
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        match self {
+            Drift::Beta(p) => p.reseed(seed),
+            Drift::TanhGaussian(p) => p.reseed(seed),
+        }
+    }
+}
+
+impl Policy<Vector<f64>> for Drift {
+    type Action = f64;
+
+    fn sample(&mut self, s: &Vector<f64>) -> f64 {
+        match self {
+            Drift::Beta(p) => from_unit(p.sample(s)),
+            Drift::TanhGaussian(p) => p.sample(s),
+        }
+    }
+
+    fn mpa(&mut self, s: &Vector<f64>) -> f64 {
+        match self {
+            Drift::Beta(p) => from_unit(p.mpa(s)),
+            Drift::TanhGaussian(p) => p.mpa(s),
+        }
+    }
+
+    fn probability(&mut self, s: &Vector<f64>, a: f64) -> f64 {
+        match self {
+            Drift::Beta(p) => p.probability(s, to_unit(a)) / 2.0,
+            Drift::TanhGaussian(p) => p.probability(s, a),
+        }
+    }
+}
+
+impl DifferentiablePolicy<Vector<f64>> for Drift {
+    fn grad_log(&self, s: &Vector<f64>, a: f64) -> Matrix<f64> {
+        match self {
+            Drift::Beta(p) => p.grad_log(s, to_unit(a)),
+            Drift::TanhGaussian(p) => p.grad_log(s, a),
+        }
+    }
+}
+
+impl KLDivergence<Vector<f64>> for Drift {
+    /// Divergence between policies of different parameterisations is not
+    /// defined, and is reported as infinite.
+    fn kl_divergence(&self, other: &Drift, s: &Vector<f64>) -> f64 {
+        match (self, other) {
+            (Drift::Beta(p), Drift::Beta(q)) => p.kl_divergence(q, s),
+            (Drift::TanhGaussian(p), Drift::TanhGaussian(q)) => p.kl_divergence(q, s),
+            _ => ::std::f64::INFINITY,
+        }
+    }
+}
+
+impl Entropy<Vector<f64>> for Drift {
+    fn entropy(&mut self, s: &Vector<f64>) -> f64 {
+        match self {
+            // Scaling [0, 1] onto [-1, 1] adds log(2):
+            Drift::Beta(p) => p.entropy(s) + 2.0f64.ln(),
+            Drift::TanhGaussian(p) => p.entropy(s),
+        }
+    }
+}
+
+impl SkippedUpdates for Drift {
+    fn take_skipped(&mut self) -> usize {
+        match self {
+            Drift::Beta(p) => p.take_skipped(),
+            Drift::TanhGaussian(p) => p.take_skipped(),
+        }
+    }
+}
+
+impl Parameterised for Drift {
+    fn weights(&self) -> Matrix<f64> {
+        match self {
+            Drift::Beta(p) => p.weights(),
+            Drift::TanhGaussian(p) => p.weights(),
+        }
+    }
+
+    fn weights_dim(&self) -> (usize, usize) {
+        match self {
+            Drift::Beta(p) => p.weights_dim(),
+            Drift::TanhGaussian(p) => p.weights_dim(),
+        }
+    }
+}
+
+impl ParameterisedPolicy<Vector<f64>> for Drift {
+    fn update(&mut self, s: &Vector<f64>, a: f64, error: f64) {
+        match self {
+            Drift::Beta(p) => p.update(s, to_unit(a), error),
+            Drift::TanhGaussian(p) => p.update(s, a, error),
+        }
+    }
+
+    fn update_raw(&mut self, errors: Matrix<f64>) {
+        match self {
+            Drift::Beta(p) => <_ as ParameterisedPolicy<Vector<f64>>>::update_raw(p, errors),
+            Drift::TanhGaussian(p) => <_ as ParameterisedPolicy<Vector<f64>>>::update_raw(p, errors),
+        }
+    }
+}
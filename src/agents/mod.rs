@@ -1,87 +1,128 @@
-extern crate bincode;
 extern crate rsrl;
 
+#[cfg(feature = "training")]
+pub mod attribution;
+#[cfg(feature = "compressed-checkpoints")]
+pub mod checkpoint;
+pub mod compression;
+#[cfg(feature = "training")]
+pub mod diff;
+pub mod drift;
+#[cfg(feature = "training")]
+pub mod ope;
+#[cfg(feature = "training")]
+pub mod scripted;
+#[cfg(feature = "training")]
 pub mod training;
 
-use self::bincode::{deserialize_from, serialize_into};
+// Construction of new agents is only needed for training; everything else in
+// this module is what inference requires to load and evaluate them.
+#[cfg(feature = "training")]
+mod spec;
+#[cfg(feature = "training")]
+pub use self::spec::*;
+
+use bincode::deserialize;
+#[cfg(feature = "fs")]
+use bincode::{deserialize_from, serialize_into};
+use crate::error::{Error, Result};
+pub use self::drift::{Drift, DriftParameterisation};
 use rsrl::{
-    control::actor_critic::TDAC,
+    control::actor_critic::{TrustRegion, TDAC},
     fa::{
         LFA,
         TransformedLFA,
-        Composable,
         basis::fixed::{Polynomial, Constant},
         transforms::Softplus,
     },
-    geometry::{continuous::Interval, product::LinearSpace},
-    policies::{gaussian::{self, Gaussian}, Beta, IPP},
+    geometry::{continuous::Interval, product::LinearSpace, Space},
+    policies::{gaussian, IPP, ParameterNoise, Squashed},
     prediction::td::TD,
 };
+#[cfg(feature = "fs")]
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(feature = "fs")]
 use std::{
-    fs::File,
+    fs::{read_dir, File},
     io::{BufReader, BufWriter},
+    path::PathBuf,
 };
 
+/// Write a checkpoint of any serialisable agent to `path`.
+#[cfg(feature = "fs")]
+pub fn save_agent<T: Serialize>(agent: &T, path: String) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    Ok(serialize_into(&mut writer, agent)?)
+}
+
+/// Read a checkpoint written by `save_agent` from `path`.
+#[cfg(feature = "fs")]
+pub fn load_agent<T: DeserializeOwned>(path: String) -> Result<T> {
+    let reader = BufReader::new(File::open(path)?);
+
+    Ok(deserialize_from(reader)?)
+}
+
 pub type Basis = Polynomial;
+/// Feature projection shared by all heads, which embed each state only once
+/// between them.
+pub type Projection = lfa::composition::Memoise<lfa::composition::Stack<Basis, Constant>>;
 pub type Critic = TD<LFA<
-    lfa::composition::Stack<Basis, Constant>,
+    Projection,
     lfa::eval::ScalarFunction
 >>;
 
 pub type RP = gaussian::Gaussian<
     gaussian::mean::Scalar<LFA<
-        lfa::composition::Stack<Basis, Constant>,
+        Projection,
         lfa::eval::ScalarFunction,
     >>,
     gaussian::stddev::Scalar<TransformedLFA<
-        lfa::composition::Stack<Basis, Constant>,
+        Projection,
         lfa::eval::ScalarFunction,
         Softplus,
     >>,
 >;
-pub type Spread = gaussian::Gaussian<
+/// Half-spread head, optionally squashed into the bounds of the spec.
+pub type Spread = Squashed<gaussian::Gaussian<
     gaussian::mean::Scalar<TransformedLFA<
-        lfa::composition::Stack<Basis, Constant>,
+        Projection,
         lfa::eval::ScalarFunction,
         Softplus,
     >>,
     gaussian::stddev::Scalar<TransformedLFA<
-        lfa::composition::Stack<Basis, Constant>,
+        Projection,
         lfa::eval::ScalarFunction,
         Softplus,
     >>,
->;
-pub type Drift = Beta<
-    TransformedLFA<
-        lfa::composition::Stack<Basis, Constant>,
-        lfa::eval::ScalarFunction,
-        Softplus,
-    >,
->;
+>>;
+pub type Trader = TDAC<Critic, ParameterNoise<IPP<RP, Spread>>, Option<TrustRegion<ParameterNoise<IPP<RP, Spread>>>>>;
+pub type Adversary = TDAC<Critic, Drift, Option<TrustRegion<Drift>>>;
+
+/// Number of state variables that the basis of an agent was built for.
+pub fn state_dim<P, R>(agent: &TDAC<Critic, P, R>) -> usize {
+    agent.critic.v_func.projector.projector().projectors().0.limits.len()
+}
 
-pub type Trader = TDAC<Critic, IPP<RP, Spread>>;
-pub type Adversary = TDAC<Critic, Drift>;
-
-// Trader:
-pub fn build_trader(state_space: LinearSpace<Interval>, critic_lr: f64, policy_lr: f64) -> Trader {
-    let basis = Basis::from_space(3, state_space).with_constant();
-    let critic = Critic::new(LFA::scalar(basis.clone()), critic_lr, 1.0);
-    let policy_rp = Gaussian::new(
-        gaussian::mean::Scalar(LFA::scalar(basis.clone())),
-        gaussian::stddev::Scalar(TransformedLFA::scalar(basis.clone(), Softplus)),
-    );
-    let policy_sp = Gaussian::new(
-        gaussian::mean::Scalar(TransformedLFA::scalar(basis.clone(), Softplus)),
-        gaussian::stddev::Scalar(TransformedLFA::scalar(basis.clone(), Softplus)),
-    );
-    let policy = IPP::new(policy_rp, policy_sp);
-
-    Trader::new(
-        critic,
-        policy,
-        policy_lr,
-        1.0,
-    )
+/// Check that the basis of an agent matches the dimensionality of
+/// `state_space`, and that the critic's weights match the basis.
+pub fn check_state_dim<P, R>(agent: &TDAC<Critic, P, R>, state_space: &LinearSpace<Interval>) -> Result<()> {
+    let expected = state_space.dim();
+    let found = state_dim(agent);
+
+    if found != expected {
+        return Err(Error::StateDimension { expected, found, });
+    }
+
+    let n_features = agent.critic.v_func.projector.dim();
+    let n_weights = agent.critic.v_func.evaluator.weights.len();
+
+    if n_weights != n_features {
+        return Err(Error::IncompatibleShapes { expected: (n_features, 1), found: (n_weights, 1), });
+    }
+
+    Ok(())
 }
 
 /// Transform trader action
@@ -92,43 +133,71 @@ pub fn tta(a: (f64, f64)) -> [f64; 2] {
     ]
 }
 
-pub fn save_trader(agent: &Trader, path: String) {
-    let mut writer = BufWriter::new(File::create(path).unwrap());
+#[cfg(feature = "fs")]
+pub fn save_trader(agent: &Trader, path: String) -> Result<()> { save_agent(agent, path) }
 
-    serialize_into(&mut writer, &agent).ok();
-}
+#[cfg(feature = "fs")]
+pub fn load_trader(path: String) -> Result<Trader> { load_agent(path) }
 
-pub fn load_trader(path: String) -> Trader {
-    let reader = BufReader::new(File::open(path).unwrap());
+/// Load a trader and check that it accepts states of `state_space`.
+#[cfg(feature = "fs")]
+pub fn load_trader_for(path: String, state_space: &LinearSpace<Interval>) -> Result<Trader> {
+    let trader = load_trader(path)?;
 
-    deserialize_from(reader).unwrap()
+    check_state_dim(&trader, state_space).map(|_| trader)
 }
 
-// Adversary:
-pub fn build_adversary(state_space: LinearSpace<Interval>, critic_lr: f64, policy_lr: f64) -> Adversary {
-    let basis = Basis::from_space(3, state_space).with_constant();
-    let critic = Critic::new(LFA::scalar(basis.clone()), critic_lr, 1.0);
-    let policy = Drift::new(
-        TransformedLFA::scalar(basis.clone(), Softplus),
-        TransformedLFA::scalar(basis, Softplus),
-    );
-
-    Adversary::new(
-        critic,
-        policy,
-        policy_lr,
-        1.0,
-    )
+pub fn trader_from_bytes(bytes: &[u8]) -> Result<Trader> {
+    Ok(deserialize(bytes)?)
 }
 
-pub fn save_adversary(agent: &Adversary, path: String) {
-    let mut writer = BufWriter::new(File::create(path).unwrap());
+#[cfg(feature = "fs")]
+pub fn save_adversary(agent: &Adversary, path: String) -> Result<()> { save_agent(agent, path) }
+
+#[cfg(feature = "fs")]
+pub fn load_adversary(path: String) -> Result<Adversary> { load_agent(path) }
+
+/// Load an adversary and check that it accepts states of `state_space`.
+#[cfg(feature = "fs")]
+pub fn load_adversary_for(path: String, state_space: &LinearSpace<Interval>) -> Result<Adversary> {
+    let adversary = load_adversary(path)?;
 
-    serialize_into(&mut writer, &agent).ok();
+    check_state_dim(&adversary, state_space).map(|_| adversary)
 }
 
-pub fn load_adversary(path: String) -> Adversary {
-    let reader = BufReader::new(File::open(path).unwrap());
+/// Checkpoints (`{prefix}*.bin`) in `dir`, labelled by file stem.
+///
+/// Numbered checkpoints come first, in numerical order, followed by the rest
+/// in lexicographic order; e.g. `adversary_2`, `adversary_10`, `adversary`,
+/// `adversary_best`.
+#[cfg(feature = "fs")]
+pub fn checkpoints_in(dir: &str, prefix: &str) -> Result<Vec<(String, PathBuf)>> {
+    let mut paths = vec![];
+
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        let stem = path.file_stem().and_then(|s| s.to_str()).map(|s| s.to_owned());
+
+        if let Some(stem) = stem {
+            if stem.starts_with(prefix) && path.extension().map_or(false, |e| e == "bin") {
+                let number: Option<u64> = stem.trim_start_matches(|c: char| !c.is_ascii_digit()).parse().ok();
+
+                paths.push((number.is_none(), number, stem, path));
+            }
+        }
+    }
+
+    paths.sort();
+
+    Ok(paths.into_iter().map(|(_, _, stem, path)| (stem, path)).collect())
+}
 
-    deserialize_from(reader).unwrap()
+/// Load every adversary checkpoint (`adversary*.bin`) in `dir`, labelled by
+/// file stem and in the order of `checkpoints_in`, and check that each
+/// accepts states of `state_space`.
+#[cfg(feature = "fs")]
+pub fn load_adversaries_in(dir: &str, state_space: &LinearSpace<Interval>) -> Result<Vec<(String, Adversary)>> {
+    checkpoints_in(dir, "adversary")?.into_iter().map(|(stem, path)| {
+        load_adversary_for(path.to_string_lossy().into_owned(), state_space).map(|a| (stem, a))
+    }).collect()
 }
@@ -0,0 +1,140 @@
+//! Off-policy evaluation of a candidate policy from episodes recorded under
+//! another (behaviour) policy, without further simulation.
+//!
+//! Returns are undiscounted, matching the training objective of the agents.
+use crate::{
+    agents::tta,
+    env::{Env, dynamics::{price::PriceDynamics, execution::ExecutionDynamics}},
+};
+use rsrl::{
+    core::ValuePredictor,
+    domains::Domain,
+    geometry::Vector,
+    policies::Policy,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Step<A> {
+    pub state: Vector<f64>,
+    pub action: A,
+    pub reward: f64,
+
+    /// Probability (density) of `action` under the behaviour policy.
+    pub behaviour_prob: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Episode<A> {
+    pub steps: Vec<Step<A>>,
+}
+
+impl<A: Clone> Episode<A> {
+    pub fn total_reward(&self) -> f64 { self.steps.iter().map(|s| s.reward).sum() }
+
+    /// Per-step importance ratios of `policy` relative to the behaviour policy.
+    fn ratios<P: Policy<Vector<f64>, Action = A>>(&self, policy: &mut P) -> Vec<f64> {
+        self.steps.iter()
+            .map(|s| policy.probability(&s.state, s.action.clone()) / s.behaviour_prob)
+            .collect()
+    }
+}
+
+/// Record an episode of `env` with quotes sampled from the trader `policy`.
+pub fn record_episode<D, E, P>(mut env: Env<D, E>, policy: &mut P) -> Episode<(f64, f64)>
+where
+    D: PriceDynamics,
+    E: ExecutionDynamics,
+    P: Policy<Vector<f64>, Action = (f64, f64)>,
+{
+    let mut steps = vec![];
+    let mut state = env.state().clone();
+
+    loop {
+        let action = policy.sample(&state);
+        let behaviour_prob = policy.probability(&state, action);
+        let t = env.step(tta(action));
+
+        steps.push(Step { state, action, reward: t.reward, behaviour_prob, });
+
+        if t.terminated() {
+            return Episode { steps, };
+        }
+
+        state = t.to.state().clone();
+    }
+}
+
+/// Trajectory-wise importance weights of `policy` for each episode.
+///
+/// Ratios are accumulated in log space to avoid underflow over long episodes.
+pub fn importance_weights<A, P>(episodes: &[Episode<A>], policy: &mut P) -> Vec<f64>
+where
+    A: Clone,
+    P: Policy<Vector<f64>, Action = A>,
+{
+    episodes.iter()
+        .map(|e| e.ratios(policy).into_iter().map(f64::ln).sum::<f64>().exp())
+        .collect()
+}
+
+/// Effective number of episodes supporting an importance-weighted estimate.
+pub fn effective_sample_size(weights: &[f64]) -> f64 {
+    let sum: f64 = weights.iter().sum();
+    let sumsq: f64 = weights.iter().map(|w| w * w).sum();
+
+    sum * sum / sumsq
+}
+
+/// Ordinary importance sampling estimate of the value of `policy`.
+///
+/// Unbiased, but with variance that grows rapidly with episode length.
+pub fn importance_sampling<A, P>(episodes: &[Episode<A>], policy: &mut P) -> f64
+where
+    A: Clone,
+    P: Policy<Vector<f64>, Action = A>,
+{
+    let weights = importance_weights(episodes, policy);
+
+    episodes.iter().zip(weights.iter())
+        .map(|(e, w)| w * e.total_reward())
+        .sum::<f64>() / episodes.len() as f64
+}
+
+/// Weighted importance sampling estimate of the value of `policy`.
+///
+/// Normalising by the total weight trades a small bias for a large reduction
+/// in variance.
+pub fn weighted_importance_sampling<A, P>(episodes: &[Episode<A>], policy: &mut P) -> f64
+where
+    A: Clone,
+    P: Policy<Vector<f64>, Action = A>,
+{
+    let weights = importance_weights(episodes, policy);
+    let total: f64 = weights.iter().sum();
+
+    episodes.iter().zip(weights.iter())
+        .map(|(e, w)| w * e.total_reward())
+        .sum::<f64>() / total
+}
+
+/// Per-decision doubly robust estimate of the value of `policy` (Jiang & Li,
+/// 2016), using the state values of `critic` as a control variate.
+///
+/// The estimate remains unbiased for any critic, and its variance shrinks as
+/// the critic approaches the true value function of `policy`.
+pub fn doubly_robust<A, P, V>(episodes: &[Episode<A>], policy: &mut P, critic: &mut V) -> f64
+where
+    A: Clone,
+    P: Policy<Vector<f64>, Action = A>,
+    V: ValuePredictor<Vector<f64>>,
+{
+    episodes.iter().map(|e| {
+        let ratios = e.ratios(policy);
+
+        e.steps.iter().zip(ratios.into_iter()).rev().fold(0.0, |dr, (s, rho)| {
+            let v = critic.predict_v(&s.state);
+
+            v + rho * (s.reward + dr - v)
+        })
+    }).sum::<f64>() / episodes.len() as f64
+}
@@ -0,0 +1,137 @@
+//! Non-learning adversaries that set the drift by a fixed rule.
+//!
+//! Training or evaluating the trader against these isolates the effect of
+//! facing a drifting market from that of an opponent whose own learning may
+//! be unstable. Learned and scripted adversaries share the `Opponent`
+//! interface, and both choose the drift as a fraction of its maximum in
+//! _[-1, 1]_.
+use crate::{
+    agents::Adversary,
+    env::Env,
+};
+use rsrl::{core::Controller, geometry::Vector};
+use std::{f64::consts::PI, str::FromStr};
+
+/// What an opponent may observe before choosing the drift for the next step.
+#[derive(Clone, Copy, Debug)]
+pub struct OpponentView<'a> {
+    /// Observation of a learned adversary.
+    pub state: &'a Vector<f64>,
+
+    pub time: f64,
+    pub dt: f64,
+    pub price: f64,
+    pub inv: f64,
+}
+
+impl<'a> OpponentView<'a> {
+    pub fn of<P, E>(env: &'a Env<P, E>, state: &'a Vector<f64>) -> OpponentView<'a> {
+        OpponentView {
+            state,
+
+            time: env.dynamics.time,
+            dt: env.dynamics.dt,
+            price: env.dynamics.price,
+            inv: env.inv,
+        }
+    }
+}
+
+/// Anything that chooses the normalised drift at each step of an episode.
+pub trait Opponent {
+    /// Prepare for a new episode.
+    fn reset(&mut self) {}
+
+    /// Return the drift for the next step, as a fraction of the maximum.
+    fn drift(&mut self, view: &OpponentView) -> f64;
+}
+
+impl Opponent for Adversary {
+    /// Drift of the adversary's target policy, without exploration.
+    fn drift(&mut self, view: &OpponentView) -> f64 { self.sample_target(view.state) }
+}
+
+/// Fixed rule by which a `ScriptedAdversary` sets the drift.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DriftRule {
+    /// `amplitude * sin(2π t / period)`.
+    Sinusoidal { amplitude: f64, period: f64 },
+
+    /// `gain` times an exponentially weighted average of price increments per
+    /// unit time, updated with weight `rate`; i.e. the drift reinforces
+    /// recent price moves.
+    Momentum { gain: f64, rate: f64 },
+
+    /// `-gain * inv`, i.e. the drift moves the price against the trader's
+    /// position.
+    InventoryTargeting { gain: f64 },
+}
+
+impl FromStr for DriftRule {
+    type Err = String;
+
+    /// Parse `sinusoidal:amplitude,period`, `momentum:gain,rate` or
+    /// `inventory:gain`.
+    fn from_str(s: &str) -> Result<DriftRule, String> {
+        let mut parts = s.splitn(2, ':');
+        let name = parts.next().unwrap().trim();
+        let params = parts.next().unwrap_or("")
+            .split(',')
+            .filter(|x| !x.trim().is_empty())
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid drift rule `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match (name, params.as_slice()) {
+            ("sinusoidal", &[amplitude, period]) if period > 0.0 =>
+                Ok(DriftRule::Sinusoidal { amplitude, period, }),
+            ("momentum", &[gain, rate]) if rate > 0.0 && rate <= 1.0 =>
+                Ok(DriftRule::Momentum { gain, rate, }),
+            ("inventory", &[gain]) =>
+                Ok(DriftRule::InventoryTargeting { gain, }),
+            _ => Err(format!(
+                "Drift rule `{}` must be sinusoidal:amplitude,period (period > 0), \
+                 momentum:gain,rate (rate in (0, 1]) or inventory:gain.", s
+            )),
+        }
+    }
+}
+
+/// Adversary following a `DriftRule`, clipped to _[-1, 1]_.
+#[derive(Clone, Debug)]
+pub struct ScriptedAdversary {
+    pub rule: DriftRule,
+
+    trend: f64,
+    last_price: Option<f64>,
+}
+
+impl ScriptedAdversary {
+    pub fn new(rule: DriftRule) -> ScriptedAdversary {
+        ScriptedAdversary { rule, trend: 0.0, last_price: None, }
+    }
+}
+
+impl Opponent for ScriptedAdversary {
+    fn reset(&mut self) {
+        self.trend = 0.0;
+        self.last_price = None;
+    }
+
+    fn drift(&mut self, view: &OpponentView) -> f64 {
+        let drift = match self.rule {
+            DriftRule::Sinusoidal { amplitude, period } => amplitude * (2.0 * PI * view.time / period).sin(),
+            DriftRule::Momentum { gain, rate } => {
+                if let Some(last_price) = self.last_price {
+                    self.trend = (1.0 - rate) * self.trend + rate * (view.price - last_price) / view.dt;
+                }
+
+                self.last_price = Some(view.price);
+
+                gain * self.trend
+            },
+            DriftRule::InventoryTargeting { gain } => -gain * view.inv,
+        };
+
+        drift.min(1.0).max(-1.0)
+    }
+}
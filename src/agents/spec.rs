@@ -0,0 +1,228 @@
+//! Construction of fresh agents for training.
+use crate::{
+    agents::{check_state_dim, Adversary, Basis, Critic, Drift, DriftParameterisation, Trader},
+    env::quotes::QuoteSpace,
+    error::{Error, Result},
+};
+use rsrl::{
+    control::actor_critic::{TwoTimescale, TDAC},
+    core::Loss,
+    domains::Truncation,
+    fa::{LFA, TransformedLFA, Composable, transforms::Softplus},
+    geometry::{continuous::Interval, product::LinearSpace, BoundedSpace},
+    policies::{gaussian::{self, Gaussian}, IPP, ParameterNoise, Squashed},
+};
+use std::str::FromStr;
+
+// Trader:
+/// Source of the trader's exploration during training.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Exploration {
+    /// Sample quotes from the Gaussian heads of the policy.
+    Action,
+
+    /// Act greedily under a perturbed copy of the policy weights, redrawn
+    /// every episode with a noise scale adapted towards `target_distance`.
+    Parameter {
+        stddev: f64,
+        target_distance: f64,
+        adaptation_rate: f64,
+    },
+}
+
+impl Default for Exploration {
+    fn default() -> Exploration { Exploration::Action }
+}
+
+impl FromStr for Exploration {
+    type Err = String;
+
+    /// Parse either `action` or `parameter:stddev,target_distance,adaptation_rate`.
+    fn from_str(s: &str) -> ::std::result::Result<Exploration, String> {
+        let mut parts = s.splitn(2, ':');
+
+        match (parts.next().unwrap().trim(), parts.next()) {
+            ("action", None) => Ok(Exploration::Action),
+            ("parameter", Some(params)) => {
+                let params = params.split(',')
+                    .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid exploration `{}`: {}.", s, e)))
+                    .collect::<::std::result::Result<Vec<f64>, String>>()?;
+
+                match params.as_slice() {
+                    &[stddev, target_distance, adaptation_rate]
+                        if stddev > 0.0 && target_distance > 0.0 && adaptation_rate > 1.0 =>
+                        Ok(Exploration::Parameter { stddev, target_distance, adaptation_rate, }),
+                    _ => Err(format!(
+                        "Exploration `{}` requires a positive stddev and target distance, and an adaptation rate above 1.", s
+                    )),
+                }
+            },
+            _ => Err(format!(
+                "Exploration `{}` must be `action` or `parameter:stddev,target_distance,adaptation_rate`.", s
+            )),
+        }
+    }
+}
+
+/// Construction options of a trader.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct TraderSpec {
+    pub learning_rates: TwoTimescale,
+    pub exploration: Exploration,
+
+    /// Bound on the KL divergence induced by each policy update.
+    pub max_kl: Option<f64>,
+
+    /// Normalise rewards by a running estimate of the return's scale.
+    pub normalise_rewards: bool,
+
+    /// Minimum and maximum half-spread, enforced by squashing the output of
+    /// the spread head.
+    pub spread_bounds: Option<(f64, f64)>,
+
+    /// Treatment of the fixed horizon by the critic and actor.
+    pub truncation: Truncation,
+
+    /// Loss on the critic's TD error; a Huber loss keeps the terminal
+    /// inventory penalty from dominating its updates.
+    pub critic_loss: Loss,
+}
+
+impl TraderSpec {
+    pub fn new(critic_lr: f64, policy_lr: f64) -> TraderSpec {
+        TraderSpec::from_timescales(TwoTimescale::new(critic_lr, policy_lr / critic_lr))
+    }
+
+    pub fn from_timescales(learning_rates: TwoTimescale) -> TraderSpec {
+        TraderSpec {
+            learning_rates,
+            exploration: Exploration::default(),
+            max_kl: None,
+            normalise_rewards: false,
+            spread_bounds: None,
+            truncation: Truncation::default(),
+            critic_loss: Loss::default(),
+        }
+    }
+
+    pub fn with_exploration(self, exploration: Exploration) -> TraderSpec {
+        TraderSpec { exploration, ..self }
+    }
+
+    pub fn with_max_kl(self, max_kl: f64) -> TraderSpec {
+        TraderSpec { max_kl: Some(max_kl), ..self }
+    }
+
+    pub fn with_spread_bounds(self, min: f64, max: f64) -> TraderSpec {
+        TraderSpec { spread_bounds: Some((min, max)), ..self }
+    }
+
+    /// Squash the spread head into the half-spread interval of `quotes`, which
+    /// should be the action space of the environment the trader will act in,
+    /// so that its sampled spreads are never clamped by the environment.
+    pub fn with_quote_space(self, quotes: &QuoteSpace) -> TraderSpec {
+        let half_spread = quotes.half_spread;
+        let min = half_spread.inf().unwrap_or(0.0);
+        let max = half_spread.sup().unwrap_or(::std::f64::INFINITY);
+
+        self.with_spread_bounds(min, max)
+    }
+
+    pub fn build(&self, state_space: LinearSpace<Interval>) -> Result<Trader> {
+        let basis = Basis::from_space(3, state_space.clone()).with_constant().memoise();
+        let critic = Critic::new(LFA::scalar(basis.clone()), self.learning_rates.critic(), 1.0)
+            .with_truncation(self.truncation)
+            .with_loss(self.critic_loss);
+        let policy_rp = Gaussian::new(
+            gaussian::mean::Scalar(LFA::scalar(basis.clone())),
+            gaussian::stddev::Scalar(TransformedLFA::scalar(basis.clone(), Softplus)),
+        );
+        let policy_sp = Gaussian::new(
+            gaussian::mean::Scalar(TransformedLFA::scalar(basis.clone(), Softplus)),
+            gaussian::stddev::Scalar(TransformedLFA::scalar(basis.clone(), Softplus)),
+        );
+        let policy_sp = match self.spread_bounds {
+            Some((min, max)) if !(min < max && min.is_finite() && max.is_finite()) =>
+                return Err(Error::InvalidParameter { name: "spread_bounds", value: max, }),
+            Some((min, max)) => Squashed::bounded(policy_sp, min, max),
+            None => Squashed::unbounded(policy_sp),
+        };
+        let policy = IPP::new(policy_rp, policy_sp);
+        let policy = match self.exploration {
+            Exploration::Action => ParameterNoise::disabled(policy),
+            Exploration::Parameter { stddev, target_distance, adaptation_rate } =>
+                ParameterNoise::new(policy, stddev, target_distance, adaptation_rate),
+        };
+
+        let trader = TDAC::new(
+            critic,
+            policy,
+            self.learning_rates.actor(),
+            1.0,
+        ).with_truncation(self.truncation).with_trust_region(self.max_kl);
+
+        let trader = if self.normalise_rewards {
+            trader.with_reward_normalisation()
+        } else {
+            trader
+        };
+
+        check_state_dim(&trader, &state_space).map(|_| trader)
+    }
+}
+
+/// Build a trader with the default specification, whose spread head is not
+/// squashed; see `TraderSpec::with_quote_space` to bound it by the
+/// environment's quotes.
+pub fn build_trader(state_space: LinearSpace<Interval>, critic_lr: f64, policy_lr: f64) -> Result<Trader> {
+    TraderSpec::new(critic_lr, policy_lr).build(state_space)
+}
+
+// Adversary:
+/// Construction options of an adversary.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct AdversarySpec {
+    pub critic_lr: f64,
+    pub policy_lr: f64,
+
+    /// Distribution of the policy over the normalised drift.
+    pub drift: DriftParameterisation,
+
+    /// Treatment of the fixed horizon by the critic and actor.
+    pub truncation: Truncation,
+}
+
+impl AdversarySpec {
+    pub fn new(critic_lr: f64, policy_lr: f64) -> AdversarySpec {
+        AdversarySpec {
+            critic_lr,
+            policy_lr,
+            drift: DriftParameterisation::default(),
+            truncation: Truncation::default(),
+        }
+    }
+
+    pub fn with_drift(self, drift: DriftParameterisation) -> AdversarySpec {
+        AdversarySpec { drift, ..self }
+    }
+
+    pub fn build(&self, state_space: LinearSpace<Interval>) -> Result<Adversary> {
+        let basis = Basis::from_space(3, state_space.clone()).with_constant().memoise();
+        let critic = Critic::new(LFA::scalar(basis.clone()), self.critic_lr, 1.0)
+            .with_truncation(self.truncation);
+        let policy = Drift::build(self.drift, basis);
+
+        let adversary = TDAC::new(
+            critic,
+            policy,
+            self.policy_lr,
+            1.0,
+        ).with_truncation(self.truncation).with_trust_region(None);
+
+        check_state_dim(&adversary, &state_space).map(|_| adversary)
+    }
+}
+
+pub fn build_adversary(state_space: LinearSpace<Interval>, critic_lr: f64, policy_lr: f64) -> Result<Adversary> {
+    AdversarySpec::new(critic_lr, policy_lr).build(state_space)
+}
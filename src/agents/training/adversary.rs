@@ -1,12 +1,15 @@
 use crate::{
-    agents::{Trader, Adversary, tta},
-    env::{Env, dynamics::{price::BrownianMotionWithDrift, execution::ExecutionDynamics}},
+    agents::{Trader, Adversary, tta, training::{probes::{ProbeValues, Probes}, stopping::StoppingRule}},
+    env::{
+        Env,
+        dynamics::{price::BrownianMotionWithDrift, execution::ExecutionDynamics},
+        observation::TrailingWindow,
+    },
     utils::Estimate,
 };
 use rsrl::{
-    core::{Algorithm, OnlineLearner, Controller},
+    core::{Algorithm, OnlineLearner, Controller, SeedStream},
     domains::Domain,
-    geometry::Vector,
     policies::Policy,
 };
 
@@ -15,6 +18,7 @@ const MAX_DRIFT: f64 = 5.0;
 #[derive(Debug, Serialize)]
 pub struct Record {
     pub episode: usize,
+    pub n_simulations: usize,
 
     pub wealth_mean: f64,
     pub wealth_stddev: f64,
@@ -25,36 +29,42 @@ pub struct Record {
     pub inv_mean: f64,
     pub inv_stddev: f64,
 
+    pub cap_rate_mean: f64,
+    pub cap_rate_stddev: f64,
+
     pub drift_mean: f64,
     pub drift_stddev: f64,
 
-    pub drift_neutral: f64,
-    pub drift_bull: f64,
-    pub drift_bear: f64,
+    #[serde(flatten)]
+    pub drift: ProbeValues,
 }
 
 pub fn train_value_function<E: ExecutionDynamics>(
     mut env: Env<BrownianMotionWithDrift, E>,
     trader: &mut Trader,
-    adversary: &mut Adversary
+    adversary: &mut Adversary,
+    window: &mut TrailingWindow,
 ) -> Env<BrownianMotionWithDrift, E>
 {
-    let obs = env.emit();
+    window.reset(&env);
 
+    let mut obs = window.observe(&env.emit());
     let mut drift = adversary.sample_behaviour(obs.state());
-    let mut quotes = trader.sample_behaviour(obs.state());
+    let mut quotes = trader.sample_behaviour(env.state());
 
     loop {
-        env.dynamics.price_dynamics.drift = MAX_DRIFT * (2.0 * drift - 1.0);
+        env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
 
-        let t = env.step(tta(quotes)).replace_action(drift).negate_reward();
+        let t = env.step(tta(quotes));
+        let t_adv = window.transition(&env, obs, drift, &t).negate_reward();
 
-        adversary.critic.handle_transition(&t);
+        adversary.critic.handle_transition(&t_adv);
 
         if t.terminated() {
             break
         } else {
-            drift = adversary.sample_behaviour(t.to.state());
+            obs = t_adv.to;
+            drift = adversary.sample_behaviour(obs.state());
             quotes = trader.sample_behaviour(t.to.state());
         }
     }
@@ -65,25 +75,29 @@ pub fn train_value_function<E: ExecutionDynamics>(
 pub fn train_adversary_once<E: ExecutionDynamics>(
     mut env: Env<BrownianMotionWithDrift, E>,
     trader: &mut Trader,
-    adversary: &mut Adversary
+    adversary: &mut Adversary,
+    window: &mut TrailingWindow,
 ) -> Env<BrownianMotionWithDrift, E>
 {
-    let obs = env.emit();
+    window.reset(&env);
 
+    let mut obs = window.observe(&env.emit());
     let mut drift = adversary.sample_behaviour(obs.state());
-    let mut quotes = trader.sample_behaviour(obs.state());
+    let mut quotes = trader.sample_behaviour(env.state());
 
     loop {
-        env.dynamics.price_dynamics.drift = MAX_DRIFT * (2.0 * drift - 1.0);
+        env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
 
-        let t = env.step(tta(quotes)).replace_action(drift).negate_reward();
+        let t = env.step(tta(quotes));
+        let t_adv = window.transition(&env, obs, drift, &t).negate_reward();
 
-        adversary.handle_transition(&t);
+        adversary.handle_transition(&t_adv);
 
         if t.terminated() {
             break
         } else {
-            drift = adversary.sample_behaviour(t.to.state());
+            obs = t_adv.to;
+            drift = adversary.sample_behaviour(obs.state());
             quotes = trader.sample_behaviour(t.to.state());
         }
     }
@@ -97,20 +111,21 @@ pub fn train_adversary_once<E: ExecutionDynamics>(
 pub fn evaluate_adversary_once<E: ExecutionDynamics>(
     mut env: Env<BrownianMotionWithDrift, E>,
     trader: &mut Trader,
-    adversary: &mut Adversary
-) -> (f64, f64, f64, f64)
+    adversary: &mut Adversary,
+    window: &mut TrailingWindow,
+) -> (f64, f64, f64, f64, f64)
 {
     let mut i = 0;
     let mut drift_sum = 0.0;
     let mut reward_sum = 0.0;
 
-    let obs = env.emit();
+    window.reset(&env);
 
-    let mut drift = adversary.sample_target(obs.state());
-    let mut quotes = trader.sample_target(obs.state());
+    let mut drift = adversary.sample_target(&window.augment(env.state()));
+    let mut quotes = trader.sample_target(env.state());
 
     loop {
-        env.dynamics.price_dynamics.drift = MAX_DRIFT * (2.0 * drift - 1.0);
+        env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
 
         let t = env.step(tta(quotes));
 
@@ -118,10 +133,15 @@ pub fn evaluate_adversary_once<E: ExecutionDynamics>(
         drift_sum += drift;
         reward_sum += t.reward;
 
+        window.record(&env, t.action);
+
         if t.terminated() {
-            return (env.wealth, drift_sum / i as f64, reward_sum, env.inv_terminal);
+            return (
+                env.wealth, drift_sum / i as f64, reward_sum, env.inv_terminal,
+                env.dynamics.cap_rate(),
+            );
         } else {
-            drift = adversary.sample_target(t.to.state());
+            drift = adversary.sample_target(&window.augment(t.to.state()));
             quotes = trader.sample_target(t.to.state());
         }
     }
@@ -133,33 +153,49 @@ pub fn evaluate_adversary<E: ExecutionDynamics>(
     adversary: &mut Adversary,
     episode: usize,
     n_simulations: usize,
+    stopping: Option<StoppingRule>,
+    seeds: SeedStream,
+    probes: &Probes,
+    window: &mut TrailingWindow,
 ) -> Record
 {
     let mut pnls = vec![];
     let mut drifts = vec![];
     let mut rewards = vec![];
     let mut terminal_qs = vec![];
+    let mut cap_rates = vec![];
+
+    for j in 0..n_simulations {
+        let seeds = seeds.substream(j as u64);
+
+        trader.reseed(seeds.seed(1));
+        adversary.reseed(seeds.seed(2));
 
-    for _ in 0..n_simulations {
-        let (p, d, r, q) = evaluate_adversary_once(env_builder(), trader, adversary);
+        let env = env_builder().seeded(seeds.seed(0));
+        let (p, d, r, q, c) = evaluate_adversary_once(env, trader, adversary, window);
 
         pnls.push(p);
         drifts.push(d);
         rewards.push(r);
         terminal_qs.push(q);
+        cap_rates.push(c);
+
+        if stopping.map_or(false, |rule| rule.is_satisfied(&pnls)) {
+            break;
+        }
     }
 
     let pnl_est = Estimate::from_slice(&pnls);
     let rwd_est = Estimate::from_slice(&rewards);
     let inv_est = Estimate::from_slice(&terminal_qs);
     let dft_est = Estimate::from_slice(&drifts);
+    let cap_est = Estimate::from_slice(&cap_rates);
 
-    let drift_neutral = adversary.policy.mpa(&Vector::from_vec(vec![0.0, 0.0]));
-    let drift_bull = adversary.policy.mpa(&Vector::from_vec(vec![0.0, 5.0]));
-    let drift_bear = adversary.policy.mpa(&Vector::from_vec(vec![0.0, -5.0]));
+    let drift = probes.evaluate("drift", |s| adversary.policy.mpa(&window.pad(s)));
 
     Record {
         episode,
+        n_simulations: pnls.len(),
 
         wealth_mean: pnl_est.0,
         wealth_stddev: pnl_est.1,
@@ -170,11 +206,12 @@ pub fn evaluate_adversary<E: ExecutionDynamics>(
         inv_mean: inv_est.0,
         inv_stddev: inv_est.1,
 
+        cap_rate_mean: cap_est.0,
+        cap_rate_stddev: cap_est.1,
+
         drift_mean: dft_est.0,
         drift_stddev: dft_est.1,
 
-        drift_neutral,
-        drift_bull,
-        drift_bear,
+        drift,
     }
 }
@@ -0,0 +1,121 @@
+//! Detection of pathological training episodes, such as non-finite rewards
+//! or diverging weights, which would otherwise silently ruin long runs.
+#[cfg(feature = "fs")]
+use crate::{agents::save_agent, error::Result};
+use crate::env::{Env, INV_BOUNDS};
+use rsrl::{
+    domains::Transition,
+    geometry::{Matrix, Vector},
+};
+#[cfg(feature = "fs")]
+use serde::Serialize;
+#[cfg(feature = "fs")]
+use std::{fs::File, io::BufWriter};
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum Anomaly {
+    /// The reward at the given step of the episode is NaN or infinite.
+    NonFiniteReward { step: usize },
+
+    /// Some weight of the agent is NaN or infinite.
+    NonFiniteWeights,
+
+    /// The norm of the agent's weights exceeds the detector's threshold.
+    ExplodingWeights { norm: f64 },
+
+    /// The episode terminated with the inventory at one of its bounds.
+    InventoryAtBound { inv: f64 },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TraceStep {
+    pub state: Vec<f64>,
+    pub quotes: [f64; 2],
+    pub reward: f64,
+}
+
+/// Per-episode checks for anomalies, keeping a trace of the current episode
+/// for post-mortem analysis.
+#[derive(Clone, Debug)]
+pub struct AnomalyDetector {
+    /// Threshold on the Frobenius norm of the agent's weights.
+    pub max_weight_norm: f64,
+
+    /// Number of episodes flagged so far.
+    pub n_flagged: usize,
+
+    trace: Vec<TraceStep>,
+}
+
+impl AnomalyDetector {
+    pub fn new(max_weight_norm: f64) -> AnomalyDetector {
+        AnomalyDetector {
+            max_weight_norm,
+            n_flagged: 0,
+
+            trace: vec![],
+        }
+    }
+
+    /// Clear the trace at the start of an episode.
+    pub fn reset(&mut self) { self.trace.clear(); }
+
+    /// Record an environment transition of the current episode.
+    pub fn record(&mut self, t: &Transition<Vector<f64>, [f64; 2]>) {
+        self.trace.push(TraceStep {
+            state: t.from.state().to_vec(),
+            quotes: t.action,
+            reward: t.reward,
+        });
+    }
+
+    pub fn trace(&self) -> &[TraceStep] { &self.trace }
+
+    /// Check the finished episode and the agent's weights for anomalies.
+    pub fn inspect<P, E>(&mut self, env: &Env<P, E>, weights: &[Matrix<f64>]) -> Vec<Anomaly> {
+        let mut anomalies: Vec<Anomaly> = self.trace.iter().enumerate()
+            .filter(|(_, s)| !s.reward.is_finite())
+            .map(|(step, _)| Anomaly::NonFiniteReward { step, })
+            .collect();
+
+        let sumsq: f64 = weights.iter().flat_map(|w| w.iter()).map(|w| w * w).sum();
+
+        if !sumsq.is_finite() {
+            anomalies.push(if weights.iter().flat_map(|w| w.iter()).all(|w| w.is_finite()) {
+                Anomaly::ExplodingWeights { norm: sumsq.sqrt() }
+            } else {
+                Anomaly::NonFiniteWeights
+            });
+        } else if sumsq.sqrt() > self.max_weight_norm {
+            anomalies.push(Anomaly::ExplodingWeights { norm: sumsq.sqrt() });
+        }
+
+        if env.inv_terminal <= INV_BOUNDS[0] || env.inv_terminal >= INV_BOUNDS[1] {
+            anomalies.push(Anomaly::InventoryAtBound { inv: env.inv_terminal });
+        }
+
+        if !anomalies.is_empty() {
+            self.n_flagged += 1;
+        }
+
+        anomalies
+    }
+
+    /// Write the agent to `{stem}.bin` and the anomalies, together with the
+    /// trace of the episode, to `{stem}.json`.
+    #[cfg(feature = "fs")]
+    pub fn snapshot<T: Serialize>(&self, stem: &str, agent: &T, anomalies: &[Anomaly]) -> Result<()> {
+        #[derive(Serialize)]
+        struct Report<'a> {
+            anomalies: &'a [Anomaly],
+            trace: &'a [TraceStep],
+        }
+
+        save_agent(agent, format!("{}.bin", stem))?;
+
+        let writer = BufWriter::new(File::create(format!("{}.json", stem))?);
+
+        serde_json::to_writer(writer, &Report { anomalies, trace: &self.trace, })
+            .map_err(|e| ::std::io::Error::from(e).into())
+    }
+}
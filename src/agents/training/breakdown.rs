@@ -0,0 +1,61 @@
+//! Evaluation outcomes grouped by the market conditions of each episode.
+//!
+//! Averages over all rollouts hide whether a trader loses money uniformly or
+//! only in, e.g., the most volatile quarter of episodes. Here episodes are
+//! ranked by their realised volatility and net drift and split into
+//! quartiles, with PnL and inventory statistics reported per quartile.
+use crate::{
+    agents::training::{probes::ProbeValues, trader::Episode},
+    utils::Estimate,
+};
+
+const N_QUANTILES: usize = 4;
+
+#[derive(Clone, Debug, Default)]
+pub struct RegimeBreakdown {
+    episodes: Vec<Episode>,
+}
+
+impl RegimeBreakdown {
+    pub fn record(&mut self, episode: &Episode) { self.episodes.push(*episode); }
+
+    fn by(&self, prefix: &'static str, key: fn(&Episode) -> f64) -> ProbeValues {
+        let mut ranked: Vec<&Episode> = self.episodes.iter().collect();
+        ranked.sort_by(|a, b| key(a).total_cmp(&key(b)));
+
+        let n = ranked.len();
+        let mut values = vec![];
+
+        for q in 0..N_QUANTILES {
+            let bucket = &ranked[q * n / N_QUANTILES..(q + 1) * n / N_QUANTILES];
+            let label = |stat: &str| format!("q{}_{}", q + 1, stat);
+
+            if bucket.is_empty() {
+                for stat in &["upper", "wealth_mean", "wealth_stddev", "inv_mean", "inv_stddev"] {
+                    values.push((label(stat), ::std::f64::NAN));
+                }
+
+                continue;
+            }
+
+            let pnl_est = Estimate::from_slice(&bucket.iter().map(|e| e.wealth).collect::<Vec<_>>());
+            let inv_est = Estimate::from_slice(&bucket.iter().map(|e| e.inv_terminal).collect::<Vec<_>>());
+
+            values.push((label("upper"), key(bucket[bucket.len() - 1])));
+            values.push((label("wealth_mean"), pnl_est.0));
+            values.push((label("wealth_stddev"), pnl_est.1));
+            values.push((label("inv_mean"), inv_est.0));
+            values.push((label("inv_stddev"), inv_est.1));
+        }
+
+        ProbeValues::new(prefix, values)
+    }
+
+    /// Statistics per quartile of realised volatility, as `vol_q{i}_{stat}`
+    /// columns, where `upper` is the largest volatility in the quartile;
+    /// NaN for quartiles left empty by fewer than four episodes.
+    pub fn by_volatility(&self) -> ProbeValues { self.by("vol", |e| e.realised_volatility) }
+
+    /// Statistics per quartile of net drift, as `drift_q{i}_{stat}` columns.
+    pub fn by_drift(&self) -> ProbeValues { self.by("drift", |e| e.realised_drift) }
+}
@@ -0,0 +1,212 @@
+//! Derivative-free search over the trader's policy weights with the
+//! cross-entropy method (CEM).
+//!
+//! The policy heads are linear in a few dozen features, which is a small
+//! enough search space for an evolutionary method to be competitive with
+//! actor-critic training; it needs neither a critic nor step sizes, so it
+//! makes for a stable baseline. Each generation samples a population of
+//! weights from a diagonal Gaussian, scores every candidate by its mean
+//! reward over the same evaluation episodes, and refits the Gaussian to the
+//! elite candidates.
+//!
+//! The trader's critic and learner parameters are carried along untouched.
+use crate::{
+    agents::{Trader, training::trader::evaluate_trader_once_with},
+    env::{
+        Env,
+        dynamics::{execution::ExecutionDynamics, price::PriceDynamics},
+    },
+    error::Result,
+    utils::Estimate,
+};
+use rand::{distributions::StandardNormal, Rng};
+use rayon::prelude::*;
+use rsrl::{
+    core::{Algorithm, SeedStream},
+    fa::Parameterised,
+    geometry::{Matrix, Vector},
+    policies::ParameterisedPolicy,
+};
+
+/// Summary of one generation of the search.
+#[derive(Clone, Debug, Serialize)]
+pub struct Record {
+    pub generation: usize,
+    pub population: usize,
+    pub n_episodes: usize,
+
+    /// Mean score over the population and over its elite.
+    pub reward_mean: f64,
+    pub reward_stddev: f64,
+    pub elite_mean: f64,
+
+    /// Score of the best candidate of the generation.
+    pub reward_best: f64,
+
+    /// Mean standard deviation of the search distribution after the update.
+    pub search_stddev: f64,
+}
+
+/// Diagonal Gaussian search distribution over the trader's policy weights.
+#[derive(Clone, Debug)]
+pub struct CrossEntropy {
+    pub population: usize,
+    pub elite_fraction: f64,
+
+    /// Floor on the standard deviation of every weight, which keeps the
+    /// search from collapsing prematurely.
+    pub min_stddev: f64,
+
+    /// Weight in (0, 1] given to the elite statistics in each update.
+    pub smoothing: f64,
+
+    pub generation: usize,
+
+    mean: Matrix<f64>,
+    stddev: Matrix<f64>,
+}
+
+impl CrossEntropy {
+    /// Centre the search on the current policy weights of `trader`.
+    pub fn new(trader: &Trader, population: usize, elite_fraction: f64, stddev: f64) -> CrossEntropy {
+        let mean = trader.policy.weights();
+        let stddev = Matrix::from_elem(mean.dim(), stddev);
+
+        CrossEntropy {
+            population: population.max(2),
+            elite_fraction,
+
+            min_stddev: 1e-3,
+            smoothing: 1.0,
+
+            generation: 0,
+
+            mean, stddev,
+        }
+    }
+
+    pub fn with_min_stddev(self, min_stddev: f64) -> CrossEntropy {
+        CrossEntropy { min_stddev, ..self }
+    }
+
+    pub fn with_smoothing(self, smoothing: f64) -> CrossEntropy {
+        CrossEntropy { smoothing, ..self }
+    }
+
+    /// Mean of the search distribution, i.e. the current solution.
+    pub fn mean(&self) -> &Matrix<f64> { &self.mean }
+
+    fn n_elite(&self) -> usize {
+        ((self.elite_fraction * self.population as f64).round() as usize).max(1).min(self.population)
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R) -> Matrix<f64> {
+        let mut weights = self.mean.clone();
+
+        weights.zip_mut_with(&self.stddev, |w, s| *w += s * rng.sample(StandardNormal));
+
+        weights
+    }
+
+    /// Run one generation, evaluating the candidates in parallel over
+    /// `n_episodes` episodes each, and set the policy weights of `trader` to
+    /// the updated mean.
+    ///
+    /// Every candidate faces the same environment and agent seeds, drawn from
+    /// `seeds`, so that they are ranked on common random numbers.
+    pub fn step<P, E, R>(
+        &mut self,
+        env_builder: impl Fn() -> Env<P, E> + Sync,
+        trader: &mut Trader,
+        n_episodes: usize,
+        seeds: SeedStream,
+        rng: &mut R,
+    ) -> Result<Record>
+    where
+        P: PriceDynamics,
+        E: ExecutionDynamics,
+        R: Rng,
+    {
+        let candidates: Vec<Matrix<f64>> = (0..self.population).map(|_| self.sample(rng)).collect();
+
+        let scores: Vec<f64> = candidates.par_iter().map(|weights| {
+            let mut candidate = trader.clone();
+
+            set_policy_weights(&mut candidate, weights);
+
+            let rewards: Vec<f64> = (0..n_episodes).map(|j| {
+                let seeds = seeds.substream(j as u64);
+
+                candidate.reseed(seeds.seed(1));
+
+                evaluate_trader_once_with(env_builder().seeded(seeds.seed(0)), &mut candidate, |_, _| {}).reward
+            }).collect();
+
+            Estimate::from_slice(&rewards).0
+        }).collect();
+
+        // Rank the candidates, treating non-finite scores as the worst:
+        let mut order: Vec<usize> = (0..scores.len()).collect();
+        let score = |i: usize| if scores[i].is_finite() { scores[i] } else { ::std::f64::NEG_INFINITY };
+
+        order.sort_by(|&i, &j| score(j).partial_cmp(&score(i)).unwrap());
+
+        let elite = &order[..self.n_elite()];
+        let n = elite.len() as f64;
+
+        let elite_mean = elite.iter().fold(Matrix::zeros(self.mean.dim()), |acc, &i| acc + &candidates[i]) / n;
+        let elite_var = elite.iter().fold(Matrix::zeros(self.mean.dim()), |acc, &i| {
+            acc + (&candidates[i] - &elite_mean).mapv(|d| d * d)
+        }) / n;
+
+        let (rho, floor) = (self.smoothing, self.min_stddev);
+
+        self.mean.zip_mut_with(&elite_mean, |m, e| *m = (1.0 - rho) * *m + rho * e);
+        self.stddev.zip_mut_with(&elite_var, |s, v| *s = ((1.0 - rho) * *s + rho * v.sqrt()).max(floor));
+        self.generation += 1;
+
+        set_policy_weights(trader, &self.mean);
+
+        let population = Estimate::from_slice(&scores);
+
+        Ok(Record {
+            generation: self.generation,
+            population: self.population,
+            n_episodes,
+
+            reward_mean: population.0,
+            reward_stddev: population.1,
+            elite_mean: elite.iter().map(|&i| scores[i]).sum::<f64>() / n,
+
+            reward_best: score(order[0]),
+
+            search_stddev: self.stddev.iter().sum::<f64>() / self.stddev.len() as f64,
+        })
+    }
+}
+
+// Policies only expose additive updates of their weights, so the difference
+// to the target is applied.
+fn set_policy_weights(trader: &mut Trader, weights: &Matrix<f64>) {
+    let delta = weights - &trader.policy.weights();
+
+    <_ as ParameterisedPolicy<Vector<f64>>>::update_raw(&mut trader.policy, delta);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{agents::build_trader, env::Env};
+    use rsrl::{domains::Domain, fa::Parameterised, geometry::Matrix};
+    use super::set_policy_weights;
+
+    #[test]
+    fn test_set_policy_weights() {
+        let mut trader = build_trader(Env::default().state_space(), 0.01, 0.001).unwrap();
+        let dim = trader.policy.weights().dim();
+        let target = Matrix::from_shape_fn(dim, |(i, j)| 0.1 * i as f64 - 0.3 * j as f64);
+
+        set_policy_weights(&mut trader, &target);
+
+        assert_eq!(trader.policy.weights(), target);
+    }
+}
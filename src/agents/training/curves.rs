@@ -0,0 +1,85 @@
+//! Characterisation of a trader's quotes as a function of its inventory.
+//!
+//! Each quote is decomposed into the offset of the reservation price from the
+//! mid price (the skew) and the spread around it. Averaging both per
+//! inventory bin over evaluation rollouts gives the policy curves of the
+//! paper directly.
+use crate::agents::training::probes::ProbeValues;
+
+/// Equal-width inventory bins; inventories outside the range fall into the
+/// outermost bins.
+#[derive(Clone, Copy, Debug)]
+pub struct InventoryBins {
+    pub lower: f64,
+    pub width: f64,
+    pub n_bins: usize,
+}
+
+impl InventoryBins {
+    pub fn new(lower: f64, upper: f64, n_bins: usize) -> InventoryBins {
+        InventoryBins { lower, width: (upper - lower) / n_bins as f64, n_bins, }
+    }
+
+    pub fn index(&self, inv: f64) -> usize {
+        let i = ((inv - self.lower) / self.width).floor().max(0.0) as usize;
+
+        i.min(self.n_bins - 1)
+    }
+
+    pub fn centre(&self, i: usize) -> f64 { self.lower + (i as f64 + 0.5) * self.width }
+}
+
+impl Default for InventoryBins {
+    /// Bins of width 2 over [-10, 10].
+    fn default() -> InventoryBins { InventoryBins::new(-10.0, 10.0, 10) }
+}
+
+/// Running means of the spread and skew in each inventory bin.
+#[derive(Clone, Debug)]
+pub struct InventoryCurves {
+    bins: InventoryBins,
+    spread_sums: Vec<f64>,
+    skew_sums: Vec<f64>,
+    counts: Vec<usize>,
+}
+
+impl InventoryCurves {
+    pub fn new(bins: InventoryBins) -> InventoryCurves {
+        InventoryCurves {
+            bins,
+            spread_sums: vec![0.0; bins.n_bins],
+            skew_sums: vec![0.0; bins.n_bins],
+            counts: vec![0; bins.n_bins],
+        }
+    }
+
+    /// Record the `quotes` (reservation price offset, half-spread) posted at
+    /// inventory `inv`.
+    pub fn record(&mut self, inv: f64, quotes: (f64, f64)) {
+        let i = self.bins.index(inv);
+
+        self.skew_sums[i] += quotes.0;
+        self.spread_sums[i] += quotes.1 * 2.0;
+        self.counts[i] += 1;
+    }
+
+    fn means(&self, prefix: &'static str, sums: &[f64]) -> ProbeValues {
+        ProbeValues::new(prefix, sums.iter().zip(self.counts.iter()).enumerate().map(|(i, (&s, &n))| {
+            (
+                format!("{}", self.bins.centre(i)),
+                if n > 0 { s / n as f64 } else { ::std::f64::NAN },
+            )
+        }).collect())
+    }
+
+    /// Mean spread per bin, as `spread_inv_{centre}` columns; NaN for bins
+    /// that were never visited.
+    pub fn spread(&self) -> ProbeValues { self.means("spread_inv", &self.spread_sums) }
+
+    /// Mean reservation price offset per bin, as `skew_inv_{centre}` columns.
+    pub fn skew(&self) -> ProbeValues { self.means("skew_inv", &self.skew_sums) }
+}
+
+impl Default for InventoryCurves {
+    fn default() -> InventoryCurves { InventoryCurves::new(InventoryBins::default()) }
+}
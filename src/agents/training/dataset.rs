@@ -0,0 +1,241 @@
+//! Recorded transitions for offline training, read back in chunks.
+//!
+//! Months of recorded ticks do not fit in memory at once. Transitions are
+//! instead stored in Parquet files with one row per transition, laid out as
+//! a `TransitionRecord`, and read back a few row groups at a time. The order
+//! of the row groups is shuffled, and the transitions of those currently
+//! buffered are shuffled among themselves before being split into
+//! mini-batches; the more groups are buffered, the closer this comes to a
+//! uniform shuffle of the whole file.
+extern crate arrow_array;
+extern crate parquet;
+
+use crate::{agents::training::probes::ProbeValues, error::Result};
+use rand::Rng;
+use rsrl::{
+    core::BatchLearner,
+    domains::{Observation, Transition},
+    geometry::Vector,
+};
+use self::arrow_array::{Array, BooleanArray, Float64Array, RecordBatch};
+use self::parquet::arrow::arrow_reader::{ArrowReaderMetadata, ParquetRecordBatchReaderBuilder};
+use std::{error::Error, fs::File, io, path::Path};
+
+type Sample = Transition<Vector<f64>, [f64; 2]>;
+
+fn invalid<E>(e: E) -> io::Error
+where
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// One transition, serialised as `from_{i}` and `to_{i}` fields for each
+/// state dimension and `action_0`, `action_1` for the quotes.
+#[derive(Clone, Debug, Serialize)]
+pub struct TransitionRecord {
+    #[serde(flatten)]
+    pub from: ProbeValues,
+    #[serde(flatten)]
+    pub action: ProbeValues,
+
+    pub reward: f64,
+
+    #[serde(flatten)]
+    pub to: ProbeValues,
+
+    pub terminal: bool,
+    pub truncated: bool,
+    pub discount: f64,
+}
+
+impl TransitionRecord {
+    pub fn of(t: &Sample) -> TransitionRecord {
+        fn labelled(xs: &[f64]) -> Vec<(String, f64)> {
+            xs.iter().enumerate().map(|(i, x)| (i.to_string(), *x)).collect()
+        }
+
+        TransitionRecord {
+            from: ProbeValues::new("from", labelled(&t.from.state().to_vec())),
+            action: ProbeValues::new("action", labelled(&t.action)),
+
+            reward: t.reward,
+
+            to: ProbeValues::new("to", labelled(&t.to.state().to_vec())),
+
+            terminal: t.terminated(),
+            truncated: t.truncated,
+            discount: t.discount,
+        }
+    }
+}
+
+/// Parquet file of recorded transitions, of which only the metadata is held
+/// in memory.
+pub struct TransitionDataset {
+    file: File,
+    metadata: ArrowReaderMetadata,
+
+    /// Dimension of the recorded states.
+    pub dim: usize,
+}
+
+impl TransitionDataset {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<TransitionDataset> {
+        let file = File::open(path)?;
+        let metadata = ArrowReaderMetadata::load(&file, Default::default()).map_err(invalid)?;
+        let dim = metadata.schema().fields().iter().filter(|f| f.name().starts_with("from_")).count();
+
+        if dim == 0 {
+            return Err(invalid("Dataset has no `from_*` state columns.").into());
+        }
+
+        Ok(TransitionDataset { file, metadata, dim, })
+    }
+
+    /// Number of recorded transitions.
+    pub fn len(&self) -> usize { self.metadata.metadata().file_metadata().num_rows() as usize }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Number of row groups, the unit in which transitions are read.
+    pub fn n_chunks(&self) -> usize { self.metadata.metadata().num_row_groups() }
+
+    /// Read the transitions of one row group, in the order recorded.
+    ///
+    /// Starting states are always restored as fully observed.
+    pub fn read_chunk(&self, chunk: usize) -> Result<Vec<Sample>> {
+        let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(self.file.try_clone()?, self.metadata.clone())
+            .with_row_groups(vec![chunk])
+            .build()
+            .map_err(invalid)?;
+
+        let mut samples = vec![];
+
+        for batch in reader {
+            self.extend_from(&batch.map_err(invalid)?, &mut samples)?;
+        }
+
+        Ok(samples)
+    }
+
+    fn extend_from(&self, batch: &RecordBatch, samples: &mut Vec<Sample>) -> io::Result<()> {
+        fn floats<'a>(batch: &'a RecordBatch, name: &str) -> io::Result<&'a Float64Array> {
+            batch.column_by_name(name)
+                .and_then(|c| c.as_any().downcast_ref::<Float64Array>())
+                .ok_or_else(|| invalid(format!("No `{}` column of floats.", name)))
+        }
+
+        fn bools<'a>(batch: &'a RecordBatch, name: &str) -> io::Result<&'a BooleanArray> {
+            batch.column_by_name(name)
+                .and_then(|c| c.as_any().downcast_ref::<BooleanArray>())
+                .ok_or_else(|| invalid(format!("No `{}` column of booleans.", name)))
+        }
+
+        let states = |prefix: &str| -> io::Result<Vec<&Float64Array>> {
+            (0..self.dim).map(|i| floats(batch, &format!("{}_{}", prefix, i))).collect()
+        };
+
+        let (from, to) = (states("from")?, states("to")?);
+        let (bid, ask) = (floats(batch, "action_0")?, floats(batch, "action_1")?);
+        let (reward, discount) = (floats(batch, "reward")?, floats(batch, "discount")?);
+        let (terminal, truncated) = (bools(batch, "terminal")?, bools(batch, "truncated")?);
+
+        samples.reserve(batch.num_rows());
+
+        for r in 0..batch.num_rows() {
+            let s = Vector::from_vec(from.iter().map(|c| c.value(r)).collect());
+            let ns = Vector::from_vec(to.iter().map(|c| c.value(r)).collect());
+
+            samples.push(Transition {
+                from: Observation::Full(s),
+                action: [bid.value(r), ask.value(r)],
+                reward: reward.value(r),
+                to: if terminal.value(r) { Observation::Terminal(ns) } else { Observation::Full(ns) },
+                discount: discount.value(r),
+                truncated: truncated.value(r),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Iterate once over the dataset in shuffled mini-batches of
+    /// `batch_size` transitions, holding at most `n_buffered` row groups (and
+    /// one batch) in memory. The last batch may be smaller.
+    pub fn batches<R: Rng>(&self, batch_size: usize, n_buffered: usize, mut rng: R) -> Batches<R> {
+        let mut chunks: Vec<usize> = (0..self.n_chunks()).collect();
+
+        rng.shuffle(&mut chunks);
+
+        Batches {
+            dataset: self,
+            rng,
+
+            batch_size: batch_size.max(1),
+            n_buffered: n_buffered.max(1),
+
+            chunks,
+            buffer: vec![],
+        }
+    }
+}
+
+pub struct Batches<'a, R> {
+    dataset: &'a TransitionDataset,
+    rng: R,
+
+    batch_size: usize,
+    n_buffered: usize,
+
+    // Row groups not read yet, taken from the back.
+    chunks: Vec<usize>,
+    buffer: Vec<Sample>,
+}
+
+impl<'a, R: Rng> Iterator for Batches<'a, R> {
+    type Item = Result<Vec<Sample>>;
+
+    fn next(&mut self) -> Option<Result<Vec<Sample>>> {
+        if self.buffer.len() < self.batch_size && !self.chunks.is_empty() {
+            for _ in 0..self.n_buffered {
+                match self.chunks.pop().map(|c| self.dataset.read_chunk(c)) {
+                    Some(Ok(samples)) => self.buffer.extend(samples),
+                    Some(Err(e)) => return Some(Err(e)),
+                    None => break,
+                }
+            }
+
+            self.rng.shuffle(&mut self.buffer);
+        }
+
+        if self.buffer.is_empty() {
+            return None;
+        }
+
+        let at = self.buffer.len() - self.batch_size.min(self.buffer.len());
+
+        Some(Ok(self.buffer.split_off(at)))
+    }
+}
+
+/// Pass `n_epochs` times over `dataset` in shuffled mini-batches, handing
+/// each to `learner`, and return the number of batches handled.
+pub fn train_offline<L, R>(learner: &mut L, dataset: &TransitionDataset,
+                           batch_size: usize, n_buffered: usize, n_epochs: usize,
+                           rng: &mut R) -> Result<usize>
+where
+    L: BatchLearner<Vector<f64>, [f64; 2]>,
+    R: Rng,
+{
+    let mut n_batches = 0;
+
+    for _ in 0..n_epochs {
+        for batch in dataset.batches(batch_size, n_buffered, &mut *rng) {
+            learner.handle_batch(&batch?);
+            n_batches += 1;
+        }
+    }
+
+    Ok(n_batches)
+}
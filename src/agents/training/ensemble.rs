@@ -0,0 +1,121 @@
+//! Evaluation of a trader against an ensemble of adversaries, e.g. every
+//! checkpoint of a zero-sum run rather than only the final one, or scripted
+//! opponents.
+use crate::{
+    agents::{
+        Adversary, Trader,
+        scripted::Opponent,
+        training::zero_sum::{evaluate_agents_once, evaluate_trader_against},
+    },
+    env::{
+        Env,
+        dynamics::{execution::ExecutionDynamics, price::BrownianMotionWithDrift},
+        observation::TrailingWindow,
+    },
+    utils::{cmp_rewards, Estimate},
+};
+use rsrl::core::{Algorithm, SeedStream};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EnsembleRecord {
+    pub adversary: String,
+    pub n_simulations: usize,
+
+    pub wealth_mean: f64,
+    pub wealth_stddev: f64,
+
+    pub reward_mean: f64,
+    pub reward_stddev: f64,
+
+    pub inv_mean: f64,
+    pub inv_stddev: f64,
+
+    pub spread_mean: f64,
+
+    pub drift_mean: f64,
+    pub drift_stddev: f64,
+}
+
+type Outcome = (f64, f64, f64, f64, f64, f64);
+
+fn summarise(label: &str, outcomes: &[Outcome]) -> EnsembleRecord {
+    let column = |f: fn(&Outcome) -> f64| -> Vec<f64> { outcomes.iter().map(f).collect() };
+
+    let pnl_est = Estimate::from_slice(&column(|o| o.0));
+    let dft_est = Estimate::from_slice(&column(|o| o.1));
+    let rwd_est = Estimate::from_slice(&column(|o| o.3));
+    let inv_est = Estimate::from_slice(&column(|o| o.4));
+
+    EnsembleRecord {
+        adversary: label.to_owned(),
+        n_simulations: outcomes.len(),
+
+        wealth_mean: pnl_est.0,
+        wealth_stddev: pnl_est.1,
+
+        reward_mean: rwd_est.0,
+        reward_stddev: rwd_est.1,
+
+        inv_mean: inv_est.0,
+        inv_stddev: inv_est.1,
+
+        spread_mean: Estimate::from_slice(&column(|o| o.2)).0,
+
+        drift_mean: dft_est.0,
+        drift_stddev: dft_est.1,
+    }
+}
+
+/// Evaluate `trader` against each of the labelled `adversaries` in turn.
+///
+/// Every adversary faces the same environment and agent seeds, so that
+/// differences between records are due to the adversaries alone.
+pub fn evaluate_ensemble<E: ExecutionDynamics>(
+    env_builder: impl Fn() -> Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    adversaries: &mut [(String, Adversary)],
+    n_simulations: usize,
+    seeds: SeedStream,
+    window: &mut TrailingWindow,
+) -> Vec<EnsembleRecord>
+{
+    adversaries.iter_mut().map(|&mut (ref label, ref mut adversary)| {
+        let outcomes: Vec<Outcome> = (0..n_simulations).map(|j| {
+            let seeds = seeds.substream(j as u64);
+
+            trader.reseed(seeds.seed(1));
+            adversary.reseed(seeds.seed(2));
+
+            evaluate_agents_once(env_builder().seeded(seeds.seed(0)), trader, adversary, window)
+        }).collect();
+
+        summarise(label, &outcomes)
+    }).collect()
+}
+
+/// Evaluate `trader` against a fixed `opponent`, with the same seeds as
+/// `evaluate_ensemble`.
+pub fn evaluate_opponent<E: ExecutionDynamics>(
+    env_builder: impl Fn() -> Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    label: &str,
+    opponent: &mut impl Opponent,
+    n_simulations: usize,
+    seeds: SeedStream,
+) -> EnsembleRecord
+{
+    let outcomes: Vec<Outcome> = (0..n_simulations).map(|j| {
+        let seeds = seeds.substream(j as u64);
+
+        trader.reseed(seeds.seed(1));
+
+        evaluate_trader_against(env_builder().seeded(seeds.seed(0)), trader, opponent)
+    }).collect();
+
+    summarise(label, &outcomes)
+}
+
+/// Record of the adversary against which the trader's mean reward is lowest.
+pub fn worst_case(records: &[EnsembleRecord]) -> Option<&EnsembleRecord> {
+    records.iter().min_by(|a, b| cmp_rewards(a.reward_mean, b.reward_mean))
+}
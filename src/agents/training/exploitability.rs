@@ -0,0 +1,205 @@
+//! Exploitability of a pair of zero-sum agents.
+//!
+//! The reward curves of a zero-sum run can flatten long before the agents
+//! approach an equilibrium, e.g. when both chase each other in a cycle. Each
+//! agent is instead frozen in turn while a fresh opponent is trained against
+//! it as an approximate best response. At a Nash equilibrium neither
+//! response improves on the value of the pair, so the gap between the two
+//! responses' values bounds how far the pair is from one.
+//!
+//! Only the per-step drift schedule is supported; responses are trained and
+//! evaluated against the frozen agent's target policy.
+use crate::{
+    agents::{Trader, Adversary, tta, training::zero_sum::evaluate_agents_once},
+    env::{
+        Env,
+        dynamics::{price::BrownianMotionWithDrift, execution::ExecutionDynamics},
+        observation::TrailingWindow,
+    },
+    utils::Estimate,
+};
+use rsrl::{
+    core::{Algorithm, OnlineLearner, Controller, SeedStream},
+    domains::Domain,
+};
+
+const MAX_DRIFT: f64 = 5.0;
+
+#[derive(Debug, Serialize)]
+pub struct Record {
+    pub episode: usize,
+    pub n_simulations: usize,
+
+    /// Mean reward of the trader against the adversary.
+    pub value: f64,
+    pub value_stddev: f64,
+
+    /// Mean reward of a trader trained in response to the frozen adversary.
+    pub trader_response: f64,
+    pub trader_response_stddev: f64,
+
+    /// Mean reward of the frozen trader against an adversary trained in
+    /// response to it.
+    pub adversary_response: f64,
+    pub adversary_response_stddev: f64,
+
+    /// How much each agent loses to its best response, and their sum.
+    pub trader_exploitability: f64,
+    pub adversary_exploitability: f64,
+    pub gap: f64,
+}
+
+// Train only the trader for one episode against the adversary's target
+// policy.
+fn train_trader_response<E: ExecutionDynamics>(
+    mut env: Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    adversary: &mut Adversary,
+    window: &mut TrailingWindow,
+)
+{
+    window.reset(&env);
+
+    let mut drift = adversary.sample_target(&window.augment(env.state()));
+    let mut quotes = trader.sample_behaviour(env.state());
+
+    loop {
+        env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
+
+        let t = env.step(tta(quotes));
+
+        window.record(&env, t.action);
+
+        let t = t.replace_action(quotes);
+
+        trader.handle_transition(&t);
+
+        if t.terminated() {
+            break
+        } else {
+            drift = adversary.sample_target(&window.augment(t.to.state()));
+            quotes = trader.sample_behaviour(t.to.state());
+        }
+    }
+
+    trader.handle_terminal();
+}
+
+// Train only the adversary for one episode against the trader's target
+// policy.
+fn train_adversary_response<E: ExecutionDynamics>(
+    mut env: Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    adversary: &mut Adversary,
+    window: &mut TrailingWindow,
+)
+{
+    window.reset(&env);
+
+    let mut obs = window.observe(&env.emit());
+    let mut drift = adversary.sample_behaviour(obs.state());
+    let mut quotes = trader.sample_target(env.state());
+
+    loop {
+        env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
+
+        let t = env.step(tta(quotes));
+        let t_adv = window.transition(&env, obs, drift, &t).negate_reward();
+
+        adversary.handle_transition(&t_adv);
+
+        if t.terminated() {
+            break
+        } else {
+            obs = t_adv.to;
+            drift = adversary.sample_behaviour(obs.state());
+            quotes = trader.sample_target(t.to.state());
+        }
+    }
+
+    adversary.handle_terminal();
+}
+
+// Rewards of the trader against the adversary over `n_simulations` episodes,
+// with the same seeds for every pair evaluated.
+fn rewards<E: ExecutionDynamics>(
+    env_builder: &impl Fn() -> Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    adversary: &mut Adversary,
+    n_simulations: usize,
+    seeds: SeedStream,
+    window: &mut TrailingWindow,
+) -> Estimate
+{
+    let rewards: Vec<f64> = (0..n_simulations).map(|j| {
+        let seeds = seeds.substream(j as u64);
+
+        trader.reseed(seeds.seed(1));
+        adversary.reseed(seeds.seed(2));
+
+        evaluate_agents_once(env_builder().seeded(seeds.seed(0)), trader, adversary, window).3
+    }).collect();
+
+    Estimate::from_slice(&rewards)
+}
+
+/// Estimate the exploitability of `trader` and `adversary`, training fresh
+/// agents from `new_trader` and `new_adversary` for `n_episodes` each as
+/// responses, and evaluating every pair over `n_simulations` episodes.
+///
+/// Neither of the given agents is modified.
+pub fn exploitability<E: ExecutionDynamics>(
+    env_builder: impl Fn() -> Env<BrownianMotionWithDrift, E>,
+    trader: &Trader,
+    adversary: &Adversary,
+    new_trader: impl Fn() -> Trader,
+    new_adversary: impl Fn() -> Adversary,
+    episode: usize,
+    n_episodes: usize,
+    n_simulations: usize,
+    seeds: SeedStream,
+    window: &mut TrailingWindow,
+) -> Record
+{
+    let (training_seeds, eval_seeds) = (seeds.substream(0), seeds.substream(1));
+
+    let mut trader = trader.clone();
+    let mut adversary = adversary.clone();
+
+    let mut trader_response = new_trader();
+    let mut adversary_response = new_adversary();
+
+    trader.reseed(training_seeds.seed(1));
+    adversary.reseed(training_seeds.seed(2));
+    trader_response.reseed(training_seeds.seed(3));
+    adversary_response.reseed(training_seeds.seed(4));
+
+    for k in 0..n_episodes {
+        let seeds = training_seeds.substream(k as u64);
+
+        train_trader_response(env_builder().seeded(seeds.seed(0)), &mut trader_response, &mut adversary, window);
+        train_adversary_response(env_builder().seeded(seeds.seed(1)), &mut trader, &mut adversary_response, window);
+    }
+
+    let value = rewards(&env_builder, &mut trader, &mut adversary, n_simulations, eval_seeds, window);
+    let tr = rewards(&env_builder, &mut trader_response, &mut adversary, n_simulations, eval_seeds, window);
+    let ar = rewards(&env_builder, &mut trader, &mut adversary_response, n_simulations, eval_seeds, window);
+
+    Record {
+        episode,
+        n_simulations,
+
+        value: value.0,
+        value_stddev: value.1,
+
+        trader_response: tr.0,
+        trader_response_stddev: tr.1,
+
+        adversary_response: ar.0,
+        adversary_response_stddev: ar.1,
+
+        trader_exploitability: value.0 - ar.0,
+        adversary_exploitability: tr.0 - value.0,
+        gap: tr.0 - ar.0,
+    }
+}
@@ -0,0 +1,122 @@
+//! Importance mixing of transitions replayed from earlier trader snapshots.
+//!
+//! Transitions generated by an older policy are distributed differently from
+//! those the current one would generate, so replaying them as they are
+//! biases the on-policy critic towards the values of its predecessors. Each
+//! stored transition is instead weighted by the likelihood ratio of its
+//! quotes under the current and generating policies, normalised to a mean of
+//! one. Transitions whose ratio has fallen below a threshold are pruned from
+//! the buffer, since they contribute little but variance.
+use crate::agents::{Critic, ope::effective_sample_size};
+use rsrl::{domains::Transition, geometry::Vector, policies::Policy};
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug)]
+pub struct StoredTransition {
+    pub transition: Transition<Vector<f64>, [f64; 2]>,
+
+    /// Action sampled from the generating policy, before transformation
+    /// into quotes.
+    pub action: (f64, f64),
+
+    /// Probability (density) of `action` under the generating policy.
+    pub behaviour_prob: f64,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct MixingStats {
+    pub n_replayed: usize,
+    pub n_pruned: usize,
+
+    /// Effective number of transitions supporting the replayed updates.
+    pub ess: f64,
+}
+
+/// Bounded buffer of past transitions, replayed into the critic with
+/// importance weights relative to the current policy.
+#[derive(Clone, Debug)]
+pub struct ImportanceMixing {
+    pub capacity: usize,
+    pub min_ratio: f64,
+
+    buffer: VecDeque<StoredTransition>,
+}
+
+impl ImportanceMixing {
+    pub fn new(capacity: usize, min_ratio: f64) -> ImportanceMixing {
+        ImportanceMixing {
+            capacity: capacity.max(1),
+            min_ratio,
+
+            buffer: VecDeque::with_capacity(capacity.max(1)),
+        }
+    }
+
+    pub fn len(&self) -> usize { self.buffer.len() }
+
+    pub fn is_empty(&self) -> bool { self.buffer.is_empty() }
+
+    /// Store a transition along with the action that produced it and its
+    /// probability under the policy at the time, evicting the oldest one if
+    /// the buffer is full.
+    pub fn record(&mut self, transition: &Transition<Vector<f64>, [f64; 2]>,
+                  action: (f64, f64), behaviour_prob: f64)
+    {
+        if self.buffer.len() == self.capacity {
+            self.buffer.pop_front();
+        }
+
+        self.buffer.push_back(StoredTransition {
+            transition: transition.clone(),
+            action,
+            behaviour_prob,
+        });
+    }
+
+    /// Likelihood ratios of the stored actions under `policy` relative to
+    /// their generating policies, oldest first.
+    pub fn ratios<P>(&self, policy: &mut P) -> Vec<f64>
+    where
+        P: Policy<Vector<f64>, Action = (f64, f64)>,
+    {
+        self.buffer.iter()
+            .map(|s| policy.probability(s.transition.from.state(), s.action) / s.behaviour_prob)
+            .collect()
+    }
+
+    /// Prune the transitions whose ratio under `policy` is below `min_ratio`,
+    /// then replay the rest into `critic` with self-normalised weights.
+    pub fn replay<P>(&mut self, critic: &mut Critic, policy: &mut P) -> MixingStats
+    where
+        P: Policy<Vector<f64>, Action = (f64, f64)>,
+    {
+        let ratios = self.ratios(policy);
+        let n_stored = self.buffer.len();
+        let min_ratio = self.min_ratio;
+
+        let (kept, ratios): (VecDeque<_>, Vec<_>) = self.buffer.drain(..)
+            .zip(ratios.into_iter())
+            .filter(|&(_, r)| r.is_finite() && r >= min_ratio)
+            .unzip();
+
+        self.buffer = kept;
+
+        let n_pruned = n_stored - self.buffer.len();
+
+        if self.buffer.is_empty() {
+            return MixingStats { n_replayed: 0, n_pruned, ess: 0.0, };
+        }
+
+        let mean = ratios.iter().sum::<f64>() / ratios.len() as f64;
+
+        for (s, r) in self.buffer.iter().zip(ratios.iter()) {
+            critic.handle_weighted_transition(&s.transition, r / mean);
+        }
+
+        MixingStats {
+            n_replayed: self.buffer.len(),
+            n_pruned,
+            ess: effective_sample_size(&ratios),
+        }
+    }
+}
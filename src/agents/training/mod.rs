@@ -1,3 +1,23 @@
+pub mod anomaly;
+pub mod breakdown;
+pub mod cem;
+pub mod curves;
+#[cfg(feature = "offline")]
+pub mod dataset;
+pub mod ensemble;
+pub mod exploitability;
 pub mod trader;
+pub mod value_error;
+pub mod mixing;
+pub mod monitor;
+#[cfg(feature = "fs")]
+pub mod population;
 pub mod adversary;
 pub mod zero_sum;
+pub mod probes;
+pub mod recorder;
+pub mod regimes;
+pub mod stopping;
+pub mod robustness;
+pub mod rolling;
+pub mod timeline;
@@ -0,0 +1,174 @@
+//! Monitors for degenerate trading policies.
+//!
+//! A trader can game its reward without learning to make markets: quoting a
+//! spread of almost zero to collect fills at no edge, quoting so wide that
+//! nothing ever fills, or letting its inventory run into a bound where one
+//! side stops trading. None of these show up as an anomaly of the learner,
+//! only as a trend in the plots, so they are checked here over consecutive
+//! training episodes instead.
+use crate::env::{
+    Env, INV_BOUNDS,
+    dynamics::{execution::ExecutionDynamics, price::PriceDynamics},
+};
+use rsrl::{domains::Transition, geometry::Vector};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Degeneracy {
+    /// The mean quoted spread has stayed below the monitor's minimum.
+    SpreadCollapse { spread: f64 },
+
+    /// The fill rate has stayed below the monitor's minimum.
+    NoFills { fill_rate: f64 },
+
+    /// The inventory has spent more than the monitor's maximum fraction of
+    /// each episode at one of its bounds.
+    InventoryPinned { fraction: f64 },
+}
+
+impl Degeneracy {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Degeneracy::SpreadCollapse { .. } => "spread_collapse",
+            Degeneracy::NoFills { .. } => "no_fills",
+            Degeneracy::InventoryPinned { .. } => "inventory_pinned",
+        }
+    }
+
+    /// Statistic of the episode that triggered the flag.
+    pub fn value(&self) -> f64 {
+        match *self {
+            Degeneracy::SpreadCollapse { spread } => spread,
+            Degeneracy::NoFills { fill_rate } => fill_rate,
+            Degeneracy::InventoryPinned { fraction } => fraction,
+        }
+    }
+}
+
+/// Flagged event, as logged to a results file.
+#[derive(Clone, Debug, Serialize)]
+pub struct MonitorEvent {
+    pub episode: usize,
+    pub kind: &'static str,
+    pub value: f64,
+
+    /// Whether the trader was reverted to its last good checkpoint.
+    pub reverted: bool,
+}
+
+impl MonitorEvent {
+    pub fn new(episode: usize, degeneracy: Degeneracy, reverted: bool) -> MonitorEvent {
+        MonitorEvent { episode, kind: degeneracy.name(), value: degeneracy.value(), reverted, }
+    }
+}
+
+/// Checks each training episode for degenerate quoting, flagging a failure
+/// mode once it has persisted for `patience` consecutive episodes.
+#[derive(Clone, Debug)]
+pub struct DegeneracyMonitor {
+    pub min_spread: f64,
+    pub min_fill_rate: f64,
+    pub max_pinned_fraction: f64,
+    pub patience: usize,
+
+    // Consecutive episodes in which each failure mode was observed, in the
+    // order of `Degeneracy`'s variants.
+    streaks: [usize; 3],
+
+    spread_sum: f64,
+    n_pinned: usize,
+    n_steps: usize,
+}
+
+impl DegeneracyMonitor {
+    pub fn new(min_spread: f64, min_fill_rate: f64, max_pinned_fraction: f64, patience: usize) -> DegeneracyMonitor {
+        DegeneracyMonitor {
+            min_spread, min_fill_rate, max_pinned_fraction,
+            patience: patience.max(1),
+
+            streaks: [0; 3],
+
+            spread_sum: 0.0,
+            n_pinned: 0,
+            n_steps: 0,
+        }
+    }
+
+    /// Clear the statistics of the current episode.
+    pub fn reset(&mut self) {
+        self.spread_sum = 0.0;
+        self.n_pinned = 0;
+        self.n_steps = 0;
+    }
+
+    /// Clear the streaks too, e.g. after reverting the trader.
+    pub fn clear(&mut self) {
+        self.reset();
+        self.streaks = [0; 3];
+    }
+
+    /// Record an environment transition of the current episode.
+    pub fn record(&mut self, t: &Transition<Vector<f64>, [f64; 2]>) {
+        let inv = t.to.state()[1];
+
+        self.spread_sum += t.action[0] + t.action[1];
+        self.n_steps += 1;
+
+        if inv <= INV_BOUNDS[0] || inv >= INV_BOUNDS[1] {
+            self.n_pinned += 1;
+        }
+    }
+
+    /// Check the episode that `env` has just completed, returning the failure
+    /// modes that have now persisted for `patience` episodes.
+    pub fn inspect<P: PriceDynamics, E: ExecutionDynamics>(&mut self, env: &Env<P, E>) -> Vec<Degeneracy> {
+        if self.n_steps == 0 {
+            return vec![];
+        }
+
+        let n = self.n_steps as f64;
+        let observed = [
+            Degeneracy::SpreadCollapse { spread: self.spread_sum / n },
+            Degeneracy::NoFills { fill_rate: env.dynamics.fill_rate() },
+            Degeneracy::InventoryPinned { fraction: self.n_pinned as f64 / n },
+        ];
+
+        let mut flagged = vec![];
+
+        for (streak, d) in self.streaks.iter_mut().zip(observed.iter()) {
+            let degenerate = match *d {
+                Degeneracy::SpreadCollapse { spread } => spread < self.min_spread,
+                Degeneracy::NoFills { fill_rate } => fill_rate < self.min_fill_rate,
+                Degeneracy::InventoryPinned { fraction } => fraction > self.max_pinned_fraction,
+            };
+
+            *streak = if degenerate { *streak + 1 } else { 0 };
+
+            if *streak >= self.patience {
+                flagged.push(*d);
+            }
+        }
+
+        flagged
+    }
+}
+
+impl FromStr for DegeneracyMonitor {
+    type Err = String;
+
+    /// Parse `min_spread,min_fill_rate,max_pinned_fraction,patience`.
+    fn from_str(s: &str) -> Result<DegeneracyMonitor, String> {
+        let params = s.split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid monitor `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match params.as_slice() {
+            &[min_spread, min_fill_rate, max_pinned_fraction, patience] if patience >= 1.0 => {
+                Ok(DegeneracyMonitor::new(min_spread, min_fill_rate, max_pinned_fraction, patience as usize))
+            },
+            _ => Err(format!(
+                "Monitor `{}` must be of the form min_spread,min_fill_rate,max_pinned_fraction,patience.", s
+            )),
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Cross-play of populations of trader and adversary checkpoints.
+//!
+//! The last iterate of a zero-sum run says little about its progress: the
+//! agents may cycle, with each checkpoint beating the previous opponent but
+//! losing to older ones. Playing every trader checkpoint against every
+//! adversary checkpoint gives the payoff matrix of a meta-game between the
+//! two populations, whose Nash equilibrium weights each agent by how well it
+//! does against the strongest mixture of opponents (Balduzzi et al., 2018).
+use crate::{
+    agents::{load_adversary_for, load_trader_for, training::zero_sum::evaluate_agents_once},
+    env::{
+        Env,
+        dynamics::{execution::ExecutionDynamics, price::BrownianMotionWithDrift},
+        observation::TrailingWindow,
+    },
+    error::Result,
+    utils::Estimate,
+};
+use rayon::prelude::*;
+use rsrl::{
+    core::{Algorithm, SeedStream},
+    domains::Domain,
+};
+use std::path::PathBuf;
+
+/// Trader's reward in one trader-adversary pairing.
+#[derive(Clone, Debug, Serialize)]
+pub struct CrossPlayRecord {
+    pub trader: String,
+    pub adversary: String,
+    pub n_simulations: usize,
+
+    pub reward_mean: f64,
+    pub reward_stddev: f64,
+}
+
+/// Trader's mean rewards, with one row per trader and one column per
+/// adversary.
+#[derive(Clone, Debug)]
+pub struct PayoffMatrix {
+    pub traders: Vec<String>,
+    pub adversaries: Vec<String>,
+    pub payoffs: Vec<Vec<f64>>,
+}
+
+impl PayoffMatrix {
+    /// Assemble the matrix from the records of `cross_play`.
+    pub fn from_records(traders: Vec<String>, adversaries: Vec<String>, records: &[CrossPlayRecord]) -> PayoffMatrix {
+        let mut payoffs = vec![vec![::std::f64::NAN; adversaries.len()]; traders.len()];
+
+        for r in records {
+            let i = traders.iter().position(|t| *t == r.trader);
+            let j = adversaries.iter().position(|a| *a == r.adversary);
+
+            if let (Some(i), Some(j)) = (i, j) {
+                payoffs[i][j] = r.reward_mean;
+            }
+        }
+
+        PayoffMatrix { traders, adversaries, payoffs, }
+    }
+
+    fn value(&self, p: &[f64], q: &[f64]) -> f64 {
+        self.payoffs.iter().zip(p).map(|(row, pi)| pi * row.iter().zip(q).map(|(a, qj)| a * qj).sum::<f64>()).sum()
+    }
+
+    // Payoff of each trader against the adversary mixture `q`.
+    fn row_payoffs(&self, q: &[f64]) -> Vec<f64> {
+        self.payoffs.iter().map(|row| row.iter().zip(q).map(|(a, qj)| a * qj).sum()).collect()
+    }
+
+    // Payoff against each adversary of the trader mixture `p`.
+    fn column_payoffs(&self, p: &[f64]) -> Vec<f64> {
+        (0..self.adversaries.len())
+            .map(|j| self.payoffs.iter().zip(p).map(|(row, pi)| pi * row[j]).sum())
+            .collect()
+    }
+
+    /// Approximate Nash equilibrium of the meta-game, in which the traders
+    /// maximise and the adversaries minimise the payoff, by `n_iterations`
+    /// rounds of multiplicative weights in self-play.
+    ///
+    /// The averaged strategies are an ε-equilibrium, with ε reported in the
+    /// result and shrinking as O(sqrt(log(n) / n_iterations)). Where the
+    /// equilibrium is not unique, the one found need not be the
+    /// maximum-entropy equilibrium of Nash averaging proper.
+    pub fn nash(&self, n_iterations: usize) -> MetaStrategy {
+        let (n, m) = (self.traders.len(), self.adversaries.len());
+
+        let min = self.payoffs.iter().flatten().cloned().fold(::std::f64::INFINITY, f64::min);
+        let max = self.payoffs.iter().flatten().cloned().fold(::std::f64::NEG_INFINITY, f64::max);
+        let range = if max > min { max - min } else { 1.0 };
+
+        let n_iterations = n_iterations.max(1);
+        let eta = (8.0 * (n.max(m) as f64).ln() / n_iterations as f64).sqrt();
+
+        let (mut log_p, mut log_q) = (vec![0.0; n], vec![0.0; m]);
+        let (mut p_sum, mut q_sum) = (vec![0.0; n], vec![0.0; m]);
+
+        let normalise = |log_w: &[f64]| -> Vec<f64> {
+            let max = log_w.iter().cloned().fold(::std::f64::NEG_INFINITY, f64::max);
+            let w: Vec<f64> = log_w.iter().map(|x| (x - max).exp()).collect();
+            let total: f64 = w.iter().sum();
+
+            w.into_iter().map(|x| x / total).collect()
+        };
+
+        for _ in 0..n_iterations {
+            let (p, q) = (normalise(&log_p), normalise(&log_q));
+
+            for (s, x) in p_sum.iter_mut().zip(p.iter()) { *s += x; }
+            for (s, x) in q_sum.iter_mut().zip(q.iter()) { *s += x; }
+
+            for (l, u) in log_p.iter_mut().zip(self.row_payoffs(&q)) { *l += eta * (u - min) / range; }
+            for (l, u) in log_q.iter_mut().zip(self.column_payoffs(&p)) { *l -= eta * (u - min) / range; }
+        }
+
+        let p: Vec<f64> = p_sum.into_iter().map(|x| x / n_iterations as f64).collect();
+        let q: Vec<f64> = q_sum.into_iter().map(|x| x / n_iterations as f64).collect();
+
+        let trader_payoffs = self.row_payoffs(&q);
+        let adversary_payoffs = self.column_payoffs(&p);
+
+        let best_trader = trader_payoffs.iter().cloned().fold(::std::f64::NEG_INFINITY, f64::max);
+        let best_adversary = adversary_payoffs.iter().cloned().fold(::std::f64::INFINITY, f64::min);
+
+        MetaStrategy {
+            value: self.value(&p, &q),
+            epsilon: best_trader - best_adversary,
+
+            traders: p,
+            adversaries: q,
+
+            trader_payoffs,
+            adversary_payoffs,
+        }
+    }
+}
+
+/// Approximate equilibrium of the meta-game between the populations.
+#[derive(Clone, Debug)]
+pub struct MetaStrategy {
+    /// Payoff of the equilibrium mixtures against each other.
+    pub value: f64,
+
+    /// Gain available to the better of the two best responses.
+    pub epsilon: f64,
+
+    /// Equilibrium weights of each trader and adversary.
+    pub traders: Vec<f64>,
+    pub adversaries: Vec<f64>,
+
+    /// Nash-averaged payoff of each trader (against the equilibrium
+    /// adversary mixture) and against each adversary (of the equilibrium
+    /// trader mixture).
+    pub trader_payoffs: Vec<f64>,
+    pub adversary_payoffs: Vec<f64>,
+}
+
+/// One agent's standing in the meta-game.
+#[derive(Clone, Debug, Serialize)]
+pub struct MetaRecord {
+    pub role: &'static str,
+    pub agent: String,
+
+    pub nash_weight: f64,
+    pub nash_payoff: f64,
+
+    /// Payoff against the uniform mixture of opponents, for comparison.
+    pub uniform_payoff: f64,
+}
+
+impl MetaStrategy {
+    pub fn records(&self, matrix: &PayoffMatrix) -> Vec<MetaRecord> {
+        let uniform = |k: usize| vec![1.0 / k as f64; k];
+
+        let uniform_rows = matrix.row_payoffs(&uniform(matrix.adversaries.len()));
+        let uniform_columns = matrix.column_payoffs(&uniform(matrix.traders.len()));
+
+        let traders = matrix.traders.iter().enumerate().map(|(i, label)| MetaRecord {
+            role: "trader",
+            agent: label.clone(),
+
+            nash_weight: self.traders[i],
+            nash_payoff: self.trader_payoffs[i],
+            uniform_payoff: uniform_rows[i],
+        });
+        let adversaries = matrix.adversaries.iter().enumerate().map(|(j, label)| MetaRecord {
+            role: "adversary",
+            agent: label.clone(),
+
+            nash_weight: self.adversaries[j],
+            nash_payoff: self.adversary_payoffs[j],
+            uniform_payoff: uniform_columns[j],
+        });
+
+        traders.chain(adversaries).collect()
+    }
+}
+
+/// Play every labelled trader checkpoint against every adversary checkpoint
+/// over `n_simulations` episodes, with the pairings evaluated in parallel.
+///
+/// Agents are loaded from disk by each worker, and every pairing faces the
+/// same environment and agent seeds, as in `evaluate_ensemble`.
+pub fn cross_play<E: ExecutionDynamics>(
+    env_builder: impl Fn() -> Env<BrownianMotionWithDrift, E> + Sync,
+    traders: &[(String, PathBuf)],
+    adversaries: &[(String, PathBuf)],
+    n_simulations: usize,
+    seeds: SeedStream,
+    window: &TrailingWindow,
+) -> Result<Vec<CrossPlayRecord>>
+{
+    let env = env_builder();
+    let (trader_space, adversary_space) = (env.state_space(), window.state_space(&env));
+
+    let pairs: Vec<(&(String, PathBuf), &(String, PathBuf))> = traders.iter()
+        .flat_map(|t| adversaries.iter().map(move |a| (t, a)))
+        .collect();
+
+    pairs.par_iter().map(|&(&(ref t_label, ref t_path), &(ref a_label, ref a_path))| {
+        let mut trader = load_trader_for(t_path.to_string_lossy().into_owned(), &trader_space)?;
+        let mut adversary = load_adversary_for(a_path.to_string_lossy().into_owned(), &adversary_space)?;
+        let mut window = window.clone();
+
+        let rewards: Vec<f64> = (0..n_simulations).map(|j| {
+            let seeds = seeds.substream(j as u64);
+
+            trader.reseed(seeds.seed(1));
+            adversary.reseed(seeds.seed(2));
+
+            evaluate_agents_once(env_builder().seeded(seeds.seed(0)), &mut trader, &mut adversary, &mut window).3
+        }).collect();
+
+        let estimate = Estimate::from_slice(&rewards);
+
+        Ok(CrossPlayRecord {
+            trader: t_label.clone(),
+            adversary: a_label.clone(),
+            n_simulations,
+
+            reward_mean: estimate.0,
+            reward_stddev: estimate.1,
+        })
+    }).collect()
+}
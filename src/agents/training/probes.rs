@@ -0,0 +1,111 @@
+//! Labelled states at which policies are probed during evaluation.
+use rsrl::geometry::Vector;
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+#[derive(Clone, Debug)]
+pub struct Probe {
+    pub label: String,
+    pub state: Vector<f64>,
+}
+
+impl FromStr for Probe {
+    type Err = String;
+
+    /// Parse a probe of the form `label=time,inventory`.
+    fn from_str(s: &str) -> Result<Probe, String> {
+        let mut parts = s.splitn(2, '=');
+
+        let label = parts.next().unwrap().trim();
+        let state = parts.next()
+            .ok_or_else(|| format!("Probe `{}` must be of the form label=time,inventory.", s))?
+            .split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid probe `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        if label.is_empty() {
+            return Err(format!("Probe `{}` is missing a label.", s));
+        }
+
+        Ok(Probe {
+            label: label.to_owned(),
+            state: Vector::from_vec(state),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Probes(pub Vec<Probe>);
+
+impl Probes {
+    /// Zero-extend each probe state to `dim` dimensions, e.g. to account for
+    /// optional observation features.
+    pub fn padded(self, dim: usize) -> Probes {
+        Probes(self.0.into_iter().map(|p| {
+            let mut state = p.state.to_vec();
+
+            if state.len() < dim {
+                state.resize(dim, 0.0);
+            }
+
+            Probe { label: p.label, state: Vector::from_vec(state) }
+        }).collect())
+    }
+
+    /// Evaluate `f` at each probe, labelling the output columns with `prefix`.
+    pub fn evaluate(&self, prefix: &'static str, mut f: impl FnMut(&Vector<f64>) -> f64) -> ProbeValues {
+        ProbeValues {
+            prefix,
+            values: self.0.iter().map(|p| (p.label.clone(), f(&p.state))).collect(),
+        }
+    }
+}
+
+impl Default for Probes {
+    /// Flat, long and short inventory positions at the start of an episode.
+    fn default() -> Probes {
+        Probes(vec![
+            Probe { label: "neutral".to_owned(), state: Vector::from_vec(vec![0.0, 0.0]) },
+            Probe { label: "long".to_owned(), state: Vector::from_vec(vec![0.0, 5.0]) },
+            Probe { label: "short".to_owned(), state: Vector::from_vec(vec![0.0, -5.0]) },
+        ])
+    }
+}
+
+/// Outputs at each probe, serialised as one `{prefix}_{label}` field per
+/// probe.
+#[derive(Clone, Debug)]
+pub struct ProbeValues {
+    prefix: &'static str,
+    values: Vec<(String, f64)>,
+}
+
+impl ProbeValues {
+    pub fn new(prefix: &'static str, values: Vec<(String, f64)>) -> ProbeValues { ProbeValues { prefix, values, } }
+}
+
+impl Serialize for ProbeValues {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.values.len()))?;
+
+        for &(ref label, value) in self.values.iter() {
+            map.serialize_entry(&format!("{}_{}", self.prefix, label), &value)?;
+        }
+
+        map.end()
+    }
+}
+
+impl fmt::Display for ProbeValues {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, &(ref label, value)) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{}={}", label, value)?;
+        }
+
+        Ok(())
+    }
+}
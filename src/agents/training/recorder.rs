@@ -0,0 +1,90 @@
+//! In-memory record of the most recent training episodes.
+//!
+//! Recording every episode of a long run to disk is prohibitively slow and
+//! large, yet by the time an episode looks anomalous the ones leading up to
+//! it are gone. A ring buffer of the last few episodes keeps that context at
+//! the cost of a little memory, and is only written out when needed.
+use crate::{agents::training::anomaly::TraceStep, env::Env};
+#[cfg(feature = "fs")]
+use crate::error::Result;
+use rsrl::{domains::Transition, geometry::Vector};
+use std::collections::VecDeque;
+#[cfg(feature = "fs")]
+use std::{fs::File, io::{BufWriter, Write}};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RecordedEpisode {
+    pub episode: usize,
+
+    pub reward: f64,
+    pub wealth: f64,
+    pub inv_terminal: f64,
+
+    pub steps: Vec<TraceStep>,
+}
+
+/// Ring buffer of the observations, quotes and rewards of the last
+/// `capacity` episodes.
+#[derive(Clone, Debug)]
+pub struct EpisodeRecorder {
+    pub capacity: usize,
+
+    episodes: VecDeque<RecordedEpisode>,
+    current: Vec<TraceStep>,
+}
+
+impl EpisodeRecorder {
+    pub fn new(capacity: usize) -> EpisodeRecorder {
+        EpisodeRecorder {
+            capacity: capacity.max(1),
+
+            episodes: VecDeque::with_capacity(capacity.max(1)),
+            current: vec![],
+        }
+    }
+
+    /// Record an environment transition of the current episode.
+    pub fn record(&mut self, t: &Transition<Vector<f64>, [f64; 2]>) {
+        self.current.push(TraceStep {
+            state: t.from.state().to_vec(),
+            quotes: t.action,
+            reward: t.reward,
+        });
+    }
+
+    /// Close the current episode, which `env` has just completed, evicting
+    /// the oldest one if the buffer is full.
+    pub fn finish<P, E>(&mut self, episode: usize, env: &Env<P, E>) {
+        if self.episodes.len() == self.capacity {
+            self.episodes.pop_front();
+        }
+
+        let steps = self.current.split_off(0);
+
+        self.episodes.push_back(RecordedEpisode {
+            episode,
+
+            reward: env.episode_reward,
+            wealth: env.wealth,
+            inv_terminal: env.inv_terminal,
+
+            steps,
+        });
+    }
+
+    /// Recorded episodes, oldest first.
+    pub fn episodes(&self) -> impl Iterator<Item = &RecordedEpisode> { self.episodes.iter() }
+
+    /// Write the recorded episodes to `path` as JSON lines, oldest first.
+    #[cfg(feature = "fs")]
+    pub fn dump(&self, path: &str) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        for e in self.episodes.iter() {
+            serde_json::to_writer(&mut writer, e).map_err(::std::io::Error::from)?;
+            writer.write_all(b"\n")?;
+        }
+
+        Ok(writer.flush()?)
+    }
+}
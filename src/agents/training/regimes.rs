@@ -0,0 +1,55 @@
+//! Realised dynamics of individual training episodes.
+//!
+//! When the dynamics vary between or within episodes, e.g. under an
+//! adversary, logging them next to each episode's outcome shows post hoc in
+//! which regimes the trader still fails.
+use crate::env::{
+    Env,
+    dynamics::{execution::ExecutionDynamics, price::PriceDynamics},
+};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EpisodeDynamics {
+    pub episode: usize,
+
+    pub reward: f64,
+    pub wealth: f64,
+    pub inv_terminal: f64,
+
+    pub drift_mean: f64,
+    pub drift_stddev: f64,
+    pub drift_min: f64,
+    pub drift_max: f64,
+
+    pub volatility_mean: f64,
+    pub volatility_max: f64,
+
+    pub fill_rate: f64,
+    pub cap_rate: f64,
+}
+
+impl EpisodeDynamics {
+    /// Summarise the episode that `env` has just completed.
+    pub fn of<P: PriceDynamics, E: ExecutionDynamics>(episode: usize, env: &Env<P, E>) -> EpisodeDynamics {
+        let dynamics = &env.dynamics;
+
+        EpisodeDynamics {
+            episode,
+
+            reward: env.episode_reward,
+            wealth: env.wealth,
+            inv_terminal: env.inv_terminal,
+
+            drift_mean: dynamics.drift_path.mean(),
+            drift_stddev: dynamics.drift_path.stddev(),
+            drift_min: dynamics.drift_path.min,
+            drift_max: dynamics.drift_path.max,
+
+            volatility_mean: dynamics.volatility_path.mean(),
+            volatility_max: dynamics.volatility_path.max,
+
+            fill_rate: dynamics.fill_rate(),
+            cap_rate: dynamics.cap_rate(),
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! Evaluation of a trader under every stressor supported by the crate,
+//! consolidated into a single report.
+//!
+//! All conditions share the same seed substreams, so that differences between
+//! them are due to the stressor rather than to sampling noise. The adversary
+//! observes the raw environment state, i.e. without a trailing window.
+use crate::{
+    agents::{Adversary, Trader, tta},
+    env::{
+        Env,
+        dynamics::{execution::PoissonRate, price::BrownianMotionWithDrift},
+    },
+    utils::{cmp_rewards, Estimate},
+};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use rsrl::{
+    core::{Algorithm, Controller, SeedStream},
+    domains::Domain,
+};
+use std::fmt::Write;
+
+const MAX_DRIFT: f64 = 5.0;
+
+pub type StressEnv = Env<BrownianMotionWithDrift, PoissonRate>;
+
+/// Deterministic perturbations of the dynamics over an episode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Script {
+    /// The drift jumps from zero to its maximum half way through the episode.
+    DriftShock,
+
+    /// The maximum drift over the first half of the episode, reversed over the
+    /// second.
+    TrendReversal,
+
+    /// Volatility tripled over the middle third of the episode.
+    VolatilitySpike,
+
+    /// Execution intensities cut to a quarter over the whole episode.
+    LiquidityDrought,
+}
+
+impl Script {
+    pub const ALL: [Script; 4] = [
+        Script::DriftShock, Script::TrendReversal, Script::VolatilitySpike, Script::LiquidityDrought,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Script::DriftShock => "drift_shock",
+            Script::TrendReversal => "trend_reversal",
+            Script::VolatilitySpike => "volatility_spike",
+            Script::LiquidityDrought => "liquidity_drought",
+        }
+    }
+
+    /// Set the dynamics of `env` for its current time, relative to the
+    /// unperturbed dynamics `nominal`.
+    fn apply(&self, env: &mut StressEnv, nominal: &StressEnv) {
+        let t = env.dynamics.time;
+
+        match self {
+            Script::DriftShock =>
+                env.dynamics.price_dynamics.drift = if t < 0.5 { 0.0 } else { MAX_DRIFT },
+            Script::TrendReversal =>
+                env.dynamics.price_dynamics.drift = if t < 0.5 { MAX_DRIFT } else { -MAX_DRIFT },
+            Script::VolatilitySpike => {
+                let scale = if t >= 1.0 / 3.0 && t < 2.0 / 3.0 { 3.0 } else { 1.0 };
+
+                env.dynamics.price_dynamics.volatility = scale * nominal.dynamics.price_dynamics.volatility;
+            },
+            Script::LiquidityDrought =>
+                env.dynamics.execution_dynamics.scale = 0.25 * nominal.dynamics.execution_dynamics.scale,
+        }
+    }
+}
+
+/// Ranges from which the dynamics of each domain-randomised episode are drawn
+/// uniformly.
+#[derive(Clone, Copy, Debug)]
+pub struct Randomisation {
+    pub drift: (f64, f64),
+    pub volatility: (f64, f64),
+    pub execution_scale: (f64, f64),
+    pub execution_decay: (f64, f64),
+}
+
+impl Default for Randomisation {
+    fn default() -> Randomisation {
+        Randomisation {
+            drift: (-MAX_DRIFT, MAX_DRIFT),
+            volatility: (1.0, 3.0),
+            execution_scale: (100.0, 180.0),
+            execution_decay: (1.0, 2.0),
+        }
+    }
+}
+
+impl Randomisation {
+    fn apply(&self, env: &mut StressEnv, seed: u64) {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut draw = |(lb, ub): (f64, f64)| if lb < ub { rng.gen_range(lb, ub) } else { lb };
+
+        env.dynamics.price_dynamics.drift = draw(self.drift);
+        env.dynamics.price_dynamics.volatility = draw(self.volatility);
+        env.dynamics.execution_dynamics.scale = draw(self.execution_scale);
+        env.dynamics.execution_dynamics.decay = draw(self.execution_decay);
+    }
+}
+
+/// Summary of the trader's performance under a single condition.
+#[derive(Clone, Debug, Serialize)]
+pub struct Row {
+    pub stressor: &'static str,
+    pub condition: String,
+    pub n_simulations: usize,
+
+    pub wealth_mean: f64,
+    pub wealth_stddev: f64,
+    pub wealth_min: f64,
+
+    pub inv_mean: f64,
+    pub inv_stddev: f64,
+    pub inv_abs_max: f64,
+
+    pub spread_mean: f64,
+}
+
+impl Row {
+    fn new(stressor: &'static str, condition: String, outcomes: &[Outcome]) -> Row {
+        let wealth: Vec<f64> = outcomes.iter().map(|o| o.wealth).collect();
+        let inv: Vec<f64> = outcomes.iter().map(|o| o.inv).collect();
+        let spread: Vec<f64> = outcomes.iter().map(|o| o.spread).collect();
+
+        let wealth_est = Estimate::from_slice(&wealth);
+        let inv_est = Estimate::from_slice(&inv);
+
+        Row {
+            stressor,
+            condition,
+            n_simulations: outcomes.len(),
+
+            wealth_mean: wealth_est.0,
+            wealth_stddev: wealth_est.1,
+            wealth_min: wealth.iter().cloned().fold(::std::f64::INFINITY, f64::min),
+
+            inv_mean: inv_est.0,
+            inv_stddev: inv_est.1,
+            inv_abs_max: inv.iter().fold(0.0, |acc: f64, q| acc.max(q.abs())),
+
+            spread_mean: Estimate::from_slice(&spread).0,
+        }
+    }
+}
+
+struct Outcome {
+    wealth: f64,
+    inv: f64,
+    spread: f64,
+}
+
+/// Run a greedy episode of `trader`, letting `control` set the dynamics
+/// before every step.
+fn run_episode(mut env: StressEnv, trader: &mut Trader, mut control: impl FnMut(&mut StressEnv)) -> Outcome {
+    let mut i = 0;
+    let mut spread_sum = 0.0;
+
+    loop {
+        control(&mut env);
+
+        let quotes = trader.sample_target(env.state());
+        let t = env.step(tta(quotes));
+
+        i += 1;
+        spread_sum += quotes.1 * 2.0;
+
+        if t.terminated() {
+            return Outcome { wealth: env.wealth, inv: env.inv_terminal, spread: spread_sum / i as f64, };
+        }
+    }
+}
+
+/// Evaluation of a trader under nominal dynamics, a sweep of fixed drifts,
+/// an optional trained adversary, randomised dynamics and scripted stress
+/// scenarios.
+pub struct RobustnessSuite {
+    pub n_simulations: usize,
+    pub drifts: Vec<f64>,
+    pub randomisation: Randomisation,
+    pub scripts: Vec<Script>,
+}
+
+impl RobustnessSuite {
+    pub fn new(n_simulations: usize) -> RobustnessSuite {
+        RobustnessSuite {
+            n_simulations,
+            drifts: vec![-MAX_DRIFT, -MAX_DRIFT / 2.0, MAX_DRIFT / 2.0, MAX_DRIFT],
+            randomisation: Randomisation::default(),
+            scripts: Script::ALL.to_vec(),
+        }
+    }
+
+    fn evaluate(
+        &self,
+        env_builder: &impl Fn() -> StressEnv,
+        trader: &mut Trader,
+        seeds: SeedStream,
+        mut setup: impl FnMut(&mut StressEnv, &SeedStream),
+        mut control: impl FnMut(&mut StressEnv, &StressEnv),
+    ) -> Vec<Outcome>
+    {
+        let nominal = env_builder();
+
+        (0..self.n_simulations).map(|j| {
+            let seeds = seeds.substream(j as u64);
+            let mut env = env_builder().seeded(seeds.seed(0));
+
+            setup(&mut env, &seeds);
+            trader.reseed(seeds.seed(1));
+
+            run_episode(env, trader, |env| control(env, &nominal))
+        }).collect()
+    }
+
+    pub fn run(
+        &self,
+        env_builder: impl Fn() -> StressEnv,
+        trader: &mut Trader,
+        mut adversary: Option<&mut Adversary>,
+        seeds: SeedStream,
+    ) -> RobustnessReport
+    {
+        let mut rows = vec![];
+
+        let outcomes = self.evaluate(&env_builder, trader, seeds, |_, _| {}, |_, _| {});
+        rows.push(Row::new("nominal", "drift=0".to_owned(), &outcomes));
+
+        for &drift in self.drifts.iter() {
+            let outcomes = self.evaluate(
+                &env_builder, trader, seeds,
+                |env, _| env.dynamics.price_dynamics.drift = drift,
+                |_, _| {},
+            );
+
+            rows.push(Row::new("fixed_drift", format!("drift={}", drift), &outcomes));
+        }
+
+        if let Some(ref mut adversary) = adversary {
+            adversary.reseed(seeds.seed(2));
+
+            let outcomes = self.evaluate(
+                &env_builder, trader, seeds,
+                |_, _| {},
+                |env, _| env.dynamics.price_dynamics.drift = MAX_DRIFT * adversary.sample_target(env.state()),
+            );
+
+            rows.push(Row::new("adversary", "trained".to_owned(), &outcomes));
+        }
+
+        let randomisation = self.randomisation;
+        let outcomes = self.evaluate(
+            &env_builder, trader, seeds,
+            |env, seeds| randomisation.apply(env, seeds.seed(2)),
+            |_, _| {},
+        );
+        rows.push(Row::new("randomised", "uniform".to_owned(), &outcomes));
+
+        for script in self.scripts.iter() {
+            let outcomes = self.evaluate(
+                &env_builder, trader, seeds,
+                |_, _| {},
+                |env, nominal| script.apply(env, nominal),
+            );
+
+            rows.push(Row::new("scripted", script.name().to_owned(), &outcomes));
+        }
+
+        RobustnessReport { rows, }
+    }
+}
+
+pub struct RobustnessReport {
+    pub rows: Vec<Row>,
+}
+
+impl RobustnessReport {
+    /// Condition with the lowest mean wealth.
+    pub fn worst_case(&self) -> Option<&Row> {
+        self.rows.iter().min_by(|a, b| cmp_rewards(a.wealth_mean, b.wealth_mean))
+    }
+
+    /// Markdown summary with the average and worst-case mean wealth of each
+    /// stressor, followed by every condition.
+    pub fn markdown(&self) -> String {
+        let mut md = String::from("# Robustness report\n\n");
+        let mut stressors: Vec<&'static str> = vec![];
+
+        for row in self.rows.iter() {
+            if !stressors.contains(&row.stressor) {
+                stressors.push(row.stressor);
+            }
+        }
+
+        md.push_str("| Stressor | Conditions | Average wealth | Worst-case wealth | Worst condition | Worst episode |\n");
+        md.push_str("|---|---|---|---|---|---|\n");
+
+        for stressor in stressors {
+            let rows: Vec<&Row> = self.rows.iter().filter(|r| r.stressor == stressor).collect();
+            let average = rows.iter().map(|r| r.wealth_mean).sum::<f64>() / rows.len() as f64;
+            let worst = rows.iter().min_by(|a, b| cmp_rewards(a.wealth_mean, b.wealth_mean)).unwrap();
+            let worst_episode = rows.iter().map(|r| r.wealth_min).fold(::std::f64::INFINITY, f64::min);
+
+            writeln!(
+                md, "| {} | {} | {:.3} | {:.3} | {} | {:.3} |",
+                stressor, rows.len(), average, worst.wealth_mean, worst.condition, worst_episode,
+            ).ok();
+        }
+
+        if let Some(worst) = self.worst_case() {
+            writeln!(
+                md, "\nWorst case overall: {} ({}), mean wealth {:.3} ± {:.3}.",
+                worst.stressor, worst.condition, worst.wealth_mean, worst.wealth_stddev,
+            ).ok();
+        }
+
+        md.push_str("\n## Conditions\n\n");
+        md.push_str("| Stressor | Condition | Wealth | Min wealth | Terminal inventory | Max abs. inventory | Spread |\n");
+        md.push_str("|---|---|---|---|---|---|---|\n");
+
+        for r in self.rows.iter() {
+            writeln!(
+                md, "| {} | {} | {:.3} ± {:.3} | {:.3} | {:.3} ± {:.3} | {:.3} | {:.3} |",
+                r.stressor, r.condition, r.wealth_mean, r.wealth_stddev, r.wealth_min,
+                r.inv_mean, r.inv_stddev, r.inv_abs_max, r.spread_mean,
+            ).ok();
+        }
+
+        md
+    }
+}
@@ -0,0 +1,157 @@
+//! Rolling-horizon deployment of a fixed-horizon trader.
+//!
+//! In practice a trader trained on episodes of unit length is operated over
+//! a longer session by restarting its clock at every horizon, without
+//! flattening the position in between. Here a session consists of
+//! consecutive horizons over a continuous price path, with the inventory
+//! carried from one to the next and only liquidated at the end of the
+//! session.
+use crate::{
+    agents::{Trader, tta},
+    env::{
+        Env,
+        dynamics::{execution::ExecutionDynamics, price::PriceDynamics},
+        liquidation::Liquidation,
+        penalty::InventoryPenalty,
+    },
+    utils::Estimate,
+};
+use rsrl::{
+    core::{Algorithm, Controller, SeedStream},
+    domains::Domain,
+};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Session {
+    pub session: usize,
+    pub n_horizons: usize,
+
+    /// Wealth and reward over the whole session, after the final
+    /// liquidation.
+    pub wealth: f64,
+    pub reward: f64,
+
+    /// Mark-to-market PnL of the individual horizons.
+    pub horizon_pnl_mean: f64,
+    pub horizon_pnl_stddev: f64,
+    pub horizon_pnl_min: f64,
+
+    /// Absolute inventory carried over from one horizon to the next.
+    pub carried_inv_mean: f64,
+    pub carried_inv_max: f64,
+
+    /// Largest absolute inventory held at any step of the session.
+    pub inv_abs_max: f64,
+
+    pub inv_terminal: f64,
+}
+
+/// Run one session of `n_horizons` consecutive horizons, the first of which
+/// starts from the environment as built.
+///
+/// Intermediate horizons carry their inventory into the next without any
+/// terminal penalty; the last is liquidated as configured by `env_builder`.
+pub fn run_session<P: PriceDynamics, E: ExecutionDynamics>(
+    env_builder: impl Fn() -> Env<P, E>,
+    trader: &mut Trader,
+    session: usize,
+    n_horizons: usize,
+    seeds: SeedStream,
+) -> Session
+{
+    trader.reseed(seeds.seed(1));
+
+    let mut price = None;
+    let mut inv = 0.0;
+
+    let mut wealth = 0.0;
+    let mut reward = 0.0;
+
+    let mut horizon_pnls = vec![];
+    let mut carried = vec![];
+    let mut inv_abs_max: f64 = 0.0;
+
+    for k in 0..n_horizons {
+        let last = k + 1 == n_horizons;
+        let mut env = env_builder().seeded(seeds.substream(k as u64).seed(0));
+
+        if let Some(price) = price {
+            env = env.continued_from(price, inv);
+        }
+
+        if !last {
+            let penalty = env.inventory_penalty;
+
+            env = env
+                .with_inventory_penalty(InventoryPenalty::new(penalty.running, 0.0))
+                .with_liquidation(Liquidation::Carry);
+        }
+
+        let (price_start, inv_start) = (env.dynamics.price, env.inv);
+        let mut quotes = trader.sample_target(env.state());
+
+        loop {
+            let t = env.step(tta(quotes));
+
+            // Carried or liquidated inventory is only left in inv_terminal:
+            inv_abs_max = inv_abs_max.max(env.inv.abs()).max(env.inv_terminal.abs());
+
+            if t.terminated() {
+                break
+            } else {
+                quotes = trader.sample_target(t.to.state());
+            }
+        }
+
+        let inv_end = if last { 0.0 } else { env.inv_terminal };
+
+        // Value the inventory at either end of the horizon at the midprice:
+        horizon_pnls.push(env.wealth + env.dynamics.price * inv_end - price_start * inv_start);
+
+        wealth += env.wealth;
+        reward += env.episode_reward;
+
+        if !last {
+            carried.push(inv_end.abs());
+        }
+
+        price = Some(env.dynamics.price);
+        inv = env.inv_terminal;
+    }
+
+    let pnl_est = Estimate::from_slice(&horizon_pnls);
+
+    Session {
+        session,
+        n_horizons,
+
+        wealth,
+        reward,
+
+        horizon_pnl_mean: pnl_est.0,
+        horizon_pnl_stddev: pnl_est.1,
+        horizon_pnl_min: horizon_pnls.iter().cloned().fold(::std::f64::INFINITY, f64::min),
+
+        carried_inv_mean: if carried.is_empty() { 0.0 } else { carried.iter().sum::<f64>() / carried.len() as f64 },
+        carried_inv_max: carried.iter().cloned().fold(0.0, f64::max),
+
+        inv_abs_max,
+
+        inv_terminal: inv,
+    }
+}
+
+/// Run `n_sessions` independent sessions, each with its own substream of
+/// `seeds`.
+pub fn evaluate_rolling<P: PriceDynamics, E: ExecutionDynamics>(
+    env_builder: impl Fn() -> Env<P, E>,
+    trader: &mut Trader,
+    n_sessions: usize,
+    n_horizons: usize,
+    seeds: SeedStream,
+) -> Vec<Session>
+{
+    (0..n_sessions).map(|j| {
+        run_session(&env_builder, trader, j, n_horizons, seeds.substream(j as u64))
+    }).collect()
+}
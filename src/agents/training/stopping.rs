@@ -0,0 +1,63 @@
+//! Sequential stopping of evaluations once the estimate of mean wealth is
+//! precise enough.
+use crate::utils::mean_var;
+use std::str::FromStr;
+
+/// Stop once the normal-approximation confidence interval on the mean is no
+/// wider than `target_width`.
+///
+/// The rule is checked after every simulation, so the nominal coverage is
+/// somewhat optimistic; `min_simulations` guards against stopping on a
+/// handful of similar outcomes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StoppingRule {
+    pub target_width: f64,
+
+    /// Number of standard errors either side of the mean, e.g. 1.96 for a
+    /// 95% interval.
+    pub z: f64,
+
+    pub min_simulations: usize,
+}
+
+impl StoppingRule {
+    pub fn new(target_width: f64) -> StoppingRule {
+        StoppingRule { target_width, z: 1.96, min_simulations: 30, }
+    }
+
+    /// Width of the confidence interval on the mean of `values`.
+    pub fn width(&self, values: &[f64]) -> f64 {
+        let n = values.len() as f64;
+        let [_, var] = mean_var(values);
+
+        // Bessel-corrected standard error:
+        2.0 * self.z * (var / (n - 1.0)).max(0.0).sqrt()
+    }
+
+    pub fn is_satisfied(&self, values: &[f64]) -> bool {
+        values.len() >= self.min_simulations.max(2) && self.width(values) <= self.target_width
+    }
+}
+
+impl FromStr for StoppingRule {
+    type Err = String;
+
+    /// Parse a rule of the form `width` or `width,z`.
+    fn from_str(s: &str) -> Result<StoppingRule, String> {
+        let params = s.split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid stopping rule `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        let rule = match params.as_slice() {
+            &[width] => StoppingRule::new(width),
+            &[width, z] => StoppingRule { z, ..StoppingRule::new(width) },
+            _ => return Err(format!("Stopping rule `{}` must be of the form width or width,z.", s)),
+        };
+
+        if rule.target_width > 0.0 && rule.z > 0.0 {
+            Ok(rule)
+        } else {
+            Err(format!("Stopping rule `{}` requires a positive width and z.", s))
+        }
+    }
+}
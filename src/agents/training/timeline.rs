@@ -0,0 +1,124 @@
+//! Step-by-step timelines of zero-sum episodes, and the cross-correlation
+//! between the adversary's drift and the trader's inventory and quotes.
+//!
+//! A correlation at a negative lag relates the drift at time _t_ to the
+//! trader's state at an earlier time; a consistently negative correlation
+//! between drift and lagged inventory means the adversary moves the price
+//! against whatever position the trader has built up.
+use crate::{
+    agents::{Adversary, Trader, training::zero_sum::evaluate_agents_once_with},
+    env::{
+        Env,
+        dynamics::{execution::ExecutionDynamics, price::BrownianMotionWithDrift},
+        observation::TrailingWindow,
+    },
+};
+use rsrl::core::{Algorithm, SeedStream};
+
+/// Per-step actions and inventory of a single zero-sum episode.
+#[derive(Clone, Debug, Default)]
+pub struct Timeline {
+    pub drift: Vec<f64>,
+    pub inv: Vec<f64>,
+    pub rp: Vec<f64>,
+    pub spread: Vec<f64>,
+}
+
+impl Timeline {
+    pub fn len(&self) -> usize { self.drift.len() }
+
+    pub fn is_empty(&self) -> bool { self.drift.is_empty() }
+}
+
+/// Record the timelines of `n_episodes` greedy zero-sum episodes, each on its
+/// own seed substream as in `evaluate_agents`.
+pub fn record_timelines<E: ExecutionDynamics>(
+    env_builder: impl Fn() -> Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    adversary: &mut Adversary,
+    n_episodes: usize,
+    seeds: SeedStream,
+    window: &mut TrailingWindow,
+) -> Vec<Timeline>
+{
+    (0..n_episodes).map(|j| {
+        let seeds = seeds.substream(j as u64);
+        let mut timeline = Timeline::default();
+
+        trader.reseed(seeds.seed(1));
+        adversary.reseed(seeds.seed(2));
+
+        evaluate_agents_once_with(
+            env_builder().seeded(seeds.seed(0)), trader, adversary, window,
+            |drift, inv, quotes| {
+                timeline.drift.push(drift);
+                timeline.inv.push(inv);
+                timeline.rp.push(quotes.0);
+                timeline.spread.push(quotes.1 * 2.0);
+            },
+        );
+
+        timeline
+    }).collect()
+}
+
+/// Correlations between the drift at time _t_ and each trader series at time
+/// _t + lag_, pooled over episodes.
+#[derive(Clone, Debug, Serialize)]
+pub struct LagRecord {
+    pub lag: i64,
+    pub n_pairs: usize,
+
+    pub drift_inv: f64,
+    pub drift_rp: f64,
+    pub drift_spread: f64,
+}
+
+/// Pearson correlation of the pairs `(x[t], y[t + lag])` drawn from every
+/// timeline; NaN if there are fewer than two pairs or either side is
+/// constant.
+fn lagged_correlation(timelines: &[Timeline], lag: i64, series: impl Fn(&Timeline) -> &[f64]) -> (usize, f64) {
+    let pairs: Vec<(f64, f64)> = timelines.iter().flat_map(|tl| {
+        let y = series(tl);
+        let n = tl.len() as i64;
+        let ts = (0.max(-lag))..(n.min(n - lag));
+
+        ts.map(move |t| (tl.drift[t as usize], y[(t + lag) as usize]))
+    }).collect();
+
+    let n = pairs.len();
+
+    if n < 2 {
+        return (n, ::std::f64::NAN);
+    }
+
+    let mx = pairs.iter().map(|p| p.0).sum::<f64>() / n as f64;
+    let my = pairs.iter().map(|p| p.1).sum::<f64>() / n as f64;
+
+    let (sxy, sxx, syy) = pairs.iter().fold((0.0, 0.0, 0.0), |(sxy, sxx, syy), &(x, y)| {
+        let (dx, dy) = (x - mx, y - my);
+
+        (sxy + dx * dy, sxx + dx * dx, syy + dy * dy)
+    });
+
+    (n, sxy / (sxx * syy).sqrt())
+}
+
+/// Cross-correlations of the drift with inventory, reservation price offset
+/// and spread for every lag in `[-max_lag, max_lag]`.
+pub fn cross_correlations(timelines: &[Timeline], max_lag: usize) -> Vec<LagRecord> {
+    let max_lag = max_lag as i64;
+
+    (-max_lag..=max_lag).map(|lag| {
+        let (n_pairs, drift_inv) = lagged_correlation(timelines, lag, |tl| &tl.inv);
+
+        LagRecord {
+            lag,
+            n_pairs,
+
+            drift_inv,
+            drift_rp: lagged_correlation(timelines, lag, |tl| &tl.rp).1,
+            drift_spread: lagged_correlation(timelines, lag, |tl| &tl.spread).1,
+        }
+    }).collect()
+}
@@ -1,18 +1,30 @@
 use crate::{
-    agents::{Trader, tta},
-    env::{Env, dynamics::{price::PriceDynamics, execution::ExecutionDynamics}},
+    agents::{
+        Trader, tta,
+        training::{
+            anomaly::AnomalyDetector, breakdown::RegimeBreakdown, curves::InventoryCurves,
+            probes::{ProbeValues, Probes},
+            stopping::StoppingRule,
+        },
+    },
+    env::{
+        Env,
+        dynamics::{price::PriceDynamics, execution::ExecutionDynamics},
+        scenarios::{ScenarioBundle, ScenarioEnv},
+    },
     utils::Estimate,
 };
 use rsrl::{
-    core::{Algorithm, OnlineLearner, Controller},
-    domains::Domain,
+    core::{Algorithm, OnlineLearner, Controller, SeedStream},
+    domains::{Domain, Transition},
     geometry::Vector,
-    policies::Policy,
+    policies::{Entropy, Policy},
 };
 
 #[derive(Debug, Serialize)]
 pub struct Record {
     pub episode: usize,
+    pub n_simulations: usize,
 
     pub wealth_mean: f64,
     pub wealth_stddev: f64,
@@ -26,9 +38,34 @@ pub struct Record {
     pub spread_mean: f64,
     pub spread_stddev: f64,
 
-    pub rp_neutral: f64,
-    pub rp_bull: f64,
-    pub rp_bear: f64,
+    pub cap_rate_mean: f64,
+    pub cap_rate_stddev: f64,
+
+    pub overrides_mean: f64,
+    pub overrides_stddev: f64,
+
+    /// Per-step entropy of the policy, and log-probability of the quotes
+    /// sampled from it, averaged over each episode.
+    pub entropy_mean: f64,
+    pub entropy_stddev: f64,
+
+    pub log_prob_mean: f64,
+    pub log_prob_stddev: f64,
+
+    #[serde(flatten)]
+    pub rp: ProbeValues,
+
+    #[serde(flatten)]
+    pub spread_curve: ProbeValues,
+
+    #[serde(flatten)]
+    pub skew_curve: ProbeValues,
+
+    #[serde(flatten)]
+    pub vol_breakdown: ProbeValues,
+
+    #[serde(flatten)]
+    pub drift_breakdown: ProbeValues,
 }
 
 fn mean(x: [f64; 2]) -> f64 { (x[0] - x[1]) / 2.0 }
@@ -38,12 +75,12 @@ pub fn train_value_function<P: PriceDynamics, E: ExecutionDynamics>(
     trader: &mut Trader,
 ) -> Env<P, E>
 {
-    let mut quotes = trader.sample_behaviour(env.emit().state());
+    let mut quotes = trader.sample_behaviour(env.state());
 
     loop {
-        let t = env.step(tta(quotes));
+        let t = env.step(tta(quotes)).replace_action(quotes);
 
-        trader.critic.handle_transition(&t.clone().replace_action(quotes));
+        trader.critic.handle_transition(&t);
 
         if t.terminated() {
             break
@@ -56,14 +93,42 @@ pub fn train_value_function<P: PriceDynamics, E: ExecutionDynamics>(
 }
 
 pub fn train_trader_once<P: PriceDynamics, E: ExecutionDynamics>(
+    env: Env<P, E>,
+    trader: &mut Trader,
+) -> Env<P, E>
+{
+    train_trader_once_with(env, trader, |_| {})
+}
+
+/// Train the trader for one episode, recording each transition with
+/// `detector` for inspection once the episode has terminated.
+pub fn train_trader_once_monitored<P: PriceDynamics, E: ExecutionDynamics>(
+    env: Env<P, E>,
+    trader: &mut Trader,
+    detector: &mut AnomalyDetector,
+) -> Env<P, E>
+{
+    detector.reset();
+
+    train_trader_once_with(env, trader, |t| detector.record(t))
+}
+
+/// Train the trader for one episode, passing each environment transition to
+/// `on_step` before the trader learns from it.
+pub fn train_trader_once_with<P: PriceDynamics, E: ExecutionDynamics>(
     mut env: Env<P, E>,
     trader: &mut Trader,
+    mut on_step: impl FnMut(&Transition<Vector<f64>, [f64; 2]>),
 ) -> Env<P, E>
 {
-    let mut quotes = trader.sample_behaviour(env.emit().state());
+    let mut quotes = trader.sample_behaviour(env.state());
 
     loop {
-        let t = env.step(tta(quotes)).replace_action(quotes);
+        let t = env.step(tta(quotes));
+
+        on_step(&t);
+
+        let t = t.replace_action(quotes);
 
         trader.handle_transition(&t);
 
@@ -79,24 +144,77 @@ pub fn train_trader_once<P: PriceDynamics, E: ExecutionDynamics>(
     env
 }
 
+/// Outcome of a single evaluation episode.
+#[derive(Clone, Copy, Debug)]
+pub struct Episode {
+    pub wealth: f64,
+    pub spread: f64,
+    pub reward: f64,
+    pub inv_terminal: f64,
+    pub cap_rate: f64,
+    pub overrides: f64,
+
+    /// Mean entropy of the policy, and log-probability of the sampled quotes,
+    /// over the steps of the episode.
+    pub entropy: f64,
+    pub log_prob: f64,
+
+    /// Net price change and realised volatility per unit time.
+    pub realised_drift: f64,
+    pub realised_volatility: f64,
+}
+
 pub fn evaluate_trader_once<P: PriceDynamics, E: ExecutionDynamics>(
+    env: Env<P, E>,
+    trader: &mut Trader,
+) -> (f64, f64, f64, f64, f64, f64)
+{
+    let e = evaluate_trader_once_with(env, trader, |_, _| {});
+
+    (e.wealth, e.spread, e.reward, e.inv_terminal, e.cap_rate, e.overrides)
+}
+
+/// Evaluate the trader for one episode, passing the inventory and the quotes
+/// (reservation price offset, half-spread) posted at each step to `on_step`.
+pub fn evaluate_trader_once_with<P: PriceDynamics, E: ExecutionDynamics>(
     mut env: Env<P, E>,
     trader: &mut Trader,
-) -> (f64, f64, f64, f64)
+    mut on_step: impl FnMut(f64, (f64, f64)),
+) -> Episode
 {
-    let mut quotes = trader.sample_target(env.emit().state());
+    let mut quotes = trader.sample_target(env.state());
 
     let mut i = 0;
     let mut reward_sum = 0.0;
     let mut spread_sum = quotes.1 * 2.0;
+    let mut entropy_sum = 0.0;
+    let mut log_prob_sum = 0.0;
 
     loop {
+        on_step(env.inv, quotes);
+
+        entropy_sum += trader.policy.entropy(env.state());
+        log_prob_sum += trader.policy.probability(env.state(), quotes).ln();
+
         let t = env.step(tta(quotes));
 
         reward_sum += t.reward;
 
         if t.terminated() {
-            return (env.wealth, spread_sum / i as f64, reward_sum, env.inv_terminal);
+            return Episode {
+                wealth: env.wealth,
+                spread: spread_sum / i as f64,
+                reward: reward_sum,
+                inv_terminal: env.inv_terminal,
+                cap_rate: env.dynamics.cap_rate(),
+                overrides: env.n_overrides as f64,
+
+                entropy: entropy_sum / (i + 1) as f64,
+                log_prob: log_prob_sum / (i + 1) as f64,
+
+                realised_drift: env.dynamics.realised_drift(),
+                realised_volatility: env.dynamics.realised_volatility(),
+            };
         } else {
             quotes = trader.sample_target(t.to.state());
 
@@ -111,33 +229,91 @@ pub fn evaluate_trader<P: PriceDynamics, E: ExecutionDynamics>(
     trader: &mut Trader,
     episode: usize,
     n_simulations: usize,
+    stopping: Option<StoppingRule>,
+    seeds: SeedStream,
+    probes: &Probes,
+) -> Record
+{
+    // Each simulation draws from its own substream, so the statistics do not
+    // depend on the order in which simulations are run.
+    let envs = (0..n_simulations).map(|j| {
+        let seeds = seeds.substream(j as u64);
+
+        (env_builder().seeded(seeds.seed(0)), seeds.seed(1))
+    });
+
+    evaluate_trader_with(envs, trader, episode, stopping, probes)
+}
+
+/// Evaluate the trader on every scenario of a bundle, in order; see
+/// `ScenarioBundle::scenario`.
+pub fn evaluate_trader_on(
+    bundle: &ScenarioBundle,
+    decorate: impl Fn(ScenarioEnv) -> ScenarioEnv,
+    trader: &mut Trader,
+    episode: usize,
+    stopping: Option<StoppingRule>,
+    probes: &Probes,
+) -> Record
+{
+    let envs = (0..bundle.len()).map(|j| bundle.scenario(j, &decorate));
+
+    evaluate_trader_with(envs, trader, episode, stopping, probes)
+}
+
+fn evaluate_trader_with<P: PriceDynamics, E: ExecutionDynamics>(
+    envs: impl Iterator<Item = (Env<P, E>, u64)>,
+    trader: &mut Trader,
+    episode: usize,
+    stopping: Option<StoppingRule>,
+    probes: &Probes,
 ) -> Record
 {
     let mut pnls = vec![];
     let mut rewards = vec![];
     let mut terminal_qs = vec![];
     let mut average_spread = vec![];
-
-    for _ in 0..n_simulations {
-        let (p, s, r, q) = evaluate_trader_once(env_builder(), trader);
-
-        pnls.push(p);
-        rewards.push(r);
-        terminal_qs.push(q);
-        average_spread.push(s);
+    let mut cap_rates = vec![];
+    let mut overrides = vec![];
+    let mut entropies = vec![];
+    let mut log_probs = vec![];
+    let mut curves = InventoryCurves::default();
+    let mut breakdown = RegimeBreakdown::default();
+
+    for (env, agent_seed) in envs {
+        trader.reseed(agent_seed);
+
+        let e = evaluate_trader_once_with(env, trader, |inv, quotes| curves.record(inv, quotes));
+
+        pnls.push(e.wealth);
+        rewards.push(e.reward);
+        terminal_qs.push(e.inv_terminal);
+        average_spread.push(e.spread);
+        cap_rates.push(e.cap_rate);
+        overrides.push(e.overrides);
+        entropies.push(e.entropy);
+        log_probs.push(e.log_prob);
+        breakdown.record(&e);
+
+        if stopping.map_or(false, |rule| rule.is_satisfied(&pnls)) {
+            break;
+        }
     }
 
     let pnl_est = Estimate::from_slice(&pnls);
     let rwd_est = Estimate::from_slice(&rewards);
     let inv_est = Estimate::from_slice(&terminal_qs);
     let spd_est = Estimate::from_slice(&average_spread);
+    let cap_est = Estimate::from_slice(&cap_rates);
+    let ovr_est = Estimate::from_slice(&overrides);
+    let ent_est = Estimate::from_slice(&entropies);
+    let lpr_est = Estimate::from_slice(&log_probs);
 
-    let rp_neutral = mean(tta(trader.policy.mpa(&Vector::from_vec(vec![0.0, 0.0]))));
-    let rp_bull = mean(tta(trader.policy.mpa(&Vector::from_vec(vec![0.0, 5.0]))));
-    let rp_bear = mean(tta(trader.policy.mpa(&Vector::from_vec(vec![0.0, -5.0]))));
+    let rp = probes.evaluate("rp", |s| mean(tta(trader.policy.mpa(s))));
 
     Record {
         episode,
+        n_simulations: pnls.len(),
 
         wealth_mean: pnl_est.0,
         wealth_stddev: pnl_est.1,
@@ -151,8 +327,24 @@ pub fn evaluate_trader<P: PriceDynamics, E: ExecutionDynamics>(
         spread_mean: spd_est.0,
         spread_stddev: spd_est.1,
 
-        rp_neutral,
-        rp_bull,
-        rp_bear,
+        cap_rate_mean: cap_est.0,
+        cap_rate_stddev: cap_est.1,
+
+        overrides_mean: ovr_est.0,
+        overrides_stddev: ovr_est.1,
+
+        entropy_mean: ent_est.0,
+        entropy_stddev: ent_est.1,
+
+        log_prob_mean: lpr_est.0,
+        log_prob_stddev: lpr_est.1,
+
+        rp,
+
+        spread_curve: curves.spread(),
+        skew_curve: curves.skew(),
+
+        vol_breakdown: breakdown.by_volatility(),
+        drift_breakdown: breakdown.by_drift(),
     }
 }
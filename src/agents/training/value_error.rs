@@ -0,0 +1,147 @@
+//! Accuracy of the trader's critic, measured against Monte-Carlo returns from
+//! exploring starts.
+//!
+//! Episodes are started from each point of a grid of _(time, inventory)_
+//! states and rolled out under the trader's behaviour policy, whose value the
+//! critic estimates. Large errors point to the critic when trading is poor;
+//! small ones to the policy improvement step.
+use crate::{
+    agents::{Trader, tta},
+    env::{
+        Env,
+        dynamics::{execution::ExecutionDynamics, price::PriceDynamics},
+        start::{Bounds, StartDistribution},
+    },
+    utils::Estimate,
+};
+use rsrl::{
+    core::{Algorithm, Controller, SeedStream, ValuePredictor},
+    domains::Domain,
+};
+use std::fmt::Write;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ValueError {
+    pub time: f64,
+    pub inv: f64,
+
+    pub predicted: f64,
+
+    pub return_mean: f64,
+    pub return_stddev: f64,
+    pub n_rollouts: usize,
+
+    /// Predicted minus mean return, and the standard error of the latter.
+    pub error: f64,
+    pub error_stderr: f64,
+}
+
+/// Discounted return of one rollout of the behaviour policy.
+fn rollout<P: PriceDynamics, E: ExecutionDynamics>(mut env: Env<P, E>, trader: &mut Trader) -> f64 {
+    let mut ret = 0.0;
+    let mut discount = 1.0;
+    let mut quotes = trader.sample_behaviour(env.state());
+
+    loop {
+        let t = env.step(tta(quotes));
+
+        ret += discount * t.reward;
+        discount *= t.discount;
+
+        if t.terminated() {
+            return ret;
+        } else {
+            quotes = trader.sample_behaviour(t.to.state());
+        }
+    }
+}
+
+/// Compare the critic's value of each start state on the grid `times` ×
+/// `invs` against the mean return of `n_rollouts` episodes started there.
+///
+/// Every start state is rolled out with the same environment and agent
+/// seeds. Times must lie in _[0, 1)_ and are rounded down to a whole number
+/// of steps.
+pub fn value_errors<P: PriceDynamics, E: ExecutionDynamics>(
+    env_builder: impl Fn() -> Env<P, E>,
+    trader: &mut Trader,
+    times: &[f64],
+    invs: &[f64],
+    n_rollouts: usize,
+    seeds: SeedStream,
+) -> Result<Vec<ValueError>, String>
+{
+    let mut errors = vec![];
+
+    for &time in times {
+        for &inv in invs {
+            let start = StartDistribution::new(Bounds::point(inv), Bounds::point(time))?;
+
+            let env = env_builder().with_random_start(start);
+            let predicted = trader.critic.predict_v(env.state());
+
+            let returns: Vec<f64> = (0..n_rollouts).map(|j| {
+                let seeds = seeds.substream(j as u64);
+
+                trader.reseed(seeds.seed(1));
+
+                rollout(env_builder().seeded(seeds.seed(0)).with_random_start(start), trader)
+            }).collect();
+
+            let est = Estimate::from_slice(&returns);
+
+            errors.push(ValueError {
+                time: env.dynamics.time,
+                inv,
+
+                predicted,
+
+                return_mean: est.0,
+                return_stddev: est.1,
+                n_rollouts,
+
+                error: predicted - est.0,
+                error_stderr: est.1 / (n_rollouts as f64).sqrt(),
+            });
+        }
+    }
+
+    Ok(errors)
+}
+
+/// Markdown heatmap of `value(e)` with a row per inventory and a column per
+/// time, in the order in which they appear in `errors`.
+pub fn heatmap(errors: &[ValueError], value: impl Fn(&ValueError) -> f64) -> String {
+    let mut times: Vec<f64> = vec![];
+    let mut invs: Vec<f64> = vec![];
+
+    for e in errors {
+        if !times.contains(&e.time) { times.push(e.time); }
+        if !invs.contains(&e.inv) { invs.push(e.inv); }
+    }
+
+    let mut md = String::from("| inv \\ time |");
+
+    for t in times.iter() {
+        write!(md, " {:.3} |", t).ok();
+    }
+
+    md.push_str("\n|---|");
+    md.push_str(&"---|".repeat(times.len()));
+    md.push('\n');
+
+    for &inv in invs.iter() {
+        write!(md, "| {} |", inv).ok();
+
+        for &t in times.iter() {
+            match errors.iter().find(|e| e.inv == inv && e.time == t) {
+                Some(e) => write!(md, " {:.3} |", value(e)).ok(),
+                None => write!(md, " |").ok(),
+            };
+        }
+
+        md.push('\n');
+    }
+
+    md
+}
@@ -1,20 +1,53 @@
 use crate::{
-    agents::{Trader, Adversary, tta},
-    env::{Env, dynamics::{price::BrownianMotionWithDrift, execution::ExecutionDynamics}},
+    agents::{
+        Trader, Adversary, tta,
+        scripted::{Opponent, OpponentView},
+        training::{probes::{ProbeValues, Probes}, stopping::StoppingRule},
+    },
+    env::{
+        Env,
+        dynamics::{price::BrownianMotionWithDrift, execution::ExecutionDynamics},
+        observation::TrailingWindow,
+    },
     utils::Estimate,
 };
 use rsrl::{
-    core::{Algorithm, OnlineLearner, Controller},
-    domains::Domain,
+    core::{Algorithm, OnlineLearner, Controller, SeedStream},
+    domains::{Domain, Observation, Transition},
     geometry::Vector,
     policies::Policy,
 };
+use std::str::FromStr;
 
 const MAX_DRIFT: f64 = 5.0;
 
+/// Frequency with which the adversary chooses the drift.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DriftSchedule {
+    /// A new drift at every step, given the adversary's current observation.
+    PerStep,
+
+    /// A single drift held fixed for the whole episode, chosen given only the
+    /// initial state; i.e. a static uncertainty set.
+    PerEpisode,
+}
+
+impl FromStr for DriftSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DriftSchedule, String> {
+        match s {
+            "per_step" => Ok(DriftSchedule::PerStep),
+            "per_episode" => Ok(DriftSchedule::PerEpisode),
+            _ => Err(format!("Unknown drift schedule `{}`; expected per_step or per_episode.", s)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct Record {
     pub episode: usize,
+    pub n_simulations: usize,
 
     pub wealth_mean: f64,
     pub wealth_stddev: f64,
@@ -28,16 +61,17 @@ pub struct Record {
     pub spread_mean: f64,
     pub spread_stddev: f64,
 
-    pub rp_neutral: f64,
-    pub rp_bull: f64,
-    pub rp_bear: f64,
+    pub cap_rate_mean: f64,
+    pub cap_rate_stddev: f64,
+
+    #[serde(flatten)]
+    pub rp: ProbeValues,
 
     pub drift_mean: f64,
     pub drift_stddev: f64,
 
-    pub drift_neutral: f64,
-    pub drift_bull: f64,
-    pub drift_bear: f64,
+    #[serde(flatten)]
+    pub drift: ProbeValues,
 }
 
 fn mean(x: [f64; 2]) -> f64 { (x[0] - x[1]) / 2.0 }
@@ -45,26 +79,31 @@ fn mean(x: [f64; 2]) -> f64 { (x[0] - x[1]) / 2.0 }
 pub fn train_value_functions<E: ExecutionDynamics>(
     mut env: Env<BrownianMotionWithDrift, E>,
     trader: &mut Trader,
-    adversary: &mut Adversary
+    adversary: &mut Adversary,
+    window: &mut TrailingWindow,
 ) -> Env<BrownianMotionWithDrift, E>
 {
-    let obs = env.emit();
+    window.reset(&env);
 
+    let mut obs = window.observe(&env.emit());
     let mut drift = adversary.sample_behaviour(obs.state());
-    let mut quotes = trader.sample_behaviour(obs.state());
+    let mut quotes = trader.sample_behaviour(env.state());
 
     loop {
-        env.dynamics.price_dynamics.drift = MAX_DRIFT * (2.0 * drift - 1.0);
+        env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
 
         let t = env.step(tta(quotes));
+        let t_adv = window.transition(&env, obs, drift, &t).negate_reward();
+        let t = t.replace_action(quotes);
 
-        trader.critic.handle_transition(&t.clone().replace_action(quotes));
-        adversary.critic.handle_transition(&t.clone().replace_action(drift).negate_reward());
+        trader.critic.handle_transition(&t);
+        adversary.critic.handle_transition(&t_adv);
 
         if t.terminated() {
             break
         } else {
-            drift = adversary.sample_behaviour(t.to.state());
+            obs = t_adv.to;
+            drift = adversary.sample_behaviour(obs.state());
             quotes = trader.sample_behaviour(t.to.state());
         }
     }
@@ -75,26 +114,30 @@ pub fn train_value_functions<E: ExecutionDynamics>(
 pub fn train_agents_once<E: ExecutionDynamics>(
     mut env: Env<BrownianMotionWithDrift, E>,
     trader: &mut Trader,
-    adversary: &mut Adversary
+    adversary: &mut Adversary,
+    window: &mut TrailingWindow,
 ) -> Env<BrownianMotionWithDrift, E>
 {
-    let obs = env.emit();
+    window.reset(&env);
 
+    let mut obs = window.observe(&env.emit());
     let mut drift = adversary.sample_behaviour(obs.state());
-    let mut quotes = trader.sample_behaviour(obs.state());
+    let mut quotes = trader.sample_behaviour(env.state());
 
     loop {
-        env.dynamics.price_dynamics.drift = MAX_DRIFT * (2.0 * drift - 1.0);
+        env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
 
         let t = env.step(tta(quotes));
+        let t_adv = window.transition(&env, obs, drift, &t).negate_reward();
 
         trader.handle_transition(&t.clone().replace_action(quotes));
-        adversary.handle_transition(&t.clone().replace_action(drift).negate_reward());
+        adversary.handle_transition(&t_adv);
 
         if t.terminated() {
             break
         } else {
-            drift = adversary.sample_behaviour(t.to.state());
+            obs = t_adv.to;
+            drift = adversary.sample_behaviour(obs.state());
             quotes = trader.sample_behaviour(t.to.state());
         }
     }
@@ -106,15 +149,30 @@ pub fn train_agents_once<E: ExecutionDynamics>(
 }
 
 pub fn evaluate_agents_once<E: ExecutionDynamics>(
+    env: Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    adversary: &mut Adversary,
+    window: &mut TrailingWindow,
+) -> (f64, f64, f64, f64, f64, f64)
+{
+    evaluate_agents_once_with(env, trader, adversary, window, |_, _, _| {})
+}
+
+/// Evaluate both agents for one episode, passing the adversary's drift action,
+/// the trader's inventory and its quotes (reservation price offset,
+/// half-spread) at each step to `on_step`.
+pub fn evaluate_agents_once_with<E: ExecutionDynamics>(
     mut env: Env<BrownianMotionWithDrift, E>,
     trader: &mut Trader,
-    adversary: &mut Adversary
-) -> (f64, f64, f64, f64, f64)
+    adversary: &mut Adversary,
+    window: &mut TrailingWindow,
+    mut on_step: impl FnMut(f64, f64, (f64, f64)),
+) -> (f64, f64, f64, f64, f64, f64)
 {
-    let obs = env.emit();
+    window.reset(&env);
 
-    let mut drift = adversary.sample_target(obs.state());
-    let mut quotes = trader.sample_target(obs.state());
+    let mut drift = adversary.sample_target(&window.augment(env.state()));
+    let mut quotes = trader.sample_target(env.state());
 
     let mut i = 0;
     let mut drift_sum = 0.0;
@@ -122,17 +180,232 @@ pub fn evaluate_agents_once<E: ExecutionDynamics>(
     let mut spread_sum = quotes.1 * 2.0;
 
     loop {
-        env.dynamics.price_dynamics.drift = MAX_DRIFT * (2.0 * drift - 1.0);
+        on_step(drift, env.inv, quotes);
+
+        env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
 
         let t = env.step(tta(quotes));
 
         drift_sum += drift;
         reward_sum += t.reward;
 
+        window.record(&env, t.action);
+
         if t.terminated() {
-            return (env.wealth, drift_sum / i as f64, spread_sum / i as f64, reward_sum, env.inv_terminal);
+            return (
+                env.wealth, drift_sum / i as f64, spread_sum / i as f64, reward_sum, env.inv_terminal,
+                env.dynamics.cap_rate(),
+            );
+        } else {
+            drift = adversary.sample_target(&window.augment(t.to.state()));
+            quotes = trader.sample_target(t.to.state());
+
+            i += 1;
+            spread_sum += quotes.1 * 2.0;
+        }
+    }
+}
+
+/// Train only the trader for one episode against a fixed `opponent`, e.g. a
+/// scripted adversary or a frozen learned one.
+pub fn train_trader_against<E: ExecutionDynamics>(
+    env: Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    opponent: &mut impl Opponent,
+) -> Env<BrownianMotionWithDrift, E>
+{
+    train_trader_against_with(env, trader, opponent, |_| {})
+}
+
+/// As `train_trader_against`, passing each environment transition to
+/// `on_step` before the trader learns from it.
+pub fn train_trader_against_with<E: ExecutionDynamics>(
+    mut env: Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    opponent: &mut impl Opponent,
+    mut on_step: impl FnMut(&Transition<Vector<f64>, [f64; 2]>),
+) -> Env<BrownianMotionWithDrift, E>
+{
+    opponent.reset();
+
+    let mut quotes = trader.sample_behaviour(env.state());
+
+    loop {
+        let drift = opponent.drift(&OpponentView::of(&env, env.state()));
+
+        env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
+
+        let t = env.step(tta(quotes));
+
+        on_step(&t);
+
+        let t = t.replace_action(quotes);
+
+        trader.handle_transition(&t);
+
+        if t.terminated() {
+            break
+        } else {
+            quotes = trader.sample_behaviour(t.to.state());
+        }
+    }
+
+    trader.handle_terminal();
+
+    env
+}
+
+/// Evaluate the trader for one episode against a fixed `opponent`; the
+/// outputs are as for `evaluate_agents_once`.
+pub fn evaluate_trader_against<E: ExecutionDynamics>(
+    mut env: Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    opponent: &mut impl Opponent,
+) -> (f64, f64, f64, f64, f64, f64)
+{
+    opponent.reset();
+
+    let mut quotes = trader.sample_target(env.state());
+
+    let mut i = 0;
+    let mut drift_sum = 0.0;
+    let mut reward_sum = 0.0;
+    let mut spread_sum = quotes.1 * 2.0;
+
+    loop {
+        let drift = opponent.drift(&OpponentView::of(&env, env.state()));
+
+        env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
+
+        let t = env.step(tta(quotes));
+
+        drift_sum += drift;
+        reward_sum += t.reward;
+
+        if t.terminated() {
+            return (
+                env.wealth, drift_sum / i as f64, spread_sum / i as f64, reward_sum, env.inv_terminal,
+                env.dynamics.cap_rate(),
+            );
+        } else {
+            quotes = trader.sample_target(t.to.state());
+
+            i += 1;
+            spread_sum += quotes.1 * 2.0;
+        }
+    }
+}
+
+// Single adversary transition spanning a whole episode, from the initial
+// observation to the terminal one, with the negated episode reward.
+fn episode_transition<E: ExecutionDynamics>(
+    env: &Env<BrownianMotionWithDrift, E>,
+    from: Observation<Vector<f64>>,
+    drift: f64,
+    to: &Observation<Vector<f64>>,
+    window: &TrailingWindow,
+) -> Transition<Vector<f64>, f64>
+{
+    Transition {
+        from,
+        action: drift,
+        reward: -env.episode_reward,
+        to: to.clone().map(|s| window.pad(&s)),
+        discount: 1.0,
+        truncated: false,
+    }
+}
+
+pub fn train_value_functions_per_episode<E: ExecutionDynamics>(
+    mut env: Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    adversary: &mut Adversary,
+    window: &TrailingWindow,
+) -> Env<BrownianMotionWithDrift, E>
+{
+    let obs = env.emit().map(|s| window.pad(&s));
+    let drift = adversary.sample_behaviour(obs.state());
+    let mut quotes = trader.sample_behaviour(env.state());
+
+    env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
+
+    loop {
+        let t = env.step(tta(quotes)).replace_action(quotes);
+
+        trader.critic.handle_transition(&t);
+
+        if t.terminated() {
+            adversary.critic.handle_transition(&episode_transition(&env, obs, drift, &t.to, window));
+
+            break
+        } else {
+            quotes = trader.sample_behaviour(t.to.state());
+        }
+    }
+
+    env
+}
+
+pub fn train_agents_once_per_episode<E: ExecutionDynamics>(
+    mut env: Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    adversary: &mut Adversary,
+    window: &TrailingWindow,
+) -> Env<BrownianMotionWithDrift, E>
+{
+    let obs = env.emit().map(|s| window.pad(&s));
+    let drift = adversary.sample_behaviour(obs.state());
+    let mut quotes = trader.sample_behaviour(env.state());
+
+    env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
+
+    loop {
+        let t = env.step(tta(quotes)).replace_action(quotes);
+
+        trader.handle_transition(&t);
+
+        if t.terminated() {
+            adversary.handle_transition(&episode_transition(&env, obs, drift, &t.to, window));
+
+            break
+        } else {
+            quotes = trader.sample_behaviour(t.to.state());
+        }
+    }
+
+    trader.handle_terminal();
+    adversary.handle_terminal();
+
+    env
+}
+
+pub fn evaluate_agents_once_per_episode<E: ExecutionDynamics>(
+    mut env: Env<BrownianMotionWithDrift, E>,
+    trader: &mut Trader,
+    adversary: &mut Adversary,
+    window: &TrailingWindow,
+) -> (f64, f64, f64, f64, f64, f64)
+{
+    let drift = adversary.sample_target(&window.pad(env.state()));
+    let mut quotes = trader.sample_target(env.state());
+
+    let mut i = 0;
+    let mut reward_sum = 0.0;
+    let mut spread_sum = quotes.1 * 2.0;
+
+    env.dynamics.price_dynamics.drift = MAX_DRIFT * drift;
+
+    loop {
+        let t = env.step(tta(quotes));
+
+        reward_sum += t.reward;
+
+        if t.terminated() {
+            return (
+                env.wealth, drift, spread_sum / i as f64, reward_sum, env.inv_terminal,
+                env.dynamics.cap_rate(),
+            );
         } else {
-            drift = adversary.sample_target(t.to.state());
             quotes = trader.sample_target(t.to.state());
 
             i += 1;
@@ -147,6 +420,11 @@ pub fn evaluate_agents<E: ExecutionDynamics>(
     adversary: &mut Adversary,
     episode: usize,
     n_simulations: usize,
+    stopping: Option<StoppingRule>,
+    seeds: SeedStream,
+    probes: &Probes,
+    window: &mut TrailingWindow,
+    schedule: DriftSchedule,
 ) -> Record
 {
     let mut pnls = vec![];
@@ -154,15 +432,30 @@ pub fn evaluate_agents<E: ExecutionDynamics>(
     let mut rewards = vec![];
     let mut terminal_qs = vec![];
     let mut average_spreads = vec![];
+    let mut cap_rates = vec![];
+
+    for j in 0..n_simulations {
+        let seeds = seeds.substream(j as u64);
 
-    for _ in 0..n_simulations {
-        let (p, d, s, r, q) = evaluate_agents_once(env_builder(), trader, adversary);
+        trader.reseed(seeds.seed(1));
+        adversary.reseed(seeds.seed(2));
+
+        let env = env_builder().seeded(seeds.seed(0));
+        let (p, d, s, r, q, c) = match schedule {
+            DriftSchedule::PerStep => evaluate_agents_once(env, trader, adversary, window),
+            DriftSchedule::PerEpisode => evaluate_agents_once_per_episode(env, trader, adversary, window),
+        };
 
         pnls.push(p);
         drifts.push(d);
         rewards.push(r);
         terminal_qs.push(q);
         average_spreads.push(s);
+        cap_rates.push(c);
+
+        if stopping.map_or(false, |rule| rule.is_satisfied(&pnls)) {
+            break;
+        }
     }
 
     let pnl_est = Estimate::from_slice(&pnls);
@@ -170,17 +463,15 @@ pub fn evaluate_agents<E: ExecutionDynamics>(
     let rwd_est = Estimate::from_slice(&rewards);
     let inv_est = Estimate::from_slice(&terminal_qs);
     let spd_est = Estimate::from_slice(&average_spreads);
+    let cap_est = Estimate::from_slice(&cap_rates);
 
-    let rp_neutral = mean(tta(trader.policy.mpa(&Vector::from_vec(vec![0.0, 0.0]))));
-    let rp_bull = mean(tta(trader.policy.mpa(&Vector::from_vec(vec![0.0, 5.0]))));
-    let rp_bear = mean(tta(trader.policy.mpa(&Vector::from_vec(vec![0.0, -5.0]))));
+    let rp = probes.evaluate("rp", |s| mean(tta(trader.policy.mpa(s))));
 
-    let drift_neutral = adversary.policy.mpa(&Vector::from_vec(vec![0.0, 0.0]));
-    let drift_bull = adversary.policy.mpa(&Vector::from_vec(vec![0.0, 5.0]));
-    let drift_bear = adversary.policy.mpa(&Vector::from_vec(vec![0.0, -5.0]));
+    let drift = probes.evaluate("drift", |s| adversary.policy.mpa(&window.pad(s)));
 
     Record {
         episode,
+        n_simulations: pnls.len(),
 
         wealth_mean: pnl_est.0,
         wealth_stddev: pnl_est.1,
@@ -194,15 +485,14 @@ pub fn evaluate_agents<E: ExecutionDynamics>(
         spread_mean: spd_est.0,
         spread_stddev: spd_est.1,
 
-        rp_neutral,
-        rp_bull,
-        rp_bear,
+        cap_rate_mean: cap_est.0,
+        cap_rate_stddev: cap_est.1,
+
+        rp,
 
         drift_mean: dft_est.0,
         drift_stddev: dft_est.1,
 
-        drift_neutral,
-        drift_bull,
-        drift_bear,
+        drift,
     }
 }
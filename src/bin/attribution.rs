@@ -0,0 +1,152 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rsrl;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::{attribution::{aligned_fills, replay, Attribution}, load_trader_for, tta},
+    env::{scenarios::ScenarioBundle, strategies::Baseline},
+    output::{Format, Output},
+    utils::Estimate,
+};
+use clap::{App, Arg};
+use rsrl::{
+    core::Algorithm,
+    domains::Domain,
+    logging,
+    policies::Policy,
+};
+
+/// Attribution of the learned and baseline strategies on one scenario, and of
+/// the difference between them.
+#[derive(Debug, Serialize)]
+struct Record {
+    scenario: usize,
+
+    learned_pnl: f64,
+    learned_spread_capture: f64,
+    learned_adverse_selection: f64,
+    learned_inventory_carry: f64,
+    learned_fills: usize,
+
+    baseline_pnl: f64,
+    baseline_spread_capture: f64,
+    baseline_adverse_selection: f64,
+    baseline_inventory_carry: f64,
+    baseline_fills: usize,
+
+    aligned_fills: usize,
+
+    pnl_diff: f64,
+    spread_capture_diff: f64,
+    adverse_selection_diff: f64,
+    inventory_carry_diff: f64,
+}
+
+impl Record {
+    fn new(scenario: usize, l: Attribution, b: Attribution, aligned_fills: usize) -> Record {
+        Record {
+            scenario,
+
+            pnl_diff: l.pnl - b.pnl,
+            spread_capture_diff: l.spread_capture - b.spread_capture,
+            adverse_selection_diff: l.adverse_selection - b.adverse_selection,
+            inventory_carry_diff: l.inventory_carry - b.inventory_carry,
+
+            learned_pnl: l.pnl,
+            learned_spread_capture: l.spread_capture,
+            learned_adverse_selection: l.adverse_selection,
+            learned_inventory_carry: l.inventory_carry,
+            learned_fills: l.n_fills,
+
+            baseline_pnl: b.pnl,
+            baseline_spread_capture: b.spread_capture,
+            baseline_adverse_selection: b.adverse_selection,
+            baseline_inventory_carry: b.inventory_carry,
+            baseline_fills: b.n_fills,
+
+            aligned_fills,
+        }
+    }
+}
+
+fn main() {
+    let matches = App::new("Execution-cost attribution")
+        .arg(Arg::with_name("trader_path")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("scenarios")
+                .index(2)
+                .help("Scenario bundle on which both strategies are replayed")
+                .required(true))
+        .arg(Arg::with_name("output")
+                .index(3)
+                .help("Path of the report, without extension")
+                .required(true))
+        .arg(Arg::with_name("baseline")
+                .long("baseline")
+                .takes_value(true)
+                .possible_values(&["linear", "linear_penalty", "exponential"])
+                .default_value("exponential"))
+        .arg(Arg::with_name("horizon")
+                .long("horizon")
+                .help("Number of steps after each fill over which adverse selection is measured")
+                .takes_value(true)
+                .default_value("10"))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .get_matches();
+
+    let bundle = ScenarioBundle::load(matches.value_of("scenarios").unwrap())
+        .expect("Failed to load scenario bundle.");
+    let output = matches.value_of("output").unwrap();
+    let baseline: Baseline = matches.value_of("baseline").unwrap().parse().unwrap();
+    let horizon: usize = matches.value_of("horizon").unwrap().parse().unwrap();
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+
+    let state_space = bundle.scenario(0, |env| env).0.state_space();
+    let mut trader = load_trader_for(matches.value_of("trader_path").unwrap().to_string(), &state_space)
+        .expect("Failed to load trader.");
+
+    let logger = logging::root(logging::stdout());
+    let mut file_logger = Output::to_path(output, format).unwrap();
+
+    let mut diffs = [vec![], vec![], vec![], vec![]];
+
+    for i in 0..bundle.len() {
+        let (env, agent_seed) = bundle.scenario(i, |env| env);
+
+        trader.reseed(agent_seed);
+
+        let (learned, learned_fills) = replay(env, |env| tta(trader.policy.mpa(env.state())), horizon);
+
+        let (env, _) = bundle.scenario(i, |env| env);
+        let (baseline, baseline_fills) = replay(env, |env| {
+            baseline.compute(env.dynamics.time, env.dynamics.price, env.inv)
+        }, horizon);
+
+        let r = Record::new(i, learned, baseline, aligned_fills(&learned_fills, &baseline_fills));
+
+        diffs[0].push(r.pnl_diff);
+        diffs[1].push(r.spread_capture_diff);
+        diffs[2].push(r.adverse_selection_diff);
+        diffs[3].push(r.inventory_carry_diff);
+
+        file_logger.serialize(r).ok();
+    }
+
+    file_logger.flush().ok();
+
+    info!(logger, "learned minus baseline"; "scenarios" => bundle.len(),
+        "pnl" => Estimate::from_slice(&diffs[0]),
+        "spread_capture" => Estimate::from_slice(&diffs[1]),
+        "adverse_selection" => Estimate::from_slice(&diffs[2]),
+        "inventory_carry" => Estimate::from_slice(&diffs[3]),
+    );
+}
@@ -0,0 +1,92 @@
+extern crate algo_hft;
+extern crate clap;
+
+use algo_hft::{
+    output::{Format, Output, read_column},
+    stats::{Comparison, verdict_table},
+};
+use clap::{App, Arg};
+
+/// Mean of `metric` over the last `last` evaluations in `{dir}/results.csv`.
+fn final_value(dir: &str, metric: &str, last: usize) -> f64 {
+    let path = format!("{}/results.csv", dir);
+    let values: Vec<f64> = read_column(&path, metric)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e))
+        .iter()
+        .map(|v| v.parse().unwrap_or_else(|_| panic!("Non-numeric `{}` in {}.", metric, path)))
+        .collect();
+
+    if values.is_empty() {
+        panic!("No results in {}.", path);
+    }
+
+    let tail = &values[values.len().saturating_sub(last)..];
+
+    tail.iter().sum::<f64>() / tail.len() as f64
+}
+
+fn main() {
+    let matches = App::new("Multi-seed run comparison")
+        .arg(Arg::with_name("baseline")
+                .long("baseline")
+                .help("Save directories of conventionally trained runs, one per seed")
+                .takes_value(true)
+                .multiple(true)
+                .required(true))
+        .arg(Arg::with_name("adversarial")
+                .long("adversarial")
+                .help("Save directories of adversarially trained runs, one per seed")
+                .takes_value(true)
+                .multiple(true)
+                .required(true))
+        .arg(Arg::with_name("metrics")
+                .long("metrics")
+                .help("Comma-separated results.csv columns to compare")
+                .takes_value(true)
+                .default_value("wealth_mean,wealth_stddev,reward_mean,inv_stddev,spread_mean"))
+        .arg(Arg::with_name("last")
+                .long("last")
+                .help("Number of final evaluations averaged per run")
+                .takes_value(true)
+                .default_value("1"))
+        .arg(Arg::with_name("alpha")
+                .long("alpha")
+                .help("Significance level at which both tests must reject for a verdict")
+                .takes_value(true)
+                .default_value("0.05"))
+        .arg(Arg::with_name("output")
+                .long("output")
+                .help("Path for the comparisons as CSV, without extension")
+                .takes_value(true))
+        .get_matches();
+
+    let baseline: Vec<&str> = matches.values_of("baseline").unwrap().collect();
+    let adversarial: Vec<&str> = matches.values_of("adversarial").unwrap().collect();
+
+    if baseline.len() < 2 || adversarial.len() < 2 {
+        panic!("Each group needs results from at least two runs.");
+    }
+
+    let last: usize = matches.value_of("last").unwrap().parse().unwrap();
+    let alpha: f64 = matches.value_of("alpha").unwrap().parse().unwrap();
+
+    let comparisons: Vec<Comparison> = matches.value_of("metrics").unwrap().split(',').map(|metric| {
+        let metric = metric.trim();
+        let a: Vec<f64> = baseline.iter().map(|dir| final_value(dir, metric, last)).collect();
+        let b: Vec<f64> = adversarial.iter().map(|dir| final_value(dir, metric, last)).collect();
+
+        Comparison::new(metric, &a, &b, alpha)
+    }).collect();
+
+    if let Some(path) = matches.value_of("output") {
+        let mut file_logger = Output::to_path(path, Format::Csv).unwrap();
+
+        for c in comparisons.iter() {
+            file_logger.serialize(c).ok();
+        }
+
+        file_logger.flush().ok();
+    }
+
+    print!("{}", verdict_table(&comparisons, "Conventional", "Adversarial"));
+}
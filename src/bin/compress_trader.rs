@@ -0,0 +1,46 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rsrl;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::{compression::compress_trader, load_trader_for, save_trader},
+    env::Env,
+};
+use clap::{App, Arg};
+use rsrl::{domains::Domain, logging};
+
+fn main() {
+    let matches = App::new("Trader compression")
+        .arg(Arg::with_name("trader")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("output")
+                .index(2)
+                .help("Path of the compressed trader")
+                .required(true))
+        .arg(Arg::with_name("tolerance")
+                .long("tolerance")
+                .help("Largest change in the output of any head over the state space")
+                .takes_value(true)
+                .default_value("0.01"))
+        .get_matches();
+
+    let tolerance: f64 = matches.value_of("tolerance").unwrap().parse().unwrap();
+
+    let logger = logging::root(logging::stdout());
+    let trader = load_trader_for(matches.value_of("trader").unwrap().to_string(), &Env::default().state_space())
+        .expect("Failed to load trader.");
+
+    let (compressed, report) = compress_trader(&trader, tolerance).expect("Failed to compress trader.");
+
+    info!(logger, "compressed trader";
+        "weights_pruned" => format!("{} / {}", report.n_pruned, report.n_weights),
+        "terms_removed" => format!("{} / {}", report.n_terms_removed, report.n_terms),
+        "error_bound" => report.error_bounds.max(),
+    );
+
+    save_trader(&compressed, matches.value_of("output").unwrap().to_string())
+        .expect("Failed to save trader.");
+}
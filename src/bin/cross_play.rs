@@ -0,0 +1,123 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rsrl;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::{
+        checkpoints_in,
+        training::population::{cross_play, PayoffMatrix},
+    },
+    env::{Env, observation::TrailingWindow},
+    output::{Format, Output},
+};
+use clap::{App, Arg};
+use rsrl::{core::SeedStream, logging};
+
+fn main() {
+    let matches = App::new("Population cross-play")
+        .arg(Arg::with_name("trader_dir")
+                .index(1)
+                .help("Directory of trader checkpoints, e.g. the save_dir of a zero-sum run")
+                .required(true))
+        .arg(Arg::with_name("adversary_dir")
+                .index(2)
+                .help("Directory of adversary checkpoints")
+                .required(true))
+        .arg(Arg::with_name("n_simulations")
+                .index(3)
+                .required(true))
+        .arg(Arg::with_name("output")
+                .index(4)
+                .help("Output path stem; writes {output}_matrix and {output}_meta")
+                .required(true))
+        .arg(Arg::with_name("adversary_window")
+                .long("adversary_window")
+                .help("Number of past steps summarised in the adversaries' observation")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("adversary_max_move")
+                .long("adversary_max_move")
+                .help("Bound on the net price move observed by the adversaries")
+                .takes_value(true)
+                .default_value("2.0"))
+        .arg(Arg::with_name("n_iterations")
+                .long("n_iterations")
+                .help("Rounds of multiplicative weights used to solve the meta-game")
+                .takes_value(true)
+                .default_value("10000"))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .get_matches();
+
+    let n_simulations: usize = matches.value_of("n_simulations").unwrap().parse().unwrap();
+    let n_iterations: usize = matches.value_of("n_iterations").unwrap().parse().unwrap();
+    let seeds = SeedStream::new(matches.value_of("seed").unwrap().parse().unwrap());
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+    let output = matches.value_of("output").unwrap();
+
+    let window = TrailingWindow::new(
+        matches.value_of("adversary_window").unwrap().parse().unwrap(),
+        matches.value_of("adversary_max_move").unwrap().parse().unwrap(),
+    );
+
+    let traders = checkpoints_in(matches.value_of("trader_dir").unwrap(), "trader")
+        .expect("Failed to list traders.");
+    let adversaries = checkpoints_in(matches.value_of("adversary_dir").unwrap(), "adversary")
+        .expect("Failed to list adversaries.");
+
+    let logger = logging::root(logging::stdout());
+
+    if traders.is_empty() || adversaries.is_empty() {
+        error!(logger, "no checkpoints found";
+            "n_traders" => traders.len(),
+            "n_adversaries" => adversaries.len(),
+        );
+
+        return;
+    }
+
+    info!(logger, "playing populations"; "n_traders" => traders.len(), "n_adversaries" => adversaries.len());
+
+    let records = cross_play(Env::default_with_drift, &traders, &adversaries, n_simulations, seeds, &window)
+        .expect("Failed to load agents.");
+
+    let mut matrix_logger = Output::to_path(&format!("{}_matrix", output), format).unwrap();
+
+    for r in records.iter() {
+        matrix_logger.serialize(r).ok();
+    }
+
+    matrix_logger.flush().ok();
+
+    let matrix = PayoffMatrix::from_records(
+        traders.into_iter().map(|(label, _)| label).collect(),
+        adversaries.into_iter().map(|(label, _)| label).collect(),
+        &records,
+    );
+    let meta = matrix.nash(n_iterations);
+
+    let mut meta_logger = Output::to_path(&format!("{}_meta", output), format).unwrap();
+
+    for r in meta.records(&matrix) {
+        info!(logger, "meta-strategy"; "role" => r.role, "agent" => &r.agent,
+            "nash_weight" => r.nash_weight,
+            "nash_payoff" => r.nash_payoff,
+            "uniform_payoff" => r.uniform_payoff,
+        );
+
+        meta_logger.serialize(&r).ok();
+    }
+
+    meta_logger.flush().ok();
+
+    info!(logger, "nash equilibrium"; "value" => meta.value, "epsilon" => meta.epsilon);
+}
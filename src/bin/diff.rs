@@ -0,0 +1,50 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rsrl;
+
+use algo_hft::{
+    agents::{diff::{diff_traders, state_grid}, load_trader_for},
+    env::Env,
+    output::{Format, Output},
+};
+use clap::{App, Arg};
+use rsrl::domains::Domain;
+
+fn main() {
+    let matches = App::new("Trader snapshot diff")
+        .arg(Arg::with_name("trader_a")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("trader_b")
+                .index(2)
+                .required(true))
+        .arg(Arg::with_name("grid_density")
+                .long("grid_density")
+                .takes_value(true)
+                .default_value("21"))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .get_matches();
+
+    let grid_density: usize = matches.value_of("grid_density").unwrap().parse().unwrap();
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+
+    let state_space = Env::default().state_space();
+
+    let mut trader_a = load_trader_for(matches.value_of("trader_a").unwrap().to_string(), &state_space)
+        .expect("Failed to load trader.");
+    let mut trader_b = load_trader_for(matches.value_of("trader_b").unwrap().to_string(), &state_space)
+        .expect("Failed to load trader.");
+
+    let grid = state_grid(state_space, grid_density);
+    let record = diff_traders(&mut trader_a, &mut trader_b, &grid)
+        .expect("Failed to compare traders.");
+
+    let mut csv_logger = Output::to_stdout(format).unwrap();
+
+    csv_logger.serialize(record).ok();
+    csv_logger.flush().ok();
+}
@@ -0,0 +1,99 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rsrl;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::{
+        load_adversary_for, load_trader_for,
+        training::timeline::{cross_correlations, record_timelines},
+    },
+    env::{Env, observation::TrailingWindow},
+    output::{Format, Output},
+};
+use clap::{App, Arg};
+use rsrl::{core::SeedStream, domains::Domain, logging};
+
+fn main() {
+    let matches = App::new("Adversary drift and trader inventory cross-correlation")
+        .arg(Arg::with_name("trader_path")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("adversary_path")
+                .index(2)
+                .required(true))
+        .arg(Arg::with_name("n_episodes")
+                .index(3)
+                .required(true))
+        .arg(Arg::with_name("output")
+                .index(4)
+                .help("Output path, without extension")
+                .required(true))
+        .arg(Arg::with_name("max_lag")
+                .long("max_lag")
+                .help("Largest lag, in steps, in either direction")
+                .takes_value(true)
+                .default_value("20"))
+        .arg(Arg::with_name("adversary_window")
+                .long("adversary_window")
+                .help("Number of past steps summarised in the adversary's observation")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("adversary_max_move")
+                .long("adversary_max_move")
+                .help("Bound on the net price move observed by the adversary")
+                .takes_value(true)
+                .default_value("2.0"))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .get_matches();
+
+    let n_episodes: usize = matches.value_of("n_episodes").unwrap().parse().unwrap();
+    let max_lag: usize = matches.value_of("max_lag").unwrap().parse().unwrap();
+    let seeds = SeedStream::new(matches.value_of("seed").unwrap().parse().unwrap());
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+
+    let mut window = TrailingWindow::new(
+        matches.value_of("adversary_window").unwrap().parse().unwrap(),
+        matches.value_of("adversary_max_move").unwrap().parse().unwrap(),
+    );
+
+    let env = Env::default_with_drift();
+    let mut trader = load_trader_for(matches.value_of("trader_path").unwrap().to_string(), &env.state_space())
+        .expect("Failed to load trader.");
+    let mut adversary = load_adversary_for(
+        matches.value_of("adversary_path").unwrap().to_string(), &window.state_space(&env),
+    ).expect("Failed to load adversary.");
+
+    let logger = logging::root(logging::stdout());
+    let timelines = record_timelines(
+        Env::default_with_drift, &mut trader, &mut adversary, n_episodes, seeds, &mut window,
+    );
+    let records = cross_correlations(&timelines, max_lag);
+
+    let mut file_logger = Output::to_path(matches.value_of("output").unwrap(), format).unwrap();
+
+    for r in records.iter() {
+        file_logger.serialize(r).ok();
+    }
+
+    file_logger.flush().ok();
+
+    if let Some(r) = records.iter().filter(|r| r.lag <= 0).min_by(|a, b| {
+        a.drift_inv.partial_cmp(&b.drift_inv).unwrap_or(::std::cmp::Ordering::Equal)
+    }) {
+        info!(logger, "strongest inventory-targeting correlation";
+            "lag" => r.lag,
+            "drift_inv" => r.drift_inv,
+            "episodes" => timelines.len(),
+        );
+    }
+}
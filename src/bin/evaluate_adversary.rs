@@ -5,7 +5,7 @@ extern crate rand;
 extern crate rsrl;
 
 use algo_hft::{
-    agents::{load_trader, Trader, load_adversary, Adversary, tta},
+    agents::{load_trader_for, Trader, load_adversary_for, Adversary, tta},
     env::Env,
     utils::Estimate,
 };
@@ -27,10 +27,10 @@ fn simulate_once(trader: &mut Trader, adversary: &mut Adversary) -> Record<f64>
     let mut domain = Env::default_with_drift();
 
     loop {
-        let d = adversary.policy.mpa(domain.emit().state());
-        let a = trader.policy.mpa(domain.emit().state());
+        let d = adversary.policy.mpa(domain.state());
+        let a = trader.policy.mpa(domain.state());
 
-        domain.dynamics.price_dynamics.drift = MAX_DRIFT * (2.0 * d - 1.0);
+        domain.dynamics.price_dynamics.drift = MAX_DRIFT * d;
         let t = domain.step(tta(a));
 
         if t.terminated() {
@@ -57,8 +57,12 @@ fn main() {
 
     let n_simulations: usize = matches.value_of("n_simulations").unwrap().parse().unwrap();
 
-    let mut trader = load_trader(matches.value_of("trader_path").unwrap().to_string());
-    let mut adversary = load_adversary(matches.value_of("adversary_path").unwrap().to_string());
+    let state_space = Env::default_with_drift().state_space();
+
+    let mut trader = load_trader_for(matches.value_of("trader_path").unwrap().to_string(), &state_space)
+        .expect("Failed to load trader.");
+    let mut adversary = load_adversary_for(matches.value_of("adversary_path").unwrap().to_string(), &state_space)
+        .expect("Failed to load adversary.");
 
     let mut wealth_values: Vec<f64> = Vec::with_capacity(n_simulations);
     let mut inv_values: Vec<f64> = Vec::with_capacity(n_simulations);
@@ -1,7 +1,6 @@
 extern crate algo_hft;
 extern crate lfa;
 extern crate bincode;
-extern crate csv;
 extern crate clap;
 extern crate rand;
 extern crate rsrl;
@@ -11,6 +10,7 @@ extern crate serde_derive;
 use algo_hft::{
     agents::{Trader, tta},
     env::Env,
+    output::{Format, Output},
     utils::Estimate,
 };
 use bincode::deserialize_from;
@@ -21,7 +21,7 @@ use rsrl::{
 };
 use std::{
     fs::File,
-    io::{BufReader, stdout},
+    io::BufReader,
 };
 
 #[derive(Debug, Serialize)]
@@ -38,7 +38,7 @@ fn simulate_once(trader: &mut Trader) -> Record<f64> {
     let mut spread_sum = 0.0;
 
     loop {
-        let a = trader.policy.mpa(domain.emit().state());
+        let a = trader.policy.mpa(domain.state());
         let t = domain.step(tta(a));
 
         i += 1;
@@ -62,10 +62,16 @@ fn main() {
         .arg(Arg::with_name("bin_path")
                 .index(2)
                 .required(true))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
         .get_matches();
 
     let n_simulations: usize = matches.value_of("n_simulations").unwrap().parse().unwrap();
     let bin_path = matches.value_of("bin_path").unwrap();
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
 
     let reader = BufReader::new(File::open(bin_path).unwrap());
     let mut trader: Trader = deserialize_from(reader).unwrap();
@@ -74,7 +80,7 @@ fn main() {
     // let mut inv_values: Vec<f64> = Vec::with_capacity(n_simulations);
     // let mut spread_values: Vec<f64> = Vec::with_capacity(n_simulations);
 
-    let mut csv_logger = csv::Writer::from_writer(stdout());
+    let mut csv_logger = Output::to_stdout(format).unwrap();
 
     (0..n_simulations).into_iter().map(|_| simulate_once(&mut trader)).for_each(|r| {
         csv_logger.serialize(r).ok();
@@ -0,0 +1,121 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rsrl;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::{
+        load_adversaries_in, load_trader_for,
+        scripted::{DriftRule, ScriptedAdversary},
+        training::ensemble::{evaluate_ensemble, evaluate_opponent, worst_case},
+    },
+    env::{Env, observation::TrailingWindow},
+    output::{Format, Output},
+};
+use clap::{App, Arg};
+use rsrl::{core::SeedStream, domains::Domain, logging};
+
+fn main() {
+    let matches = App::new("Adversary ensemble evaluation")
+        .arg(Arg::with_name("trader_path")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("adversary_dir")
+                .index(2)
+                .help("Directory of adversary checkpoints, e.g. the save_dir of a zero-sum run")
+                .required(true))
+        .arg(Arg::with_name("n_simulations")
+                .index(3)
+                .required(true))
+        .arg(Arg::with_name("output")
+                .index(4)
+                .help("Output path, without extension")
+                .required(true))
+        .arg(Arg::with_name("adversary_window")
+                .long("adversary_window")
+                .help("Number of past steps summarised in the adversaries' observation")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("adversary_max_move")
+                .long("adversary_max_move")
+                .help("Bound on the net price move observed by the adversaries")
+                .takes_value(true)
+                .default_value("2.0"))
+        .arg(Arg::with_name("scripted")
+                .long("scripted")
+                .help("Scripted adversary to add to the ensemble, e.g. sinusoidal:1.0,0.5, momentum:0.2,0.1 or inventory:0.2")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .get_matches();
+
+    let n_simulations: usize = matches.value_of("n_simulations").unwrap().parse().unwrap();
+    let seeds = SeedStream::new(matches.value_of("seed").unwrap().parse().unwrap());
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+
+    let mut window = TrailingWindow::new(
+        matches.value_of("adversary_window").unwrap().parse().unwrap(),
+        matches.value_of("adversary_max_move").unwrap().parse().unwrap(),
+    );
+
+    let env = Env::default_with_drift();
+    let mut trader = load_trader_for(matches.value_of("trader_path").unwrap().to_string(), &env.state_space())
+        .expect("Failed to load trader.");
+    let mut adversaries = load_adversaries_in(matches.value_of("adversary_dir").unwrap(), &window.state_space(&env))
+        .expect("Failed to load adversaries.");
+
+    let scripted: Vec<(&str, DriftRule)> = matches.values_of("scripted").map_or(vec![], |rs| {
+        rs.map(|r| (r, r.parse().unwrap())).collect()
+    });
+
+    let logger = logging::root(logging::stdout());
+
+    if adversaries.is_empty() && scripted.is_empty() {
+        error!(logger, "no adversary checkpoints found"; "dir" => matches.value_of("adversary_dir").unwrap());
+
+        return;
+    }
+
+    let mut records = evaluate_ensemble(
+        Env::default_with_drift, &mut trader, &mut adversaries, n_simulations, seeds, &mut window,
+    );
+
+    for (label, rule) in scripted {
+        records.push(evaluate_opponent(
+            Env::default_with_drift, &mut trader, label, &mut ScriptedAdversary::new(rule), n_simulations, seeds,
+        ));
+    }
+
+    let mut file_logger = Output::to_path(matches.value_of("output").unwrap(), format).unwrap();
+
+    for r in records.iter() {
+        info!(logger, "evaluated adversary"; "adversary" => &r.adversary,
+            "wealth" => format!("{} +/- {}", r.wealth_mean, r.wealth_stddev),
+            "reward" => format!("{} +/- {}", r.reward_mean, r.reward_stddev),
+            "drift" => format!("{} +/- {}", r.drift_mean, r.drift_stddev),
+        );
+
+        file_logger.serialize(r).ok();
+    }
+
+    file_logger.flush().ok();
+
+    let r = worst_case(&records).unwrap();
+    let mean_reward = records.iter().map(|r| r.reward_mean).sum::<f64>() / records.len() as f64;
+
+    info!(logger, "worst case"; "adversary" => &r.adversary,
+        "wealth" => format!("{} +/- {}", r.wealth_mean, r.wealth_stddev),
+        "reward" => r.reward_mean,
+        "ensemble_mean_reward" => mean_reward,
+    );
+}
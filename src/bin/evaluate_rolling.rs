@@ -0,0 +1,83 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rsrl;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::{load_trader_for, training::rolling::{evaluate_rolling, Session}},
+    env::{Env, liquidation::Liquidation},
+    output::{Format, Output},
+    utils::Estimate,
+};
+use clap::{App, Arg};
+use rsrl::{core::SeedStream, domains::Domain, logging};
+
+fn main() {
+    let matches = App::new("Rolling-horizon evaluation")
+        .arg(Arg::with_name("trader_path")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("n_sessions")
+                .index(2)
+                .required(true))
+        .arg(Arg::with_name("n_horizons")
+                .index(3)
+                .help("Number of consecutive horizons per session")
+                .required(true))
+        .arg(Arg::with_name("output")
+                .index(4)
+                .help("Output path, without extension")
+                .required(true))
+        .arg(Arg::with_name("liquidation")
+                .long("liquidation")
+                .help("Model of the liquidation at the end of each session: mid, impact:eta, spread:half_spread or gradual:n_slices,eta")
+                .takes_value(true)
+                .default_value("mid"))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .get_matches();
+
+    let n_sessions: usize = matches.value_of("n_sessions").unwrap().parse().unwrap();
+    let n_horizons: usize = matches.value_of("n_horizons").unwrap().parse().unwrap();
+    let liquidation: Liquidation = matches.value_of("liquidation").unwrap().parse().unwrap();
+    let seeds = SeedStream::new(matches.value_of("seed").unwrap().parse().unwrap());
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+
+    let mut trader = load_trader_for(
+        matches.value_of("trader_path").unwrap().to_string(), &Env::default_with_drift().state_space()
+    ).expect("Failed to load trader.");
+
+    let logger = logging::root(logging::stdout());
+    let sessions = evaluate_rolling(
+        || Env::default_with_drift().with_liquidation(liquidation), &mut trader, n_sessions, n_horizons, seeds,
+    );
+
+    let mut file_logger = Output::to_path(matches.value_of("output").unwrap(), format).unwrap();
+
+    for s in sessions.iter() {
+        file_logger.serialize(s).ok();
+    }
+
+    file_logger.flush().ok();
+
+    let column = |f: fn(&Session) -> f64| -> Estimate {
+        Estimate::from_slice(&sessions.iter().map(f).collect::<Vec<f64>>())
+    };
+
+    info!(logger, "rolling evaluation";
+        "n_sessions" => n_sessions,
+        "n_horizons" => n_horizons,
+        "wealth" => column(|s| s.wealth),
+        "horizon_pnl" => column(|s| s.horizon_pnl_mean),
+        "carried_inv" => column(|s| s.carried_inv_mean),
+        "inv_abs_max" => column(|s| s.inv_abs_max),
+    );
+}
@@ -0,0 +1,67 @@
+extern crate algo_hft;
+extern crate clap;
+
+use algo_hft::{
+    env::dynamics::execution::{fill_rates, IntensityMapping, PoissonRate},
+    output::{Format, Output},
+};
+use clap::{App, Arg};
+
+fn main() {
+    let matches = App::new("Execution probability export")
+        .arg(Arg::with_name("dt")
+                .long("dt")
+                .takes_value(true)
+                .default_value("0.005"))
+        .arg(Arg::with_name("scale")
+                .long("scale")
+                .takes_value(true)
+                .default_value("140.0"))
+        .arg(Arg::with_name("decay")
+                .long("decay")
+                .takes_value(true)
+                .default_value("1.5"))
+        .arg(Arg::with_name("mapping")
+                .long("mapping")
+                .takes_value(true)
+                .possible_values(&["clamped", "exact"])
+                .default_value("clamped"))
+        .arg(Arg::with_name("max_offset")
+                .long("max_offset")
+                .takes_value(true)
+                .default_value("5.0"))
+        .arg(Arg::with_name("n_offsets")
+                .long("n_offsets")
+                .takes_value(true)
+                .default_value("101"))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .get_matches();
+
+    let dt: f64 = matches.value_of("dt").unwrap().parse().unwrap();
+    let scale: f64 = matches.value_of("scale").unwrap().parse().unwrap();
+    let decay: f64 = matches.value_of("decay").unwrap().parse().unwrap();
+    let mapping: IntensityMapping = matches.value_of("mapping").unwrap().parse().unwrap();
+    let max_offset: f64 = matches.value_of("max_offset").unwrap().parse().unwrap();
+    let n_offsets: usize = matches.value_of("n_offsets").unwrap().parse().unwrap();
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+
+    let dynamics = PoissonRate::new(dt, scale, decay).with_mapping(mapping);
+    let offsets: Vec<f64> = (0..n_offsets)
+        .map(|i| max_offset * i as f64 / (n_offsets.max(2) - 1) as f64)
+        .collect();
+
+    // Episodes terminate once the clock reaches one:
+    let n_steps = (1.0 / dt).ceil() as usize;
+
+    let mut csv_logger = Output::to_stdout(format).unwrap();
+
+    for r in fill_rates(&dynamics, &offsets, n_steps) {
+        csv_logger.serialize(r).ok();
+    }
+
+    csv_logger.flush().ok();
+}
@@ -0,0 +1,63 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rand;
+extern crate rsrl;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::env::{
+    dynamics::execution::{PerSide, PoissonRate},
+    scenarios::{DynamicsSpec, ScenarioBundle},
+};
+use clap::{App, Arg};
+use rsrl::{core::SeedStream, logging};
+
+fn main() {
+    let matches = App::new("Scenario bundle generator")
+        .arg(Arg::with_name("n_scenarios")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("output")
+                .index(2)
+                .help("Path of the bundle, e.g. scenarios.json")
+                .required(true))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("drift")
+                .long("drift")
+                .help("Drift of the midprice")
+                .takes_value(true)
+                .default_value("0.0"))
+        .arg(Arg::with_name("ask_rate")
+                .long("ask_rate")
+                .help("Scale and decay of the ask side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("bid_rate")
+                .long("bid_rate")
+                .help("Scale and decay of the bid side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .get_matches();
+
+    let n_scenarios: usize = matches.value_of("n_scenarios").unwrap().parse().unwrap();
+    let output = matches.value_of("output").unwrap();
+    let seed: u64 = matches.value_of("seed").map_or_else(rand::random, |s| s.parse().unwrap());
+    let drift: f64 = matches.value_of("drift").unwrap().parse().unwrap();
+    let execution = PerSide::new(
+        matches.value_of("ask_rate").unwrap().parse::<PoissonRate>().unwrap(),
+        matches.value_of("bid_rate").unwrap().parse::<PoissonRate>().unwrap(),
+    );
+
+    let logger = logging::root(logging::stdout());
+
+    info!(logger, "master seed"; "seed" => seed);
+
+    ScenarioBundle::generate(DynamicsSpec::new(drift, execution), n_scenarios, SeedStream::new(seed))
+        .and_then(|bundle| bundle.save(output))
+        .expect("Failed to generate scenario bundle.");
+
+    info!(logger, "saved {} scenarios", n_scenarios; "output" => output);
+}
@@ -0,0 +1,29 @@
+extern crate algo_hft;
+extern crate clap;
+
+use algo_hft::output::merge_csv;
+use clap::{App, Arg};
+
+fn main() {
+    let matches = App::new("Results merger")
+        .arg(Arg::with_name("output")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("inputs")
+                .index(2)
+                .help("CSV results, in order of precedence for duplicated keys")
+                .multiple(true)
+                .required(true))
+        .arg(Arg::with_name("key")
+                .long("key")
+                .help("Column identifying duplicated records")
+                .takes_value(true)
+                .default_value("episode"))
+        .get_matches();
+
+    let inputs: Vec<&str> = matches.values_of("inputs").unwrap().collect();
+    let n_records = merge_csv(&inputs, matches.value_of("output").unwrap(), matches.value_of("key").unwrap())
+        .expect("Failed to merge results.");
+
+    println!("Merged {} records from {} files.", n_records, inputs.len());
+}
@@ -0,0 +1,87 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rsrl;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::{load_adversary_for, load_trader_for, training::robustness::RobustnessSuite},
+    env::Env,
+    output::{Format, Output},
+};
+use clap::{App, Arg};
+use rsrl::{core::SeedStream, domains::Domain, logging};
+use std::fs;
+
+fn main() {
+    let matches = App::new("Trader robustness report")
+        .arg(Arg::with_name("trader_path")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("n_simulations")
+                .index(2)
+                .help("Number of episodes per condition")
+                .required(true))
+        .arg(Arg::with_name("adversary")
+                .long("adversary")
+                .help("Path of a trained adversary to evaluate against")
+                .takes_value(true))
+        .arg(Arg::with_name("drifts")
+                .long("drifts")
+                .help("Comma-separated fixed drifts to sweep")
+                .takes_value(true)
+                .default_value("-5,-2.5,2.5,5"))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("save_dir")
+                .long("save_dir")
+                .takes_value(true)
+                .default_value("/tmp/"))
+        .get_matches();
+
+    let n_simulations: usize = matches.value_of("n_simulations").unwrap().parse().unwrap();
+    let seeds = SeedStream::new(matches.value_of("seed").unwrap().parse().unwrap());
+    let save_dir = matches.value_of("save_dir").unwrap();
+
+    let state_space = Env::default_with_drift().state_space();
+
+    let mut trader = load_trader_for(matches.value_of("trader_path").unwrap().to_string(), &state_space)
+        .expect("Failed to load trader.");
+    let mut adversary = matches.value_of("adversary").map(|path| {
+        load_adversary_for(path.to_string(), &state_space).expect("Failed to load adversary.")
+    });
+
+    let mut suite = RobustnessSuite::new(n_simulations);
+
+    suite.drifts = matches.value_of("drifts").unwrap()
+        .split(',')
+        .map(|d| d.trim().parse().expect("Drifts must be numbers."))
+        .collect();
+
+    let logger = logging::root(logging::stdout());
+    let report = suite.run(Env::default_with_drift, &mut trader, adversary.as_mut(), seeds);
+
+    let mut file_logger = Output::to_path(format!("{}/robustness", save_dir), Format::Csv).unwrap();
+
+    for row in report.rows.iter() {
+        info!(logger, "evaluated condition";
+            "stressor" => row.stressor,
+            "condition" => &row.condition,
+            "wealth" => row.wealth_mean,
+            "inv" => row.inv_mean,
+        );
+
+        file_logger.serialize(row).ok();
+    }
+
+    file_logger.flush().ok();
+
+    fs::write(format!("{}/robustness.md", save_dir), report.markdown()).expect("Failed to write summary.");
+
+    if let Some(worst) = report.worst_case() {
+        info!(logger, "worst case"; "stressor" => worst.stressor, "condition" => &worst.condition,
+            "wealth" => worst.wealth_mean);
+    }
+}
@@ -0,0 +1,241 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rand;
+extern crate rsrl;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::{load_trader_for, tta, Trader},
+    env::{
+        Env,
+        dynamics::{
+            ASDynamics,
+            composite::CompositeSpec,
+            execution::{ExecutionDynamics, PerSide, PoissonRate},
+            price::PriceDynamics,
+        },
+        scenarios::ScenarioBundle,
+        strategies::Baseline,
+    },
+    output::{Format, Output},
+};
+use clap::{App, Arg};
+use rsrl::{
+    core::{Algorithm, SeedStream},
+    domains::Domain,
+    logging,
+    policies::Policy,
+};
+
+/// One step of one episode.
+#[derive(Debug, Serialize)]
+struct Record {
+    episode: usize,
+    t: f64,
+
+    midprice: f64,
+
+    ask_quote: f64,
+    bid_quote: f64,
+
+    ask_filled: bool,
+    bid_filled: bool,
+
+    inventory: f64,
+    wealth: f64,
+    reward: f64,
+}
+
+enum Strategy {
+    Trader(Trader),
+    Baseline(Baseline),
+}
+
+impl Strategy {
+    fn reseed(&mut self, seed: u64) {
+        if let Strategy::Trader(trader) = self {
+            trader.reseed(seed);
+        }
+    }
+
+    /// `[ask, bid]` offsets from the midprice.
+    fn quotes<P: PriceDynamics, E: ExecutionDynamics>(&mut self, env: &Env<P, E>) -> [f64; 2] {
+        match self {
+            Strategy::Trader(trader) => tta(trader.policy.mpa(env.state())),
+            Strategy::Baseline(b) => b.compute(env.dynamics.time, env.dynamics.price, env.inv),
+        }
+    }
+}
+
+fn simulate_once<P, E>(env: &mut Env<P, E>, strategy: &mut Strategy, episode: usize, file_logger: &mut Output)
+where
+    P: PriceDynamics,
+    E: ExecutionDynamics,
+{
+    loop {
+        let time = env.dynamics.time;
+        let midprice = env.dynamics.price;
+        let a = strategy.quotes(env);
+        let t = env.step(a);
+
+        file_logger.serialize(Record {
+            episode,
+            t: time,
+
+            midprice,
+
+            ask_quote: midprice + a[0],
+            bid_quote: midprice - a[1],
+
+            ask_filled: env.ask_executed,
+            bid_filled: env.bid_executed,
+
+            inventory: env.inv,
+            wealth: env.wealth,
+            reward: t.reward,
+        }).ok();
+
+        if t.terminated() {
+            break;
+        }
+    }
+}
+
+fn main() {
+    let matches = App::new("Batch market making simulator")
+        .arg(Arg::with_name("n_episodes")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("output")
+                .index(2)
+                .help("Path of the dataset, without extension")
+                .required(true))
+        .arg(Arg::with_name("strategy")
+                .long("strategy")
+                .takes_value(true)
+                .possible_values(&["trader", "linear", "linear_penalty", "exponential"])
+                .default_value("exponential"))
+        .arg(Arg::with_name("trader_path")
+                .long("trader_path")
+                .help("Path of a saved trader, required by --strategy trader")
+                .takes_value(true)
+                .required_if("strategy", "trader"))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("parquet"))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("drift")
+                .long("drift")
+                .help("Drift of the midprice")
+                .takes_value(true)
+                .default_value("0.0"))
+        .arg(Arg::with_name("ask_rate")
+                .long("ask_rate")
+                .help("Scale and decay of the ask side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("bid_rate")
+                .long("bid_rate")
+                .help("Scale and decay of the bid side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("scenarios")
+                .long("scenarios")
+                .help("Scenario bundle to cycle through; its dynamics replace --drift, --ask_rate and --bid_rate")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("dynamics")
+                .long("dynamics")
+                .help("TOML specification of composite dynamics to replace --drift, --ask_rate and --bid_rate")
+                .takes_value(true)
+                .conflicts_with("scenarios")
+                .required(false))
+        .arg(Arg::with_name("log-level")
+                .long("log-level")
+                .help("Minimum level of log records, optionally per module, e.g. info,rsrl=debug")
+                .takes_value(true)
+                .default_value("info"))
+        .arg(Arg::with_name("log-json")
+                .long("log-json")
+                .help("File to which log records are also written as JSON lines")
+                .takes_value(true)
+                .required(false))
+        .get_matches();
+
+    let n_episodes: usize = matches.value_of("n_episodes").unwrap().parse().unwrap();
+    let output = matches.value_of("output").unwrap();
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+    let seed: u64 = matches.value_of("seed").map_or_else(rand::random, |s| s.parse().unwrap());
+    let drift: f64 = matches.value_of("drift").unwrap().parse().unwrap();
+    let execution = PerSide::new(
+        matches.value_of("ask_rate").unwrap().parse::<PoissonRate>().unwrap(),
+        matches.value_of("bid_rate").unwrap().parse::<PoissonRate>().unwrap(),
+    );
+
+    let scenarios: Option<ScenarioBundle> = matches.value_of("scenarios").map(|path| {
+        ScenarioBundle::load(path).expect("Failed to load scenario bundle.")
+    });
+    let composite: Option<CompositeSpec> = matches.value_of("dynamics").map(|path| {
+        CompositeSpec::load(path).expect("Failed to load the dynamics specification.")
+    });
+
+    let logger = logging::configured(
+        matches.value_of("log-level").unwrap().parse().unwrap(),
+        matches.value_of("log-json"),
+    ).expect("Failed to open log file.");
+    let seeds = SeedStream::new(seed);
+
+    info!(logger, "master seed"; "seed" => seed);
+
+    let env_builder = || {
+        Env::new(ASDynamics::default_with_drift(drift).with_execution_dynamics(execution.clone()))
+    };
+
+    let mut strategy = match matches.value_of("strategy").unwrap() {
+        "trader" => Strategy::Trader(load_trader_for(
+            matches.value_of("trader_path").unwrap().to_string(),
+            &env_builder().state_space(),
+        ).expect("Failed to load the trader.")),
+        baseline => Strategy::Baseline(baseline.parse().unwrap()),
+    };
+
+    let mut file_logger = Output::to_path(output, format).unwrap();
+
+    for i in 0..n_episodes {
+        let seeds = seeds.substream(i as u64);
+
+        match (scenarios.as_ref(), composite.as_ref()) {
+            (Some(bundle), _) => {
+                let (mut env, agent_seed) = bundle.scenario(i, |env| env);
+
+                strategy.reseed(agent_seed);
+                simulate_once(&mut env, &mut strategy, i, &mut file_logger);
+            },
+            (None, Some(spec)) => {
+                let dynamics = spec.build().expect("Composite dynamics are checked on loading.");
+                let mut env = Env::new(dynamics).seeded(seeds.seed(0));
+
+                strategy.reseed(seeds.seed(1));
+                simulate_once(&mut env, &mut strategy, i, &mut file_logger);
+            },
+            (None, None) => {
+                let mut env = env_builder().seeded(seeds.seed(0));
+
+                strategy.reseed(seeds.seed(1));
+                simulate_once(&mut env, &mut strategy, i, &mut file_logger);
+            },
+        }
+    }
+
+    file_logger.flush().ok();
+
+    info!(logger, "simulated {} episodes", n_episodes; "output" => output);
+}
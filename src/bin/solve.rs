@@ -0,0 +1,180 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rsrl;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::{load_trader_for, tta, Trader},
+    env::{
+        Env,
+        dynamics::{ASDynamics, execution::{PerSide, PoissonRate}},
+        liquidation::Liquidation,
+        penalty::InventoryPenalty,
+        solver::{Solution, ValueIteration, DRIFT_BOUND},
+    },
+    output::{Format, Output},
+};
+use slog::Logger;
+use clap::{App, Arg};
+use rsrl::{
+    core::ValuePredictor,
+    domains::Domain,
+    geometry::Vector,
+    logging,
+    policies::Policy,
+};
+
+/// Optimal value and quotes at a point of the grid, alongside the trader's
+/// critic and greedy quotes when one is given.
+#[derive(Debug, Serialize)]
+struct Record {
+    time: f64,
+    inv: f64,
+
+    value: f64,
+    ask: f64,
+    bid: f64,
+    drift: f64,
+
+    critic: Option<f64>,
+    policy_ask: Option<f64>,
+    policy_bid: Option<f64>,
+}
+
+// Write the grids of `solution` to `path`, comparing them with `trader` if
+// given, and log a summary.
+fn write_solution(logger: &Logger, label: &str, solution: &Solution, trader: &mut Option<Trader>, path: String, format: Format) {
+    let mut file_logger = Output::to_path(path, format).expect("Failed to open output.");
+    let mut critic_error = 0.0;
+    let mut n_records = 0;
+
+    for r in solution.records() {
+        let comparison = trader.as_mut().map(|trader| {
+            let s = Vector::from_vec(vec![r.time, r.inv]);
+
+            (trader.critic.predict_v(&s), tta(trader.policy.mpa(&s)))
+        });
+
+        if let Some((v, _)) = comparison {
+            critic_error += (v - r.value).abs();
+            n_records += 1;
+        }
+
+        file_logger.serialize(Record {
+            time: r.time,
+            inv: r.inv,
+
+            value: r.value,
+            ask: r.ask,
+            bid: r.bid,
+            drift: r.drift,
+
+            critic: comparison.map(|c| c.0),
+            policy_ask: comparison.map(|c| c.1[0]),
+            policy_bid: comparison.map(|c| c.1[1]),
+        }).ok();
+    }
+
+    file_logger.flush().ok();
+
+    let quotes = solution.quotes(0.0, 0.0);
+
+    info!(logger, "{} solution", label;
+        "value" => solution.value(0.0, 0.0),
+        "ask" => quotes[0],
+        "bid" => quotes[1],
+    );
+
+    if n_records > 0 {
+        info!(logger, "{} critic", label; "mean_abs_error" => critic_error / n_records as f64);
+    }
+}
+
+fn main() {
+    let drift_bound = DRIFT_BOUND.to_string();
+    let matches = App::new("Value iteration")
+        .arg(Arg::with_name("output")
+                .index(1)
+                .help("Stem of the nominal and robust grids, written to {output}_nominal and {output}_robust")
+                .required(true))
+        .arg(Arg::with_name("drift")
+                .long("drift")
+                .help("Drift of the midprice in the nominal problem")
+                .takes_value(true)
+                .default_value("0.0"))
+        .arg(Arg::with_name("drift_bound")
+                .long("drift_bound")
+                .help("Largest drift the adversary may choose in the robust problem")
+                .takes_value(true)
+                .default_value(&drift_bound))
+        .arg(Arg::with_name("ask_rate")
+                .long("ask_rate")
+                .help("Scale and decay of the ask side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("bid_rate")
+                .long("bid_rate")
+                .help("Scale and decay of the bid side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("inventory_penalty")
+                .long("inventory_penalty")
+                .help("Weights of the running and terminal quadratic inventory penalties, e.g. 0.0,0.5")
+                .takes_value(true)
+                .default_value("0.0,0.5"))
+        .arg(Arg::with_name("liquidation")
+                .long("liquidation")
+                .help("Model of the liquidation at the horizon: mid, impact:eta, spread:half_spread or gradual:n_slices,eta")
+                .takes_value(true)
+                .default_value("mid"))
+        .arg(Arg::with_name("max_offset")
+                .long("max_offset")
+                .help("Largest offset from the midprice searched on each side")
+                .takes_value(true)
+                .default_value("4.0"))
+        .arg(Arg::with_name("n_offsets")
+                .long("n_offsets")
+                .help("Number of offsets searched on each side")
+                .takes_value(true)
+                .default_value("201"))
+        .arg(Arg::with_name("trader_path")
+                .long("trader_path")
+                .help("Trader whose critic and greedy quotes are compared with the solution")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .get_matches();
+
+    let output = matches.value_of("output").unwrap();
+    let drift: f64 = matches.value_of("drift").unwrap().parse().unwrap();
+    let drift_bound: f64 = matches.value_of("drift_bound").unwrap().parse().unwrap();
+    let execution = PerSide::new(
+        matches.value_of("ask_rate").unwrap().parse::<PoissonRate>().unwrap(),
+        matches.value_of("bid_rate").unwrap().parse::<PoissonRate>().unwrap(),
+    );
+    let penalty: InventoryPenalty = matches.value_of("inventory_penalty").unwrap().parse().unwrap();
+    let liquidation: Liquidation = matches.value_of("liquidation").unwrap().parse().unwrap();
+    let max_offset: f64 = matches.value_of("max_offset").unwrap().parse().unwrap();
+    let n_offsets: usize = matches.value_of("n_offsets").unwrap().parse().unwrap();
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+
+    let logger = logging::root(logging::stdout());
+
+    let dynamics = ASDynamics::default_with_drift(drift).with_execution_dynamics(execution);
+    let solver = ValueIteration::for_dynamics(&dynamics, max_offset, n_offsets).with_penalty(penalty)
+        .with_liquidation(liquidation);
+
+    let mut trader = matches.value_of("trader_path").map(|path| {
+        load_trader_for(path.to_string(), &Env::default_with_drift().state_space()).expect("Failed to load trader.")
+    });
+
+    write_solution(&logger, "nominal", &solver.solve(), &mut trader, format!("{}_nominal", output), format);
+    write_solution(&logger, "robust", &solver.solve_robust(drift_bound), &mut trader, format!("{}_robust", output), format);
+}
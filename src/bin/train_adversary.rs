@@ -4,39 +4,96 @@ extern crate rand;
 extern crate rsrl;
 #[macro_use]
 extern crate slog;
-extern crate csv;
 
 use algo_hft::{
-    agents::{build_adversary, save_adversary, load_trader, training::adversary::*},
-    env::Env,
+    agents::{AdversarySpec, DriftParameterisation, load_adversary, save_adversary, load_trader_for, training::{probes::Probes, regimes::EpisodeDynamics, stopping::StoppingRule, adversary::*}},
+    env::{
+        Env,
+        dynamics::{ASDynamics, execution::{PerSide, PoissonRate}},
+        observation::TrailingWindow,
+    },
+    metrics::Metrics,
+    output::{read_column, Format, Output},
 };
 use clap::{App, Arg};
 use rsrl::{
+    control::actor_critic::{RewardScale, TrustRegion},
+    core::SeedStream,
     domains::Domain,
     logging,
+    policies::SkippedUpdates,
 };
+use slog::Logger;
 use std::f64;
 
-fn run_experiment(save_dir: &str, eval_interval: usize, trader_path: &str) {
-    let logger = logging::root(logging::stdout());
-    let mut file_logger = csv::Writer::from_path(format!("{}/results.csv", save_dir)).unwrap();
+fn run_experiment(logger: Logger, save_dir: &str, eval_interval: usize, trader_path: &str, metrics_port: Option<u16>, format: Format, seed: u64, probes: Probes, mut window: TrailingWindow, execution: PerSide<PoissonRate, PoissonRate>, max_kl: Option<f64>, normalise_rewards: bool, drift: DriftParameterisation, stopping: Option<StoppingRule>, log_dynamics: bool, resume: bool) {
+    let metrics = Metrics::new(100);
+    let seeds = SeedStream::new(seed);
 
-    let mut min_pnl = f64::INFINITY;
-    let mut max_reward = f64::NEG_INFINITY;
+    info!(logger, "master seed"; "seed" => seed);
 
-    let env_builder = || Env::default_with_drift();
+    if let Some(port) = metrics_port {
+        metrics.serve(port).expect("Failed to start metrics server.");
+    }
+
+    // When resuming, evaluations already recorded are kept and training
+    // continues from the checkpoint of the last one:
+    let recorded = |column| if resume {
+        read_column(format!("{}/results.csv", save_dir), column).unwrap_or_default()
+    } else {
+        vec![]
+    };
+    let start = recorded("episode").last().map_or(0, |e| e.parse::<usize>().unwrap() / eval_interval);
+
+    let mut file_logger = if resume {
+        Output::append_to_path(format!("{}/results", save_dir), format, "episode")
+    } else {
+        Output::to_path(format!("{}/results", save_dir), format)
+    }.expect("Failed to open results.");
+
+    let mut dynamics_logger = if !log_dynamics {
+        None
+    } else if resume {
+        Some(Output::append_to_path(format!("{}/dynamics", save_dir), format, "episode"))
+    } else {
+        Some(Output::to_path(format!("{}/dynamics", save_dir), format))
+    }.map(|o| o.expect("Failed to open dynamics log."));
+    let values = |column| -> Vec<f64> { recorded(column).iter().filter_map(|x| x.parse().ok()).collect() };
+
+    let mut min_pnl = values("wealth_mean").into_iter().fold(f64::INFINITY, f64::min);
+    let mut max_reward = values("reward_mean").into_iter().fold(f64::NEG_INFINITY, f64::max);
+
+    let env_builder = || {
+        Env::new(ASDynamics::default_with_drift(0.0).with_execution_dynamics(execution.clone()))
+    };
 
     // Build adversary:
-    let mut trader = load_trader(trader_path.to_owned());
-    let mut adversary = build_adversary(env_builder().state_space(), 0.1, 0.0001);
+    let mut trader = load_trader_for(trader_path.to_owned(), &env_builder().state_space())
+        .expect("Failed to load trader.");
+    let mut adversary = if start > 0 {
+        info!(logger, "resuming from checkpoint"; "episode" => start * eval_interval);
 
-    // Pre-train value function:
-    for _ in 0..1000 {
-        train_value_function(env_builder(), &mut trader, &mut adversary);
-    }
+        load_adversary(format!("{}/adversary.bin", save_dir)).expect("Failed to load adversary checkpoint.")
+    } else {
+        let mut adversary = AdversarySpec::new(0.1, 0.0001)
+            .with_drift(drift)
+            .build(window.state_space(&env_builder()))
+            .expect("Failed to build adversary.");
+        adversary.trust_region = max_kl.map(TrustRegion::new);
+        if normalise_rewards {
+            adversary.reward_scale = Some(RewardScale::new());
+        }
+
+        // Pre-train value function:
+        for _ in 0..1000 {
+            train_value_function(env_builder(), &mut trader, &mut adversary, &mut window);
+        }
+
+        adversary
+    };
 
     // Run experiment:
-    for i in 0..(1200*eval_interval) {
+    for i in start..(1200*eval_interval) {
         // Perform evaluation:
         if i % eval_interval == 0 {
             let r = evaluate_adversary(
@@ -45,6 +102,10 @@ fn run_experiment(save_dir: &str, eval_interval: usize, trader_path: &str) {
                 &mut adversary,
                 i * eval_interval,
                 1000,
+                stopping,
+                seeds.substream(i as u64),
+                &probes,
+                &mut window,
             );
 
             // Serialise the adversary if it performed better:
@@ -52,29 +113,52 @@ fn run_experiment(save_dir: &str, eval_interval: usize, trader_path: &str) {
                 min_pnl = r.wealth_mean;
                 max_reward = r.reward_mean;
 
-                save_adversary(&adversary, format!("{}/adversary_best.bin", save_dir));
+                save_adversary(&adversary, format!("{}/adversary_best.bin", save_dir)).expect("Failed to save adversary.");
             }
 
             // Serialise latest adversary too:
-            save_adversary(&adversary, format!("{}/adversary.bin", save_dir));
+            save_adversary(&adversary, format!("{}/adversary.bin", save_dir)).expect("Failed to save adversary.");
 
             // Log plotting data:
             info!(logger, "evaluation {}", i / eval_interval;
+                "simulations" => r.n_simulations,
                 "wealth" => format!("{} +/- {}", r.wealth_mean, r.wealth_stddev),
                 "reward" => format!("{} +/- {}", r.reward_mean, r.reward_stddev),
                 "inv" => format!("{} +/- {}", r.inv_mean, r.inv_stddev),
+                "cap_rate" => format!("{} +/- {}", r.cap_rate_mean, r.cap_rate_stddev),
                 "drift" => format!("{} +/- {}", r.drift_mean, r.drift_stddev),
-                "drift_neutral" => r.drift_neutral,
-                "drift_bull" => r.drift_bull,
-                "drift_bear" => r.drift_bear,
+                "drift_probes" => r.drift.to_string(),
+                "lr_ratio" => adversary.timescale_ratio(),
             );
 
             file_logger.serialize(r).ok();
             file_logger.flush().ok();
+
+            if let Some(ref mut sink) = dynamics_logger {
+                sink.flush().ok();
+            }
         }
 
         // Train adversary for one episode:
-        train_adversary_once(env_builder(), &mut trader, &mut adversary);
+        let env = train_adversary_once(env_builder(), &mut trader, &mut adversary, &mut window);
+
+        let n_triggered = adversary.trust_region.as_mut().map_or(0, |tr| tr.take_triggered());
+        if n_triggered > 0 {
+            warn!(logger, "trust region triggered"; "agent" => "adversary", "episode" => i, "updates" => n_triggered);
+        }
+
+        let n_skipped = adversary.policy.take_skipped();
+        if n_skipped > 0 {
+            warn!(logger, "non-finite updates skipped"; "agent" => "adversary", "episode" => i, "updates" => n_skipped);
+        }
+
+        if let Some(ref mut sink) = dynamics_logger {
+            sink.serialize(EpisodeDynamics::of(i, &env)).ok();
+        }
+
+        metrics.record_episode((env.dynamics.time / env.dynamics.dt).round() as u64, -env.episode_reward);
+        metrics.set_learning_rate("adversary_critic", adversary.critic.alpha.value());
+        metrics.set_learning_rate("adversary_policy", adversary.alpha.value());
     }
 }
 
@@ -89,11 +173,112 @@ fn main() {
         .arg(Arg::with_name("trader_path")
                 .index(3)
                 .required(true))
+        .arg(Arg::with_name("metrics_port")
+                .long("metrics_port")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("probe")
+                .long("probe")
+                .help("Labelled evaluation state, e.g. long=0.0,5.0")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1))
+        .arg(Arg::with_name("adversary_window")
+                .long("adversary_window")
+                .help("Number of past steps summarised in the adversary's observation")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("adversary_max_move")
+                .long("adversary_max_move")
+                .help("Bound on the net price move observed by the adversary")
+                .takes_value(true)
+                .default_value("2.0"))
+        .arg(Arg::with_name("ask_rate")
+                .long("ask_rate")
+                .help("Scale and decay of the ask side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("bid_rate")
+                .long("bid_rate")
+                .help("Scale and decay of the bid side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("max_kl")
+                .long("max_kl")
+                .help("Bound on the KL divergence induced by each policy update")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("normalise_rewards")
+                .long("normalise_rewards")
+                .help("Normalise rewards by a running estimate of the return's scale"))
+        .arg(Arg::with_name("drift_policy")
+                .long("drift_policy")
+                .help("Distribution of the adversary's policy over the drift")
+                .takes_value(true)
+                .possible_values(&["beta", "tanh_gaussian"])
+                .default_value("beta"))
+        .arg(Arg::with_name("eval_width")
+                .long("eval_width")
+                .help("Stop each evaluation early once the 95% interval on mean wealth is this narrow, e.g. 0.5 or 0.5,2.58 for another z")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("log_dynamics")
+                .long("log_dynamics")
+                .help("Write the realised drift, volatility and fill rate of every training episode to save_dir/dynamics"))
+        .arg(Arg::with_name("resume")
+                .long("resume")
+                .help("Continue from the latest checkpoint in save_dir, appending to its results (CSV only)"))
+        .arg(Arg::with_name("log-level")
+                .long("log-level")
+                .help("Minimum level of log records, optionally per module, e.g. info,rsrl=debug")
+                .takes_value(true)
+                .default_value("info"))
+        .arg(Arg::with_name("log-json")
+                .long("log-json")
+                .help("File to which log records are also written as JSON lines")
+                .takes_value(true)
+                .required(false))
         .get_matches();
 
     let save_dir = matches.value_of("save_dir").unwrap();
     let eval_interval: usize = matches.value_of("eval_interval").unwrap().parse().unwrap();
     let trader_path = matches.value_of("trader_path").unwrap();
+    let metrics_port: Option<u16> = matches.value_of("metrics_port").map(|s| s.parse().unwrap());
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+    let seed: u64 = matches.value_of("seed").map_or_else(rand::random, |s| s.parse().unwrap());
+    let probes = matches.values_of("probe").map_or_else(Probes::default, |ps| {
+        Probes(ps.map(|p| p.parse().unwrap()).collect())
+    });
+    let window = TrailingWindow::new(
+        matches.value_of("adversary_window").unwrap().parse().unwrap(),
+        matches.value_of("adversary_max_move").unwrap().parse().unwrap(),
+    );
+    let execution = PerSide::new(
+        matches.value_of("ask_rate").unwrap().parse::<PoissonRate>().unwrap(),
+        matches.value_of("bid_rate").unwrap().parse::<PoissonRate>().unwrap(),
+    );
+
+    let max_kl: Option<f64> = matches.value_of("max_kl").map(|s| s.parse().unwrap());
+    let normalise_rewards = matches.is_present("normalise_rewards");
+    let drift: DriftParameterisation = matches.value_of("drift_policy").unwrap().parse().unwrap();
+
+    let stopping: Option<StoppingRule> = matches.value_of("eval_width").map(|s| s.parse().unwrap());
+    let log_dynamics = matches.is_present("log_dynamics");
+    let resume = matches.is_present("resume");
+
+    let logger = logging::configured(
+        matches.value_of("log-level").unwrap().parse().unwrap(),
+        matches.value_of("log-json"),
+    ).expect("Failed to open log file.");
 
-    run_experiment(save_dir, eval_interval, trader_path);
+    run_experiment(logger, save_dir, eval_interval, trader_path, metrics_port, format, seed, probes, window, execution, max_kl, normalise_rewards, drift, stopping, log_dynamics, resume);
 }
@@ -0,0 +1,158 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rand;
+extern crate rsrl;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::{
+        build_trader, save_trader,
+        training::{cem::CrossEntropy, probes::Probes, trader::evaluate_trader},
+    },
+    env::{Env, liquidation::Liquidation, penalty::InventoryPenalty},
+    output::{Format, Output},
+};
+use clap::{App, Arg};
+use rand::{rngs::SmallRng, SeedableRng};
+use rsrl::{core::SeedStream, domains::Domain, geometry::Space, logging};
+use std::f64;
+
+fn main() {
+    let matches = App::new("Cross-entropy method trader")
+        .arg(Arg::with_name("save_dir")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("n_generations")
+                .index(2)
+                .required(true))
+        .arg(Arg::with_name("population")
+                .long("population")
+                .help("Number of candidate weights sampled per generation")
+                .takes_value(true)
+                .default_value("50"))
+        .arg(Arg::with_name("elite_fraction")
+                .long("elite_fraction")
+                .help("Fraction of the population to which the search distribution is refitted")
+                .takes_value(true)
+                .default_value("0.2"))
+        .arg(Arg::with_name("stddev")
+                .long("stddev")
+                .help("Initial standard deviation of every weight")
+                .takes_value(true)
+                .default_value("0.1"))
+        .arg(Arg::with_name("min_stddev")
+                .long("min_stddev")
+                .help("Floor on the standard deviation of every weight")
+                .takes_value(true)
+                .default_value("0.001"))
+        .arg(Arg::with_name("smoothing")
+                .long("smoothing")
+                .help("Weight in (0, 1] given to the elite statistics in each update")
+                .takes_value(true)
+                .default_value("1.0"))
+        .arg(Arg::with_name("n_episodes")
+                .long("n_episodes")
+                .help("Episodes over which each candidate is scored")
+                .takes_value(true)
+                .default_value("20"))
+        .arg(Arg::with_name("eval_interval")
+                .long("eval_interval")
+                .help("Generations between full evaluations of the mean weights")
+                .takes_value(true)
+                .default_value("10"))
+        .arg(Arg::with_name("inventory_penalty")
+                .long("inventory_penalty")
+                .help("Weights of the running and terminal quadratic inventory penalties, e.g. 0.0,0.5")
+                .takes_value(true)
+                .default_value("0.0,0.5"))
+        .arg(Arg::with_name("liquidation")
+                .long("liquidation")
+                .help("Model of the liquidation at the horizon: mid, impact:eta, spread:half_spread or gradual:n_slices,eta")
+                .takes_value(true)
+                .default_value("mid"))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .get_matches();
+
+    let save_dir = matches.value_of("save_dir").unwrap();
+    let n_generations: usize = matches.value_of("n_generations").unwrap().parse().unwrap();
+    let population: usize = matches.value_of("population").unwrap().parse().unwrap();
+    let elite_fraction: f64 = matches.value_of("elite_fraction").unwrap().parse().unwrap();
+    let stddev: f64 = matches.value_of("stddev").unwrap().parse().unwrap();
+    let min_stddev: f64 = matches.value_of("min_stddev").unwrap().parse().unwrap();
+    let smoothing: f64 = matches.value_of("smoothing").unwrap().parse().unwrap();
+    let n_episodes: usize = matches.value_of("n_episodes").unwrap().parse().unwrap();
+    let eval_interval: usize = matches.value_of("eval_interval").unwrap().parse().unwrap();
+    let penalty: InventoryPenalty = matches.value_of("inventory_penalty").unwrap().parse().unwrap();
+    let liquidation: Liquidation = matches.value_of("liquidation").unwrap().parse().unwrap();
+    let seed: u64 = matches.value_of("seed").map_or_else(rand::random, |s| s.parse().unwrap());
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+
+    let logger = logging::root(logging::stdout());
+    let seeds = SeedStream::new(seed);
+
+    info!(logger, "master seed"; "seed" => seed);
+
+    let (search_seeds, eval_seeds) = (seeds.substream(0), seeds.substream(1));
+    let mut rng = SmallRng::seed_from_u64(seeds.seed(2));
+
+    let env_builder = || Env::default_with_drift().with_inventory_penalty(penalty).with_liquidation(liquidation);
+    let probes = Probes::default().padded(env_builder().state_space().dim());
+
+    let mut trader = build_trader(env_builder().state_space(), 0.01, 0.000001).expect("Failed to build trader.");
+    let mut cem = CrossEntropy::new(&trader, population, elite_fraction, stddev)
+        .with_min_stddev(min_stddev)
+        .with_smoothing(smoothing);
+
+    let mut cem_logger = Output::to_path(format!("{}/cem", save_dir), format).expect("Failed to open CEM log.");
+    let mut file_logger = Output::to_path(format!("{}/results", save_dir), format).expect("Failed to open results.");
+
+    let mut max_reward = f64::NEG_INFINITY;
+
+    for g in 0..n_generations {
+        let r = cem.step(env_builder, &mut trader, n_episodes, search_seeds.substream(g as u64), &mut rng)
+            .expect("Failed to evaluate candidates.");
+
+        info!(logger, "generation {}", r.generation;
+            "reward" => format!("{} +/- {}", r.reward_mean, r.reward_stddev),
+            "elite" => r.elite_mean,
+            "best" => r.reward_best,
+            "search_stddev" => r.search_stddev,
+        );
+
+        cem_logger.serialize(r).ok();
+        cem_logger.flush().ok();
+
+        if (g + 1) % eval_interval == 0 || g + 1 == n_generations {
+            let r = evaluate_trader(
+                env_builder, &mut trader, g + 1, 1000, None, eval_seeds.substream(g as u64), &probes,
+            );
+
+            if r.reward_mean > max_reward {
+                max_reward = r.reward_mean;
+
+                save_trader(&trader, format!("{}/trader_best.bin", save_dir)).expect("Failed to save trader.");
+            }
+
+            save_trader(&trader, format!("{}/trader.bin", save_dir)).expect("Failed to save trader.");
+
+            info!(logger, "evaluation";
+                "generation" => g + 1,
+                "wealth" => format!("{} +/- {}", r.wealth_mean, r.wealth_stddev),
+                "reward" => format!("{} +/- {}", r.reward_mean, r.reward_stddev),
+                "spread" => format!("{} +/- {}", r.spread_mean, r.spread_stddev),
+            );
+
+            file_logger.serialize(r).ok();
+            file_logger.flush().ok();
+        }
+    }
+}
@@ -0,0 +1,308 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rand;
+extern crate rsrl;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::Basis,
+    env::{
+        Env,
+        discrete::{Discretised, QuoteGrid},
+        dynamics::{ASDynamics, execution::{PerSide, PoissonRate}, price::BrownianMotion},
+    },
+    output::{Format, Output},
+    utils::mean_var,
+};
+use clap::{App, Arg};
+use rsrl::{
+    control::{actor_critic::TDAC, td::{QLearning, SARSA, SARSALambda, PAL}, totd::TOSARSALambda},
+    core::{make_shared, Algorithm, Controller, OnlineLearner, Parameter, SeedStream, Trace, TraceType},
+    domains::Domain,
+    fa::{Composable, LFA},
+    geometry::{Space, Vector},
+    logging,
+    policies::{EpsilonGreedy, Greedy, PreferenceSoftmax, Random, TieBreak},
+    prediction::td::TD,
+};
+use slog::Logger;
+
+type DiscreteEnv = Discretised<BrownianMotion, PerSide<PoissonRate, PoissonRate>>;
+
+#[derive(Debug, Serialize)]
+struct Record {
+    episode: usize,
+
+    wealth_mean: f64,
+    wealth_stddev: f64,
+
+    reward_mean: f64,
+    reward_stddev: f64,
+
+    inv_mean: f64,
+    inv_stddev: f64,
+}
+
+fn train_once<A>(mut env: DiscreteEnv, agent: &mut A)
+where
+    A: OnlineLearner<Vector<f64>, usize> + Controller<Vector<f64>, usize>,
+{
+    let mut a = agent.sample_behaviour(env.emit().state());
+
+    loop {
+        let t = env.step(a);
+
+        agent.handle_transition(&t);
+
+        if t.terminated() {
+            break;
+        } else {
+            a = agent.sample_behaviour(t.to.state());
+        }
+    }
+
+    agent.handle_terminal();
+}
+
+fn evaluate<A>(env_builder: &impl Fn() -> DiscreteEnv, agent: &mut A, episode: usize, n_simulations: usize, seeds: SeedStream) -> Record
+where
+    A: Algorithm + Controller<Vector<f64>, usize>,
+{
+    let mut pnls = vec![];
+    let mut rewards = vec![];
+    let mut inventories = vec![];
+
+    for j in 0..n_simulations {
+        let seeds = seeds.substream(j as u64);
+
+        agent.reseed(seeds.seed(1));
+
+        let mut env = env_builder().seeded(seeds.seed(0));
+
+        let mut reward = 0.0;
+        let mut a = agent.sample_target(env.emit().state());
+
+        loop {
+            let t = env.step(a);
+
+            reward += t.reward;
+
+            if t.terminated() {
+                break;
+            } else {
+                a = agent.sample_target(t.to.state());
+            }
+        }
+
+        pnls.push(env.env.wealth);
+        rewards.push(reward);
+        inventories.push(env.env.inv_terminal);
+    }
+
+    let [wealth_mean, wealth_var] = mean_var(&pnls);
+    let [reward_mean, reward_var] = mean_var(&rewards);
+    let [inv_mean, inv_var] = mean_var(&inventories);
+
+    Record {
+        episode,
+
+        wealth_mean,
+        wealth_stddev: wealth_var.sqrt(),
+
+        reward_mean,
+        reward_stddev: reward_var.sqrt(),
+
+        inv_mean,
+        inv_stddev: inv_var.sqrt(),
+    }
+}
+
+fn run_experiment<A>(
+    agent: &mut A,
+    env_builder: impl Fn() -> DiscreteEnv,
+    eval_interval: usize,
+    n_evaluations: usize,
+    seeds: SeedStream,
+    logger: &Logger,
+    file_logger: &mut Output,
+)
+where
+    A: Algorithm + OnlineLearner<Vector<f64>, usize> + Controller<Vector<f64>, usize>,
+{
+    // Evaluations draw from the leading substreams, so training episodes take
+    // theirs from the far end of the stream:
+    let train_seeds = seeds.substream(u64::max_value());
+
+    for i in 0..(n_evaluations * eval_interval) {
+        if i % eval_interval == 0 {
+            let r = evaluate(&env_builder, agent, i, 1000, seeds.substream(i as u64));
+
+            info!(logger, "evaluation {}", i / eval_interval;
+                "wealth" => format!("{} +/- {}", r.wealth_mean, r.wealth_stddev),
+                "reward" => format!("{} +/- {}", r.reward_mean, r.reward_stddev),
+                "inv" => format!("{} +/- {}", r.inv_mean, r.inv_stddev),
+            );
+
+            file_logger.serialize(r).ok();
+            file_logger.flush().ok();
+        }
+
+        train_once(env_builder().seeded(train_seeds.seed(i as u64)), agent);
+    }
+}
+
+fn main() {
+    let matches = App::new("Discrete-action trader")
+        .arg(Arg::with_name("save_dir")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("eval_interval")
+                .index(2)
+                .required(true))
+        .arg(Arg::with_name("algorithm")
+                .long("algorithm")
+                .takes_value(true)
+                .possible_values(&["q_learning", "sarsa", "pal", "sarsa_lambda", "to_sarsa_lambda", "actor_critic"])
+                .default_value("q_learning"))
+        .arg(Arg::with_name("trace")
+                .long("trace")
+                .help("Eligibility trace of the lambda algorithms")
+                .takes_value(true)
+                .possible_values(&["accumulating", "replacing", "dutch"])
+                .default_value("dutch"))
+        .arg(Arg::with_name("lambda")
+                .long("lambda")
+                .takes_value(true)
+                .default_value("0.9"))
+        .arg(Arg::with_name("epsilon")
+                .long("epsilon")
+                .help("Initial value, floor and per-episode decay of the exploration rate, e.g. 0.3,0.01,0.999")
+                .takes_value(true)
+                .default_value("0.3,0.01,0.999"))
+        .arg(Arg::with_name("tie_break")
+                .long("tie_break")
+                .help("Choice between greedy actions of equal value")
+                .takes_value(true)
+                .possible_values(&["first", "random", "lowest_index"])
+                .default_value("random"))
+        .arg(Arg::with_name("grid")
+                .long("grid")
+                .help("Reservation offsets and half-spreads of the action grid, e.g. -2,2,9;0,2,5")
+                .takes_value(true)
+                .default_value("-2,2,9;0,2,5"))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("ask_rate")
+                .long("ask_rate")
+                .help("Scale and decay of the ask side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("bid_rate")
+                .long("bid_rate")
+                .help("Scale and decay of the bid side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("log-level")
+                .long("log-level")
+                .help("Minimum level of log records, optionally per module, e.g. info,rsrl=debug")
+                .takes_value(true)
+                .default_value("info"))
+        .arg(Arg::with_name("log-json")
+                .long("log-json")
+                .help("File to which log records are also written as JSON lines")
+                .takes_value(true)
+                .required(false))
+        .get_matches();
+
+    let save_dir = matches.value_of("save_dir").unwrap();
+    let eval_interval: usize = matches.value_of("eval_interval").unwrap().parse().unwrap();
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+    let seed: u64 = matches.value_of("seed").map_or_else(rand::random, |s| s.parse().unwrap());
+    let grid: QuoteGrid = matches.value_of("grid").unwrap().parse().unwrap();
+    let trace_type: TraceType = matches.value_of("trace").unwrap().parse().unwrap();
+    let lambda: f64 = matches.value_of("lambda").unwrap().parse().unwrap();
+    let epsilon = {
+        let params: Vec<f64> = matches.value_of("epsilon").unwrap()
+            .split(',').map(|x| x.trim().parse().unwrap()).collect();
+
+        match params.as_slice() {
+            &[init, floor, tau] => Parameter::exponential(init, floor, tau),
+            _ => panic!("Epsilon schedule must be of the form init,floor,tau."),
+        }
+    };
+    let tie_break: TieBreak = matches.value_of("tie_break").unwrap().parse().unwrap();
+    let execution = PerSide::new(
+        matches.value_of("ask_rate").unwrap().parse::<PoissonRate>().unwrap(),
+        matches.value_of("bid_rate").unwrap().parse::<PoissonRate>().unwrap(),
+    );
+
+    let logger = logging::configured(
+        matches.value_of("log-level").unwrap().parse().unwrap(),
+        matches.value_of("log-json"),
+    ).expect("Failed to open log file.");
+    let seeds = SeedStream::new(seed);
+
+    info!(logger, "master seed"; "seed" => seed);
+
+    let mut file_logger = Output::to_path(format!("{}/results", save_dir), format).unwrap();
+
+    let env_builder = || {
+        Discretised::new(
+            Env::new(ASDynamics::default().with_execution_dynamics(execution.clone())),
+            grid.clone(),
+        )
+    };
+
+    let n_actions = grid.len();
+    let basis = Basis::from_space(3, env_builder().state_space()).with_constant();
+    let trace = Trace::new(trace_type, lambda, basis.dim());
+    let q_func = make_shared(LFA::vector(basis.clone(), n_actions));
+    let policy = EpsilonGreedy::new(
+        Greedy::new(q_func.clone()).with_tie_break(tie_break),
+        Random::new(n_actions),
+        epsilon,
+    );
+
+    match matches.value_of("algorithm").unwrap() {
+        "q_learning" => run_experiment(
+            &mut QLearning::new(q_func, policy, 0.001, 1.0),
+            env_builder, eval_interval, 1200, seeds, &logger, &mut file_logger,
+        ),
+        "sarsa" => run_experiment(
+            &mut SARSA::new(q_func, policy, 0.001, 1.0),
+            env_builder, eval_interval, 1200, seeds, &logger, &mut file_logger,
+        ),
+        "pal" => run_experiment(
+            &mut PAL::new(q_func, policy, 0.001, 1.0),
+            env_builder, eval_interval, 1200, seeds, &logger, &mut file_logger,
+        ),
+        "sarsa_lambda" => run_experiment(
+            &mut SARSALambda::new(q_func, policy, trace, 0.001, 1.0),
+            env_builder, eval_interval, 1200, seeds, &logger, &mut file_logger,
+        ),
+        "to_sarsa_lambda" => run_experiment(
+            &mut TOSARSALambda::new(q_func, policy, trace, 0.001, 1.0),
+            env_builder, eval_interval, 1200, seeds, &logger, &mut file_logger,
+        ),
+        "actor_critic" => run_experiment(
+            &mut TDAC::new(
+                TD::new(LFA::scalar(basis.clone()), 0.001, 1.0),
+                PreferenceSoftmax::standard(LFA::vector(basis, n_actions)),
+                0.0001,
+                1.0,
+            ),
+            env_builder, eval_interval, 1200, seeds, &logger, &mut file_logger,
+        ),
+        _ => unreachable!(),
+    }
+}
@@ -1,77 +1,389 @@
 extern crate algo_hft;
 extern crate clap;
+extern crate rand;
 extern crate rsrl;
 #[macro_use]
 extern crate slog;
-extern crate csv;
 
 use algo_hft::{
-    agents::{build_trader, save_trader, training::trader::*},
-    env::Env,
+    agents::{
+        load_trader, save_trader, Exploration, TraderSpec,
+        scripted::{DriftRule, ScriptedAdversary},
+        training::{
+            anomaly::AnomalyDetector, monitor::{DegeneracyMonitor, MonitorEvent}, probes::Probes, recorder::EpisodeRecorder, regimes::EpisodeDynamics,
+            stopping::StoppingRule, trader::*, zero_sum::train_trader_against_with,
+        },
+    },
+    env::{
+        Env,
+        clock::Clock,
+        fees::FeeSchedule,
+        dynamics::{ASDynamics, execution::{AdverseSelection, PerSide, PoissonRate}},
+        liquidation::Liquidation,
+        observation::{DriftEstimator, View},
+        penalty::{InventoryPenalty, PenaltySchedule},
+        quotes::QuoteSpace,
+        scenarios::{ScenarioBundle, ScenarioEnv},
+        shield::Shield,
+        signal::Signal,
+        start::{Bounds, StartDistribution},
+    },
+    metrics::Metrics,
+    output::{read_column, Format, Output},
 };
 use clap::{App, Arg};
 use rsrl::{
-    domains::Domain,
+    control::actor_critic::TwoTimescale,
+    core::{Algorithm, Loss, Parameter, SeedStream},
+    domains::{Domain, Transition, Truncation},
+    fa::Parameterised,
+    geometry::{continuous::Interval, Space, Vector},
     logging,
+    policies::SkippedUpdates,
 };
-use std::f64;
+use slog::Logger;
+use std::{f64, fs, path::Path};
 
-fn run_experiment(save_dir: &str, eval_interval: usize, _risk_param: Option<f64>) {
-    let logger = logging::root(logging::stdout());
-    let mut file_logger = csv::Writer::from_path(format!("{}/results.csv", save_dir)).unwrap();
+fn run_experiment(
+    logger: Logger,
+    save_dir: &str,
+    eval_interval: usize,
+    _risk_param: Option<f64>,
+    metrics_port: Option<u16>,
+    format: Format,
+    seed: u64,
+    probes: Probes,
+    inventory_view: View,
+    price_view: View,
+    execution: PerSide<PoissonRate, PoissonRate>,
+    drift_estimator: Option<DriftEstimator>,
+    signal: Option<Signal>,
+    exploration: Exploration,
+    max_kl: Option<f64>,
+    normalise_rewards: bool,
+    learning_rates: TwoTimescale,
+    shield: Option<Shield>,
+    quote_space: Option<QuoteSpace>,
+    hazard_rate: f64,
+    clock: Clock,
+    precompute_draws: Option<usize>,
+    adverse_selection: Option<AdverseSelection>,
+    mut penalty: PenaltySchedule,
+    liquidation: Liquidation,
+    fees: FeeSchedule,
+    initial: StartDistribution,
+    truncation: Truncation,
+    critic_loss: Loss,
+    mut detector: Option<AnomalyDetector>,
+    snapshot_anomalies: bool,
+    mut monitor: Option<DegeneracyMonitor>,
+    revert_degenerate: bool,
+    mut recorder: Option<EpisodeRecorder>,
+    scenarios: Option<ScenarioBundle>,
+    stopping: Option<StoppingRule>,
+    opponent: Option<DriftRule>,
+    log_dynamics: bool,
+    resume: bool,
+) {
+    let metrics = Metrics::new(100);
+    let seeds = SeedStream::new(seed);
 
-    let mut max_pnl = f64::NEG_INFINITY;
-    let mut max_reward = f64::NEG_INFINITY;
+    info!(logger, "master seed"; "seed" => seed);
 
-    let env_builder = || Env::default();
+    if let Some(port) = metrics_port {
+        metrics.serve(port).expect("Failed to start metrics server.");
+    }
+
+    // When resuming, evaluations already recorded are kept and training
+    // continues from the checkpoint of the last one:
+    let recorded = |column| if resume {
+        read_column(format!("{}/results.csv", save_dir), column).unwrap_or_default()
+    } else {
+        vec![]
+    };
+    let best = |column| recorded(column).iter().filter_map(|x| x.parse().ok()).fold(f64::NEG_INFINITY, f64::max);
+
+    let start = recorded("episode").last().map_or(0, |e| e.parse::<usize>().unwrap() / eval_interval);
+
+    let mut file_logger = if resume {
+        Output::append_to_path(format!("{}/results", save_dir), format, "episode")
+    } else {
+        Output::to_path(format!("{}/results", save_dir), format)
+    }.expect("Failed to open results.");
+
+    let mut dynamics_logger = if !log_dynamics {
+        None
+    } else if resume {
+        Some(Output::append_to_path(format!("{}/dynamics", save_dir), format, "episode"))
+    } else {
+        Some(Output::to_path(format!("{}/dynamics", save_dir), format))
+    }.map(|o| o.expect("Failed to open dynamics log."));
+
+    let mut monitor_logger = if monitor.is_none() {
+        None
+    } else if resume {
+        Some(Output::append_to_path(format!("{}/monitor", save_dir), format, "episode"))
+    } else {
+        Some(Output::to_path(format!("{}/monitor", save_dir), format))
+    }.map(|o| o.expect("Failed to open monitor log."));
+
+    let mut opponent = opponent.map(ScriptedAdversary::new);
+
+    let mut max_pnl = best("wealth_mean");
+    let mut max_reward = best("reward_mean");
+
+    let target_penalty = penalty.target;
+    let decorate = |env: ScenarioEnv| {
+        let env = env.with_views(inventory_view, price_view);
+        let env = match drift_estimator {
+            Some(estimator) => env.with_drift_estimator(estimator),
+            None => env,
+        };
+
+        let env = match signal {
+            Some(signal) => env.with_signal(signal),
+            None => env,
+        };
+
+        let env = match precompute_draws {
+            Some(n_steps) => env.with_precomputed_draws(n_steps),
+            None => env,
+        };
+
+        let env = match shield {
+            Some(shield) => env.with_shield(shield),
+            None => env,
+        };
+
+        let env = match adverse_selection {
+            Some(adverse_selection) => env.with_adverse_selection(adverse_selection),
+            None => env,
+        };
+
+        let env = match quote_space {
+            Some(quote_space) => env.with_quote_space(quote_space),
+            None => env,
+        };
+
+        env.with_random_horizon(hazard_rate)
+            .with_clock(clock)
+            .with_inventory_penalty(target_penalty)
+            .with_liquidation(liquidation)
+            .with_fees(fees)
+    };
+    let env_builder = || {
+        decorate(Env::new(ASDynamics::default_with_drift(0.0).with_execution_dynamics(execution.clone())))
+    };
+    let probes = probes.padded(env_builder().state_space().dim());
+
+    env_builder().config().save(&format!("{}/env.json", save_dir)).expect("Failed to save environment config.");
 
     // Build trader:
-    let mut trader = build_trader(env_builder().state_space(), 0.01, 0.000001);
+    let spec = TraderSpec {
+        max_kl, normalise_rewards, truncation, critic_loss,
+        ..TraderSpec::from_timescales(learning_rates)
+    };
+    let spec = match quote_space {
+        Some(ref quote_space) => spec.with_quote_space(quote_space),
+        None => spec,
+    };
+    let mut trader = if start > 0 {
+        info!(logger, "resuming from checkpoint"; "episode" => start * eval_interval);
+
+        load_trader(format!("{}/trader.bin", save_dir)).expect("Failed to load trader checkpoint.")
+    } else {
+        spec
+            .with_exploration(exploration)
+            .build(env_builder().state_space())
+            .expect("Failed to build trader.")
+    };
+
+    for _ in 0..start {
+        penalty.step();
+    }
 
     // Pre-train value function:
-    for _ in 0..1000 {
-        train_value_function(env_builder(), &mut trader);
+    if start == 0 {
+        for _ in 0..1000 {
+            train_value_function(env_builder(), &mut trader);
+        }
     }
 
     // Run experiment:
-    for i in 0..(1200*eval_interval) {
+    for i in start..(1200*eval_interval) {
         // Perform evaluation:
         if i % eval_interval == 0 {
-            let r = evaluate_trader(
-                env_builder,
-                &mut trader,
-                i * eval_interval,
-                1000,
-            );
+            let r = match scenarios {
+                Some(ref bundle) => evaluate_trader_on(bundle, &decorate, &mut trader, i * eval_interval, stopping, &probes),
+                None => evaluate_trader(
+                    env_builder,
+                    &mut trader,
+                    i * eval_interval,
+                    1000,
+                    stopping,
+                    seeds.substream(i as u64),
+                    &probes,
+                ),
+            };
 
             // Serialise the trader if it performed better:
             if r.wealth_mean > max_pnl || r.reward_mean > max_reward {
                 max_pnl = r.wealth_mean;
                 max_reward = r.reward_mean;
 
-                save_trader(&trader, format!("{}/trader_best.bin", save_dir));
+                save_trader(&trader, format!("{}/trader_best.bin", save_dir)).expect("Failed to save trader.");
             }
 
             // Serialise latest trader too:
-            save_trader(&trader, format!("{}/trader.bin", save_dir));
+            save_trader(&trader, format!("{}/trader.bin", save_dir)).expect("Failed to save trader.");
 
             // Log plotting data:
             info!(logger, "evaluation {}", i / eval_interval;
+                "simulations" => r.n_simulations,
                 "wealth" => format!("{} +/- {}", r.wealth_mean, r.wealth_stddev),
                 "reward" => format!("{} +/- {}", r.reward_mean, r.reward_stddev),
                 "inv" => format!("{} +/- {}", r.inv_mean, r.inv_stddev),
+                "cap_rate" => format!("{} +/- {}", r.cap_rate_mean, r.cap_rate_stddev),
+                "overrides" => format!("{} +/- {}", r.overrides_mean, r.overrides_stddev),
                 "spread" => format!("{} +/- {}", r.spread_mean, r.spread_stddev),
-                "rp_neutral" => r.rp_neutral,
-                "rp_bull" => r.rp_bull,
-                "rp_bear" => r.rp_bear,
+                "entropy" => format!("{} +/- {}", r.entropy_mean, r.entropy_stddev),
+                "rp" => r.rp.to_string(),
+                "lr_ratio" => trader.timescale_ratio(),
+                "terminal_penalty" => penalty.current().terminal,
             );
 
             file_logger.serialize(r).ok();
             file_logger.flush().ok();
+
+            if let Some(ref mut sink) = dynamics_logger {
+                sink.flush().ok();
+            }
+        }
+
+        // Train trader for one episode, cycling through the scenarios if given:
+        let env = match scenarios {
+            Some(ref bundle) => {
+                let (env, agent_seed) = bundle.scenario(i, &decorate);
+
+                trader.reseed(agent_seed);
+
+                env
+            },
+            None => env_builder(),
+        };
+        // Evaluations use the target penalty, training the annealed one:
+        let env = env.with_inventory_penalty(penalty.current());
+        let env = if initial.is_default() { env } else { env.with_random_start(initial) };
+        if let Some(ref mut detector) = detector {
+            detector.reset();
+        }
+        if let Some(ref mut monitor) = monitor {
+            monitor.reset();
+        }
+
+        let env = {
+            let mut on_step = |t: &Transition<Vector<f64>, [f64; 2]>| {
+                if let Some(ref mut detector) = detector { detector.record(t); }
+                if let Some(ref mut monitor) = monitor { monitor.record(t); }
+                if let Some(ref mut recorder) = recorder { recorder.record(t); }
+            };
+
+            match opponent {
+                Some(ref mut opponent) => train_trader_against_with(env, &mut trader, opponent, &mut on_step),
+                None => train_trader_once_with(env, &mut trader, &mut on_step),
+            }
+        };
+
+        let mut dump_recorded = false;
+
+        if let Some(ref mut detector) = detector {
+            let anomalies = detector.inspect(&env, &[trader.critic.weights(), trader.policy.weights()]);
+
+            if !anomalies.is_empty() {
+                warn!(logger, "anomalous episode"; "episode" => i, "anomalies" => format!("{:?}", anomalies));
+
+                if snapshot_anomalies {
+                    detector.snapshot(&format!("{}/anomaly_{}", save_dir, i), &trader, &anomalies)
+                        .expect("Failed to snapshot anomalous episode.");
+                }
+
+                dump_recorded = true;
+            }
+        }
+
+        if let Some(ref mut monitor) = monitor {
+            let flagged = monitor.inspect(&env);
+
+            if !flagged.is_empty() {
+                warn!(logger, "degenerate policy"; "episode" => i, "flagged" => format!("{:?}", flagged));
+
+                // Fall back on the best checkpoint so far, if there is one:
+                let reverted = revert_degenerate && match load_trader(format!("{}/trader_best.bin", save_dir)) {
+                    Ok(best) => {
+                        info!(logger, "reverted to best checkpoint"; "episode" => i);
+
+                        trader = best;
+                        monitor.clear();
+
+                        true
+                    },
+                    Err(e) => {
+                        warn!(logger, "failed to revert trader"; "episode" => i, "error" => e.to_string());
+
+                        false
+                    },
+                };
+
+                if let Some(ref mut sink) = monitor_logger {
+                    for d in flagged {
+                        sink.serialize(MonitorEvent::new(i, d, reverted)).ok();
+                    }
+
+                    sink.flush().ok();
+                }
+
+                dump_recorded = true;
+            }
+        }
+
+        if let Some(ref mut recorder) = recorder {
+            recorder.finish(i, &env);
+
+            // Touching save_dir/dump_episodes requests a dump of the buffer:
+            let request = Path::new(save_dir).join("dump_episodes");
+
+            if request.exists() {
+                fs::remove_file(&request).ok();
+                dump_recorded = true;
+            }
+
+            if dump_recorded {
+                let path = format!("{}/episodes_{}.jsonl", save_dir, i);
+
+                recorder.dump(&path).expect("Failed to dump recorded episodes.");
+                info!(logger, "dumped recorded episodes"; "episode" => i, "path" => path);
+            }
         }
 
-        // Train trader for one episode:
-        train_trader_once(env_builder(), &mut trader);
+        penalty.step();
+
+        let n_triggered = trader.trust_region.as_mut().map_or(0, |tr| tr.take_triggered());
+        if n_triggered > 0 {
+            warn!(logger, "trust region triggered"; "agent" => "trader", "episode" => i, "updates" => n_triggered);
+        }
+
+        let n_skipped = trader.policy.take_skipped();
+        if n_skipped > 0 {
+            warn!(logger, "non-finite updates skipped"; "agent" => "trader", "episode" => i, "updates" => n_skipped);
+        }
+
+        if let Some(ref mut sink) = dynamics_logger {
+            sink.serialize(EpisodeDynamics::of(i, &env)).ok();
+        }
+
+        metrics.record_episode((env.dynamics.time / env.dynamics.dt).round() as u64, env.episode_reward);
+        metrics.set_learning_rate("trader_critic", trader.critic.alpha.value());
+        metrics.set_learning_rate("trader_policy", trader.alpha.value());
     }
 }
 
@@ -86,11 +398,296 @@ fn main() {
         .arg(Arg::with_name("risk_param")
                 .long("risk_param")
                 .required(false))
+        .arg(Arg::with_name("metrics_port")
+                .long("metrics_port")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("probe")
+                .long("probe")
+                .help("Labelled evaluation state, e.g. long=0.0,5.0")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1))
+        .arg(Arg::with_name("inventory_view")
+                .long("inventory_view")
+                .help("Delay (in steps) and noise of the trader's inventory observations, e.g. 2,0.5")
+                .takes_value(true)
+                .default_value("0,0"))
+        .arg(Arg::with_name("price_view")
+                .long("price_view")
+                .help("Delay (in steps) and noise of the trader's midprice observations, e.g. 2,0.5")
+                .takes_value(true)
+                .default_value("0,0"))
+        .arg(Arg::with_name("ask_rate")
+                .long("ask_rate")
+                .help("Scale and decay of the ask side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("bid_rate")
+                .long("bid_rate")
+                .help("Scale and decay of the bid side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("drift_estimator")
+                .long("drift_estimator")
+                .help("Rate and bound of an EWMA drift estimate added to the trader's state, e.g. 0.05,5.0")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("signal")
+                .long("signal")
+                .help("Observed OU signal as rate,volatility,correlation,loading; a non-zero loading makes it predictive of the midprice")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("exploration")
+                .long("exploration")
+                .help("Exploration during training: action, or parameter:stddev,target_distance,adaptation_rate")
+                .takes_value(true)
+                .default_value("action"))
+        .arg(Arg::with_name("max_kl")
+                .long("max_kl")
+                .help("Bound on the KL divergence induced by each policy update")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("normalise_rewards")
+                .long("normalise_rewards")
+                .help("Normalise rewards by a running estimate of the return's scale"))
+        .arg(Arg::with_name("critic_lr")
+                .long("critic_lr")
+                .help("Initial learning rate of the critic")
+                .takes_value(true)
+                .default_value("0.01"))
+        .arg(Arg::with_name("lr_ratio")
+                .long("lr_ratio")
+                .help("Ratio of the actor's learning rate to the critic's")
+                .takes_value(true)
+                .default_value("0.0001"))
+        .arg(Arg::with_name("lr_decay")
+                .long("lr_decay")
+                .help("Exponent of a polynomial decay applied jointly to both learning rates")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("shield")
+                .long("shield")
+                .help("Distance from the inventory bounds within which only the reducing side is quoted")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("spread_bounds")
+                .long("spread_bounds")
+                .help("Minimum and maximum half-spread quoted by the trader, e.g. 0.01,2.0")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("hazard_rate")
+                .long("hazard_rate")
+                .help("Rate at which episodes end before the horizon, discounting the critic's targets")
+                .takes_value(true)
+                .default_value("0.0"))
+        .arg(Arg::with_name("clock")
+                .long("clock")
+                .help("Clock by which each step advances: fixed, or event:tick to hold quotes until a fill or a move of tick in the midprice")
+                .takes_value(true)
+                .default_value("fixed"))
+        .arg(Arg::with_name("precompute_draws")
+                .long("precompute_draws")
+                .help("Draw the random numbers of this many steps at a time, e.g. 200 for one episode, for faster training")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("adverse_selection")
+                .long("adverse_selection")
+                .help("Move of the midprice against the trader per unit filled: a jump, or jump,drift,decay, e.g. 0.05,1.0,20")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("inventory_penalty")
+                .long("inventory_penalty")
+                .help("Weights of the running and terminal quadratic inventory penalties, e.g. 0.0,0.5")
+                .takes_value(true)
+                .default_value("0.0,0.5"))
+        .arg(Arg::with_name("penalty_anneal")
+                .long("penalty_anneal")
+                .help("Factor by which the shortfall from the full inventory penalty decays per episode, starting from no penalty, e.g. 0.999")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("liquidation")
+                .long("liquidation")
+                .help("Model of the liquidation at the horizon: mid, impact:eta, spread:half_spread or gradual:n_slices,eta")
+                .takes_value(true)
+                .default_value("mid"))
+        .arg(Arg::with_name("fees")
+                .long("fees")
+                .help("Maker rebate, taker fee and fixed cost per unit traded, e.g. 0.002,0.003,0.0")
+                .takes_value(true)
+                .default_value("0,0,0"))
+        .arg(Arg::with_name("initial_inventory")
+                .long("initial_inventory")
+                .help("Bounds of the uniformly drawn inventory at the start of training episodes, e.g. -10,10")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("initial_time")
+                .long("initial_time")
+                .help("Bounds of the uniformly drawn time at the start of training episodes, e.g. 0,0.9")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("bootstrap_horizon")
+                .long("bootstrap_horizon")
+                .help("Bootstrap from the value of the state at the horizon rather than treating it as terminal"))
+        .arg(Arg::with_name("critic_loss")
+                .long("critic_loss")
+                .help("Loss on the critic's TD error, squared or huber:delta")
+                .takes_value(true)
+                .default_value("squared"))
+        .arg(Arg::with_name("max_weight_norm")
+                .long("max_weight_norm")
+                .help("Flag episodes with non-finite rewards, weights above this norm or inventory at the bounds")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("snapshot_anomalies")
+                .long("snapshot_anomalies")
+                .help("Save the trader and episode trace of every flagged episode")
+                .requires("max_weight_norm"))
+        .arg(Arg::with_name("monitor")
+                .long("monitor")
+                .help("Flag degenerate quoting persisting for some episodes in save_dir/monitor, as min_spread,min_fill_rate,max_pinned_fraction,patience, e.g. 0.05,0.01,0.5,20")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("revert_degenerate")
+                .long("revert_degenerate")
+                .help("Reload the best checkpoint whenever the monitor flags the trader")
+                .requires("monitor"))
+        .arg(Arg::with_name("record_episodes")
+                .long("record_episodes")
+                .help("Keep the last N training episodes in memory, writing them to save_dir/episodes_{episode}.jsonl on an anomaly or when save_dir/dump_episodes is created")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("scenarios")
+                .long("scenarios")
+                .help("Scenario bundle to train and evaluate against; its dynamics replace --ask_rate and --bid_rate")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("eval_width")
+                .long("eval_width")
+                .help("Stop each evaluation early once the 95% interval on mean wealth is this narrow, e.g. 0.5 or 0.5,2.58 for another z")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("opponent")
+                .long("opponent")
+                .help("Scripted adversary setting the drift during training, e.g. sinusoidal:1.0,0.5, momentum:0.2,0.1 or inventory:0.2")
+                .takes_value(true)
+                .conflicts_with("max_weight_norm")
+                .required(false))
+        .arg(Arg::with_name("log_dynamics")
+                .long("log_dynamics")
+                .help("Write the realised drift, volatility and fill rate of every training episode to save_dir/dynamics"))
+        .arg(Arg::with_name("resume")
+                .long("resume")
+                .help("Continue from the latest checkpoint in save_dir, appending to its results (CSV only)"))
+        .arg(Arg::with_name("log-level")
+                .long("log-level")
+                .help("Minimum level of log records, optionally per module, e.g. info,rsrl=debug")
+                .takes_value(true)
+                .default_value("info"))
+        .arg(Arg::with_name("log-json")
+                .long("log-json")
+                .help("File to which log records are also written as JSON lines")
+                .takes_value(true)
+                .required(false))
         .get_matches();
 
     let save_dir = matches.value_of("save_dir").unwrap();
     let eval_interval: usize = matches.value_of("eval_interval").unwrap().parse().unwrap();
     let risk_param: Option<f64> = matches.value_of("risk_param").map(|s| s.parse().unwrap());
+    let metrics_port: Option<u16> = matches.value_of("metrics_port").map(|s| s.parse().unwrap());
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+    let seed: u64 = matches.value_of("seed").map_or_else(rand::random, |s| s.parse().unwrap());
+    let probes = matches.values_of("probe").map_or_else(Probes::default, |ps| {
+        Probes(ps.map(|p| p.parse().unwrap()).collect())
+    });
+
+    let inventory_view: View = matches.value_of("inventory_view").unwrap().parse().unwrap();
+    let price_view: View = matches.value_of("price_view").unwrap().parse().unwrap();
+    let execution = PerSide::new(
+        matches.value_of("ask_rate").unwrap().parse::<PoissonRate>().unwrap(),
+        matches.value_of("bid_rate").unwrap().parse::<PoissonRate>().unwrap(),
+    );
+
+    let drift_estimator: Option<DriftEstimator> =
+        matches.value_of("drift_estimator").map(|s| s.parse().unwrap());
+    let signal: Option<Signal> = matches.value_of("signal").map(|s| s.parse().unwrap());
+    let exploration: Exploration = matches.value_of("exploration").unwrap().parse().unwrap();
+    let max_kl: Option<f64> = matches.value_of("max_kl").map(|s| s.parse().unwrap());
+    let normalise_rewards = matches.is_present("normalise_rewards");
+
+    let shield: Option<Shield> = matches.value_of("shield").map(|s| s.parse().unwrap());
+    let quote_space: Option<QuoteSpace> = matches.value_of("spread_bounds").map(|s| {
+        let bounds: Vec<f64> = s.split(',').map(|b| b.trim().parse().unwrap()).collect();
+
+        match bounds.as_slice() {
+            &[min, max] => QuoteSpace::new(QuoteSpace::default().reservation, Interval::bounded(min, max)),
+            _ => panic!("Spread bounds `{}` must be of the form min,max.", s),
+        }
+    });
+    let hazard_rate: f64 = matches.value_of("hazard_rate").unwrap().parse().unwrap();
+    let clock: Clock = matches.value_of("clock").unwrap().parse().unwrap();
+    let precompute_draws: Option<usize> = matches.value_of("precompute_draws").map(|s| s.parse().unwrap());
+    let adverse_selection: Option<AdverseSelection> =
+        matches.value_of("adverse_selection").map(|s| s.parse().unwrap());
+    let inventory_penalty: InventoryPenalty = matches.value_of("inventory_penalty").unwrap().parse().unwrap();
+    let penalty = match matches.value_of("penalty_anneal") {
+        Some(tau) => PenaltySchedule::annealed(inventory_penalty, tau.parse().unwrap()),
+        None => PenaltySchedule::fixed(inventory_penalty),
+    };
+    let liquidation: Liquidation = matches.value_of("liquidation").unwrap().parse().unwrap();
+    let fees: FeeSchedule = matches.value_of("fees").unwrap().parse().unwrap();
+    let initial = StartDistribution::new(
+        matches.value_of("initial_inventory").unwrap().parse::<Bounds>().unwrap(),
+        matches.value_of("initial_time").unwrap().parse::<Bounds>().unwrap(),
+    ).unwrap();
+    let truncation = if matches.is_present("bootstrap_horizon") {
+        Truncation::Bootstrap
+    } else {
+        Truncation::Terminate
+    };
+    let critic_loss: Loss = matches.value_of("critic_loss").unwrap().parse().unwrap();
+    let detector: Option<AnomalyDetector> =
+        matches.value_of("max_weight_norm").map(|s| AnomalyDetector::new(s.parse().unwrap()));
+    let snapshot_anomalies = matches.is_present("snapshot_anomalies");
+    let monitor: Option<DegeneracyMonitor> = matches.value_of("monitor").map(|s| s.parse().unwrap());
+    let revert_degenerate = matches.is_present("revert_degenerate");
+    let recorder: Option<EpisodeRecorder> =
+        matches.value_of("record_episodes").map(|s| EpisodeRecorder::new(s.parse().unwrap()));
+    let scenarios: Option<ScenarioBundle> = matches.value_of("scenarios").map(|path| {
+        ScenarioBundle::load(path).expect("Failed to load scenario bundle.")
+    });
+
+    let critic_lr: f64 = matches.value_of("critic_lr").unwrap().parse().unwrap();
+    let learning_rates = TwoTimescale::new(
+        matches.value_of("lr_decay").map_or(Parameter::fixed(critic_lr), |tau| {
+            Parameter::polynomial(critic_lr, 0.0, tau.parse().unwrap())
+        }),
+        matches.value_of("lr_ratio").unwrap().parse().unwrap(),
+    );
+
+    let stopping: Option<StoppingRule> = matches.value_of("eval_width").map(|s| s.parse().unwrap());
+    let opponent: Option<DriftRule> = matches.value_of("opponent").map(|s| s.parse().unwrap());
+    let log_dynamics = matches.is_present("log_dynamics");
+    let resume = matches.is_present("resume");
+
+    let logger = logging::configured(
+        matches.value_of("log-level").unwrap().parse().unwrap(),
+        matches.value_of("log-json"),
+    ).expect("Failed to open log file.");
 
-    run_experiment(save_dir, eval_interval, risk_param);
+    run_experiment(
+        logger, save_dir, eval_interval, risk_param, metrics_port, format, seed, probes,
+        inventory_view, price_view, execution, drift_estimator, signal, exploration, max_kl, normalise_rewards,
+        learning_rates, shield, quote_space, hazard_rate, clock, precompute_draws, adverse_selection, penalty, liquidation, fees, initial, truncation, critic_loss, detector, snapshot_anomalies, monitor, revert_degenerate, recorder, scenarios, stopping,
+        opponent, log_dynamics, resume,
+    );
 }
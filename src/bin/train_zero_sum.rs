@@ -1,41 +1,157 @@
 extern crate algo_hft;
 extern crate clap;
+extern crate rand;
 extern crate rsrl;
 #[macro_use]
 extern crate slog;
-extern crate csv;
 
 use algo_hft::{
     agents::{
-        build_adversary, save_adversary,
-        build_trader, save_trader,
-        training::zero_sum::*,
+        AdversarySpec, DriftParameterisation, load_adversary, save_adversary,
+        build_trader, load_trader, save_trader,
+        training::{exploitability::exploitability, probes::Probes, regimes::EpisodeDynamics, stopping::StoppingRule, zero_sum::*},
     },
-    env::Env,
+    env::{
+        Env,
+        dynamics::{ASDynamics, execution::{PerSide, PoissonRate}},
+        liquidation::Liquidation,
+        observation::{AttackObservation, DriftEstimator, TrailingWindow},
+        penalty::{InventoryPenalty, PenaltySchedule},
+        start::{Bounds, StartDistribution},
+    },
+    metrics::Metrics,
+    output::{read_column, Format, Output},
 };
 use clap::{App, Arg};
 use rsrl::{
+    control::actor_critic::{RewardScale, TrustRegion},
+    core::SeedStream,
     domains::Domain,
+    geometry::Space,
     logging,
+    policies::SkippedUpdates,
 };
+use slog::Logger;
+
+fn run_experiment(logger: Logger, save_dir: &str, eval_interval: usize, metrics_port: Option<u16>, format: Format, seed: u64, probes: Probes, mut window: TrailingWindow, execution: PerSide<PoissonRate, PoissonRate>, schedule: DriftSchedule, drift_estimator: Option<DriftEstimator>, attack_observation: Option<AttackObservation>, max_kl: Option<f64>, normalise_rewards: bool, drift: DriftParameterisation, stopping: Option<StoppingRule>, mut penalty: PenaltySchedule, liquidation: Liquidation, initial: StartDistribution, log_dynamics: bool, exploitability_episodes: Option<usize>, resume: bool) {
+    let metrics = Metrics::new(100);
+    let seeds = SeedStream::new(seed);
+    // Kept apart from the substreams of the evaluations:
+    let exploitability_seeds = seeds.substream(::std::u64::MAX);
+
+    info!(logger, "master seed"; "seed" => seed);
+
+    if let Some(port) = metrics_port {
+        metrics.serve(port).expect("Failed to start metrics server.");
+    }
+
+    // When resuming, evaluations already recorded are kept and training
+    // continues from the checkpoint of the last one:
+    let recorded = |column| if resume {
+        read_column(format!("{}/results.csv", save_dir), column).unwrap_or_default()
+    } else {
+        vec![]
+    };
+    let start = recorded("episode").last().map_or(0, |e| e.parse::<usize>().unwrap() / eval_interval);
+
+    let mut file_logger = if resume {
+        Output::append_to_path(format!("{}/results", save_dir), format, "episode")
+    } else {
+        Output::to_path(format!("{}/results", save_dir), format)
+    }.expect("Failed to open results.");
+
+    let mut dynamics_logger = if !log_dynamics {
+        None
+    } else if resume {
+        Some(Output::append_to_path(format!("{}/dynamics", save_dir), format, "episode"))
+    } else {
+        Some(Output::to_path(format!("{}/dynamics", save_dir), format))
+    }.map(|o| o.expect("Failed to open dynamics log."));
+
+    let mut exploitability_logger = match exploitability_episodes {
+        None => None,
+        Some(_) if resume => Some(Output::append_to_path(format!("{}/exploitability", save_dir), format, "episode")),
+        Some(_) => Some(Output::to_path(format!("{}/exploitability", save_dir), format)),
+    }.map(|o| o.expect("Failed to open exploitability log."));
+
+    let target_penalty = penalty.target;
+    let env_builder = || {
+        let env = Env::new(ASDynamics::default_with_drift(0.0).with_execution_dynamics(execution.clone()))
+            .with_inventory_penalty(target_penalty)
+            .with_liquidation(liquidation);
+
+        let env = match drift_estimator {
+            Some(estimator) => env.with_drift_estimator(estimator),
+            None => env,
+        };
+
+        match attack_observation {
+            Some(observation) => env.with_attack_observation(observation),
+            None => env,
+        }
+    };
+    let probes = probes.padded(env_builder().state_space().dim());
+
+    let adversary_space = window.state_space(&env_builder());
+    let new_trader = || {
+        let mut trader = build_trader(env_builder().state_space(), 0.01, 0.000001)
+            .expect("Failed to build trader.");
+
+        trader.trust_region = max_kl.map(TrustRegion::new);
+
+        if normalise_rewards {
+            trader.reward_scale = Some(RewardScale::new());
+        }
+
+        trader
+    };
+    let new_adversary = || {
+        let mut adversary = AdversarySpec::new(0.1, 0.0001)
+            .with_drift(drift)
+            .build(adversary_space.clone())
+            .expect("Failed to build adversary.");
 
-fn run_experiment(save_dir: &str, eval_interval: usize) {
-    let logger = logging::root(logging::stdout());
-    let mut file_logger = csv::Writer::from_path(format!("{}/results.csv", save_dir)).unwrap();
+        adversary.trust_region = max_kl.map(TrustRegion::new);
 
-    let env_builder = || Env::default_with_drift();
+        if normalise_rewards {
+            adversary.reward_scale = Some(RewardScale::new());
+        }
+
+        adversary
+    };
 
     // Build adversary:
-    let mut trader = build_trader(env_builder().state_space(), 0.01, 0.000001);
-    let mut adversary = build_adversary(env_builder().state_space(), 0.1, 0.0001);
+    // Build adversary:
+    let (mut trader, mut adversary) = if start > 0 {
+        info!(logger, "resuming from checkpoint"; "episode" => start * eval_interval);
+
+        (
+            load_trader(format!("{}/trader_{}.bin", save_dir, start)).expect("Failed to load trader checkpoint."),
+            load_adversary(format!("{}/adversary_{}.bin", save_dir, start)).expect("Failed to load adversary checkpoint."),
+        )
+    } else {
+        let mut trader = new_trader();
+        let mut adversary = new_adversary();
+
+        // Pre-train value function:
+        for _ in 0..1000 {
+            match schedule {
+                DriftSchedule::PerStep =>
+                    train_value_functions(env_builder(), &mut trader, &mut adversary, &mut window),
+                DriftSchedule::PerEpisode =>
+                    train_value_functions_per_episode(env_builder(), &mut trader, &mut adversary, &window),
+            };
+        }
 
-    // Pre-train value function:
-    for _ in 0..1000 {
-        train_value_functions(env_builder(), &mut trader, &mut adversary);
+        (trader, adversary)
+    };
+
+    for _ in 0..start {
+        penalty.step();
     }
 
     // Run experiment:
-    for i in 0.. {
+    for i in start.. {
         // Perform evaluation:
         if i % eval_interval == 0 {
             let r = evaluate_agents(
@@ -44,25 +160,101 @@ fn run_experiment(save_dir: &str, eval_interval: usize) {
                 &mut adversary,
                 i * eval_interval,
                 1000,
+                stopping,
+                seeds.substream(i as u64),
+                &probes,
+                &mut window,
+                schedule,
             );
 
             // Serialise every agent:
-            save_trader(&trader, format!("{}/trader_{}.bin", save_dir, i));
-            save_adversary(&adversary, format!("{}/adversary_{}.bin", save_dir, i));
+            save_trader(&trader, format!("{}/trader_{}.bin", save_dir, i)).expect("Failed to save trader.");
+            save_adversary(&adversary, format!("{}/adversary_{}.bin", save_dir, i)).expect("Failed to save adversary.");
 
             // Log plotting data:
             info!(logger, "evaluation {}", i / eval_interval;
+                "simulations" => r.n_simulations,
                 "wealth" => format!("{} +/- {}", r.wealth_mean, r.wealth_stddev),
                 "reward" => format!("{} +/- {}", r.reward_mean, r.reward_stddev),
                 "inv" => format!("{} +/- {}", r.inv_mean, r.inv_stddev),
+                "cap_rate" => format!("{} +/- {}", r.cap_rate_mean, r.cap_rate_stddev),
+                "trader_lr_ratio" => trader.timescale_ratio(),
+                "adversary_lr_ratio" => adversary.timescale_ratio(),
             );
 
             file_logger.serialize(r).ok();
             file_logger.flush().ok();
+
+            if let (Some(n_episodes), Some(ref mut sink)) = (exploitability_episodes, exploitability_logger.as_mut()) {
+                let e = exploitability(
+                    env_builder,
+                    &trader,
+                    &adversary,
+                    new_trader,
+                    new_adversary,
+                    i * eval_interval,
+                    n_episodes,
+                    1000,
+                    exploitability_seeds.substream(i as u64),
+                    &mut window,
+                );
+
+                info!(logger, "exploitability {}", i / eval_interval;
+                    "trader" => e.trader_exploitability,
+                    "adversary" => e.adversary_exploitability,
+                    "gap" => e.gap,
+                );
+
+                sink.serialize(e).ok();
+                sink.flush().ok();
+            }
+
+            if let Some(ref mut sink) = dynamics_logger {
+                sink.flush().ok();
+            }
+        }
+
+        // Train agent for one episode, with the annealed penalty:
+        let env = env_builder().with_inventory_penalty(penalty.current());
+        let env = if initial.is_default() { env } else { env.with_random_start(initial) };
+        let env = match schedule {
+            DriftSchedule::PerStep =>
+                train_agents_once(env, &mut trader, &mut adversary, &mut window),
+            DriftSchedule::PerEpisode =>
+                train_agents_once_per_episode(env, &mut trader, &mut adversary, &window),
+        };
+
+        penalty.step();
+
+        let n_triggered = trader.trust_region.as_mut().map_or(0, |tr| tr.take_triggered());
+        if n_triggered > 0 {
+            warn!(logger, "trust region triggered"; "agent" => "trader", "episode" => i, "updates" => n_triggered);
         }
 
-        // Train agent for one episode:
-        train_agents_once(env_builder(), &mut trader, &mut adversary);
+        let n_skipped = trader.policy.take_skipped();
+        if n_skipped > 0 {
+            warn!(logger, "non-finite updates skipped"; "agent" => "trader", "episode" => i, "updates" => n_skipped);
+        }
+
+        let n_triggered = adversary.trust_region.as_mut().map_or(0, |tr| tr.take_triggered());
+        if n_triggered > 0 {
+            warn!(logger, "trust region triggered"; "agent" => "adversary", "episode" => i, "updates" => n_triggered);
+        }
+
+        let n_skipped = adversary.policy.take_skipped();
+        if n_skipped > 0 {
+            warn!(logger, "non-finite updates skipped"; "agent" => "adversary", "episode" => i, "updates" => n_skipped);
+        }
+
+        if let Some(ref mut sink) = dynamics_logger {
+            sink.serialize(EpisodeDynamics::of(i, &env)).ok();
+        }
+
+        metrics.record_episode((env.dynamics.time / env.dynamics.dt).round() as u64, env.episode_reward);
+        metrics.set_learning_rate("trader_critic", trader.critic.alpha.value());
+        metrics.set_learning_rate("trader_policy", trader.alpha.value());
+        metrics.set_learning_rate("adversary_critic", adversary.critic.alpha.value());
+        metrics.set_learning_rate("adversary_policy", adversary.alpha.value());
     }
 }
 
@@ -74,10 +266,180 @@ fn main() {
         .arg(Arg::with_name("eval_interval")
                 .index(2)
                 .required(true))
+        .arg(Arg::with_name("metrics_port")
+                .long("metrics_port")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["csv", "parquet"])
+                .default_value("csv"))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("probe")
+                .long("probe")
+                .help("Labelled evaluation state, e.g. long=0.0,5.0")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1))
+        .arg(Arg::with_name("adversary_window")
+                .long("adversary_window")
+                .help("Number of past steps summarised in the adversary's observation")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("adversary_max_move")
+                .long("adversary_max_move")
+                .help("Bound on the net price move observed by the adversary")
+                .takes_value(true)
+                .default_value("2.0"))
+        .arg(Arg::with_name("ask_rate")
+                .long("ask_rate")
+                .help("Scale and decay of the ask side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("bid_rate")
+                .long("bid_rate")
+                .help("Scale and decay of the bid side's execution intensity, e.g. 140,1.5")
+                .takes_value(true)
+                .default_value("140,1.5"))
+        .arg(Arg::with_name("drift_schedule")
+                .long("drift_schedule")
+                .help("Whether the adversary picks a drift every step or once per episode")
+                .takes_value(true)
+                .possible_values(&["per_step", "per_episode"])
+                .default_value("per_step"))
+        .arg(Arg::with_name("drift_estimator")
+                .long("drift_estimator")
+                .help("Rate and bound of an EWMA drift estimate added to the trader's state, e.g. 0.05,5.0")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("observe_attack")
+                .long("observe_attack")
+                .help("Ablation revealing the attack to the trader: indicator of a non-zero drift, or the drift itself as drift:bound")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("max_kl")
+                .long("max_kl")
+                .help("Bound on the KL divergence induced by each policy update")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("normalise_rewards")
+                .long("normalise_rewards")
+                .help("Normalise rewards by a running estimate of the return's scale"))
+        .arg(Arg::with_name("drift_policy")
+                .long("drift_policy")
+                .help("Distribution of the adversary's policy over the drift")
+                .takes_value(true)
+                .possible_values(&["beta", "tanh_gaussian"])
+                .default_value("beta"))
+        .arg(Arg::with_name("eval_width")
+                .long("eval_width")
+                .help("Stop each evaluation early once the 95% interval on mean wealth is this narrow, e.g. 0.5 or 0.5,2.58 for another z")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("inventory_penalty")
+                .long("inventory_penalty")
+                .help("Weights of the running and terminal quadratic inventory penalties, e.g. 0.0,0.5")
+                .takes_value(true)
+                .default_value("0.0,0.5"))
+        .arg(Arg::with_name("penalty_anneal")
+                .long("penalty_anneal")
+                .help("Factor by which the shortfall from the full inventory penalty decays per episode, starting from no penalty, e.g. 0.999")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("liquidation")
+                .long("liquidation")
+                .help("Model of the liquidation at the horizon: mid, impact:eta, spread:half_spread or gradual:n_slices,eta")
+                .takes_value(true)
+                .default_value("mid"))
+        .arg(Arg::with_name("initial_inventory")
+                .long("initial_inventory")
+                .help("Bounds of the uniformly drawn inventory at the start of training episodes, e.g. -10,10")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("initial_time")
+                .long("initial_time")
+                .help("Bounds of the uniformly drawn time at the start of training episodes, e.g. 0,0.9")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("log_dynamics")
+                .long("log_dynamics")
+                .help("Write the realised drift, volatility and fill rate of every training episode to save_dir/dynamics"))
+        .arg(Arg::with_name("exploitability")
+                .long("exploitability")
+                .help("At each evaluation, train fresh best responses to each agent for this many episodes and write the exploitability gap to save_dir/exploitability")
+                .takes_value(true)
+                .required(false))
+        .arg(Arg::with_name("resume")
+                .long("resume")
+                .help("Continue from the latest checkpoint in save_dir, appending to its results (CSV only)"))
+        .arg(Arg::with_name("log-level")
+                .long("log-level")
+                .help("Minimum level of log records, optionally per module, e.g. info,rsrl=debug")
+                .takes_value(true)
+                .default_value("info"))
+        .arg(Arg::with_name("log-json")
+                .long("log-json")
+                .help("File to which log records are also written as JSON lines")
+                .takes_value(true)
+                .required(false))
         .get_matches();
 
     let save_dir = matches.value_of("save_dir").unwrap();
     let eval_interval: usize = matches.value_of("eval_interval").unwrap().parse().unwrap();
 
-    run_experiment(save_dir, eval_interval);
+    let metrics_port: Option<u16> = matches.value_of("metrics_port").map(|s| s.parse().unwrap());
+    let format: Format = matches.value_of("format").unwrap().parse().unwrap();
+    let seed: u64 = matches.value_of("seed").map_or_else(rand::random, |s| s.parse().unwrap());
+    let probes = matches.values_of("probe").map_or_else(Probes::default, |ps| {
+        Probes(ps.map(|p| p.parse().unwrap()).collect())
+    });
+    let window = TrailingWindow::new(
+        matches.value_of("adversary_window").unwrap().parse().unwrap(),
+        matches.value_of("adversary_max_move").unwrap().parse().unwrap(),
+    );
+    let execution = PerSide::new(
+        matches.value_of("ask_rate").unwrap().parse::<PoissonRate>().unwrap(),
+        matches.value_of("bid_rate").unwrap().parse::<PoissonRate>().unwrap(),
+    );
+
+    let schedule: DriftSchedule = matches.value_of("drift_schedule").unwrap().parse().unwrap();
+
+    let drift_estimator: Option<DriftEstimator> =
+        matches.value_of("drift_estimator").map(|s| s.parse().unwrap());
+    let attack_observation: Option<AttackObservation> =
+        matches.value_of("observe_attack").map(|s| s.parse().unwrap());
+
+    let max_kl: Option<f64> = matches.value_of("max_kl").map(|s| s.parse().unwrap());
+    let normalise_rewards = matches.is_present("normalise_rewards");
+    let drift: DriftParameterisation = matches.value_of("drift_policy").unwrap().parse().unwrap();
+
+    let stopping: Option<StoppingRule> = matches.value_of("eval_width").map(|s| s.parse().unwrap());
+    let inventory_penalty: InventoryPenalty = matches.value_of("inventory_penalty").unwrap().parse().unwrap();
+    let penalty = match matches.value_of("penalty_anneal") {
+        Some(tau) => PenaltySchedule::annealed(inventory_penalty, tau.parse().unwrap()),
+        None => PenaltySchedule::fixed(inventory_penalty),
+    };
+    let liquidation: Liquidation = matches.value_of("liquidation").unwrap().parse().unwrap();
+    let initial = StartDistribution::new(
+        matches.value_of("initial_inventory").unwrap().parse::<Bounds>().unwrap(),
+        matches.value_of("initial_time").unwrap().parse::<Bounds>().unwrap(),
+    ).unwrap();
+    let log_dynamics = matches.is_present("log_dynamics");
+    let exploitability_episodes: Option<usize> = matches.value_of("exploitability").map(|s| s.parse().unwrap());
+    assert!(
+        exploitability_episodes.is_none() || schedule == DriftSchedule::PerStep,
+        "Exploitability is only estimated under the per_step drift schedule."
+    );
+    let resume = matches.is_present("resume");
+
+    let logger = logging::configured(
+        matches.value_of("log-level").unwrap().parse().unwrap(),
+        matches.value_of("log-json"),
+    ).expect("Failed to open log file.");
+
+    run_experiment(logger, save_dir, eval_interval, metrics_port, format, seed, probes, window, execution, schedule, drift_estimator, attack_observation, max_kl, normalise_rewards, drift, stopping, penalty, liquidation, initial, log_dynamics, exploitability_episodes, resume);
 }
@@ -0,0 +1,94 @@
+extern crate algo_hft;
+extern crate clap;
+extern crate rsrl;
+#[macro_use]
+extern crate slog;
+
+use algo_hft::{
+    agents::{load_trader_for, training::value_error::{heatmap, value_errors}},
+    env::Env,
+    output::{Format, Output},
+};
+use clap::{App, Arg};
+use rsrl::{core::SeedStream, domains::Domain, logging};
+use std::fs;
+
+fn parse_list(s: &str) -> Vec<f64> {
+    s.split(',').map(|x| x.trim().parse().expect("Grid points must be numbers.")).collect()
+}
+
+fn main() {
+    let matches = App::new("Critic value error")
+        .arg(Arg::with_name("trader_path")
+                .index(1)
+                .required(true))
+        .arg(Arg::with_name("n_rollouts")
+                .index(2)
+                .help("Number of episodes per start state")
+                .required(true))
+        .arg(Arg::with_name("times")
+                .long("times")
+                .help("Comma-separated start times, in [0, 1)")
+                .takes_value(true)
+                .default_value("0,0.2,0.4,0.6,0.8,0.95"))
+        .arg(Arg::with_name("invs")
+                .long("invs")
+                .help("Comma-separated start inventories")
+                .takes_value(true)
+                .default_value("-10,-5,-2,0,2,5,10"))
+        .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .default_value("0"))
+        .arg(Arg::with_name("save_dir")
+                .long("save_dir")
+                .takes_value(true)
+                .default_value("/tmp/"))
+        .get_matches();
+
+    let n_rollouts: usize = matches.value_of("n_rollouts").unwrap().parse().unwrap();
+    let seeds = SeedStream::new(matches.value_of("seed").unwrap().parse().unwrap());
+    let save_dir = matches.value_of("save_dir").unwrap();
+
+    let times = parse_list(matches.value_of("times").unwrap());
+    let invs = parse_list(matches.value_of("invs").unwrap());
+
+    let mut trader = load_trader_for(
+        matches.value_of("trader_path").unwrap().to_string(), &Env::default_with_drift().state_space()
+    ).expect("Failed to load trader.");
+
+    let logger = logging::root(logging::stdout());
+    let errors = value_errors(Env::default_with_drift, &mut trader, &times, &invs, n_rollouts, seeds)
+        .expect("Invalid start states.");
+
+    let mut file_logger = Output::to_path(format!("{}/value_error", save_dir), Format::Csv).unwrap();
+
+    for e in errors.iter() {
+        file_logger.serialize(e).ok();
+    }
+
+    file_logger.flush().ok();
+
+    let mae = errors.iter().map(|e| e.error.abs()).sum::<f64>() / errors.len() as f64;
+    let worst = errors.iter().max_by(|a, b| a.error.abs().partial_cmp(&b.error.abs()).unwrap()).unwrap();
+
+    info!(logger, "value error";
+        "mean_abs_error" => mae,
+        "worst_time" => worst.time,
+        "worst_inv" => worst.inv,
+        "worst_error" => format!("{} +/- {}", worst.error, worst.error_stderr),
+    );
+
+    let md = format!(
+        "# Critic value error\n\nMean absolute error: {:.3}\n\n\
+         ## Predicted minus Monte-Carlo value\n\n{}\n\
+         ## Predicted value\n\n{}\n\
+         ## Monte-Carlo value\n\n{}",
+        mae,
+        heatmap(&errors, |e| e.error),
+        heatmap(&errors, |e| e.predicted),
+        heatmap(&errors, |e| e.return_mean),
+    );
+
+    fs::write(format!("{}/value_error.md", save_dir), md).expect("Failed to write heatmaps.");
+}
@@ -0,0 +1,33 @@
+//! Clocks by which the environment advances on each step.
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Clock {
+    /// One increment of `dt` per step.
+    Fixed,
+
+    /// Increments of `dt` with the quotes held fixed until the next event: a
+    /// fill on either side, or a move of the midprice by at least `tick`
+    /// since the step began. Steps are fewer in quiet markets, and the time
+    /// each took is reported by the environment.
+    Event { tick: f64 },
+}
+
+impl Default for Clock {
+    fn default() -> Clock { Clock::Fixed }
+}
+
+impl FromStr for Clock {
+    type Err = String;
+
+    /// Parse `fixed` or `event:tick`.
+    fn from_str(s: &str) -> Result<Clock, String> {
+        let mut parts = s.splitn(2, ':');
+
+        match (parts.next().unwrap().trim(), parts.next().map(|x| x.trim().parse::<f64>())) {
+            ("fixed", None) => Ok(Clock::Fixed),
+            ("event", Some(Ok(tick))) if tick > 0.0 => Ok(Clock::Event { tick, }),
+            _ => Err(format!("Clock `{}` must be fixed or event:tick with tick > 0.", s)),
+        }
+    }
+}
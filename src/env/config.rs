@@ -0,0 +1,57 @@
+//! Serialisable configuration of the environment's market rules and costs.
+//!
+//! These are the settings that determine what a trade earns, as opposed to
+//! the dynamics and the trader's observations, so that a run can record
+//! exactly which costs its rewards were measured under.
+use crate::env::{clock::Clock, fees::FeeSchedule, liquidation::Liquidation, penalty::InventoryPenalty};
+#[cfg(feature = "fs")]
+use crate::error::Result;
+#[cfg(feature = "fs")]
+use std::{fs::File, io::{BufReader, BufWriter}};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EnvConfig {
+    pub order_size: f64,
+    pub hazard_rate: f64,
+    pub clock: Clock,
+    pub inventory_penalty: InventoryPenalty,
+    pub liquidation: Liquidation,
+    pub fees: FeeSchedule,
+}
+
+impl EnvConfig {
+    pub fn with_fees(self, fees: FeeSchedule) -> EnvConfig {
+        EnvConfig { fees, ..self }
+    }
+
+    /// Write the configuration as JSON to `path`.
+    #[cfg(feature = "fs")]
+    pub fn save(&self, path: &str) -> Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+
+        serde_json::to_writer_pretty(writer, self).map_err(|e| ::std::io::Error::from(e).into())
+    }
+
+    /// Read a configuration written by `save`.
+    #[cfg(feature = "fs")]
+    pub fn load(path: &str) -> Result<EnvConfig> {
+        let reader = BufReader::new(File::open(path)?);
+
+        serde_json::from_reader(reader).map_err(|e| ::std::io::Error::from(e).into())
+    }
+}
+
+impl Default for EnvConfig {
+    /// Unit orders over a fixed horizon, liquidated at the midprice without
+    /// fees, with the default inventory penalty.
+    fn default() -> EnvConfig {
+        EnvConfig {
+            order_size: 1.0,
+            hazard_rate: 0.0,
+            clock: Clock::default(),
+            inventory_penalty: InventoryPenalty::default(),
+            liquidation: Liquidation::default(),
+            fees: FeeSchedule::default(),
+        }
+    }
+}
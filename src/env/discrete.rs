@@ -0,0 +1,157 @@
+//! Finite grid over the quote space, for use with discrete-action algorithms.
+use crate::{
+    env::{
+        Env,
+        dynamics::{execution::ExecutionDynamics, price::PriceDynamics},
+    },
+    error::{Error, Result},
+};
+use rsrl::{
+    domains::{Domain, Observation, Transition},
+    geometry::{continuous::Interval, discrete::Ordinal, product::LinearSpace, BoundedSpace, Vector},
+};
+use std::str::FromStr;
+
+fn linspace(bounds: Interval, n: usize) -> Result<Vec<f64>> {
+    let lb = bounds.inf().unwrap_or(::std::f64::NEG_INFINITY);
+    let ub = bounds.sup().unwrap_or(::std::f64::INFINITY);
+
+    if !lb.is_finite() {
+        return Err(Error::InvalidParameter { name: "grid_lower_bound", value: lb });
+    }
+
+    if !ub.is_finite() {
+        return Err(Error::InvalidParameter { name: "grid_upper_bound", value: ub });
+    }
+
+    if n == 0 {
+        return Err(Error::InvalidParameter { name: "grid_points", value: 0.0 });
+    }
+
+    Ok(if n == 1 {
+        vec![(lb + ub) / 2.0]
+    } else {
+        (0..n).map(|i| lb + (ub - lb) * i as f64 / (n - 1) as f64).collect()
+    })
+}
+
+/// Product grid of reservation price offsets and half-spreads.
+///
+/// Actions enumerate the grid with the reservation offset varying slowest.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuoteGrid {
+    pub reservation: Vec<f64>,
+    pub half_spread: Vec<f64>,
+}
+
+impl QuoteGrid {
+    /// Grid over the given points of each axis, which must be non-empty and
+    /// finite.
+    pub fn new(reservation: Vec<f64>, half_spread: Vec<f64>) -> Result<QuoteGrid> {
+        for &(name, axis) in &[("reservation", &reservation), ("half_spread", &half_spread)] {
+            if axis.is_empty() {
+                return Err(Error::InvalidParameter { name, value: 0.0 });
+            }
+
+            if let Some(&x) = axis.iter().find(|x| !x.is_finite()) {
+                return Err(Error::InvalidParameter { name, value: x });
+            }
+        }
+
+        Ok(QuoteGrid { reservation, half_spread, })
+    }
+
+    /// Evenly spaced grid including the endpoints of each interval, which
+    /// must be bounded.
+    pub fn uniform(reservation: Interval, n_reservation: usize, half_spread: Interval, n_half_spread: usize) -> Result<QuoteGrid> {
+        QuoteGrid::new(linspace(reservation, n_reservation)?, linspace(half_spread, n_half_spread)?)
+    }
+
+    pub fn len(&self) -> usize { self.reservation.len() * self.half_spread.len() }
+
+    /// Reservation price offset and half-spread of an action.
+    pub fn decode(&self, action: usize) -> (f64, f64) {
+        let n = self.half_spread.len();
+
+        (self.reservation[action / n], self.half_spread[action % n])
+    }
+
+    /// `[ask, bid]` offsets of an action, as consumed by `Env::step`.
+    pub fn quotes(&self, action: usize) -> [f64; 2] {
+        let (rp, hs) = self.decode(action);
+
+        [rp + hs, hs - rp]
+    }
+}
+
+impl FromStr for QuoteGrid {
+    type Err = String;
+
+    /// Parse a grid of the form `rp_min,rp_max,n_rp;hs_min,hs_max,n_hs`.
+    fn from_str(s: &str) -> ::std::result::Result<QuoteGrid, String> {
+        let axis = |part: &str| -> ::std::result::Result<(Interval, usize), String> {
+            let fields: Vec<&str> = part.split(',').map(|x| x.trim()).collect();
+
+            match fields.as_slice() {
+                &[lb, ub, n] => {
+                    let lb = lb.parse::<f64>().map_err(|e| format!("Invalid grid `{}`: {}.", s, e))?;
+                    let ub = ub.parse::<f64>().map_err(|e| format!("Invalid grid `{}`: {}.", s, e))?;
+                    let n = n.parse::<usize>().map_err(|e| format!("Invalid grid `{}`: {}.", s, e))?;
+
+                    if n == 0 || lb > ub {
+                        Err(format!("Grid `{}` requires ordered bounds and at least one point per axis.", s))
+                    } else {
+                        Ok((Interval::bounded(lb, ub), n))
+                    }
+                },
+                _ => Err(format!("Grid `{}` must be of the form rp_min,rp_max,n_rp;hs_min,hs_max,n_hs.", s)),
+            }
+        };
+
+        let mut parts = s.splitn(2, ';');
+        let (rp, n_rp) = axis(parts.next().unwrap())?;
+        let (hs, n_hs) = axis(parts.next().ok_or_else(|| {
+            format!("Grid `{}` must be of the form rp_min,rp_max,n_rp;hs_min,hs_max,n_hs.", s)
+        })?)?;
+
+        QuoteGrid::uniform(rp, n_rp, hs, n_hs).map_err(|e| format!("Invalid grid `{}`: {}.", s, e))
+    }
+}
+
+/// Environment whose actions are indices into a `QuoteGrid`.
+#[derive(Debug)]
+pub struct Discretised<P, E> {
+    pub env: Env<P, E>,
+    pub grid: QuoteGrid,
+}
+
+impl<P, E> Discretised<P, E> {
+    pub fn new(env: Env<P, E>, grid: QuoteGrid) -> Discretised<P, E> { Discretised { env, grid, } }
+}
+
+impl<P: PriceDynamics, E: ExecutionDynamics> Discretised<P, E> {
+    pub fn seeded(self, seed: u64) -> Self {
+        Discretised { env: self.env.seeded(seed), grid: self.grid, }
+    }
+}
+
+impl<P: PriceDynamics, E: ExecutionDynamics> Domain for Discretised<P, E> {
+    type StateSpace = LinearSpace<Interval>;
+    type ActionSpace = Ordinal;
+
+    fn emit(&self) -> Observation<Vector<f64>> { self.env.emit() }
+
+    fn step(&mut self, action: usize) -> Transition<Vector<f64>, usize> {
+        self.env.step(self.grid.quotes(action)).replace_action(action)
+    }
+
+    fn is_terminal(&self) -> bool { self.env.is_terminal() }
+
+    fn reward(&self, from: &Observation<Vector<f64>>, to: &Observation<Vector<f64>>) -> f64 {
+        self.env.reward(from, to)
+    }
+
+    fn state_space(&self) -> Self::StateSpace { self.env.state_space() }
+
+    fn action_space(&self) -> Ordinal { Ordinal::new(self.grid.len()) }
+}
@@ -0,0 +1,512 @@
+//! Price and execution dynamics composed at runtime from a declarative
+//! specification, so that new combinations of models and wrappers need no
+//! new code.
+//!
+//! A specification names a base price model and execution model, wrappers
+//! stacked on each in the order listed, and optionally the adverse selection
+//! applied after fills. With the `toml-dynamics` feature it can be read from
+//! a TOML file such as:
+//!
+//! ```toml
+//! dt = 0.005
+//! price = 100.0
+//!
+//! [price_dynamics]
+//! model = "geometric_brownian_motion"
+//! drift = 0.0
+//! volatility = 0.02
+//!
+//! [[price_wrappers]]
+//! wrapper = "jumps"
+//! rate = 5.0
+//! size = { Normal = { mean = 0.0, stddev = 1.0 } }
+//!
+//! [[price_wrappers]]
+//! wrapper = "seasonal"
+//! period = 1.0
+//! profile = [1.5, 1.0, 1.0, 1.5]
+//!
+//! [execution_dynamics]
+//! model = "per_side"
+//! ask = { model = "poisson", scale = 140.0, decay = 1.5 }
+//! bid = { model = "poisson", scale = 120.0, decay = 1.5 }
+//!
+//! [[execution_wrappers]]
+//! wrapper = "queue_position"
+//! depth = 5.0
+//! trade_volume = 2.0
+//!
+//! [adverse_selection]
+//! jump = 0.01
+//! drift = 0.0
+//! decay = 0.0
+//! ```
+use crate::{
+    env::dynamics::{
+        ASDynamics,
+        execution::{
+            AdverseSelection, ExecutionDynamics, FillSize, IntensityMapping, LinearRate, PerSide, PoissonRate,
+            PowerLawRate, QueuePosition, Side,
+        },
+        price::{
+            BrownianMotionWithDrift, FractionalBrownianMotion, Garch, GeometricBrownianMotion, Heston, JumpDiffusion,
+            JumpSize, Jumps, OrnsteinUhlenbeckWithDrift, PriceDynamics, Seasonal,
+        },
+    },
+    error::{Error, Result},
+};
+use rand::{rngs::SmallRng, FromEntropy, Rng};
+#[cfg(feature = "toml-dynamics")]
+use std::{fs, io, str::FromStr};
+
+/// Price model at the base of a stack of wrappers.
+///
+/// Parameters are as for the constructor of the corresponding model, at the
+/// time step of the enclosing `CompositeSpec`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum PriceSpec {
+    BrownianMotion {
+        #[serde(default)]
+        drift: f64,
+        volatility: f64,
+    },
+    GeometricBrownianMotion { drift: f64, volatility: f64 },
+
+    /// Mean reversion at `rate` towards the level `drift`.
+    OrnsteinUhlenbeck {
+        rate: f64,
+        #[serde(default)]
+        drift: f64,
+        volatility: f64,
+    },
+
+    /// GARCH(1, 1) with the given long-run volatility per unit time.
+    Garch { drift: f64, volatility: f64, alpha: f64, beta: f64 },
+    Heston { drift: f64, kappa: f64, theta: f64, xi: f64, rho: f64 },
+    FractionalBrownianMotion { drift: f64, volatility: f64, hurst: f64 },
+    JumpDiffusion { drift: f64, volatility: f64, jump_rate: f64, jump_size: JumpSize },
+}
+
+/// Wrapper stacked on the price model.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "wrapper", rename_all = "snake_case")]
+pub enum PriceWrapper {
+    /// Compound Poisson jumps arriving at `rate` per unit time.
+    Jumps { rate: f64, size: JumpSize },
+
+    /// Increments scaled by a periodic, piecewise-constant profile.
+    Seasonal { period: f64, profile: Vec<f64> },
+}
+
+/// Execution model at the base of a stack of wrappers.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum ExecutionSpec {
+    Poisson {
+        scale: f64,
+        decay: f64,
+        #[serde(default)]
+        mapping: IntensityMapping,
+        #[serde(default)]
+        fill_size: FillSize,
+    },
+    Linear {
+        scale: f64,
+        slope: f64,
+        #[serde(default)]
+        mapping: IntensityMapping,
+        #[serde(default)]
+        fill_size: FillSize,
+    },
+    PowerLaw {
+        scale: f64,
+        exponent: f64,
+        #[serde(default = "default_min_offset")]
+        min_offset: f64,
+        #[serde(default)]
+        mapping: IntensityMapping,
+        #[serde(default)]
+        fill_size: FillSize,
+    },
+
+    /// Independent models for the ask and bid sides of the book.
+    PerSide { ask: Box<ExecutionSpec>, bid: Box<ExecutionSpec> },
+}
+
+fn default_min_offset() -> f64 { PowerLawRate::DEFAULT_MIN_OFFSET }
+
+/// Wrapper stacked on the execution model.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "wrapper", rename_all = "snake_case")]
+pub enum ExecutionWrapper {
+    /// Orders wait in a simulated queue at their price level; see
+    /// `QueuePosition`.
+    QueuePosition {
+        depth: f64,
+        trade_volume: f64,
+        #[serde(default)]
+        cancel_rate: f64,
+        #[serde(default)]
+        tolerance: Option<f64>,
+    },
+}
+
+/// Price dynamics built from a `PriceSpec` and its wrappers.
+#[derive(Debug)]
+pub enum AnyPrice {
+    BrownianMotion(BrownianMotionWithDrift),
+    GeometricBrownianMotion(GeometricBrownianMotion),
+    OrnsteinUhlenbeck(OrnsteinUhlenbeckWithDrift),
+    Garch(Garch),
+    Heston(Heston),
+    FractionalBrownianMotion(FractionalBrownianMotion),
+    JumpDiffusion(JumpDiffusion),
+
+    Jumps(Box<Jumps<AnyPrice>>),
+    Seasonal(Box<Seasonal<AnyPrice>>),
+}
+
+macro_rules! dispatch_price {
+    ($self:expr, $p:ident => $body:expr) => {
+        match $self {
+            AnyPrice::BrownianMotion($p) => $body,
+            AnyPrice::GeometricBrownianMotion($p) => $body,
+            AnyPrice::OrnsteinUhlenbeck($p) => $body,
+            AnyPrice::Garch($p) => $body,
+            AnyPrice::Heston($p) => $body,
+            AnyPrice::FractionalBrownianMotion($p) => $body,
+            AnyPrice::JumpDiffusion($p) => $body,
+            AnyPrice::Jumps($p) => $body,
+            AnyPrice::Seasonal($p) => $body,
+        }
+    };
+}
+
+impl PriceDynamics for AnyPrice {
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, x: f64) -> f64 {
+        dispatch_price!(self, p => p.sample_increment(rng, x))
+    }
+
+    fn drift(&self) -> f64 { dispatch_price!(self, p => p.drift()) }
+
+    fn volatility(&self) -> f64 { dispatch_price!(self, p => p.volatility()) }
+
+    fn gaussian_increment(&self, x: f64) -> Option<(f64, f64)> {
+        dispatch_price!(self, p => p.gaussian_increment(x))
+    }
+}
+
+/// Execution dynamics built from an `ExecutionSpec` and its wrappers.
+#[derive(Clone, Debug)]
+pub enum AnyExecution {
+    Poisson(PoissonRate),
+    Linear(LinearRate),
+    PowerLaw(PowerLawRate),
+
+    PerSide(Box<PerSide<AnyExecution, AnyExecution>>),
+    QueuePosition(Box<QueuePosition<AnyExecution>>),
+}
+
+macro_rules! dispatch_execution {
+    ($self:expr, $e:ident => $body:expr) => {
+        match $self {
+            AnyExecution::Poisson($e) => $body,
+            AnyExecution::Linear($e) => $body,
+            AnyExecution::PowerLaw($e) => $body,
+            AnyExecution::PerSide($e) => $body,
+            AnyExecution::QueuePosition($e) => $body,
+        }
+    };
+}
+
+impl ExecutionDynamics for AnyExecution {
+    fn match_prob(&self, offset: f64) -> f64 { dispatch_execution!(self, e => e.match_prob(offset)) }
+
+    fn is_capped(&self, offset: f64) -> bool { dispatch_execution!(self, e => e.is_capped(offset)) }
+
+    fn side_match_prob(&self, side: Side, offset: f64) -> f64 {
+        dispatch_execution!(self, e => e.side_match_prob(side, offset))
+    }
+
+    fn side_is_capped(&self, side: Side, offset: f64) -> bool {
+        dispatch_execution!(self, e => e.side_is_capped(side, offset))
+    }
+
+    fn sample_fill_size<R: Rng>(&self, rng: &mut R, side: Side, posted: f64) -> f64 {
+        dispatch_execution!(self, e => e.sample_fill_size(rng, side, posted))
+    }
+
+    fn advance(&mut self) { dispatch_execution!(self, e => e.advance()) }
+
+    fn post(&mut self, side: Side, offset: f64) { dispatch_execution!(self, e => e.post(side, offset)) }
+
+    fn filled(&mut self, side: Side) { dispatch_execution!(self, e => e.filled(side)) }
+}
+
+impl PriceSpec {
+    fn build(&self, dt: f64) -> Result<AnyPrice> {
+        Ok(match *self {
+            PriceSpec::BrownianMotion { drift, volatility } =>
+                AnyPrice::BrownianMotion(BrownianMotionWithDrift::new(dt, drift, volatility)),
+            PriceSpec::GeometricBrownianMotion { drift, volatility } =>
+                AnyPrice::GeometricBrownianMotion(GeometricBrownianMotion::new(dt, drift, volatility)),
+            PriceSpec::OrnsteinUhlenbeck { rate, drift, volatility } =>
+                AnyPrice::OrnsteinUhlenbeck(OrnsteinUhlenbeckWithDrift::new(dt, rate, drift, volatility)),
+            PriceSpec::Garch { drift, volatility, alpha, beta } =>
+                AnyPrice::Garch(Garch::with_long_run_volatility(dt, drift, volatility, alpha, beta)?),
+            PriceSpec::Heston { drift, kappa, theta, xi, rho } =>
+                AnyPrice::Heston(Heston::new(dt, drift, kappa, theta, xi, rho)?),
+            PriceSpec::FractionalBrownianMotion { drift, volatility, hurst } =>
+                AnyPrice::FractionalBrownianMotion(FractionalBrownianMotion::new(dt, drift, volatility, hurst)?),
+            PriceSpec::JumpDiffusion { drift, volatility, jump_rate, jump_size } =>
+                AnyPrice::JumpDiffusion(JumpDiffusion::new(dt, drift, volatility, jump_rate, jump_size)?),
+        })
+    }
+}
+
+impl PriceWrapper {
+    fn wrap(&self, dt: f64, base: AnyPrice) -> Result<AnyPrice> {
+        Ok(match *self {
+            PriceWrapper::Jumps { rate, size } => AnyPrice::Jumps(Box::new(Jumps::new(dt, base, rate, size)?)),
+            PriceWrapper::Seasonal { period, ref profile } =>
+                AnyPrice::Seasonal(Box::new(Seasonal::new(dt, base, period, profile.clone())?)),
+        })
+    }
+}
+
+impl ExecutionSpec {
+    fn build(&self, dt: f64) -> AnyExecution {
+        match *self {
+            ExecutionSpec::Poisson { scale, decay, mapping, fill_size } => AnyExecution::Poisson(
+                PoissonRate::new(dt, scale, decay).with_mapping(mapping).with_fill_size(fill_size)
+            ),
+            ExecutionSpec::Linear { scale, slope, mapping, fill_size } => AnyExecution::Linear(
+                LinearRate::new(dt, scale, slope).with_mapping(mapping).with_fill_size(fill_size)
+            ),
+            ExecutionSpec::PowerLaw { scale, exponent, min_offset, mapping, fill_size } => AnyExecution::PowerLaw(
+                PowerLawRate::new(dt, scale, exponent)
+                    .with_min_offset(min_offset)
+                    .with_mapping(mapping)
+                    .with_fill_size(fill_size)
+            ),
+            ExecutionSpec::PerSide { ref ask, ref bid } =>
+                AnyExecution::PerSide(Box::new(PerSide::new(ask.build(dt), bid.build(dt)))),
+        }
+    }
+}
+
+impl ExecutionWrapper {
+    fn wrap(&self, base: AnyExecution) -> Result<AnyExecution> {
+        match *self {
+            ExecutionWrapper::QueuePosition { depth, trade_volume, cancel_rate, tolerance } => {
+                if !(depth >= 0.0 && depth.is_finite()) {
+                    return Err(Error::InvalidParameter { name: "depth", value: depth });
+                }
+
+                if !(trade_volume > 0.0) {
+                    return Err(Error::InvalidParameter { name: "trade_volume", value: trade_volume });
+                }
+
+                if !(cancel_rate >= 0.0 && cancel_rate <= 1.0) {
+                    return Err(Error::InvalidParameter { name: "cancel_rate", value: cancel_rate });
+                }
+
+                let queue = QueuePosition::new(base, depth, trade_volume).with_cancel_rate(cancel_rate);
+
+                Ok(AnyExecution::QueuePosition(Box::new(match tolerance {
+                    Some(tolerance) => queue.with_tolerance(tolerance),
+                    None => queue,
+                })))
+            },
+        }
+    }
+}
+
+/// Serialisable specification of composite price and execution dynamics.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompositeSpec {
+    pub dt: f64,
+    pub price: f64,
+
+    pub price_dynamics: PriceSpec,
+    #[serde(default)]
+    pub price_wrappers: Vec<PriceWrapper>,
+
+    pub execution_dynamics: ExecutionSpec,
+    #[serde(default)]
+    pub execution_wrappers: Vec<ExecutionWrapper>,
+
+    #[serde(default)]
+    pub adverse_selection: Option<AdverseSelection>,
+}
+
+impl CompositeSpec {
+    /// Construct the dynamics, with a generator to be seeded by the caller.
+    ///
+    /// Stateful models, such as `Garch` and `Seasonal`, evolve as they are
+    /// sampled, so the dynamics should be built afresh for each episode.
+    pub fn build(&self) -> Result<ASDynamics<AnyPrice, AnyExecution>> {
+        let price_dynamics = self.price_wrappers.iter()
+            .try_fold(self.price_dynamics.build(self.dt)?, |p, w| w.wrap(self.dt, p))?;
+        let execution_dynamics = self.execution_wrappers.iter()
+            .try_fold(self.execution_dynamics.build(self.dt), |e, w| w.wrap(e))?;
+
+        let dynamics = ASDynamics::new(
+            self.dt, self.price, SmallRng::from_entropy(), price_dynamics, execution_dynamics,
+        )?;
+
+        Ok(match self.adverse_selection {
+            Some(adverse_selection) => dynamics.with_adverse_selection(adverse_selection),
+            None => dynamics,
+        })
+    }
+
+    /// Read a TOML specification from `path`, checking that it builds.
+    #[cfg(feature = "toml-dynamics")]
+    pub fn load(path: &str) -> Result<CompositeSpec> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+#[cfg(feature = "toml-dynamics")]
+impl FromStr for CompositeSpec {
+    type Err = Error;
+
+    /// Parse a TOML specification, checking that it builds.
+    fn from_str(s: &str) -> Result<CompositeSpec> {
+        let spec: CompositeSpec = ::toml::from_str(s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        spec.build().map(|_| spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> CompositeSpec {
+        CompositeSpec {
+            dt: 0.005,
+            price: 100.0,
+
+            price_dynamics: PriceSpec::GeometricBrownianMotion { drift: 0.0, volatility: 0.02 },
+            price_wrappers: vec![
+                PriceWrapper::Jumps { rate: 5.0, size: JumpSize::Normal { mean: 0.0, stddev: 1.0 } },
+                PriceWrapper::Seasonal { period: 1.0, profile: vec![1.5, 1.0, 1.0, 1.5] },
+            ],
+
+            execution_dynamics: ExecutionSpec::PerSide {
+                ask: Box::new(ExecutionSpec::Poisson {
+                    scale: 140.0, decay: 1.5, mapping: IntensityMapping::Clamped, fill_size: FillSize::Full,
+                }),
+                bid: Box::new(ExecutionSpec::Linear {
+                    scale: 140.0, slope: 100.0, mapping: IntensityMapping::Exact, fill_size: FillSize::Full,
+                }),
+            },
+            execution_wrappers: vec![ExecutionWrapper::QueuePosition {
+                depth: 5.0, trade_volume: 2.0, cancel_rate: 0.0, tolerance: None,
+            }],
+
+            adverse_selection: Some(AdverseSelection::jump(0.01)),
+        }
+    }
+
+    #[test]
+    fn test_composite_stacking() {
+        let mut dynamics = spec().build().unwrap();
+
+        match dynamics.price_dynamics {
+            AnyPrice::Seasonal(ref s) => match s.base {
+                AnyPrice::Jumps(ref j) => match j.base {
+                    AnyPrice::GeometricBrownianMotion(_) => {},
+                    ref p => panic!("Unexpected base price dynamics: {:?}.", p),
+                },
+                ref p => panic!("Unexpected jump base: {:?}.", p),
+            },
+            ref p => panic!("Unexpected outer price dynamics: {:?}.", p),
+        }
+
+        match dynamics.execution_dynamics {
+            AnyExecution::QueuePosition(ref q) => match q.base {
+                AnyExecution::PerSide(_) => {},
+                ref e => panic!("Unexpected queue base: {:?}.", e),
+            },
+            ref e => panic!("Unexpected execution dynamics: {:?}.", e),
+        }
+
+        assert!(dynamics.adverse_selection.is_some());
+
+        dynamics.reseed(0);
+
+        for _ in 0..200 {
+            dynamics.innovate();
+        }
+
+        assert!(dynamics.price.is_finite() && dynamics.price > 0.0);
+    }
+
+    #[test]
+    fn test_composite_invalid() {
+        let mut s = spec();
+        s.price_wrappers.push(PriceWrapper::Seasonal { period: 1.0, profile: vec![] });
+        assert!(s.build().is_err());
+
+        let mut s = spec();
+        s.price_dynamics = PriceSpec::Garch { drift: 0.0, volatility: 2.0, alpha: 0.5, beta: 0.6 };
+        assert!(s.build().is_err());
+
+        let mut s = spec();
+        s.execution_wrappers = vec![ExecutionWrapper::QueuePosition {
+            depth: 5.0, trade_volume: 0.0, cancel_rate: 0.0, tolerance: None,
+        }];
+        assert!(s.build().is_err());
+
+        let mut s = spec();
+        s.dt = 0.0;
+        assert!(s.build().is_err());
+    }
+
+    #[cfg(feature = "toml-dynamics")]
+    #[test]
+    fn test_composite_toml() {
+        let parsed: CompositeSpec = r#"
+            dt = 0.005
+            price = 100
+
+            [price_dynamics]
+            model = "geometric_brownian_motion"
+            drift = 0.0
+            volatility = 0.02
+
+            [[price_wrappers]]
+            wrapper = "jumps"
+            rate = 5.0
+            size = { Normal = { mean = 0.0, stddev = 1.0 } }
+
+            [[price_wrappers]]
+            wrapper = "seasonal"
+            period = 1.0
+            profile = [1.5, 1.0, 1.0, 1.5]
+
+            [execution_dynamics]
+            model = "per_side"
+            ask = { model = "poisson", scale = 140.0, decay = 1.5 }
+            bid = { model = "linear", scale = 140.0, slope = 100.0, mapping = "Exact" }
+
+            [[execution_wrappers]]
+            wrapper = "queue_position"
+            depth = 5.0
+            trade_volume = 2.0
+
+            [adverse_selection]
+            jump = 0.01
+            drift = 0.0
+            decay = 0.0
+        "#.parse().unwrap();
+
+        assert_eq!(parsed, spec());
+
+        assert!("dt = 0.005\nprice = 100.0\n".parse::<CompositeSpec>().is_err());
+    }
+}
@@ -0,0 +1,58 @@
+//! Random numbers drawn in bulk ahead of their use.
+//!
+//! Normals and uniforms are drawn a block at a time into buffers that are
+//! allocated once and refilled in place whenever they run out, so a step
+//! reads its draws from memory instead of calling into the generator for
+//! each one. Note that the resulting stream of draws differs from that of
+//! drawing on demand, even for the same seed.
+use rand::{distributions::StandardNormal, Rng};
+use rsrl::geometry::Vector;
+
+#[derive(Clone, Debug)]
+pub struct Draws {
+    normals: Vector<f64>,
+    uniforms: Vector<f64>,
+
+    i_normal: usize,
+    i_uniform: usize,
+}
+
+impl Draws {
+    /// Buffers of `n_normals` standard normals and `n_uniforms` uniforms on
+    /// _[0, 1)_, filled on first use and whenever exhausted.
+    pub fn new(n_normals: usize, n_uniforms: usize) -> Draws {
+        Draws {
+            normals: Vector::zeros(n_normals.max(1)),
+            uniforms: Vector::zeros(n_uniforms.max(1)),
+
+            i_normal: n_normals.max(1),
+            i_uniform: n_uniforms.max(1),
+        }
+    }
+
+    /// Discard any draws not yet used, e.g. after reseeding the generator.
+    pub fn clear(&mut self) {
+        self.i_normal = self.normals.len();
+        self.i_uniform = self.uniforms.len();
+    }
+
+    pub fn next_normal<R: Rng>(&mut self, rng: &mut R) -> f64 {
+        if self.i_normal == self.normals.len() {
+            self.normals.mapv_inplace(|_| rng.sample(StandardNormal));
+            self.i_normal = 0;
+        }
+
+        self.i_normal += 1;
+        self.normals[self.i_normal - 1]
+    }
+
+    pub fn next_uniform<R: Rng>(&mut self, rng: &mut R) -> f64 {
+        if self.i_uniform == self.uniforms.len() {
+            self.uniforms.mapv_inplace(|_| rng.gen::<f64>());
+            self.i_uniform = 0;
+        }
+
+        self.i_uniform += 1;
+        self.uniforms[self.i_uniform - 1]
+    }
+}
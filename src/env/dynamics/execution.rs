@@ -1,25 +1,241 @@
+use rand::Rng;
+use std::str::FromStr;
+
+/// Side of the book on which an order rests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    Ask,
+    Bid,
+}
+
 pub trait ExecutionDynamics {
     fn match_prob(&self, offset: f64) -> f64;
+
+    /// Returns true if the match probability at `offset` was truncated to
+    /// remain a valid probability.
+    fn is_capped(&self, _offset: f64) -> bool { false }
+
+    /// Match probability of an order on the given side of the book; defaults
+    /// to the side-agnostic `match_prob`.
+    fn side_match_prob(&self, _side: Side, offset: f64) -> f64 { self.match_prob(offset) }
+
+    /// Side-specific counterpart of `is_capped`.
+    fn side_is_capped(&self, _side: Side, offset: f64) -> bool { self.is_capped(offset) }
+
+    /// Sample the quantity executed against a matched order of size `posted`;
+    /// defaults to filling the order in full.
+    fn sample_fill_size<R: Rng>(&self, _rng: &mut R, _side: Side, posted: f64) -> f64 { posted }
+
+    /// Move on to the next step, before any orders are matched in it; only
+    /// needed by dynamics that vary over time.
+    fn advance(&mut self) {}
+
+    /// Note that an order rests at `offset` on `side` over the current step,
+    /// before it is matched; only needed by dynamics that track the agent's
+    /// orders.
+    fn post(&mut self, _side: Side, _offset: f64) {}
+
+    /// Note that the order on `side` was matched, and so left the book.
+    fn filled(&mut self, _side: Side) {}
 }
 
-#[derive(Debug)]
+/// Move of the midprice against the agent after each of its fills, as when
+/// trading with informed (toxic) flow.
+///
+/// Per unit filled, the midprice jumps by `jump` at the start of the next
+/// step, and picks up a drift of `drift` per unit time that decays at the
+/// rate `decay`; both are upwards after an ask is lifted and downwards after
+/// a bid is hit.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AdverseSelection {
+    pub jump: f64,
+    pub drift: f64,
+    pub decay: f64,
+}
+
+impl AdverseSelection {
+    pub fn new(jump: f64, drift: f64, decay: f64) -> AdverseSelection {
+        AdverseSelection { jump, drift, decay, }
+    }
+
+    /// Jump of `jump` per unit filled, without any drift.
+    pub fn jump(jump: f64) -> AdverseSelection { AdverseSelection::new(jump, 0.0, 0.0) }
+
+    /// Direction in which the midprice moves after a fill on `side`.
+    pub fn sign(side: Side) -> f64 {
+        match side {
+            Side::Ask => 1.0,
+            Side::Bid => -1.0,
+        }
+    }
+}
+
+impl FromStr for AdverseSelection {
+    type Err = String;
+
+    /// Parse `jump` or `jump,drift,decay`.
+    fn from_str(s: &str) -> Result<AdverseSelection, String> {
+        let params = s.split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid adverse selection `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match params.as_slice() {
+            &[jump] => Ok(AdverseSelection::jump(jump)),
+            &[jump, drift, decay] if decay >= 0.0 => Ok(AdverseSelection::new(jump, drift, decay)),
+            _ => Err(format!(
+                "Adverse selection `{}` must be of the form jump or jump,drift,decay with a non-negative decay.", s
+            )),
+        }
+    }
+}
+
+/// Distribution of the quantity executed against a matched order, in units,
+/// before capping by the posted size.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FillSize {
+    /// The order is filled in full.
+    Full,
+
+    /// Geometrically distributed on {1, 2, ...} with success probability `p`.
+    Geometric { p: f64 },
+
+    /// Discretised Pareto distribution on {1, 2, ...} with tail index `alpha`,
+    /// i.e. `P(size >= n) ~ n^-alpha`.
+    PowerLaw { alpha: f64 },
+}
+
+impl FillSize {
+    pub fn sample<R: Rng>(&self, rng: &mut R, posted: f64) -> f64 {
+        // Uniform on (0, 1], avoiding log(0) and division by zero below:
+        let mut u = || 1.0 - rng.gen::<f64>();
+
+        let size = match *self {
+            FillSize::Full => return posted,
+            FillSize::Geometric { p } => (u().ln() / (1.0 - p).ln()).ceil().max(1.0),
+            FillSize::PowerLaw { alpha } => u().powf(-1.0 / alpha).floor(),
+        };
+
+        size.min(posted)
+    }
+}
+
+impl Default for FillSize {
+    fn default() -> FillSize { FillSize::Full }
+}
+
+impl FromStr for FillSize {
+    type Err = String;
+
+    /// Parse `full`, `geometric:p` or `power_law:alpha`.
+    fn from_str(s: &str) -> Result<FillSize, String> {
+        let mut parts = s.splitn(2, ':');
+        let kind = parts.next().unwrap().trim();
+        let param = parts.next().map(|x| {
+            x.trim().parse::<f64>().map_err(|e| format!("Invalid fill size `{}`: {}.", s, e))
+        });
+
+        match (kind, param) {
+            ("full", None) => Ok(FillSize::Full),
+            ("geometric", Some(p)) => {
+                let p = p?;
+
+                if p > 0.0 && p <= 1.0 {
+                    Ok(FillSize::Geometric { p })
+                } else {
+                    Err(format!("Fill size `{}` requires p in (0, 1].", s))
+                }
+            },
+            ("power_law", Some(alpha)) => {
+                let alpha = alpha?;
+
+                if alpha > 0.0 {
+                    Ok(FillSize::PowerLaw { alpha })
+                } else {
+                    Err(format!("Fill size `{}` requires a positive alpha.", s))
+                }
+            },
+            _ => Err(format!("Fill size `{}` must be full, geometric:p or power_law:alpha.", s)),
+        }
+    }
+}
+
+/// Mapping from an arrival intensity over one step to a match probability.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntensityMapping {
+    /// `lambda*dt` clamped to [0, 1], i.e. the first-order approximation used
+    /// by Avellaneda & Stoikov (2008).
+    Clamped,
+
+    /// `1 - exp(-lambda*dt)`, the probability of at least one arrival of a
+    /// Poisson process over the step.
+    Exact,
+}
+
+impl Default for IntensityMapping {
+    fn default() -> IntensityMapping { IntensityMapping::Clamped }
+}
+
+impl FromStr for IntensityMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<IntensityMapping, String> {
+        match s {
+            "clamped" => Ok(IntensityMapping::Clamped),
+            "exact" => Ok(IntensityMapping::Exact),
+            _ => Err(format!("Unknown intensity mapping `{}`; expected clamped or exact.", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PoissonRate {
     dt: f64,
     pub scale: f64,
     pub decay: f64,
+    pub mapping: IntensityMapping,
+    pub fill_size: FillSize,
 }
 
 impl PoissonRate {
     pub fn new(dt: f64, scale: f64, decay: f64) -> PoissonRate {
-        PoissonRate { dt, scale, decay, }
+        PoissonRate {
+            dt, scale, decay,
+
+            mapping: IntensityMapping::default(),
+            fill_size: FillSize::default(),
+        }
+    }
+
+    pub fn with_mapping(self, mapping: IntensityMapping) -> PoissonRate {
+        PoissonRate { mapping, ..self }
+    }
+
+    pub fn with_fill_size(self, fill_size: FillSize) -> PoissonRate {
+        PoissonRate { fill_size, ..self }
+    }
+
+    /// Expected number of arrivals over one step at the given offset.
+    pub fn intensity(&self, offset: f64) -> f64 {
+        self.scale * (-self.decay * offset).exp() * self.dt
     }
 }
 
 impl ExecutionDynamics for PoissonRate {
     fn match_prob(&self, offset: f64) -> f64 {
-        let lambda = self.scale * (-self.decay * offset).exp();
+        let intensity = self.intensity(offset);
+
+        match self.mapping {
+            IntensityMapping::Clamped => intensity.max(0.0).min(1.0),
+            IntensityMapping::Exact => 1.0 - (-intensity.max(0.0)).exp(),
+        }
+    }
 
-        (lambda * self.dt).max(0.0).min(1.0)
+    fn is_capped(&self, offset: f64) -> bool {
+        self.mapping == IntensityMapping::Clamped && self.intensity(offset) > 1.0
+    }
+
+    fn sample_fill_size<R: Rng>(&self, rng: &mut R, _: Side, posted: f64) -> f64 {
+        self.fill_size.sample(rng, posted)
     }
 }
 
@@ -28,3 +244,439 @@ impl Default for PoissonRate {
         PoissonRate::new(0.005, 140.0, 1.5)
     }
 }
+
+impl FromStr for PoissonRate {
+    type Err = String;
+
+    /// Parse a rate of the form `scale,decay` at the default time step.
+    fn from_str(s: &str) -> Result<PoissonRate, String> {
+        let params = s.split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid rate `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match params.as_slice() {
+            &[scale, decay] => Ok(PoissonRate { scale, decay, ..PoissonRate::default() }),
+            _ => Err(format!("Rate `{}` must be of the form scale,decay.", s)),
+        }
+    }
+}
+
+/// Arrival intensity decaying linearly in the offset, `scale - slope*offset`,
+/// and clipped at zero; the alternative to `PoissonRate` considered by
+/// Avellaneda & Stoikov (2008).
+///
+/// Orders at offsets beyond `scale / slope` are never matched.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinearRate {
+    dt: f64,
+    pub scale: f64,
+    pub slope: f64,
+    pub mapping: IntensityMapping,
+    pub fill_size: FillSize,
+}
+
+impl LinearRate {
+    pub fn new(dt: f64, scale: f64, slope: f64) -> LinearRate {
+        LinearRate {
+            dt, scale, slope,
+
+            mapping: IntensityMapping::default(),
+            fill_size: FillSize::default(),
+        }
+    }
+
+    pub fn with_mapping(self, mapping: IntensityMapping) -> LinearRate {
+        LinearRate { mapping, ..self }
+    }
+
+    pub fn with_fill_size(self, fill_size: FillSize) -> LinearRate {
+        LinearRate { fill_size, ..self }
+    }
+
+    /// Expected number of arrivals over one step at the given offset.
+    pub fn intensity(&self, offset: f64) -> f64 {
+        (self.scale - self.slope * offset).max(0.0) * self.dt
+    }
+}
+
+impl ExecutionDynamics for LinearRate {
+    fn match_prob(&self, offset: f64) -> f64 {
+        let intensity = self.intensity(offset);
+
+        match self.mapping {
+            IntensityMapping::Clamped => intensity.min(1.0),
+            IntensityMapping::Exact => 1.0 - (-intensity).exp(),
+        }
+    }
+
+    fn is_capped(&self, offset: f64) -> bool {
+        self.mapping == IntensityMapping::Clamped && self.intensity(offset) > 1.0
+    }
+
+    fn sample_fill_size<R: Rng>(&self, rng: &mut R, _: Side, posted: f64) -> f64 {
+        self.fill_size.sample(rng, posted)
+    }
+}
+
+impl Default for LinearRate {
+    /// Tangent at zero offset to the default `PoissonRate`.
+    fn default() -> LinearRate {
+        LinearRate::new(0.005, 140.0, 210.0)
+    }
+}
+
+impl FromStr for LinearRate {
+    type Err = String;
+
+    /// Parse a rate of the form `scale,slope` at the default time step.
+    fn from_str(s: &str) -> Result<LinearRate, String> {
+        let params = s.split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid rate `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match params.as_slice() {
+            &[scale, slope] => Ok(LinearRate { scale, slope, ..LinearRate::default() }),
+            _ => Err(format!("Rate `{}` must be of the form scale,slope.", s)),
+        }
+    }
+}
+
+/// Arrival intensity decaying as a power of the offset, `scale *
+/// offset^-exponent`, as found in empirical studies of limit order books
+/// (e.g. Bouchaud et al., 2002).
+///
+/// The intensity diverges as the offset goes to zero, so offsets are floored
+/// at `min_offset` before it is evaluated.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PowerLawRate {
+    dt: f64,
+    pub scale: f64,
+    pub exponent: f64,
+    pub min_offset: f64,
+    pub mapping: IntensityMapping,
+    pub fill_size: FillSize,
+}
+
+impl PowerLawRate {
+    pub const DEFAULT_MIN_OFFSET: f64 = 0.01;
+
+    pub fn new(dt: f64, scale: f64, exponent: f64) -> PowerLawRate {
+        PowerLawRate {
+            dt, scale, exponent,
+
+            min_offset: PowerLawRate::DEFAULT_MIN_OFFSET,
+            mapping: IntensityMapping::default(),
+            fill_size: FillSize::default(),
+        }
+    }
+
+    pub fn with_min_offset(self, min_offset: f64) -> PowerLawRate {
+        PowerLawRate { min_offset, ..self }
+    }
+
+    pub fn with_mapping(self, mapping: IntensityMapping) -> PowerLawRate {
+        PowerLawRate { mapping, ..self }
+    }
+
+    pub fn with_fill_size(self, fill_size: FillSize) -> PowerLawRate {
+        PowerLawRate { fill_size, ..self }
+    }
+
+    /// Expected number of arrivals over one step at the given offset.
+    pub fn intensity(&self, offset: f64) -> f64 {
+        self.scale * offset.max(self.min_offset).powf(-self.exponent) * self.dt
+    }
+}
+
+impl ExecutionDynamics for PowerLawRate {
+    fn match_prob(&self, offset: f64) -> f64 {
+        let intensity = self.intensity(offset);
+
+        match self.mapping {
+            IntensityMapping::Clamped => intensity.max(0.0).min(1.0),
+            IntensityMapping::Exact => 1.0 - (-intensity.max(0.0)).exp(),
+        }
+    }
+
+    fn is_capped(&self, offset: f64) -> bool {
+        self.mapping == IntensityMapping::Clamped && self.intensity(offset) > 1.0
+    }
+
+    fn sample_fill_size<R: Rng>(&self, rng: &mut R, _: Side, posted: f64) -> f64 {
+        self.fill_size.sample(rng, posted)
+    }
+}
+
+impl Default for PowerLawRate {
+    fn default() -> PowerLawRate {
+        PowerLawRate::new(0.005, 30.0, 1.5)
+    }
+}
+
+impl FromStr for PowerLawRate {
+    type Err = String;
+
+    /// Parse a rate of the form `scale,exponent` or
+    /// `scale,exponent,min_offset` at the default time step.
+    fn from_str(s: &str) -> Result<PowerLawRate, String> {
+        let params = s.split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid rate `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        let rate = match params.as_slice() {
+            &[scale, exponent] => PowerLawRate { scale, exponent, ..PowerLawRate::default() },
+            &[scale, exponent, min_offset] => PowerLawRate { scale, exponent, min_offset, ..PowerLawRate::default() },
+            _ => return Err(format!("Rate `{}` must be of the form scale,exponent[,min_offset].", s)),
+        };
+
+        if rate.min_offset > 0.0 {
+            Ok(rate)
+        } else {
+            Err(format!("Rate `{}` requires a positive min_offset.", s))
+        }
+    }
+}
+
+/// Independent execution models for the ask and bid sides of the book.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PerSide<A, B> {
+    pub ask: A,
+    pub bid: B,
+}
+
+impl<A, B> PerSide<A, B> {
+    pub fn new(ask: A, bid: B) -> PerSide<A, B> { PerSide { ask, bid, } }
+}
+
+impl<E: Clone> PerSide<E, E> {
+    pub fn symmetric(dynamics: E) -> PerSide<E, E> { PerSide::new(dynamics.clone(), dynamics) }
+}
+
+impl<A: ExecutionDynamics, B: ExecutionDynamics> ExecutionDynamics for PerSide<A, B> {
+    /// Mean match probability over the two sides.
+    fn match_prob(&self, offset: f64) -> f64 {
+        (self.ask.match_prob(offset) + self.bid.match_prob(offset)) / 2.0
+    }
+
+    fn is_capped(&self, offset: f64) -> bool {
+        self.ask.is_capped(offset) || self.bid.is_capped(offset)
+    }
+
+    fn side_match_prob(&self, side: Side, offset: f64) -> f64 {
+        match side {
+            Side::Ask => self.ask.match_prob(offset),
+            Side::Bid => self.bid.match_prob(offset),
+        }
+    }
+
+    fn side_is_capped(&self, side: Side, offset: f64) -> bool {
+        match side {
+            Side::Ask => self.ask.is_capped(offset),
+            Side::Bid => self.bid.is_capped(offset),
+        }
+    }
+
+    fn sample_fill_size<R: Rng>(&self, rng: &mut R, side: Side, posted: f64) -> f64 {
+        match side {
+            Side::Ask => self.ask.sample_fill_size(rng, side, posted),
+            Side::Bid => self.bid.sample_fill_size(rng, side, posted),
+        }
+    }
+
+    fn advance(&mut self) {
+        self.ask.advance();
+        self.bid.advance();
+    }
+
+    fn post(&mut self, side: Side, offset: f64) {
+        match side {
+            Side::Ask => self.ask.post(side, offset),
+            Side::Bid => self.bid.post(side, offset),
+        }
+    }
+
+    fn filled(&mut self, side: Side) {
+        match side {
+            Side::Ask => self.ask.filled(side),
+            Side::Bid => self.bid.filled(side),
+        }
+    }
+}
+
+// The agent's order on one side of the book, if any.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Queue {
+    offset: Option<f64>,
+    ahead: f64,
+    posted: bool,
+}
+
+/// Executions of orders that wait in a simulated queue at their price level.
+///
+/// Whether marketable flow reaches an order's level at all is given by the
+/// base model; the volume it trades there is then exponentially distributed
+/// with mean `trade_volume`, and the order is only matched once the volume
+/// ahead of it in the queue has been consumed. An order joins the back of
+/// the queue, behind `depth` units, and moves up as the queue ahead of it
+/// trades away or is cancelled, at the rate `cancel_rate` per step.
+///
+/// Orders are treated as pegged to the midprice: requoting within
+/// `tolerance` of the resting offset keeps the order's place, while moving it
+/// further, withdrawing it for a step, or having it filled sends the next
+/// order to the back of the queue.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QueuePosition<E> {
+    pub base: E,
+    pub depth: f64,
+    pub trade_volume: f64,
+    pub cancel_rate: f64,
+    pub tolerance: f64,
+
+    // Ask and bid orders, in that order.
+    queues: [Queue; 2],
+}
+
+impl<E> QueuePosition<E> {
+    pub fn new(base: E, depth: f64, trade_volume: f64) -> QueuePosition<E> {
+        QueuePosition {
+            base, depth, trade_volume,
+
+            cancel_rate: 0.0,
+            tolerance: 1e-6,
+
+            queues: [Queue::default(); 2],
+        }
+    }
+
+    pub fn with_cancel_rate(self, cancel_rate: f64) -> QueuePosition<E> {
+        QueuePosition { cancel_rate, ..self }
+    }
+
+    pub fn with_tolerance(self, tolerance: f64) -> QueuePosition<E> {
+        QueuePosition { tolerance, ..self }
+    }
+
+    fn index(side: Side) -> usize {
+        match side {
+            Side::Ask => 0,
+            Side::Bid => 1,
+        }
+    }
+
+    /// Volume ahead of the agent's order on `side`, if one is resting.
+    pub fn queue_ahead(&self, side: Side) -> Option<f64> {
+        let q = &self.queues[QueuePosition::<E>::index(side)];
+
+        q.offset.map(|_| q.ahead)
+    }
+
+    // Probability that the flow reaching the level trades through `ahead`
+    // units.
+    fn clearance_prob(&self, ahead: f64) -> f64 {
+        (-ahead / self.trade_volume).exp()
+    }
+}
+
+impl<E: ExecutionDynamics> ExecutionDynamics for QueuePosition<E> {
+    /// Match probability of a new order, at the back of the queue.
+    fn match_prob(&self, offset: f64) -> f64 {
+        self.base.match_prob(offset) * self.clearance_prob(self.depth)
+    }
+
+    fn is_capped(&self, offset: f64) -> bool { self.base.is_capped(offset) }
+
+    fn side_match_prob(&self, side: Side, offset: f64) -> f64 {
+        let q = &self.queues[QueuePosition::<E>::index(side)];
+        let ahead = match q.offset {
+            Some(o) if (o - offset).abs() <= self.tolerance => q.ahead,
+            _ => self.depth,
+        };
+
+        self.base.side_match_prob(side, offset) * self.clearance_prob(ahead)
+    }
+
+    fn side_is_capped(&self, side: Side, offset: f64) -> bool { self.base.side_is_capped(side, offset) }
+
+    fn sample_fill_size<R: Rng>(&self, rng: &mut R, side: Side, posted: f64) -> f64 {
+        self.base.sample_fill_size(rng, side, posted)
+    }
+
+    /// Deplete the queue ahead of each resting order by the expected volume
+    /// traded and cancelled over the last step, and drop orders that were
+    /// not reposted during it.
+    fn advance(&mut self) {
+        for &side in &[Side::Ask, Side::Bid] {
+            let i = QueuePosition::<E>::index(side);
+            let q = self.queues[i];
+
+            self.queues[i] = match q.offset {
+                Some(offset) if q.posted => {
+                    let traded = self.base.side_match_prob(side, offset) * self.trade_volume;
+
+                    Queue {
+                        ahead: (q.ahead * (1.0 - self.cancel_rate) - traded).max(0.0),
+                        posted: false,
+                        ..q
+                    }
+                },
+                _ => Queue::default(),
+            };
+        }
+
+        self.base.advance();
+    }
+
+    fn post(&mut self, side: Side, offset: f64) {
+        let (depth, tolerance) = (self.depth, self.tolerance);
+        let q = &mut self.queues[QueuePosition::<E>::index(side)];
+
+        if q.offset.map_or(true, |o| (o - offset).abs() > tolerance) {
+            q.ahead = depth;
+        }
+
+        q.offset = Some(offset);
+        q.posted = true;
+
+        self.base.post(side, offset);
+    }
+
+    fn filled(&mut self, side: Side) {
+        self.queues[QueuePosition::<E>::index(side)] = Queue::default();
+
+        self.base.filled(side);
+    }
+}
+
+/// Execution statistics of a resting order at a fixed offset from the
+/// midprice.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct FillRate {
+    pub offset: f64,
+
+    pub match_prob: f64,
+    pub expected_fills: f64,
+
+    pub capped: bool,
+}
+
+/// Tabulate the match probability and expected number of fills per episode
+/// of `n_steps` for each of the given offsets.
+pub fn fill_rates<E: ExecutionDynamics>(
+    dynamics: &E,
+    offsets: &[f64],
+    n_steps: usize,
+) -> Vec<FillRate>
+{
+    offsets.iter().map(|&offset| {
+        let match_prob = dynamics.match_prob(offset);
+
+        FillRate {
+            offset,
+
+            match_prob,
+            expected_fills: match_prob * n_steps as f64,
+
+            capped: dynamics.is_capped(offset),
+        }
+    }).collect()
+}
@@ -0,0 +1,232 @@
+//! Dynamics replayed from recorded market data rather than simulated.
+//!
+//! Driving the environment with a recorded midprice series, and matching
+//! quotes against the recorded tape, turns it into a backtester, e.g. for
+//! evaluating saved traders on real market days.
+#[cfg(feature = "offline")]
+extern crate arrow_array;
+#[cfg(feature = "offline")]
+extern crate parquet;
+
+use crate::error::{Error, Result};
+use rand::Rng;
+use std::sync::Arc;
+use super::{execution::{ExecutionDynamics, Side}, price::PriceDynamics};
+
+/// Midprice increments streamed, one per step, from a recorded series.
+///
+/// The series is shared between clones, so that an environment can cheaply
+/// be built per episode, e.g. with `starting_at` to replay a different
+/// stretch of the record each time. Once the record is exhausted the price
+/// stays flat.
+#[derive(Clone, Debug)]
+pub struct HistoricalPrices {
+    prices: Arc<Vec<f64>>,
+    cursor: usize,
+
+    // Realised drift and volatility over the whole record.
+    drift: f64,
+    volatility: f64,
+}
+
+impl HistoricalPrices {
+    /// Replay the midprices `prices`, one row per step of length `dt`, which
+    /// must all be finite.
+    pub fn new(dt: f64, prices: Vec<f64>) -> Result<HistoricalPrices> {
+        if let Some(&p) = prices.iter().find(|p| !p.is_finite()) {
+            return Err(Error::InvalidParameter { name: "midprice", value: p });
+        }
+
+        if prices.len() < 2 {
+            return Err(Error::InvalidParameter { name: "n_prices", value: prices.len() as f64 });
+        }
+
+        let n = (prices.len() - 1) as f64;
+        let increments = prices.windows(2).map(|w| w[1] - w[0]);
+
+        let drift = (prices[prices.len() - 1] - prices[0]) / (n * dt);
+        let volatility = (increments.map(|x| x * x).sum::<f64>() / (n * dt)).sqrt();
+
+        Ok(HistoricalPrices { prices: Arc::new(prices), cursor: 0, drift, volatility, })
+    }
+
+    /// Read the `midprice` column of a CSV file whose rows are in time order.
+    #[cfg(feature = "cli")]
+    pub fn from_csv(dt: f64, path: &str) -> Result<HistoricalPrices> {
+        HistoricalPrices::new(dt, read_csv_column(path, "midprice")?)
+    }
+
+    /// Read the `midprice` column of a Parquet file whose rows are in time
+    /// order.
+    #[cfg(feature = "offline")]
+    pub fn from_parquet(dt: f64, path: &str) -> Result<HistoricalPrices> {
+        HistoricalPrices::new(dt, read_parquet_column(path, "midprice")?)
+    }
+
+    /// A replay of the same record from row `index` onwards.
+    pub fn starting_at(&self, index: usize) -> HistoricalPrices {
+        HistoricalPrices { cursor: index.min(self.prices.len() - 1), ..self.clone() }
+    }
+
+    /// Number of increments in the record.
+    pub fn len(&self) -> usize { self.prices.len() - 1 }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Number of increments left to replay.
+    pub fn remaining(&self) -> usize { self.prices.len() - 1 - self.cursor }
+
+    /// Midprice at the current position in the record, e.g. for the initial
+    /// price of the dynamics.
+    pub fn price(&self) -> f64 { self.prices[self.cursor] }
+}
+
+// Numeric column of a CSV file, with empty or malformed cells read as NaN.
+#[cfg(feature = "cli")]
+fn read_csv_column(path: &str, column: &str) -> Result<Vec<f64>> {
+    Ok(crate::output::read_column(path, column)?
+        .into_iter()
+        .map(|x| x.trim().parse::<f64>().unwrap_or(::std::f64::NAN))
+        .collect())
+}
+
+// Float column of a Parquet file, with nulls read as NaN.
+#[cfg(feature = "offline")]
+fn read_parquet_column(path: &str, column: &str) -> Result<Vec<f64>> {
+    use self::arrow_array::{Array, Float64Array};
+    use self::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::{fs::File, io};
+
+    let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)
+        .and_then(|b| b.build())
+        .map_err(|e| invalid(e.to_string()))?;
+
+    let mut values = vec![];
+
+    for batch in reader {
+        let batch = batch.map_err(|e| invalid(e.to_string()))?;
+        let floats = batch.column_by_name(column)
+            .and_then(|c| c.as_any().downcast_ref::<Float64Array>())
+            .ok_or_else(|| invalid(format!("No `{}` column of floats.", column)))?;
+
+        values.extend((0..floats.len()).map(|i| if floats.is_null(i) { ::std::f64::NAN } else { floats.value(i) }));
+    }
+
+    Ok(values)
+}
+
+impl PriceDynamics for HistoricalPrices {
+    fn sample_increment<R: Rng>(&mut self, _: &mut R, _: f64) -> f64 {
+        if self.cursor + 1 < self.prices.len() {
+            self.cursor += 1;
+
+            self.prices[self.cursor] - self.prices[self.cursor - 1]
+        } else {
+            0.0
+        }
+    }
+
+    /// Realised drift over the whole record.
+    fn drift(&self) -> f64 { self.drift }
+
+    /// Realised volatility over the whole record.
+    fn volatility(&self) -> f64 { self.volatility }
+}
+
+/// State of the book recorded at the end of one step, and the range of the
+/// trades printed during it.
+///
+/// Without any trades, `trade_high` and `trade_low` are NaN.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TapeRow {
+    pub midprice: f64,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub trade_high: f64,
+    pub trade_low: f64,
+}
+
+/// Executions replayed from a recorded tape, aligned row for row with a
+/// `HistoricalPrices` record of the same market.
+///
+/// A quote is filled, in full, when the tape shows a trade through its level
+/// during the step, or when it crosses the recorded opposite best quote at
+/// the end of it. Offsets are taken relative to the recorded midprice, so
+/// the environment's midprice must follow the same record.
+#[derive(Clone, Debug)]
+pub struct HistoricalExecutions {
+    rows: Arc<Vec<TapeRow>>,
+    cursor: usize,
+}
+
+impl HistoricalExecutions {
+    pub fn new(rows: Vec<TapeRow>) -> HistoricalExecutions {
+        HistoricalExecutions { rows: Arc::new(rows), cursor: 0, }
+    }
+
+    /// Read the `midprice`, `best_bid`, `best_ask`, `trade_high` and
+    /// `trade_low` columns of a CSV file whose rows are in time order.
+    #[cfg(feature = "cli")]
+    pub fn from_csv(path: &str) -> Result<HistoricalExecutions> {
+        HistoricalExecutions::from_columns(|c| read_csv_column(path, c))
+    }
+
+    /// Read the columns listed under `from_csv` from a Parquet file.
+    #[cfg(feature = "offline")]
+    pub fn from_parquet(path: &str) -> Result<HistoricalExecutions> {
+        HistoricalExecutions::from_columns(|c| read_parquet_column(path, c))
+    }
+
+    #[cfg(any(feature = "cli", feature = "offline"))]
+    fn from_columns(read: impl Fn(&str) -> Result<Vec<f64>>) -> Result<HistoricalExecutions> {
+        let midprice = read("midprice")?;
+        let best_bid = read("best_bid")?;
+        let best_ask = read("best_ask")?;
+        let trade_high = read("trade_high")?;
+        let trade_low = read("trade_low")?;
+
+        Ok(HistoricalExecutions::new((0..midprice.len()).map(|i| TapeRow {
+            midprice: midprice[i],
+            best_bid: best_bid[i],
+            best_ask: best_ask[i],
+            trade_high: trade_high[i],
+            trade_low: trade_low[i],
+        }).collect()))
+    }
+
+    /// A replay of the same tape from row `index` onwards, to match
+    /// `HistoricalPrices::starting_at`.
+    pub fn starting_at(&self, index: usize) -> HistoricalExecutions {
+        HistoricalExecutions { cursor: index, ..self.clone() }
+    }
+
+    /// Row of the current step, if the tape is not exhausted.
+    pub fn current(&self) -> Option<&TapeRow> { self.rows.get(self.cursor) }
+}
+
+impl ExecutionDynamics for HistoricalExecutions {
+    /// Mean match probability over the two sides.
+    fn match_prob(&self, offset: f64) -> f64 {
+        (self.side_match_prob(Side::Ask, offset) + self.side_match_prob(Side::Bid, offset)) / 2.0
+    }
+
+    fn side_match_prob(&self, side: Side, offset: f64) -> f64 {
+        let filled = self.current().map_or(false, |r| match side {
+            Side::Ask => {
+                let level = r.midprice + offset;
+
+                r.trade_high > level || r.best_bid >= level
+            },
+            Side::Bid => {
+                let level = r.midprice - offset;
+
+                r.trade_low < level || r.best_ask <= level
+            },
+        });
+
+        if filled { 1.0 } else { 0.0 }
+    }
+
+    fn advance(&mut self) { self.cursor += 1; }
+}
@@ -1,11 +1,57 @@
-use rand::{ThreadRng, thread_rng, prelude::*};
+use crate::error::{Error, Result};
+use rand::{distributions::StandardNormal, rngs::SmallRng, prelude::*};
 
+pub mod composite;
+pub mod draws;
+pub mod historical;
 pub mod price;
 pub mod execution;
 
+use self::draws::Draws;
+
+/// Running summary of a quantity observed once per step.
+#[derive(Clone, Copy, Debug)]
+pub struct PathSummary {
+    pub n: usize,
+    pub min: f64,
+    pub max: f64,
+
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl PathSummary {
+    pub fn new() -> PathSummary {
+        PathSummary { n: 0, min: ::std::f64::INFINITY, max: ::std::f64::NEG_INFINITY, sum: 0.0, sum_sq: 0.0, }
+    }
+
+    pub fn record(&mut self, x: f64) {
+        self.n += 1;
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        self.sum += x;
+        self.sum_sq += x * x;
+    }
+
+    /// Mean of the recorded values; NaN if there are none.
+    pub fn mean(&self) -> f64 { self.sum / self.n as f64 }
+
+    /// Population standard deviation of the recorded values.
+    pub fn stddev(&self) -> f64 {
+        let mean = self.mean();
+
+        (self.sum_sq / self.n as f64 - mean * mean).max(0.0).sqrt()
+    }
+}
+
+impl Default for PathSummary {
+    fn default() -> PathSummary { PathSummary::new() }
+}
+
 #[derive(Debug)]
 pub struct ASDynamics<P, E> {
-    rng: ThreadRng,
+    rng: SmallRng,
+    draws: Option<Draws>,
 
     pub dt: f64,
     pub time: f64,
@@ -14,14 +60,55 @@ pub struct ASDynamics<P, E> {
 
     pub price_dynamics: P,
     pub execution_dynamics: E,
+
+    /// Number of orders submitted for matching and, of those, the number
+    /// whose match probability was capped by the execution dynamics.
+    pub n_orders: usize,
+    pub n_capped: usize,
+
+    /// Number of submitted orders that were (at least partly) filled.
+    pub n_filled: usize,
+
+    /// Drift and volatility of the price dynamics in effect at each step,
+    /// which may be changed between steps, e.g. by an adversary.
+    pub drift_path: PathSummary,
+    pub volatility_path: PathSummary,
+
+    /// Sum of squared price increments since the start of the episode.
+    pub quadratic_variation: f64,
+
+    /// Move of the midprice against the agent after its fills, if any.
+    pub adverse_selection: Option<execution::AdverseSelection>,
+
+    // Jump still to be applied at the next step, and current drift, due to
+    // adverse selection.
+    impact_jump: f64,
+    impact_drift: f64,
 }
 
 impl<P, E> ASDynamics<P, E> {
-    pub fn new(dt: f64, price: f64, rng: ThreadRng,
-               price_dynamics: P, execution_dynamics: E) -> Self
+    /// Construct the dynamics, checking that `dt` lies in (0, 1] and that the
+    /// initial `price` is finite.
+    pub fn new(dt: f64, price: f64, rng: SmallRng,
+               price_dynamics: P, execution_dynamics: E) -> Result<Self>
+    {
+        if !(dt > 0.0 && dt <= 1.0) {
+            return Err(Error::InvalidParameter { name: "dt", value: dt });
+        }
+
+        if !price.is_finite() {
+            return Err(Error::InvalidParameter { name: "price", value: price });
+        }
+
+        Ok(ASDynamics::new_unchecked(dt, price, rng, price_dynamics, execution_dynamics))
+    }
+
+    fn new_unchecked(dt: f64, price: f64, rng: SmallRng,
+                     price_dynamics: P, execution_dynamics: E) -> Self
     {
         ASDynamics {
             rng,
+            draws: None,
 
             dt,
             time: 0.0,
@@ -30,6 +117,150 @@ impl<P, E> ASDynamics<P, E> {
 
             price_dynamics,
             execution_dynamics,
+
+            n_orders: 0,
+            n_capped: 0,
+            n_filled: 0,
+
+            drift_path: PathSummary::new(),
+            volatility_path: PathSummary::new(),
+
+            quadratic_variation: 0.0,
+
+            adverse_selection: None,
+
+            impact_jump: 0.0,
+            impact_drift: 0.0,
+        }
+    }
+
+    /// Reset the random number generator driving prices and executions.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = SmallRng::seed_from_u64(seed);
+
+        if let Some(ref mut draws) = self.draws {
+            draws.clear();
+        }
+    }
+
+    /// Consume the dynamics, returning them with the normals driving
+    /// Gaussian price increments and the uniforms deciding order matches
+    /// drawn in bulk, `n_steps` steps' worth at a time.
+    pub fn with_precomputed_draws(mut self, n_steps: usize) -> Self {
+        self.draws = Some(Draws::new(n_steps, 2 * n_steps));
+        self
+    }
+
+    /// Consume the dynamics, returning them with the midprice moving against
+    /// the agent after each of its fills.
+    pub fn with_adverse_selection(mut self, adverse_selection: execution::AdverseSelection) -> Self {
+        self.adverse_selection = Some(adverse_selection);
+        self
+    }
+
+    /// Consume the dynamics, returning them with the given price model.
+    pub fn with_price_dynamics<P2>(self, price_dynamics: P2) -> ASDynamics<P2, E> {
+        ASDynamics {
+            rng: self.rng,
+            draws: self.draws,
+
+            dt: self.dt,
+            time: self.time,
+            price: self.price,
+            price_initial: self.price_initial,
+
+            price_dynamics,
+            execution_dynamics: self.execution_dynamics,
+
+            n_orders: self.n_orders,
+            n_capped: self.n_capped,
+            n_filled: self.n_filled,
+
+            drift_path: self.drift_path,
+            volatility_path: self.volatility_path,
+
+            quadratic_variation: self.quadratic_variation,
+
+            adverse_selection: self.adverse_selection,
+
+            impact_jump: self.impact_jump,
+            impact_drift: self.impact_drift,
+        }
+    }
+
+    /// Consume the dynamics, returning them with the given execution model.
+    pub fn with_execution_dynamics<E2>(self, execution_dynamics: E2) -> ASDynamics<P, E2> {
+        ASDynamics {
+            rng: self.rng,
+            draws: self.draws,
+
+            dt: self.dt,
+            time: self.time,
+            price: self.price,
+            price_initial: self.price_initial,
+
+            price_dynamics: self.price_dynamics,
+            execution_dynamics,
+
+            n_orders: self.n_orders,
+            n_capped: self.n_capped,
+            n_filled: self.n_filled,
+
+            drift_path: self.drift_path,
+            volatility_path: self.volatility_path,
+
+            quadratic_variation: self.quadratic_variation,
+
+            adverse_selection: self.adverse_selection,
+
+            impact_jump: self.impact_jump,
+            impact_drift: self.impact_drift,
+        }
+    }
+
+    /// Fraction of submitted orders whose match probability was capped.
+    pub fn cap_rate(&self) -> f64 {
+        if self.n_orders == 0 {
+            0.0
+        } else {
+            self.n_capped as f64 / self.n_orders as f64
+        }
+    }
+
+    /// Fraction of submitted orders that were filled.
+    pub fn fill_rate(&self) -> f64 {
+        if self.n_orders == 0 {
+            0.0
+        } else {
+            self.n_filled as f64 / self.n_orders as f64
+        }
+    }
+
+    /// Net change in the price per unit time elapsed; zero before the first
+    /// step.
+    pub fn realised_drift(&self) -> f64 {
+        if self.time > 0.0 { (self.price - self.price_initial) / self.time } else { 0.0 }
+    }
+
+    /// Volatility implied by the squared price increments, per square root of
+    /// unit time elapsed; zero before the first step.
+    pub fn realised_volatility(&self) -> f64 {
+        if self.time > 0.0 { (self.quadratic_variation / self.time).sqrt() } else { 0.0 }
+    }
+
+    /// Sample uniformly from _[lower, upper)_ with the same generator. No
+    /// numbers are drawn if the interval is a single point.
+    pub fn sample_uniform(&mut self, lower: f64, upper: f64) -> f64 {
+        if upper > lower { self.rng.gen_range(lower, upper) } else { lower }
+    }
+
+    /// Sample zero-mean Gaussian noise from the same generator, e.g. for
+    /// corrupting observations. No numbers are drawn if `stddev` is zero.
+    pub fn sample_noise(&mut self, stddev: f64) -> f64 {
+        if stddev > 0.0 {
+            stddev * self.rng.sample(StandardNormal)
+        } else {
+            0.0
         }
     }
 }
@@ -41,7 +272,7 @@ impl ASDynamics<price::BrownianMotionWithDrift, execution::PoissonRate> {
         let pd = price::BrownianMotionWithDrift::new(DT, drift, 2.0);
         let ed = execution::PoissonRate::new(DT, 140.0, 1.5);
 
-        ASDynamics::new(DT, 100.0, thread_rng(), pd, ed)
+        ASDynamics::new_unchecked(DT, 100.0, SmallRng::from_entropy(), pd, ed)
     }
 }
 
@@ -52,7 +283,7 @@ impl Default for ASDynamics<price::BrownianMotion, execution::PoissonRate> {
         let pd = price::BrownianMotion::new(DT, 2.0);
         let ed = execution::PoissonRate::new(DT, 140.0, 1.5);
 
-        ASDynamics::new(DT, 100.0, thread_rng(), pd, ed)
+        ASDynamics::new_unchecked(DT, 100.0, SmallRng::from_entropy(), pd, ed)
     }
 }
 
@@ -62,35 +293,93 @@ where
     E: execution::ExecutionDynamics,
 {
     pub fn innovate(&mut self) -> f64 {
-        let mut rng = thread_rng();
+        self.drift_path.record(self.price_dynamics.drift());
+        self.volatility_path.record(self.price_dynamics.volatility());
 
-        let price_inc = self.price_dynamics.sample_increment(&mut rng, self.price);
+        let price_inc = match (self.draws.as_mut(), self.price_dynamics.gaussian_increment(self.price)) {
+            (Some(draws), Some((mean, stddev))) => mean + stddev * draws.next_normal(&mut self.rng),
+            _ => self.price_dynamics.sample_increment(&mut self.rng, self.price),
+        } + self.take_impact();
 
         self.time += self.dt;
         self.price += price_inc;
+        self.quadratic_variation += price_inc * price_inc;
+
+        self.execution_dynamics.advance();
 
         price_inc
     }
 
-    fn try_execute(&mut self, offset: f64) -> Option<f64> {
-        let match_prob = self.execution_dynamics.match_prob(offset);
+    // Price move due to adverse selection over the coming step, decaying the
+    // impact drift.
+    fn take_impact(&mut self) -> f64 {
+        let adverse_selection = match self.adverse_selection {
+            Some(adverse_selection) => adverse_selection,
+            None => return 0.0,
+        };
+
+        let impact = self.impact_jump + self.impact_drift * self.dt;
+
+        self.impact_jump = 0.0;
+        self.impact_drift *= (-adverse_selection.decay * self.dt).exp();
+
+        impact
+    }
+
+    /// Sample a price increment over one step without advancing the clock or
+    /// recording it in the path statistics, e.g. for moves after the horizon.
+    pub fn sample_price_increment(&mut self) -> f64 {
+        self.price_dynamics.sample_increment(&mut self.rng, self.price)
+    }
+
+    fn try_execute(&mut self, side: execution::Side, offset: f64, size: f64) -> Option<(f64, f64)> {
+        self.execution_dynamics.post(side, offset);
+
+        let match_prob = self.execution_dynamics.side_match_prob(side, offset);
+
+        self.n_orders += 1;
+
+        if self.execution_dynamics.side_is_capped(side, offset) {
+            self.n_capped += 1;
+        }
+
+        let matched = match self.draws {
+            Some(ref mut draws) => draws.next_uniform(&mut self.rng) < match_prob,
+            None => self.rng.gen_bool(match_prob),
+        };
+
+        if matched {
+            let filled = self.execution_dynamics.sample_fill_size(&mut self.rng, side, size);
+
+            self.execution_dynamics.filled(side);
+            self.n_filled += 1;
+
+            if let Some(adverse_selection) = self.adverse_selection {
+                let sign = execution::AdverseSelection::sign(side);
+
+                self.impact_jump += sign * adverse_selection.jump * filled;
+                self.impact_drift += sign * adverse_selection.drift * filled;
+            }
 
-        if self.rng.gen_bool(match_prob) {
-            Some(offset)
+            Some((offset, filled))
         } else {
             None
         }
     }
 
-    pub fn try_execute_ask(&mut self, order_price: f64) -> Option<f64> {
+    /// Attempt to match an ask of the given size, returning its offset from
+    /// the midprice and the quantity executed.
+    pub fn try_execute_ask(&mut self, order_price: f64, size: f64) -> Option<(f64, f64)> {
         let offset = order_price - self.price;
 
-        self.try_execute(offset)
+        self.try_execute(execution::Side::Ask, offset, size)
     }
 
-    pub fn try_execute_bid(&mut self, order_price: f64) -> Option<f64> {
+    /// Attempt to match a bid of the given size, returning its offset from
+    /// the midprice and the quantity executed.
+    pub fn try_execute_bid(&mut self, order_price: f64, size: f64) -> Option<(f64, f64)> {
         let offset = self.price - order_price;
 
-        self.try_execute(offset)
+        self.try_execute(execution::Side::Bid, offset, size)
     }
 }
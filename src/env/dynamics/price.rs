@@ -1,10 +1,23 @@
 use rand::{
     prelude::*,
-    distributions::StandardNormal,
+    distributions::{Exp, Poisson, StandardNormal},
 };
+use crate::error::{Error, Result};
+use std::f64::consts::PI;
 
 pub trait PriceDynamics {
-    fn sample_increment<R: Rng>(&self, rng: &mut R, x: f64) -> f64;
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, x: f64) -> f64;
+
+    /// Current drift of the price per unit time, for dynamics with one.
+    fn drift(&self) -> f64 { 0.0 }
+
+    /// Current volatility of the price.
+    fn volatility(&self) -> f64;
+
+    /// Mean and standard deviation of the next increment from `x`, for
+    /// dynamics whose increments are Gaussian, so that they can be generated
+    /// from precomputed standard normals.
+    fn gaussian_increment(&self, _x: f64) -> Option<(f64, f64)> { None }
 }
 
 #[derive(Debug)]
@@ -20,9 +33,13 @@ impl BrownianMotion {
 }
 
 impl PriceDynamics for BrownianMotion {
-    fn sample_increment<R: Rng>(&self, rng: &mut R, _: f64) -> f64 {
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, _: f64) -> f64 {
         self.volatility * self.dt.sqrt() * rng.sample(StandardNormal)
     }
+
+    fn volatility(&self) -> f64 { self.volatility }
+
+    fn gaussian_increment(&self, _: f64) -> Option<(f64, f64)> { Some((0.0, self.volatility * self.dt.sqrt())) }
 }
 
 impl Default for BrownianMotion {
@@ -31,7 +48,7 @@ impl Default for BrownianMotion {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BrownianMotionWithDrift {
     dt: f64,
     pub drift: f64,
@@ -45,9 +62,17 @@ impl BrownianMotionWithDrift {
 }
 
 impl PriceDynamics for BrownianMotionWithDrift {
-    fn sample_increment<R: Rng>(&self, rng: &mut R, _: f64) -> f64 {
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, _: f64) -> f64 {
         self.drift * self.dt + self.volatility * self.dt.sqrt() * rng.sample(StandardNormal)
     }
+
+    fn drift(&self) -> f64 { self.drift }
+
+    fn volatility(&self) -> f64 { self.volatility }
+
+    fn gaussian_increment(&self, _: f64) -> Option<(f64, f64)> {
+        Some((self.drift * self.dt, self.volatility * self.dt.sqrt()))
+    }
 }
 
 impl Default for BrownianMotionWithDrift {
@@ -56,6 +81,44 @@ impl Default for BrownianMotionWithDrift {
     }
 }
 
+/// Multiplicative Brownian motion, under which the price stays positive.
+///
+/// The `drift` and `volatility` are relative to the current price; each step
+/// is sampled exactly from the log-normal transition.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GeometricBrownianMotion {
+    dt: f64,
+    pub drift: f64,
+    pub volatility: f64,
+}
+
+impl GeometricBrownianMotion {
+    pub fn new(dt: f64, drift: f64, volatility: f64) -> GeometricBrownianMotion {
+        GeometricBrownianMotion { dt, drift, volatility, }
+    }
+}
+
+impl PriceDynamics for GeometricBrownianMotion {
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, x: f64) -> f64 {
+        let log_return = (self.drift - 0.5 * self.volatility * self.volatility) * self.dt
+            + self.volatility * self.dt.sqrt() * rng.sample(StandardNormal);
+
+        x * log_return.exp_m1()
+    }
+
+    fn drift(&self) -> f64 { self.drift }
+
+    fn volatility(&self) -> f64 { self.volatility }
+}
+
+impl Default for GeometricBrownianMotion {
+    /// Relative volatility of 2%, matching `BrownianMotion` about a price of
+    /// 100.
+    fn default() -> GeometricBrownianMotion {
+        GeometricBrownianMotion::new(0.005, 0.0, 0.02)
+    }
+}
+
 #[derive(Debug)]
 pub struct OrnsteinUhlenbeck {
     dt: f64,
@@ -70,11 +133,17 @@ impl OrnsteinUhlenbeck {
 }
 
 impl PriceDynamics for OrnsteinUhlenbeck {
-    fn sample_increment<R: Rng>(&self, rng: &mut R, x: f64) -> f64 {
-        let w = BrownianMotion::new(self.dt, self.volatility);
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, x: f64) -> f64 {
+        let mut w = BrownianMotion::new(self.dt, self.volatility);
 
         -self.rate * x * self.dt + w.sample_increment(rng, x)
     }
+
+    fn volatility(&self) -> f64 { self.volatility }
+
+    fn gaussian_increment(&self, x: f64) -> Option<(f64, f64)> {
+        Some((-self.rate * x * self.dt, self.volatility * self.dt.sqrt()))
+    }
 }
 
 impl Default for OrnsteinUhlenbeck {
@@ -98,11 +167,17 @@ impl OrnsteinUhlenbeckWithDrift {
 }
 
 impl PriceDynamics for OrnsteinUhlenbeckWithDrift {
-    fn sample_increment<R: Rng>(&self, rng: &mut R, x: f64) -> f64 {
-        let w = BrownianMotion::new(self.dt, self.volatility);
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, x: f64) -> f64 {
+        let mut w = BrownianMotion::new(self.dt, self.volatility);
 
         self.rate * (self.drift - x) * self.dt + w.sample_increment(rng, x)
     }
+
+    fn volatility(&self) -> f64 { self.volatility }
+
+    fn gaussian_increment(&self, x: f64) -> Option<(f64, f64)> {
+        Some((self.rate * (self.drift - x) * self.dt, self.volatility * self.dt.sqrt()))
+    }
 }
 
 impl Default for OrnsteinUhlenbeckWithDrift {
@@ -110,3 +185,629 @@ impl Default for OrnsteinUhlenbeckWithDrift {
         OrnsteinUhlenbeckWithDrift::new(1.0, 1.0, 0.0, 1.0)
     }
 }
+
+/// Brownian increments whose variance follows a GARCH(1, 1) process, giving
+/// volatility clustering within an episode.
+///
+/// The conditional variance of the increment over one step is
+/// _h_t = omega + alpha * e_{t-1}^2 + beta * h_{t-1}_, where _e_ is the
+/// previous shock, starting from the long-run variance
+/// _omega / (1 - alpha - beta)_. The variance is updated on every sample, so
+/// a fresh instance is needed per episode.
+#[derive(Clone, Debug)]
+pub struct Garch {
+    dt: f64,
+    pub drift: f64,
+
+    pub omega: f64,
+    pub alpha: f64,
+    pub beta: f64,
+
+    variance: f64,
+}
+
+impl Garch {
+    /// Construct the process from per-step parameters, which must satisfy
+    /// `omega > 0`, `alpha, beta >= 0` and `alpha + beta < 1`.
+    pub fn new(dt: f64, drift: f64, omega: f64, alpha: f64, beta: f64) -> Result<Garch> {
+        if omega.is_nan() || omega <= 0.0 {
+            return Err(Error::InvalidParameter { name: "omega", value: omega });
+        }
+
+        if alpha.is_nan() || alpha < 0.0 {
+            return Err(Error::InvalidParameter { name: "alpha", value: alpha });
+        }
+
+        if !(beta >= 0.0 && alpha + beta < 1.0) {
+            return Err(Error::InvalidParameter { name: "beta", value: beta });
+        }
+
+        Ok(Garch {
+            dt, drift, omega, alpha, beta,
+
+            variance: omega / (1.0 - alpha - beta),
+        })
+    }
+
+    /// Construct the process with the given long-run volatility per unit
+    /// time, i.e. by variance targeting.
+    pub fn with_long_run_volatility(dt: f64, drift: f64, volatility: f64, alpha: f64, beta: f64) -> Result<Garch> {
+        Garch::new(dt, drift, volatility * volatility * dt * (1.0 - alpha - beta), alpha, beta)
+    }
+
+    /// Long-run volatility per unit time.
+    pub fn long_run_volatility(&self) -> f64 {
+        (self.omega / (1.0 - self.alpha - self.beta) / self.dt).sqrt()
+    }
+
+    /// Fit the process to observed price `increments` over steps of `dt` by
+    /// Gaussian quasi-maximum likelihood.
+    ///
+    /// The long-run variance is targeted to the sample variance, and `alpha`
+    /// and `beta` are found by a grid search with a spacing of 0.01. At least
+    /// two increments are needed, and they must not all be equal.
+    pub fn fit(dt: f64, increments: &[f64]) -> Result<Garch> {
+        if increments.len() < 2 {
+            return Err(Error::InvalidParameter { name: "n_increments", value: increments.len() as f64 });
+        }
+
+        let n = increments.len() as f64;
+        let drift = increments.iter().sum::<f64>() / n;
+        let shocks: Vec<f64> = increments.iter().map(|x| x - drift).collect();
+        let var = shocks.iter().map(|e| e * e).sum::<f64>() / n;
+
+        let log_likelihood = |alpha: f64, beta: f64| {
+            let omega = var * (1.0 - alpha - beta);
+
+            shocks.iter().fold((var, 0.0), |(h, ll), e| {
+                (omega + alpha * e * e + beta * h, ll - h.ln() - e * e / h)
+            }).1
+        };
+
+        let mut best = (0.0, 0.0, log_likelihood(0.0, 0.0));
+
+        for i in 0..100 {
+            for j in 0..(100 - i) {
+                let (alpha, beta) = (i as f64 / 100.0, j as f64 / 100.0);
+                let ll = log_likelihood(alpha, beta);
+
+                if ll > best.2 {
+                    best = (alpha, beta, ll);
+                }
+            }
+        }
+
+        Garch::new(dt, drift / dt, var * (1.0 - best.0 - best.1), best.0, best.1)
+    }
+
+    /// Conditional variance of the next increment.
+    pub fn variance(&self) -> f64 { self.variance }
+}
+
+impl PriceDynamics for Garch {
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, _: f64) -> f64 {
+        let shock = self.variance.sqrt() * rng.sample(StandardNormal);
+
+        self.variance = self.omega + self.alpha * shock * shock + self.beta * self.variance;
+
+        self.drift * self.dt + shock
+    }
+
+    fn drift(&self) -> f64 { self.drift }
+
+    fn volatility(&self) -> f64 { (self.variance / self.dt).sqrt() }
+}
+
+impl Default for Garch {
+    /// Long-run volatility of 2, as `BrownianMotion`, with persistent
+    /// volatility shocks.
+    fn default() -> Garch {
+        Garch::with_long_run_volatility(0.005, 0.0, 2.0, 0.1, 0.85).expect("The default parameters are valid.")
+    }
+}
+
+/// Brownian increments with the stochastic variance of Heston (1993).
+///
+/// The variance _v_ reverts at rate `kappa` to `theta`, with volatility of
+/// volatility `xi` and correlation `rho` between the price and variance
+/// shocks; both are in units per unit time. Negative excursions of the
+/// discretised variance are truncated to zero. As for `Garch`, the variance
+/// is updated on every sample.
+#[derive(Clone, Debug)]
+pub struct Heston {
+    dt: f64,
+    pub drift: f64,
+
+    pub kappa: f64,
+    pub theta: f64,
+    pub xi: f64,
+    pub rho: f64,
+
+    variance: f64,
+}
+
+impl Heston {
+    /// Construct the process starting from its long-run variance `theta`.
+    pub fn new(dt: f64, drift: f64, kappa: f64, theta: f64, xi: f64, rho: f64) -> Result<Heston> {
+        for &(name, value) in &[("kappa", kappa), ("theta", theta), ("xi", xi)] {
+            if value.is_nan() || value < 0.0 {
+                return Err(Error::InvalidParameter { name, value });
+            }
+        }
+
+        if !(rho >= -1.0 && rho <= 1.0) {
+            return Err(Error::InvalidParameter { name: "rho", value: rho });
+        }
+
+        Ok(Heston { dt, drift, kappa, theta, xi, rho, variance: theta, })
+    }
+
+    /// Consume the process, returning it starting from the given variance.
+    pub fn with_initial_variance(self, variance: f64) -> Heston {
+        Heston { variance: variance.max(0.0), ..self }
+    }
+
+    /// Instantaneous variance per unit time.
+    pub fn variance(&self) -> f64 { self.variance }
+}
+
+impl PriceDynamics for Heston {
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, _: f64) -> f64 {
+        let variance = self.variance;
+
+        let z1: f64 = rng.sample(StandardNormal);
+        let z2: f64 = self.rho * z1 + (1.0 - self.rho * self.rho).sqrt() * rng.sample(StandardNormal);
+
+        let sqrt_dt = self.dt.sqrt();
+        let next = variance + self.kappa * (self.theta - variance) * self.dt + self.xi * variance.sqrt() * sqrt_dt * z2;
+
+        self.variance = next.max(0.0);
+
+        self.drift * self.dt + variance.sqrt() * sqrt_dt * z1
+    }
+
+    fn drift(&self) -> f64 { self.drift }
+
+    fn volatility(&self) -> f64 { self.variance.sqrt() }
+}
+
+impl Default for Heston {
+    /// Long-run volatility of 2, as `BrownianMotion`, with fast mean reversion
+    /// of the variance within an episode.
+    fn default() -> Heston {
+        Heston::new(0.005, 0.0, 5.0, 4.0, 2.0, -0.5).expect("The default parameters are valid.")
+    }
+}
+
+// In-place radix-2 discrete Fourier transform, X_k = sum_j x_j exp(-2πi jk/n),
+// of a sequence whose length is a power of two.
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    let mut j = 0;
+
+    for i in 1..n {
+        let mut bit = n >> 1;
+
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+
+        j ^= bit;
+
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+
+    while len <= n {
+        let (w_re, w_im) = ((-2.0 * PI / len as f64).cos(), (-2.0 * PI / len as f64).sin());
+
+        for start in (0..n).step_by(len) {
+            let (mut c_re, mut c_im) = (1.0, 0.0);
+
+            for k in 0..len / 2 {
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * c_re - im[b] * c_im;
+                let t_im = re[b] * c_im + im[b] * c_re;
+
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+
+                let next_re = c_re * w_re - c_im * w_im;
+                c_im = c_re * w_im + c_im * w_re;
+                c_re = next_re;
+            }
+        }
+
+        len <<= 1;
+    }
+}
+
+/// Increments of fractional Brownian motion with Hurst exponent `hurst` in
+/// _(0, 1)_, i.e. fractional Gaussian noise.
+///
+/// A Hurst exponent above one half gives persistent, trending prices; below
+/// one half, rough and mean-reverting ones; one half recovers
+/// `BrownianMotionWithDrift`. The variance over a unit of time is
+/// `volatility^2` in all cases.
+///
+/// Increments are generated exactly by the circulant embedding method of
+/// Davies and Harte (1987), in blocks long enough for an episode of unit
+/// length at O(n log n) cost per block. Successive blocks are independent,
+/// so the long memory does not extend past the first block.
+#[derive(Debug)]
+pub struct FractionalBrownianMotion {
+    dt: f64,
+    pub drift: f64,
+    pub volatility: f64,
+    pub hurst: f64,
+
+    // Square roots of the eigenvalues of the circulant embedding of the
+    // autocovariance, scaled for the synthesis below.
+    scales: Vec<f64>,
+    increments: Vec<f64>,
+}
+
+impl FractionalBrownianMotion {
+    pub fn new(dt: f64, drift: f64, volatility: f64, hurst: f64) -> Result<FractionalBrownianMotion> {
+        if !(dt > 0.0 && dt <= 1.0) {
+            return Err(Error::InvalidParameter { name: "dt", value: dt });
+        }
+
+        if !(hurst > 0.0 && hurst < 1.0) {
+            return Err(Error::InvalidParameter { name: "hurst", value: hurst });
+        }
+
+        let n = ((1.0 / dt).ceil() as usize).next_power_of_two();
+        let m = 2 * n;
+
+        // Autocovariance of unit fractional Gaussian noise at lag k:
+        let gamma = |k: f64| 0.5 * ((k + 1.0).powf(2.0 * hurst) - 2.0 * k.powf(2.0 * hurst) + (k - 1.0).abs().powf(2.0 * hurst));
+
+        let mut re: Vec<f64> = (0..m).map(|j| gamma(if j <= n { j } else { m - j } as f64)).collect();
+        let mut im = vec![0.0; m];
+
+        fft(&mut re, &mut im);
+
+        let scales = re.into_iter().enumerate().map(|(k, lambda)| {
+            let weight = if k == 0 || k == n { m as f64 } else { 2.0 * m as f64 };
+
+            (lambda.max(0.0) / weight).sqrt()
+        }).collect();
+
+        Ok(FractionalBrownianMotion {
+            dt, drift, volatility, hurst, scales,
+
+            increments: vec![],
+        })
+    }
+
+    // Draw a block of unit fractional Gaussian noise.
+    fn sample_block<R: Rng>(&self, rng: &mut R) -> Vec<f64> {
+        let m = self.scales.len();
+        let n = m / 2;
+
+        let mut re = vec![0.0; m];
+        let mut im = vec![0.0; m];
+
+        re[0] = self.scales[0] * rng.sample(StandardNormal);
+        re[n] = self.scales[n] * rng.sample(StandardNormal);
+
+        for k in 1..n {
+            re[k] = self.scales[k] * rng.sample(StandardNormal);
+            im[k] = self.scales[k] * rng.sample(StandardNormal);
+
+            re[m - k] = re[k];
+            im[m - k] = -im[k];
+        }
+
+        fft(&mut re, &mut im);
+        re.truncate(n);
+        re
+    }
+}
+
+impl PriceDynamics for FractionalBrownianMotion {
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, _: f64) -> f64 {
+        if self.increments.is_empty() {
+            self.increments = self.sample_block(rng);
+        }
+
+        let noise = self.increments.pop().unwrap();
+
+        self.drift * self.dt + self.volatility * self.dt.powf(self.hurst) * noise
+    }
+
+    fn drift(&self) -> f64 { self.drift }
+
+    fn volatility(&self) -> f64 { self.volatility }
+}
+
+impl Default for FractionalBrownianMotion {
+    /// Volatility of 2, as `BrownianMotion`, with mildly persistent
+    /// increments.
+    fn default() -> FractionalBrownianMotion {
+        FractionalBrownianMotion::new(0.005, 0.0, 2.0, 0.6).expect("The default parameters are valid.")
+    }
+}
+
+/// Distribution of the size of individual price jumps.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum JumpSize {
+    /// Normally distributed, as in Merton (1976).
+    Normal { mean: f64, stddev: f64 },
+
+    /// Upwards with probability `p_up` and exponentially distributed in
+    /// magnitude with the given means in either direction, as in Kou (2002).
+    DoubleExponential { p_up: f64, mean_up: f64, mean_down: f64 },
+}
+
+impl JumpSize {
+    fn check(&self) -> Result<()> {
+        match *self {
+            JumpSize::Normal { mean, stddev } => {
+                if !mean.is_finite() {
+                    return Err(Error::InvalidParameter { name: "mean", value: mean });
+                }
+
+                if !(stddev >= 0.0 && stddev.is_finite()) {
+                    return Err(Error::InvalidParameter { name: "stddev", value: stddev });
+                }
+            },
+            JumpSize::DoubleExponential { p_up, mean_up, mean_down } => {
+                if !(p_up >= 0.0 && p_up <= 1.0) {
+                    return Err(Error::InvalidParameter { name: "p_up", value: p_up });
+                }
+
+                for &(name, value) in &[("mean_up", mean_up), ("mean_down", mean_down)] {
+                    if !(value > 0.0 && value.is_finite()) {
+                        return Err(Error::InvalidParameter { name, value });
+                    }
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        match *self {
+            JumpSize::Normal { mean, stddev } => mean + stddev * rng.sample(StandardNormal),
+            JumpSize::DoubleExponential { p_up, mean_up, mean_down } => if rng.gen_bool(p_up) {
+                rng.sample(Exp::new(1.0 / mean_up))
+            } else {
+                -rng.sample(Exp::new(1.0 / mean_down))
+            },
+        }
+    }
+
+    /// Mean squared jump size.
+    pub fn second_moment(&self) -> f64 {
+        match *self {
+            JumpSize::Normal { mean, stddev } => mean * mean + stddev * stddev,
+            JumpSize::DoubleExponential { p_up, mean_up, mean_down } =>
+                2.0 * (p_up * mean_up * mean_up + (1.0 - p_up) * mean_down * mean_down),
+        }
+    }
+}
+
+// Add to `x` the jumps arriving over one step, of which `intensity` are
+// expected.
+fn add_jumps<R: Rng>(rng: &mut R, x: f64, intensity: f64, size: &JumpSize) -> f64 {
+    let n_jumps = if intensity > 0.0 { rng.sample(Poisson::new(intensity)) } else { 0 };
+
+    (0..n_jumps).fold(x, |x, _| x + size.sample(rng))
+}
+
+/// Brownian motion with drift plus a compound Poisson process of jumps,
+/// arriving at `jump_rate` per unit time.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JumpDiffusion {
+    dt: f64,
+    pub drift: f64,
+    pub volatility: f64,
+
+    pub jump_rate: f64,
+    pub jump_size: JumpSize,
+}
+
+impl JumpDiffusion {
+    /// Construct the process, which requires `volatility, jump_rate >= 0`,
+    /// `p_up` in _[0, 1]_ and positive mean jump sizes.
+    pub fn new(dt: f64, drift: f64, volatility: f64, jump_rate: f64, jump_size: JumpSize) -> Result<JumpDiffusion> {
+        if volatility.is_nan() || volatility < 0.0 {
+            return Err(Error::InvalidParameter { name: "volatility", value: volatility });
+        }
+
+        if !(jump_rate >= 0.0 && jump_rate.is_finite()) {
+            return Err(Error::InvalidParameter { name: "jump_rate", value: jump_rate });
+        }
+
+        jump_size.check()?;
+
+        Ok(JumpDiffusion { dt, drift, volatility, jump_rate, jump_size, })
+    }
+}
+
+impl PriceDynamics for JumpDiffusion {
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, _: f64) -> f64 {
+        let diffusion = self.drift * self.dt + self.volatility * self.dt.sqrt() * rng.sample(StandardNormal);
+
+        add_jumps(rng, diffusion, self.jump_rate * self.dt, &self.jump_size)
+    }
+
+    fn drift(&self) -> f64 { self.drift }
+
+    /// Volatility of the diffusion and jumps combined.
+    fn volatility(&self) -> f64 {
+        (self.volatility.powi(2) + self.jump_rate * self.jump_size.second_moment()).sqrt()
+    }
+}
+
+impl Default for JumpDiffusion {
+    /// Diffusive volatility of 2, as `BrownianMotion`, with five symmetric
+    /// jumps of about one unit per episode.
+    fn default() -> JumpDiffusion {
+        JumpDiffusion::new(0.005, 0.0, 2.0, 5.0, JumpSize::Normal { mean: 0.0, stddev: 1.0 })
+            .expect("The default parameters are valid.")
+    }
+}
+
+/// Compound Poisson jumps, arriving at `rate` per unit time, added to the
+/// increments of a base process; `JumpDiffusion` with any other process in
+/// place of its Brownian motion.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Jumps<P> {
+    dt: f64,
+    pub base: P,
+
+    pub rate: f64,
+    pub size: JumpSize,
+}
+
+impl<P> Jumps<P> {
+    /// Wrap `base`, which requires `rate >= 0` and a valid jump size
+    /// distribution, as for `JumpDiffusion`.
+    pub fn new(dt: f64, base: P, rate: f64, size: JumpSize) -> Result<Jumps<P>> {
+        if !(rate >= 0.0 && rate.is_finite()) {
+            return Err(Error::InvalidParameter { name: "jump_rate", value: rate });
+        }
+
+        size.check()?;
+
+        Ok(Jumps { dt, base, rate, size, })
+    }
+}
+
+impl<P: PriceDynamics> PriceDynamics for Jumps<P> {
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, x: f64) -> f64 {
+        let increment = self.base.sample_increment(rng, x);
+
+        add_jumps(rng, increment, self.rate * self.dt, &self.size)
+    }
+
+    fn drift(&self) -> f64 { self.base.drift() }
+
+    /// Volatility of the base process and jumps combined.
+    fn volatility(&self) -> f64 {
+        (self.base.volatility().powi(2) + self.rate * self.size.second_moment()).sqrt()
+    }
+}
+
+/// Intraday seasonality: the increments of a base process scaled by a
+/// periodic, piecewise-constant profile, e.g. with more activity around the
+/// open and close.
+///
+/// The `period` is split into as many equal segments as there are entries in
+/// `profile`, each giving the multiplier in effect over its segment. The
+/// wrapper keeps its own clock, advanced on every sample, so as for `Garch`
+/// a fresh instance is needed per episode.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Seasonal<P> {
+    dt: f64,
+    pub base: P,
+
+    pub period: f64,
+    pub profile: Vec<f64>,
+
+    time: f64,
+}
+
+impl<P> Seasonal<P> {
+    /// Wrap `base`, which requires a positive `period` and a non-empty
+    /// `profile` of non-negative multipliers.
+    pub fn new(dt: f64, base: P, period: f64, profile: Vec<f64>) -> Result<Seasonal<P>> {
+        if !(period > 0.0 && period.is_finite()) {
+            return Err(Error::InvalidParameter { name: "period", value: period });
+        }
+
+        if profile.is_empty() {
+            return Err(Error::InvalidParameter { name: "profile", value: 0.0 });
+        }
+
+        if let Some(&m) = profile.iter().find(|&&m| !(m >= 0.0 && m.is_finite())) {
+            return Err(Error::InvalidParameter { name: "profile", value: m });
+        }
+
+        Ok(Seasonal { dt, base, period, profile, time: 0.0, })
+    }
+
+    /// Multiplier in effect over the current step.
+    pub fn multiplier(&self) -> f64 {
+        let n = self.profile.len();
+        let phase = (self.time / self.period).fract();
+
+        self.profile[((phase * n as f64) as usize).min(n - 1)]
+    }
+}
+
+impl<P: PriceDynamics> PriceDynamics for Seasonal<P> {
+    fn sample_increment<R: Rng>(&mut self, rng: &mut R, x: f64) -> f64 {
+        let m = self.multiplier();
+
+        self.time += self.dt;
+
+        m * self.base.sample_increment(rng, x)
+    }
+
+    fn drift(&self) -> f64 { self.multiplier() * self.base.drift() }
+
+    fn volatility(&self) -> f64 { self.multiplier() * self.base.volatility() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BrownianMotion, JumpDiffusion, JumpSize, Jumps, PriceDynamics, Seasonal};
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn kou(p_up: f64, mean_up: f64, mean_down: f64) -> JumpSize {
+        JumpSize::DoubleExponential { p_up, mean_up, mean_down }
+    }
+
+    #[test]
+    fn test_jump_diffusion_valid() {
+        assert!(JumpDiffusion::new(0.005, 0.0, 2.0, 5.0, kou(0.4, 1.0, 0.5)).is_ok());
+        assert!(JumpDiffusion::new(0.005, 0.0, 2.0, 0.0, JumpSize::Normal { mean: -1.0, stddev: 0.0 }).is_ok());
+    }
+
+    #[test]
+    fn test_jump_diffusion_invalid() {
+        assert!(JumpDiffusion::new(0.005, 0.0, -1.0, 5.0, kou(0.4, 1.0, 0.5)).is_err());
+        assert!(JumpDiffusion::new(0.005, 0.0, 2.0, -5.0, kou(0.4, 1.0, 0.5)).is_err());
+        assert!(JumpDiffusion::new(0.005, 0.0, 2.0, 5.0, kou(1.5, 1.0, 0.5)).is_err());
+        assert!(JumpDiffusion::new(0.005, 0.0, 2.0, 5.0, kou(::std::f64::NAN, 1.0, 0.5)).is_err());
+        assert!(JumpDiffusion::new(0.005, 0.0, 2.0, 5.0, kou(0.4, 0.0, 0.5)).is_err());
+        assert!(JumpDiffusion::new(0.005, 0.0, 2.0, 5.0, kou(0.4, 1.0, -0.5)).is_err());
+        assert!(JumpDiffusion::new(0.005, 0.0, 2.0, 5.0, JumpSize::Normal { mean: 0.0, stddev: -1.0 }).is_err());
+    }
+
+    #[test]
+    fn test_jumps_invalid() {
+        assert!(Jumps::new(0.005, BrownianMotion::default(), 5.0, kou(0.4, 1.0, 0.5)).is_ok());
+        assert!(Jumps::new(0.005, BrownianMotion::default(), -5.0, kou(0.4, 1.0, 0.5)).is_err());
+        assert!(Jumps::new(0.005, BrownianMotion::default(), 5.0, kou(0.4, -1.0, 0.5)).is_err());
+    }
+
+    #[test]
+    fn test_seasonal_profile() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut s = Seasonal::new(0.25, BrownianMotion::new(0.25, 2.0), 1.0, vec![2.0, 1.0]).unwrap();
+
+        for &m in &[2.0, 2.0, 1.0, 1.0, 2.0] {
+            assert_eq!(s.multiplier(), m);
+            assert_eq!(s.volatility(), 2.0 * m);
+
+            s.sample_increment(&mut rng, 100.0);
+        }
+
+        assert!(Seasonal::new(0.25, BrownianMotion::default(), 0.0, vec![1.0]).is_err());
+        assert!(Seasonal::new(0.25, BrownianMotion::default(), 1.0, vec![]).is_err());
+        assert!(Seasonal::new(0.25, BrownianMotion::default(), 1.0, vec![1.0, -1.0]).is_err());
+    }
+}
@@ -0,0 +1,49 @@
+//! Exchange fees and rebates charged on the trader's executions.
+//!
+//! Limit orders that are filled add liquidity and typically earn a maker
+//! rebate, while the market orders used to liquidate the inventory at the
+//! horizon pay a taker fee; both may come on top of a fixed clearing cost
+//! per unit traded. All amounts are per unit of quantity.
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    pub maker_rebate: f64,
+    pub taker_fee: f64,
+    pub fixed_cost: f64,
+}
+
+impl FeeSchedule {
+    pub fn new(maker_rebate: f64, taker_fee: f64, fixed_cost: f64) -> FeeSchedule {
+        FeeSchedule { maker_rebate, taker_fee, fixed_cost, }
+    }
+
+    /// No fees or rebates.
+    pub fn free() -> FeeSchedule { FeeSchedule::new(0.0, 0.0, 0.0) }
+
+    /// Net amount received for a fill of `size` units of a limit order.
+    pub fn maker(&self, size: f64) -> f64 { (self.maker_rebate - self.fixed_cost) * size.abs() }
+
+    /// Amount paid for a market order of `size` units, bought or sold.
+    pub fn taker(&self, size: f64) -> f64 { (self.taker_fee + self.fixed_cost) * size.abs() }
+}
+
+impl Default for FeeSchedule {
+    fn default() -> FeeSchedule { FeeSchedule::free() }
+}
+
+impl FromStr for FeeSchedule {
+    type Err = String;
+
+    /// Parse `maker_rebate,taker_fee,fixed_cost`.
+    fn from_str(s: &str) -> Result<FeeSchedule, String> {
+        let params = s.split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid fees `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match params.as_slice() {
+            &[maker_rebate, taker_fee, fixed_cost] => Ok(FeeSchedule::new(maker_rebate, taker_fee, fixed_cost)),
+            _ => Err(format!("Fees `{}` must be of the form maker_rebate,taker_fee,fixed_cost.", s)),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Models of how the inventory left at the horizon is liquidated.
+//!
+//! Liquidating the whole position at the midprice is free, leaving the
+//! quadratic terminal penalty as the only deterrent against carrying
+//! inventory to the horizon. The alternatives here charge for liquidation
+//! directly; since the learned policies are sensitive to this choice, it
+//! pays to check conclusions under more than one of them.
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Liquidation {
+    /// A market order filled entirely at the midprice.
+    Midprice,
+
+    /// A market order filled at the midprice less an impact of `eta` per unit
+    /// of inventory, i.e. at a cost of `eta * inv^2`.
+    LinearImpact { eta: f64 },
+
+    /// A market order crossing a spread of `2 * half_spread`, i.e. at a cost
+    /// of `half_spread * |inv|`.
+    CrossSpread { half_spread: f64 },
+
+    /// Equal slices over `n_slices` further steps past the horizon, each with
+    /// linear impact `eta`; the position still held remains exposed to price
+    /// moves between slices.
+    Gradual { n_slices: usize, eta: f64 },
+
+    /// No liquidation: the inventory is carried past the horizon, e.g. into
+    /// the next horizon of a rolling deployment, and its value is left out of
+    /// the wealth.
+    Carry,
+}
+
+impl Liquidation {
+    /// Expected cost of liquidating `inv` units, as charged to the reward,
+    /// leaving out price moves between the slices of a gradual liquidation.
+    pub fn expected_cost(&self, inv: f64) -> f64 {
+        match *self {
+            Liquidation::Midprice | Liquidation::Carry => 0.0,
+            Liquidation::LinearImpact { eta } => eta * inv * inv,
+            Liquidation::CrossSpread { half_spread } => half_spread * inv.abs(),
+            Liquidation::Gradual { n_slices, eta } => eta * inv * inv / n_slices as f64,
+        }
+    }
+}
+
+impl Default for Liquidation {
+    fn default() -> Liquidation { Liquidation::Midprice }
+}
+
+impl FromStr for Liquidation {
+    type Err = String;
+
+    /// Parse `mid`, `impact:eta`, `spread:half_spread`,
+    /// `gradual:n_slices,eta` or `carry`.
+    fn from_str(s: &str) -> Result<Liquidation, String> {
+        let mut parts = s.splitn(2, ':');
+        let name = parts.next().unwrap().trim();
+        let params = parts.next().unwrap_or("")
+            .split(',')
+            .filter(|x| !x.trim().is_empty())
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid liquidation model `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match (name, params.as_slice()) {
+            ("mid", &[]) => Ok(Liquidation::Midprice),
+            ("carry", &[]) => Ok(Liquidation::Carry),
+            ("impact", &[eta]) if eta >= 0.0 => Ok(Liquidation::LinearImpact { eta, }),
+            ("spread", &[half_spread]) if half_spread >= 0.0 => Ok(Liquidation::CrossSpread { half_spread, }),
+            ("gradual", &[n_slices, eta]) if n_slices >= 1.0 && n_slices.fract() == 0.0 && eta >= 0.0 =>
+                Ok(Liquidation::Gradual { n_slices: n_slices as usize, eta, }),
+            _ => Err(format!(
+                "Liquidation model `{}` must be mid, impact:eta, spread:half_spread, gradual:n_slices,eta or carry \
+                 with non-negative parameters and a whole number of slices.", s
+            )),
+        }
+    }
+}
@@ -1,29 +1,106 @@
 use crate::{
     env::dynamics::{
         ASDynamics,
-        execution::{ExecutionDynamics, PoissonRate},
+        execution::{AdverseSelection, ExecutionDynamics, PoissonRate},
         price::{PriceDynamics, BrownianMotion, BrownianMotionWithDrift},
     },
+    env::{clock::Clock, config::EnvConfig, fees::FeeSchedule, observation::{AttackObservation, DriftEstimator, View}, liquidation::Liquidation, penalty::InventoryPenalty, quotes::QuoteSpace, shield::Shield, signal::Signal, start::StartDistribution},
 };
-use rand::thread_rng;
 use rsrl::{
     domains::{Domain, Transition, Observation},
     geometry::{
-        continuous::{Interval, Reals},
-        product::{DoubleSpace, LinearSpace},
+        continuous::Interval,
+        product::LinearSpace,
+        Surjection,
         Vector,
     },
 };
+use std::collections::VecDeque;
 
+pub mod clock;
+pub mod config;
+pub mod discrete;
 pub mod dynamics;
+pub mod fees;
+pub mod liquidation;
+pub mod observation;
+pub mod penalty;
+pub mod quotes;
+pub mod scenarios;
+pub mod shield;
+pub mod signal;
+pub mod solver;
+pub mod start;
 pub mod strategies;
 
-const INV_BOUNDS: [f64; 2] = [-50.0, 50.0];
+/// Bounds on the market maker's inventory.
+pub const INV_BOUNDS: [f64; 2] = [-50.0, 50.0];
 
 #[derive(Debug)]
 pub struct Env<P, E> {
     pub dynamics: ASDynamics<P, E>,
 
+    /// Bounds onto which all quotes are projected before execution.
+    pub quote_space: QuoteSpace,
+
+    /// Views of the inventory and midprice given to the trader. The
+    /// environment itself always evolves on the true values.
+    pub inventory_view: View,
+    pub price_view: View,
+
+    /// Midprice as observed by the trader, relative to which quotes are placed.
+    pub observed_price: f64,
+
+    /// Optional estimator of the price drift from observed prices, whose
+    /// output is appended to the trader's state.
+    pub drift_estimator: Option<DriftEstimator>,
+    pub drift_estimate: f64,
+
+    /// Optional exogenous signal, appended to the trader's state after any
+    /// drift estimate.
+    pub signal: Option<Signal>,
+
+    /// Optional observation of the adversary's attack on the price drift,
+    /// appended to the trader's state after any signal.
+    pub attack_observation: Option<AttackObservation>,
+
+    /// Optional bound on the instantaneous volatility of the price dynamics,
+    /// which, clipped to it, is appended to the trader's state last.
+    pub volatility_bound: Option<f64>,
+
+    /// Quantity posted on each side of the book, capped so that executions
+    /// cannot breach the inventory bounds.
+    pub order_size: f64,
+
+    // Ask and bid sizes posted over the current step by `step_sized`, in
+    // place of `order_size`.
+    quote_sizes: Option<[f64; 2]>,
+
+    /// Optional override of quotes near the inventory bounds, and the number
+    /// of steps in the episode on which it withdrew a quote.
+    pub shield: Option<Shield>,
+    pub n_overrides: usize,
+
+    /// Rate per unit time at which the episode ends before the fixed horizon.
+    /// Rather than cutting episodes short, the probability of continuing is
+    /// reported as each transition's discount.
+    pub hazard_rate: f64,
+
+    /// Clock by which each step advances, and the time elapsed over the last
+    /// step.
+    pub clock: Clock,
+    pub elapsed: f64,
+
+    /// Weights of the quadratic inventory penalties in the reward.
+    pub inventory_penalty: InventoryPenalty,
+
+    /// How the inventory remaining at the horizon is liquidated.
+    pub liquidation: Liquidation,
+
+    /// Fees and rebates on fills and on the liquidation at the horizon,
+    /// charged to both the reward and the wealth.
+    pub fees: FeeSchedule,
+
     pub inv: f64,
     pub inv_terminal: f64,
 
@@ -32,33 +109,67 @@ pub struct Env<P, E> {
 
     pub reward: f64,
     pub wealth: f64,
+    pub episode_reward: f64,
+
+    // Preallocated [time, inventory(, drift_estimate)] buffers, refreshed on
+    // every step.
+    state: Vector<f64>,
+    true_state: Vector<f64>,
+
+    // Recent true [price, inventory] pairs, most recent last.
+    history: VecDeque<[f64; 2]>,
 }
 
 impl Env<BrownianMotion, PoissonRate> {
     pub fn default() -> Self {
-        Self::new(ASDynamics::new(
-            0.005, 100.0, thread_rng(),
-            BrownianMotion::new(0.005, 2.0),
-            PoissonRate::default()
-        ))
+        Self::new(ASDynamics::default())
     }
 }
 
 impl Env<BrownianMotionWithDrift, PoissonRate> {
     pub fn default_with_drift() -> Self {
-        Self::new(ASDynamics::new(
-            0.005, 100.0, thread_rng(),
-            BrownianMotionWithDrift::new(0.005, 0.0, 2.0),
-            PoissonRate::default()
-        ))
+        Self::new(ASDynamics::default_with_drift(0.0))
     }
 }
 
 impl<P: PriceDynamics, E: ExecutionDynamics> Env<P, E> {
     pub fn new(dynamics: ASDynamics<P, E>) -> Self {
-        Self {
+        let mut env = Self {
             dynamics,
 
+            quote_space: QuoteSpace::default(),
+
+            inventory_view: View::exact(),
+            price_view: View::exact(),
+
+            observed_price: 0.0,
+
+            drift_estimator: None,
+            drift_estimate: 0.0,
+
+            signal: None,
+
+            attack_observation: None,
+
+            volatility_bound: None,
+
+            order_size: 1.0,
+            quote_sizes: None,
+
+            shield: None,
+            n_overrides: 0,
+
+            hazard_rate: 0.0,
+
+            clock: Clock::default(),
+            elapsed: 0.0,
+
+            inventory_penalty: InventoryPenalty::default(),
+
+            liquidation: Liquidation::default(),
+
+            fees: FeeSchedule::default(),
+
             inv: 0.0,
             inv_terminal: 0.0,
 
@@ -67,68 +178,489 @@ impl<P: PriceDynamics, E: ExecutionDynamics> Env<P, E> {
 
             reward: 0.0,
             wealth: 0.0,
+            episode_reward: 0.0,
+
+            state: Vector::zeros(2),
+            true_state: Vector::zeros(2),
+
+            history: VecDeque::new(),
+        };
+
+        env.refresh_state();
+        env
+    }
+
+    /// Consume the environment, returning it with the given views of the
+    /// inventory and midprice.
+    pub fn with_views(mut self, inventory_view: View, price_view: View) -> Self {
+        self.inventory_view = inventory_view;
+        self.price_view = price_view;
+
+        self.history.clear();
+        self.refresh_state();
+        self
+    }
+
+    /// Consume the environment, returning it with the given drift estimator
+    /// appended to the trader's state.
+    pub fn with_drift_estimator(mut self, estimator: DriftEstimator) -> Self {
+        self.drift_estimator = Some(estimator);
+        self.drift_estimate = 0.0;
+
+        self.resize_state();
+        self.refresh_state();
+        self
+    }
+
+    /// Consume the environment, returning it with the given exogenous signal
+    /// appended to the trader's state.
+    pub fn with_signal(mut self, signal: Signal) -> Self {
+        self.signal = Some(signal);
+
+        self.resize_state();
+        self.refresh_state();
+        self
+    }
+
+    /// Consume the environment, returning it with the given observation of the
+    /// drift set by an adversary appended to the trader's state. The drift is
+    /// read after each step, i.e. that of the step just taken.
+    pub fn with_attack_observation(mut self, observation: AttackObservation) -> Self {
+        self.attack_observation = Some(observation);
+
+        self.resize_state();
+        self.refresh_state();
+        self
+    }
+
+    /// Consume the environment, returning it with the instantaneous volatility
+    /// of the price dynamics, up to `bound`, appended to the trader's state.
+    pub fn with_volatility_observation(mut self, bound: f64) -> Self {
+        self.volatility_bound = Some(bound);
+
+        self.resize_state();
+        self.refresh_state();
+        self
+    }
+
+    fn resize_state(&mut self) {
+        let dim = 2
+            + self.drift_estimator.map_or(0, |_| 1)
+            + self.signal.map_or(0, |_| 1)
+            + self.attack_observation.map_or(0, |_| 1)
+            + self.volatility_bound.map_or(0, |_| 1);
+
+        self.state = Vector::zeros(dim);
+        self.true_state = Vector::zeros(dim);
+    }
+
+    /// Consume the environment, returning it with `size` units posted on each
+    /// side of the book by default.
+    pub fn with_order_size(mut self, size: f64) -> Self {
+        self.order_size = size;
+        self
+    }
+
+    /// Step the environment with the given quotes, posting `sizes` units on
+    /// the ask and bid sides respectively in place of `order_size`.
+    ///
+    /// Matched orders may still be filled only partially, as sampled by the
+    /// execution dynamics, and a side with a size of zero is not quoted.
+    pub fn step_sized(&mut self, quotes: [f64; 2], sizes: [f64; 2]) -> Transition<Vector<f64>, [f64; 2]> {
+        self.quote_sizes = Some([sizes[0].max(0.0), sizes[1].max(0.0)]);
+
+        let t = self.step(quotes);
+
+        self.quote_sizes = None;
+
+        t
+    }
+
+    /// Consume the environment, returning it with the given space of quotes,
+    /// onto which the trader's quotes are mapped.
+    pub fn with_quote_space(mut self, quote_space: QuoteSpace) -> Self {
+        self.quote_space = quote_space;
+        self
+    }
+
+    /// Consume the environment, returning it with the given shield over the
+    /// trader's quotes.
+    pub fn with_shield(mut self, shield: Shield) -> Self {
+        self.shield = Some(shield);
+        self
+    }
+
+    /// Consume the environment, returning it with a random horizon that ends
+    /// the episode at the given rate, in addition to the fixed horizon.
+    pub fn with_random_horizon(mut self, hazard_rate: f64) -> Self {
+        self.hazard_rate = hazard_rate;
+        self
+    }
+
+    /// Consume the environment, returning it with random numbers drawn in
+    /// bulk, `n_steps` steps' worth at a time; see `ASDynamics`.
+    pub fn with_precomputed_draws(mut self, n_steps: usize) -> Self {
+        self.dynamics = self.dynamics.with_precomputed_draws(n_steps);
+        self
+    }
+
+    /// Consume the environment, returning it with the midprice moving against
+    /// the trader after each of its fills; see `ASDynamics`.
+    pub fn with_adverse_selection(mut self, adverse_selection: AdverseSelection) -> Self {
+        self.dynamics = self.dynamics.with_adverse_selection(adverse_selection);
+        self
+    }
+
+    /// Consume the environment, returning it with the given clock.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Consume the environment, returning it with the given inventory
+    /// penalties.
+    pub fn with_inventory_penalty(mut self, penalty: InventoryPenalty) -> Self {
+        self.inventory_penalty = penalty;
+        self
+    }
+
+    /// Consume the environment, returning it with its initial inventory and
+    /// time drawn from `start`.
+    ///
+    /// The draws use the random number generator of the dynamics, so this
+    /// should follow any call to `seeded`.
+    pub fn with_random_start(mut self, start: StartDistribution) -> Self {
+        let inv = self.dynamics.sample_uniform(start.inv.0, start.inv.1);
+        let time = self.dynamics.sample_uniform(start.time.0, start.time.1);
+
+        self.inv = inv;
+        self.dynamics.time = (time / self.dynamics.dt).floor() * self.dynamics.dt;
+
+        self.history.clear();
+        self.refresh_state();
+        self
+    }
+
+    /// Consume the environment, returning it continued from the midprice and
+    /// inventory at which a previous episode ended, e.g. one whose inventory
+    /// was carried past the horizon.
+    pub fn continued_from(mut self, price: f64, inv: f64) -> Self {
+        self.dynamics.price = price;
+        self.dynamics.price_initial = price;
+        self.inv = inv;
+
+        self.history.clear();
+        self.refresh_state();
+        self
+    }
+
+    /// Consume the environment, returning it with the given model of terminal
+    /// liquidation.
+    pub fn with_liquidation(mut self, liquidation: Liquidation) -> Self {
+        self.liquidation = liquidation;
+        self
+    }
+
+    /// Consume the environment, returning it with the given fee schedule.
+    pub fn with_fees(mut self, fees: FeeSchedule) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Consume the environment, returning it with the market rules and costs
+    /// of `config`.
+    pub fn with_config(self, config: EnvConfig) -> Self {
+        self.with_order_size(config.order_size)
+            .with_random_horizon(config.hazard_rate)
+            .with_clock(config.clock)
+            .with_inventory_penalty(config.inventory_penalty)
+            .with_liquidation(config.liquidation)
+            .with_fees(config.fees)
+    }
+
+    /// Market rules and costs currently in effect.
+    pub fn config(&self) -> EnvConfig {
+        EnvConfig {
+            order_size: self.order_size,
+            hazard_rate: self.hazard_rate,
+            clock: self.clock,
+            inventory_penalty: self.inventory_penalty,
+            liquidation: self.liquidation,
+            fees: self.fees,
+        }
+    }
+
+    /// Current state of the environment as observed by the trader, without
+    /// allocating an observation.
+    ///
+    /// Note that direct modifications of `inv` or `dynamics.time` are only
+    /// reflected after the next call to `step`.
+    pub fn state(&self) -> &Vector<f64> { &self.state }
+
+    /// Current state of the environment, uncorrupted by the trader's views.
+    pub fn true_state(&self) -> &Vector<f64> { &self.true_state }
+
+    /// True if the trader observes the inventory and midprice exactly.
+    pub fn is_fully_observed(&self) -> bool {
+        self.inventory_view.is_exact() && self.price_view.is_exact()
+    }
+
+    /// Reset the random number generator of the underlying dynamics.
+    pub fn reseed(&mut self, seed: u64) { self.dynamics.reseed(seed); }
+
+    /// Consume the environment, returning it with the given seed.
+    ///
+    /// The initial observation is redrawn so that it too is reproducible.
+    pub fn seeded(mut self, seed: u64) -> Self {
+        self.reseed(seed);
+
+        if !self.is_fully_observed() {
+            self.history.clear();
+            self.refresh_state();
         }
+
+        self
     }
 
-    fn do_executions(&mut self, ask_price: f64, bid_price: f64) {
-        if self.inv > INV_BOUNDS[0] {
-            if let Some(ask_offset) = self.dynamics.try_execute_ask(ask_price) {
+    fn refresh_state(&mut self) {
+        let max_delay = self.inventory_view.delay.max(self.price_view.delay);
+
+        self.history.push_back([self.dynamics.price, self.inv]);
+
+        while self.history.len() > max_delay + 1 {
+            self.history.pop_front();
+        }
+
+        let inv = self.observe(self.inventory_view, 1);
+        let last_price = self.observed_price;
+
+        self.observed_price = self.observe(self.price_view, 0);
+
+        self.state[0] = self.dynamics.time;
+        self.state[1] = inv.min(INV_BOUNDS[1]).max(INV_BOUNDS[0]);
+
+        self.true_state[0] = self.dynamics.time;
+        self.true_state[1] = self.inv.min(INV_BOUNDS[1]).max(INV_BOUNDS[0]);
+
+        if let Some(estimator) = self.drift_estimator {
+            // Only update on steps, not when the initial state is redrawn:
+            if self.dynamics.time > 0.0 {
+                self.drift_estimate = estimator.update(
+                    self.drift_estimate, self.observed_price - last_price, self.dynamics.dt
+                );
+            }
+
+            self.state[2] = self.drift_estimate;
+            self.true_state[2] = self.drift_estimate;
+        }
+
+        if let Some(signal) = self.signal {
+            let i = 2 + self.drift_estimator.map_or(0, |_| 1);
+
+            self.state[i] = signal.observed();
+            self.true_state[i] = signal.observed();
+        }
+
+        if let Some(observation) = self.attack_observation {
+            let i = 2 + self.drift_estimator.map_or(0, |_| 1) + self.signal.map_or(0, |_| 1);
+            let value = observation.observe(self.dynamics.price_dynamics.drift());
+
+            self.state[i] = value;
+            self.true_state[i] = value;
+        }
+
+        if let Some(bound) = self.volatility_bound {
+            let i = self.state.len() - 1;
+            let volatility = self.dynamics.price_dynamics.volatility().min(bound);
+
+            self.state[i] = volatility;
+            self.true_state[i] = volatility;
+        }
+    }
+
+    // Lagged and noisy value of the given component of the history, falling
+    // back on the oldest record early in the episode.
+    fn observe(&mut self, view: View, component: usize) -> f64 {
+        let n = self.history.len();
+        let value = self.history[n - 1 - view.delay.min(n - 1)][component];
+
+        value + self.dynamics.sample_noise(view.noise)
+    }
+
+    fn do_executions(&mut self, ask_price: f64, bid_price: f64, allowed: [bool; 2]) {
+        let posted = self.quote_sizes.unwrap_or([self.order_size; 2]);
+
+        if allowed[0] && posted[0] > 0.0 && self.inv > INV_BOUNDS[0] {
+            let ask_size = posted[0].min(self.inv - INV_BOUNDS[0]);
+
+            if let Some((ask_offset, size)) = self.dynamics.try_execute_ask(ask_price, ask_size) {
+                let rebate = self.fees.maker(size);
+
                 self.ask_executed = true;
-                self.inv -= 1.0;
-                self.reward += ask_offset;
-                self.wealth += ask_price;
+                self.inv -= size;
+                self.reward += ask_offset * size + rebate;
+                self.wealth += ask_price * size + rebate;
             }
         }
 
-        if self.inv < INV_BOUNDS[1] {
-            if let Some(bid_offset) = self.dynamics.try_execute_bid(bid_price) {
+        if allowed[1] && posted[1] > 0.0 && self.inv < INV_BOUNDS[1] {
+            let bid_size = posted[1].min(INV_BOUNDS[1] - self.inv);
+
+            if let Some((bid_offset, size)) = self.dynamics.try_execute_bid(bid_price, bid_size) {
+                let rebate = self.fees.maker(size);
+
                 self.bid_executed = true;
-                self.inv += 1.0;
-                self.reward += bid_offset;
-                self.wealth -= bid_price;
+                self.inv += size;
+                self.reward += bid_offset * size + rebate;
+                self.wealth -= bid_price * size - rebate;
             }
         }
     }
 
+    // Close out the inventory at the horizon, charging any liquidation costs
+    // and price moves to both wealth and reward.
+    fn liquidate(&mut self) {
+        let inv = self.inv;
+
+        match self.liquidation {
+            Liquidation::Midprice => self.sell_at_impact(inv, 0.0),
+            Liquidation::LinearImpact { eta } => self.sell_at_impact(inv, eta * inv),
+            Liquidation::CrossSpread { half_spread } => self.sell_at_impact(inv, half_spread * inv.signum()),
+            Liquidation::Carry => {},
+            Liquidation::Gradual { n_slices, eta } => {
+                let slice = inv / n_slices as f64;
+                let mut held = inv;
+
+                for k in 0..n_slices {
+                    self.sell_at_impact(slice, eta * slice);
+                    held -= slice;
+
+                    if k + 1 < n_slices {
+                        let price_inc = self.dynamics.sample_price_increment();
+
+                        self.dynamics.price += price_inc;
+                        self.reward += held * price_inc;
+                    }
+                }
+            },
+        }
+    }
+
+    // Sell `size` (buy if negative) at the midprice less `impact`, paying the
+    // taker fee.
+    fn sell_at_impact(&mut self, size: f64, impact: f64) {
+        let fee = self.fees.taker(size);
+
+        self.wealth += (self.dynamics.price - impact) * size - fee;
+        self.reward -= impact * size + fee;
+    }
+
+    // Advance the midprice, and the signal if any, by one step, returning the
+    // price increment.
+    fn innovate(&mut self) -> f64 {
+        let signal = match self.signal {
+            Some(signal) => signal,
+            None => return self.dynamics.innovate(),
+        };
+
+        let scale = self.dynamics.price_dynamics.volatility() * self.dynamics.dt.sqrt();
+        let alpha = signal.price_drift() * self.dynamics.dt;
+        let price_inc = self.dynamics.innovate();
+
+        self.dynamics.price += alpha;
+
+        let price_shock = if scale > 0.0 {
+            (price_inc - self.dynamics.price_dynamics.drift() * self.dynamics.dt) / scale
+        } else {
+            0.0
+        };
+        let noise = self.dynamics.sample_noise(1.0);
+
+        if let Some(ref mut signal) = self.signal {
+            signal.advance(self.dynamics.dt, price_shock, noise);
+        }
+
+        price_inc + alpha
+    }
+
     fn update_state(&mut self, ask_offset: f64, bid_offset: f64) {
-        let ask_price = self.dynamics.price + ask_offset;
-        let bid_price = self.dynamics.price - bid_offset;
+        let ask_price = self.observed_price + ask_offset;
+        let bid_price = self.observed_price - bid_offset;
 
-        self.reward = self.inv * self.dynamics.innovate();
+        self.reward = self.inv * self.innovate();
         self.ask_executed = false;
         self.bid_executed = false;
 
-        self.do_executions(ask_price, bid_price);
+        let allowed = self.shield.map_or([true, true], |s| s.allowed_sides(self.inv, INV_BOUNDS));
+
+        if !(allowed[0] && allowed[1]) {
+            self.n_overrides += 1;
+        }
+
+        self.do_executions(ask_price, bid_price, allowed);
+
+        self.reward -= self.inventory_penalty.running * self.inv.powi(2) * self.dynamics.dt;
+
+        // The horizon is observed with the inventory still held, since a
+        // learner bootstrapping from the truncated transition should value the
+        // position rather than the flat book left once it is liquidated:
+        self.refresh_state();
 
         if self.is_terminal() {
-            // Execute market order favourably at midprice:
-            self.wealth += self.dynamics.price * self.inv;
-            self.reward -= 0.5 * self.inv.powi(2);
+            self.reward -= self.inventory_penalty.terminal * self.inv.powi(2);
+            self.liquidate();
 
             self.inv_terminal = self.inv;
             self.inv = 0.0;
         }
+
+        self.episode_reward += self.reward;
     }
 }
 
 impl<P: PriceDynamics, E: ExecutionDynamics> Domain for Env<P, E> {
     type StateSpace = LinearSpace<Interval>;
-    type ActionSpace = DoubleSpace<Reals>;
+    type ActionSpace = QuoteSpace;
 
     fn emit(&self) -> Observation<Vector<f64>> {
-        let state = vec![self.dynamics.time, self.inv.min(INV_BOUNDS[1]).max(INV_BOUNDS[0])];
-
         if self.is_terminal() {
-            Observation::Terminal(state.into())
+            Observation::Terminal(self.state.clone())
+        } else if self.is_fully_observed() {
+            Observation::Full(self.state.clone())
         } else {
-            Observation::Full(state.into())
+            Observation::Partial(self.state.clone())
         }
     }
 
     fn step(&mut self, action: [f64; 2]) -> Transition<Vector<f64>, [f64; 2]> {
         let from = self.emit();
+        let action = self.quote_space.map(action);
+
+        match self.clock {
+            Clock::Fixed => {
+                self.update_state(action[0], action[1]);
+                self.elapsed = self.dynamics.dt;
+            },
+            Clock::Event { tick } => {
+                let time = self.dynamics.time;
+                let price = self.dynamics.price;
+                let mut reward = 0.0;
+
+                loop {
+                    self.update_state(action[0], action[1]);
+                    reward += self.reward;
 
-        self.update_state(action[0], action[1]);
+                    if self.ask_executed || self.bid_executed || self.is_terminal()
+                        || (self.dynamics.price - price).abs() >= tick
+                    {
+                        break
+                    }
+                }
+
+                self.reward = reward;
+                self.elapsed = self.dynamics.time - time;
+            },
+        }
 
         let to = self.emit();
         let reward = self.reward(&from, &to);
@@ -138,6 +670,10 @@ impl<P: PriceDynamics, E: ExecutionDynamics> Domain for Env<P, E> {
             action,
             reward,
             to,
+            discount: (-self.hazard_rate * self.elapsed).exp(),
+            // The trader's horizon is an artefact of the episode, not an end
+            // to the market:
+            truncated: self.is_terminal(),
         }
     }
 
@@ -150,12 +686,60 @@ impl<P: PriceDynamics, E: ExecutionDynamics> Domain for Env<P, E> {
     }
 
     fn state_space(&self) -> Self::StateSpace {
-        LinearSpace::empty()
+        let space = LinearSpace::empty()
             + Interval::bounded(0.0, 1.0)
-            + Interval::bounded(INV_BOUNDS[0], INV_BOUNDS[1])
+            + Interval::bounded(INV_BOUNDS[0], INV_BOUNDS[1]);
+
+        let space = match self.drift_estimator {
+            Some(estimator) => space + estimator.space(),
+            None => space,
+        };
+        let space = match self.signal {
+            Some(signal) => space + Interval::bounded(-signal.bound, signal.bound),
+            None => space,
+        };
+        let space = match self.attack_observation {
+            Some(observation) => space + observation.space(),
+            None => space,
+        };
+
+        match self.volatility_bound {
+            Some(bound) => space + Interval::bounded(0.0, bound),
+            None => space,
+        }
     }
 
-    fn action_space(&self) -> Self::ActionSpace {
-        DoubleSpace::new(Reals, Reals)
+    fn action_space(&self) -> Self::ActionSpace { self.quote_space }
+}
+
+#[cfg(test)]
+mod tests {
+    use rsrl::domains::{Domain, Observation};
+    use super::Env;
+
+    #[test]
+    fn test_horizon_transition() {
+        // Quotes too wide to be filled, so the inventory is held throughout:
+        let mut env = Env::default().seeded(0).continued_from(100.0, 5.0);
+
+        let t = loop {
+            let t = env.step([10.0, 10.0]);
+
+            if t.terminated() { break t; }
+
+            assert!(!t.truncated);
+        };
+
+        assert!(t.truncated);
+        assert_eq!(t.from.state()[1], 5.0);
+        assert_eq!(t.to.state()[1], 5.0);
+
+        match t.to {
+            Observation::Terminal(_) => {},
+            _ => panic!("The horizon should be terminal."),
+        }
+
+        assert_eq!(env.inv, 0.0);
+        assert_eq!(env.inv_terminal, 5.0);
     }
 }
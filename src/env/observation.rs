@@ -0,0 +1,298 @@
+//! Agent-specific observations built on top of the environment state.
+use crate::env::{
+    Env,
+    dynamics::{execution::ExecutionDynamics, price::PriceDynamics},
+};
+use rsrl::{
+    domains::{Domain, Observation, Transition},
+    geometry::{continuous::Interval, product::LinearSpace, Vector},
+};
+use std::{collections::VecDeque, str::FromStr};
+
+/// Delayed and noisy view of a quantity tracked by the environment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct View {
+    /// Number of steps by which the observed value lags the true value.
+    pub delay: usize,
+
+    /// Standard deviation of the Gaussian noise added to the lagged value.
+    pub noise: f64,
+}
+
+impl View {
+    pub fn new(delay: usize, noise: f64) -> View { View { delay, noise, } }
+
+    /// View of the true, current value.
+    pub fn exact() -> View { View::default() }
+
+    pub fn is_exact(&self) -> bool { self.delay == 0 && self.noise == 0.0 }
+}
+
+impl FromStr for View {
+    type Err = String;
+
+    /// Parse a view of the form `delay,noise`.
+    fn from_str(s: &str) -> Result<View, String> {
+        let mut parts = s.splitn(2, ',');
+
+        let delay = parts.next().unwrap().trim().parse::<usize>()
+            .map_err(|e| format!("Invalid delay in view `{}`: {}.", s, e))?;
+        let noise = parts.next()
+            .ok_or_else(|| format!("View `{}` must be of the form delay,noise.", s))?
+            .trim().parse::<f64>()
+            .map_err(|e| format!("Invalid noise in view `{}`: {}.", s, e))?;
+
+        if noise < 0.0 {
+            return Err(format!("View `{}` has negative noise.", s));
+        }
+
+        Ok(View::new(delay, noise))
+    }
+}
+
+/// Observation builder augmenting the `[time, inventory]` state with a
+/// trailing window of market and quoting history.
+///
+/// Over the last `len` steps, the window summarises the net midprice move and
+/// the trader's mean reservation price offset and half-spread. The resulting
+/// observation is `[time, inventory, price_move, reservation, half_spread]`,
+/// or the unaltered state when `len` is zero. Summaries keep the observation
+/// low-dimensional, which matters for tensor-product bases such as
+/// `Polynomial`.
+#[derive(Clone, Debug)]
+pub struct TrailingWindow {
+    len: usize,
+    max_move: f64,
+
+    prices: VecDeque<f64>,
+    quotes: VecDeque<[f64; 2]>,
+}
+
+impl TrailingWindow {
+    /// Window over the last `len` steps, with net price moves clipped to
+    /// `[-max_move, max_move]`.
+    pub fn new(len: usize, max_move: f64) -> TrailingWindow {
+        TrailingWindow {
+            len,
+            max_move,
+
+            prices: VecDeque::with_capacity(len + 1),
+            quotes: VecDeque::with_capacity(len),
+        }
+    }
+
+    /// Builder that passes the environment state through unchanged.
+    pub fn disabled() -> TrailingWindow { TrailingWindow::new(0, 0.0) }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_enabled(&self) -> bool { self.len > 0 }
+
+    /// Space of observations produced for the given environment.
+    pub fn state_space<P, E>(&self, env: &Env<P, E>) -> LinearSpace<Interval>
+    where
+        P: PriceDynamics,
+        E: ExecutionDynamics,
+    {
+        let space = env.state_space();
+
+        if self.is_enabled() {
+            space
+                + Interval::bounded(-self.max_move, self.max_move)
+                + env.quote_space.reservation
+                + env.quote_space.half_spread
+        } else {
+            space
+        }
+    }
+
+    /// Clear the history at the start of an episode.
+    pub fn reset<P, E>(&mut self, env: &Env<P, E>) {
+        self.prices.clear();
+        self.quotes.clear();
+
+        self.prices.push_back(env.dynamics.price);
+    }
+
+    /// Record the outcome of a step taken with the given `[ask, bid]` quote
+    /// offsets, as returned in the environment's transition.
+    pub fn record<P, E>(&mut self, env: &Env<P, E>, quotes: [f64; 2]) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.prices.push_back(env.dynamics.price);
+        self.quotes.push_back([(quotes[0] - quotes[1]) / 2.0, (quotes[0] + quotes[1]) / 2.0]);
+
+        if self.prices.len() > self.len + 1 {
+            self.prices.pop_front();
+        }
+
+        if self.quotes.len() > self.len {
+            self.quotes.pop_front();
+        }
+    }
+
+    /// Record an environment transition and rewrite it in terms of the
+    /// observations of an agent that took `action` having observed `from`.
+    pub fn transition<P, E, A>(
+        &mut self,
+        env: &Env<P, E>,
+        from: Observation<Vector<f64>>,
+        action: A,
+        t: &Transition<Vector<f64>, [f64; 2]>,
+    ) -> Transition<Vector<f64>, A>
+    {
+        self.record(env, t.action);
+
+        Transition {
+            from,
+            action,
+            reward: t.reward,
+            to: self.observe(&t.to),
+            discount: t.discount,
+            truncated: t.truncated,
+        }
+    }
+
+    /// Augment an environment observation with the current history.
+    pub fn observe(&self, obs: &Observation<Vector<f64>>) -> Observation<Vector<f64>> {
+        obs.clone().map(|s| self.augment(&s))
+    }
+
+    /// Augment a state with the current history.
+    pub fn augment(&self, state: &Vector<f64>) -> Vector<f64> {
+        if !self.is_enabled() {
+            return state.clone();
+        }
+
+        let price_move = match (self.prices.front(), self.prices.back()) {
+            (Some(first), Some(last)) => (last - first).min(self.max_move).max(-self.max_move),
+            _ => 0.0,
+        };
+
+        let n = self.quotes.len().max(1) as f64;
+        let (reservation, half_spread) = self.quotes.iter().fold((0.0, 0.0), |acc, q| {
+            (acc.0 + q[0] / n, acc.1 + q[1] / n)
+        });
+
+        Self::extend(state, [price_move, reservation, half_spread])
+    }
+
+    /// Augment a state with an empty history, e.g. for probing a policy.
+    pub fn pad(&self, state: &Vector<f64>) -> Vector<f64> {
+        if self.is_enabled() {
+            Self::extend(state, [0.0; 3])
+        } else {
+            state.clone()
+        }
+    }
+
+    fn extend(state: &Vector<f64>, summary: [f64; 3]) -> Vector<f64> {
+        state.iter().cloned().chain(summary.iter().cloned()).collect()
+    }
+}
+
+impl Default for TrailingWindow {
+    fn default() -> TrailingWindow { TrailingWindow::disabled() }
+}
+
+/// Online estimate of the price drift, as an exponentially weighted moving
+/// average of observed price increments per unit time.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DriftEstimator {
+    /// Weight in (0, 1] given to the most recent increment.
+    pub rate: f64,
+
+    /// Bound on the magnitude of the estimate, which is clipped accordingly.
+    pub max_drift: f64,
+}
+
+impl DriftEstimator {
+    pub fn new(rate: f64, max_drift: f64) -> DriftEstimator { DriftEstimator { rate, max_drift, } }
+
+    /// Range of the estimate.
+    pub fn space(&self) -> Interval { Interval::bounded(-self.max_drift, self.max_drift) }
+
+    /// Update the estimate given the latest price increment over `dt`.
+    pub fn update(&self, estimate: f64, increment: f64, dt: f64) -> f64 {
+        let estimate = (1.0 - self.rate) * estimate + self.rate * increment / dt;
+
+        estimate.min(self.max_drift).max(-self.max_drift)
+    }
+}
+
+impl FromStr for DriftEstimator {
+    type Err = String;
+
+    /// Parse an estimator of the form `rate,max_drift`.
+    fn from_str(s: &str) -> Result<DriftEstimator, String> {
+        let params = s.split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid estimator `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match params.as_slice() {
+            &[rate, max_drift] if rate > 0.0 && rate <= 1.0 && max_drift > 0.0 =>
+                Ok(DriftEstimator::new(rate, max_drift)),
+            &[_, _] => Err(format!("Estimator `{}` requires a rate in (0, 1] and a positive bound.", s)),
+            _ => Err(format!("Estimator `{}` must be of the form rate,max_drift.", s)),
+        }
+    }
+}
+
+/// What the trader is told of the adversary's attack, for ablations of how
+/// much of the robustness gap is due to the attack being unobserved.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AttackObservation {
+    /// Whether the price currently has a non-zero drift, i.e. whether an
+    /// attack is under way.
+    Indicator,
+
+    /// The drift of the price itself, clipped to `bound`; an oracle.
+    Drift { bound: f64 },
+}
+
+impl AttackObservation {
+    /// Range of the observed value.
+    pub fn space(&self) -> Interval {
+        match *self {
+            AttackObservation::Indicator => Interval::bounded(0.0, 1.0),
+            AttackObservation::Drift { bound } => Interval::bounded(-bound, bound),
+        }
+    }
+
+    /// Observed value given the drift of the price dynamics.
+    pub fn observe(&self, drift: f64) -> f64 {
+        match *self {
+            AttackObservation::Indicator => if drift != 0.0 { 1.0 } else { 0.0 },
+            AttackObservation::Drift { bound } => drift.min(bound).max(-bound),
+        }
+    }
+}
+
+impl FromStr for AttackObservation {
+    type Err = String;
+
+    /// Parse `indicator` or `drift:bound`.
+    fn from_str(s: &str) -> Result<AttackObservation, String> {
+        let mut parts = s.splitn(2, ':');
+        let kind = parts.next().unwrap().trim();
+        let bound = parts.next().map(|x| {
+            x.trim().parse::<f64>().map_err(|e| format!("Invalid attack observation `{}`: {}.", s, e))
+        });
+
+        match (kind, bound) {
+            ("indicator", None) => Ok(AttackObservation::Indicator),
+            ("drift", Some(bound)) => {
+                let bound = bound?;
+
+                if bound > 0.0 {
+                    Ok(AttackObservation::Drift { bound })
+                } else {
+                    Err(format!("Attack observation `{}` requires a positive bound.", s))
+                }
+            },
+            _ => Err(format!("Attack observation `{}` must be indicator or drift:bound.", s)),
+        }
+    }
+}
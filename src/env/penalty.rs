@@ -0,0 +1,76 @@
+//! Quadratic penalties on the trader's inventory, and their annealing over
+//! the course of training.
+use rsrl::core::Parameter;
+use std::str::FromStr;
+
+/// Weights of the quadratic inventory penalties subtracted from the reward.
+///
+/// The running penalty is charged on the inventory held at the end of every
+/// step, in proportion to the step's duration; the terminal penalty on the
+/// inventory liquidated at the end of the episode.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InventoryPenalty {
+    pub running: f64,
+    pub terminal: f64,
+}
+
+impl InventoryPenalty {
+    pub fn new(running: f64, terminal: f64) -> InventoryPenalty { InventoryPenalty { running, terminal, } }
+
+    pub fn scaled(self, factor: f64) -> InventoryPenalty {
+        InventoryPenalty::new(self.running * factor, self.terminal * factor)
+    }
+}
+
+impl Default for InventoryPenalty {
+    /// Terminal penalty of one half, without a running penalty.
+    fn default() -> InventoryPenalty { InventoryPenalty::new(0.0, 0.5) }
+}
+
+impl FromStr for InventoryPenalty {
+    type Err = String;
+
+    /// Parse a penalty of the form `running,terminal`.
+    fn from_str(s: &str) -> Result<InventoryPenalty, String> {
+        let params = s.split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid penalty `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match params.as_slice() {
+            &[running, terminal] if running >= 0.0 && terminal >= 0.0 =>
+                Ok(InventoryPenalty::new(running, terminal)),
+            &[_, _] => Err(format!("Penalty `{}` must have non-negative weights.", s)),
+            _ => Err(format!("Penalty `{}` must be of the form running,terminal.", s)),
+        }
+    }
+}
+
+/// Penalty that grows from zero towards `target` as training progresses.
+///
+/// Starting from the full penalty tends to collapse early learning into
+/// quoting wide enough never to trade; annealing lets the trader first learn
+/// to capture the spread. The shortfall from the target decays by a factor
+/// of `tau` per episode.
+#[derive(Clone, Copy, Debug)]
+pub struct PenaltySchedule {
+    pub target: InventoryPenalty,
+
+    shortfall: Parameter,
+}
+
+impl PenaltySchedule {
+    /// The full `target` penalty throughout.
+    pub fn fixed(target: InventoryPenalty) -> PenaltySchedule {
+        PenaltySchedule { target, shortfall: Parameter::fixed(0.0), }
+    }
+
+    pub fn annealed(target: InventoryPenalty, tau: f64) -> PenaltySchedule {
+        PenaltySchedule { target, shortfall: Parameter::exponential(1.0, 0.0, tau), }
+    }
+
+    /// Penalty for the current episode.
+    pub fn current(&self) -> InventoryPenalty { self.target.scaled(1.0 - self.shortfall.value()) }
+
+    /// Advance the schedule by one episode.
+    pub fn step(&mut self) { self.shortfall = self.shortfall.step(); }
+}
@@ -0,0 +1,98 @@
+//! Bounded space of quotes placed by the market maker.
+use rsrl::geometry::{continuous::Interval, BoundedSpace, Card, Space, Surjection};
+
+const MAX_OFFSET: f64 = 10.0;
+
+/// Product of a reservation price offset interval and a non-negative
+/// half-spread interval.
+///
+/// Values are `[ask_offset, bid_offset]` pairs, as consumed by `Env::step`,
+/// where `ask_offset = reservation + half_spread` and
+/// `bid_offset = half_spread - reservation`. Mapping a pair onto the space
+/// clamps its reservation offset and half-spread to their respective bounds.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct QuoteSpace {
+    pub reservation: Interval,
+    pub half_spread: Interval,
+}
+
+impl QuoteSpace {
+    pub fn new(reservation: Interval, half_spread: Interval) -> QuoteSpace {
+        QuoteSpace {
+            reservation,
+            half_spread: Interval::new(
+                Some(half_spread.inf().map_or(0.0, |lb| lb.max(0.0))),
+                half_spread.sup(),
+            ),
+        }
+    }
+}
+
+impl Default for QuoteSpace {
+    fn default() -> QuoteSpace {
+        QuoteSpace::new(
+            Interval::bounded(-MAX_OFFSET, MAX_OFFSET),
+            Interval::bounded(0.0, MAX_OFFSET),
+        )
+    }
+}
+
+impl Space for QuoteSpace {
+    type Value = [f64; 2];
+
+    fn dim(&self) -> usize { 2 }
+
+    fn card(&self) -> Card { Card::Infinite }
+}
+
+impl Surjection<[f64; 2], [f64; 2]> for QuoteSpace {
+    fn map(&self, quotes: [f64; 2]) -> [f64; 2] {
+        let reservation = self.reservation.map((quotes[0] - quotes[1]) / 2.0);
+        let half_spread = self.half_spread.map((quotes[0] + quotes[1]) / 2.0);
+
+        [reservation + half_spread, half_spread - reservation]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rsrl::geometry::{continuous::Interval, BoundedSpace, Surjection};
+    use super::QuoteSpace;
+
+    fn space() -> QuoteSpace {
+        QuoteSpace::new(Interval::bounded(-1.0, 1.0), Interval::bounded(0.0, 2.0))
+    }
+
+    #[test]
+    fn test_within_bounds() {
+        assert_eq!(space().map([1.0, 0.5]), [1.0, 0.5]);
+        assert_eq!(space().map([0.0, 0.0]), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_clamp_upper_bounds() {
+        // Reservation offset 8 and half-spread 2:
+        assert_eq!(space().map([10.0, -6.0]), [3.0, 1.0]);
+
+        // Reservation offset 0 and half-spread 5:
+        assert_eq!(space().map([5.0, 5.0]), [2.0, 2.0]);
+    }
+
+    #[test]
+    fn test_clamp_lower_bounds() {
+        // Reservation offset -8 and half-spread 2:
+        assert_eq!(space().map([-6.0, 10.0]), [1.0, 3.0]);
+
+        // Reservation offset -1 and half-spread -2:
+        assert_eq!(space().map([-3.0, -1.0]), [-1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_non_negative_half_spread() {
+        let space = QuoteSpace::new(Interval::bounded(-1.0, 1.0), Interval::bounded(-1.0, 2.0));
+
+        assert_eq!(space.half_spread.inf(), Some(0.0));
+        // Reservation offset 2 and half-spread -1:
+        assert_eq!(space.map([1.0, -3.0]), [1.0, -1.0]);
+    }
+}
@@ -0,0 +1,137 @@
+//! Bundles of seeded scenarios for reproducible training and evaluation.
+//!
+//! A bundle records the dynamics from which paths are drawn together with
+//! the seeds of each path, rather than the paths themselves: executions
+//! depend on the quotes of the agent being evaluated, so the realised path is
+//! only fixed once both the seeds and the agent are.
+use crate::{
+    env::{
+        Env,
+        dynamics::{ASDynamics, execution::{PerSide, PoissonRate}, price::BrownianMotionWithDrift},
+    },
+    error::{Error, Result},
+};
+use rand::{rngs::SmallRng, FromEntropy};
+use rsrl::core::SeedStream;
+#[cfg(feature = "fs")]
+use std::{fs::File, io::{BufReader, BufWriter}};
+
+pub type ScenarioEnv = Env<BrownianMotionWithDrift, PerSide<PoissonRate, PoissonRate>>;
+
+/// Serialisable specification of the price and execution dynamics.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DynamicsSpec {
+    pub dt: f64,
+    pub price: f64,
+
+    pub price_dynamics: BrownianMotionWithDrift,
+    pub execution_dynamics: PerSide<PoissonRate, PoissonRate>,
+}
+
+impl DynamicsSpec {
+    /// Dynamics of `ASDynamics::default_with_drift` with the given drift and
+    /// execution model.
+    pub fn new(drift: f64, execution_dynamics: PerSide<PoissonRate, PoissonRate>) -> DynamicsSpec {
+        const DT: f64 = 0.005;
+
+        DynamicsSpec {
+            dt: DT,
+            price: 100.0,
+
+            price_dynamics: BrownianMotionWithDrift::new(DT, drift, 2.0),
+            execution_dynamics,
+        }
+    }
+
+    /// Construct the dynamics, with a generator to be seeded by the caller.
+    pub fn build(&self) -> Result<ASDynamics<BrownianMotionWithDrift, PerSide<PoissonRate, PoissonRate>>> {
+        ASDynamics::new(
+            self.dt, self.price, SmallRng::from_entropy(),
+            self.price_dynamics.clone(), self.execution_dynamics.clone(),
+        )
+    }
+}
+
+/// Seeds of the environment and the agent for a single episode.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub env_seed: u64,
+    pub agent_seed: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScenarioBundle {
+    version: u32,
+    dynamics: DynamicsSpec,
+    scenarios: Vec<Scenario>,
+}
+
+impl ScenarioBundle {
+    /// Format version written by this build.
+    pub const VERSION: u32 = 1;
+
+    /// Draw `n` scenarios from `seeds`, using the same substreams as
+    /// `evaluate_trader`, so that evaluating against the bundle reproduces an
+    /// evaluation run with the same master seed.
+    pub fn generate(dynamics: DynamicsSpec, n: usize, seeds: SeedStream) -> Result<ScenarioBundle> {
+        if n == 0 {
+            return Err(Error::InvalidParameter { name: "scenarios", value: 0.0 });
+        }
+
+        dynamics.build()?;
+
+        let scenarios = (0..n).map(|j| {
+            let seeds = seeds.substream(j as u64);
+
+            Scenario { env_seed: seeds.seed(0), agent_seed: seeds.seed(1), }
+        }).collect();
+
+        Ok(ScenarioBundle { version: ScenarioBundle::VERSION, dynamics, scenarios, })
+    }
+
+    pub fn dynamics(&self) -> &DynamicsSpec { &self.dynamics }
+
+    pub fn scenarios(&self) -> &[Scenario] { &self.scenarios }
+
+    pub fn len(&self) -> usize { self.scenarios.len() }
+
+    pub fn is_empty(&self) -> bool { self.scenarios.is_empty() }
+
+    /// Environment of the `i`th scenario (modulo the size of the bundle) and
+    /// the seed for the agent. The environment is passed through `decorate`,
+    /// e.g. to configure observations, before it is seeded.
+    pub fn scenario<F>(&self, i: usize, decorate: F) -> (ScenarioEnv, u64)
+    where
+        F: Fn(ScenarioEnv) -> ScenarioEnv,
+    {
+        let s = self.scenarios[i % self.scenarios.len()];
+        let dynamics = self.dynamics.build().expect("Scenario dynamics are checked on construction.");
+
+        (decorate(Env::new(dynamics)).seeded(s.env_seed), s.agent_seed)
+    }
+
+    /// Write the bundle as JSON to `path`.
+    #[cfg(feature = "fs")]
+    pub fn save(&self, path: &str) -> Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+
+        serde_json::to_writer_pretty(writer, self).map_err(|e| ::std::io::Error::from(e).into())
+    }
+
+    /// Read a bundle written by `save`, checking its version and dynamics.
+    #[cfg(feature = "fs")]
+    pub fn load(path: &str) -> Result<ScenarioBundle> {
+        let reader = BufReader::new(File::open(path)?);
+        let bundle: ScenarioBundle = serde_json::from_reader(reader).map_err(::std::io::Error::from)?;
+
+        if bundle.version != ScenarioBundle::VERSION {
+            return Err(Error::BundleVersion { expected: ScenarioBundle::VERSION, found: bundle.version, });
+        }
+
+        if bundle.scenarios.is_empty() {
+            return Err(Error::InvalidParameter { name: "scenarios", value: 0.0 });
+        }
+
+        bundle.dynamics.build().map(|_| bundle)
+    }
+}
@@ -0,0 +1,35 @@
+//! Rule-based safety layer over the trader's quotes.
+use std::str::FromStr;
+
+/// Override of the trader's quotes near the inventory bounds.
+///
+/// Within `buffer` of either bound, the side of the book that would grow the
+/// position is withdrawn and only the reducing side is quoted.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Shield {
+    pub buffer: f64,
+}
+
+impl Shield {
+    pub fn new(buffer: f64) -> Shield { Shield { buffer, } }
+
+    /// Whether the `[ask, bid]` sides may be quoted at inventory `inv`.
+    pub fn allowed_sides(&self, inv: f64, bounds: [f64; 2]) -> [bool; 2] {
+        [inv > bounds[0] + self.buffer, inv < bounds[1] - self.buffer]
+    }
+}
+
+impl FromStr for Shield {
+    type Err = String;
+
+    /// Parse the buffer of a shield.
+    fn from_str(s: &str) -> Result<Shield, String> {
+        let buffer = s.trim().parse::<f64>().map_err(|e| format!("Invalid shield `{}`: {}.", s, e))?;
+
+        if buffer < 0.0 {
+            return Err(format!("Shield `{}` has a negative buffer.", s));
+        }
+
+        Ok(Shield::new(buffer))
+    }
+}
@@ -0,0 +1,78 @@
+//! Exogenous signal observed by the trader alongside its own state.
+//!
+//! The signal follows Ornstein-Uhlenbeck dynamics whose shocks may be
+//! correlated with those of the midprice, like the price of a related
+//! futures contract, and may in turn drive the midprice, like a short-term
+//! alpha. Quoting strategies conditioned on the signal can then be learned
+//! in the same environment.
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Signal {
+    /// Rate of mean reversion to zero and volatility, per unit time.
+    pub rate: f64,
+    pub volatility: f64,
+
+    /// Correlation of the signal's shocks with those of the midprice over
+    /// the same step.
+    pub correlation: f64,
+
+    /// Drift contributed to the midprice per unit of signal, making the
+    /// signal predictive of future price moves.
+    pub loading: f64,
+
+    /// Bound on the signal as observed by the trader.
+    pub bound: f64,
+
+    pub value: f64,
+}
+
+impl Signal {
+    pub fn new(rate: f64, volatility: f64, correlation: f64, loading: f64) -> Signal {
+        // Three stationary standard deviations, or enough for ten units of
+        // time's worth of diffusion without mean reversion:
+        let bound = if rate > 0.0 {
+            3.0 * volatility / (2.0 * rate).sqrt()
+        } else {
+            3.0 * volatility * 10.0f64.sqrt()
+        };
+
+        Signal { rate, volatility, correlation, loading, bound: bound.max(1e-6), value: 0.0, }
+    }
+
+    /// Drift of the midprice due to the signal over the next step.
+    pub fn price_drift(&self) -> f64 { self.loading * self.value }
+
+    /// Advance the signal by `dt`, given the standardised shock of the
+    /// midprice over the same step and an independent standard normal
+    /// `noise`.
+    pub fn advance(&mut self, dt: f64, price_shock: f64, noise: f64) {
+        let shock = self.correlation * price_shock + (1.0 - self.correlation.powi(2)).sqrt() * noise;
+
+        self.value += -self.rate * self.value * dt + self.volatility * dt.sqrt() * shock;
+    }
+
+    /// Signal as observed by the trader.
+    pub fn observed(&self) -> f64 { self.value.min(self.bound).max(-self.bound) }
+}
+
+impl FromStr for Signal {
+    type Err = String;
+
+    /// Parse a signal of the form `rate,volatility,correlation,loading`.
+    fn from_str(s: &str) -> Result<Signal, String> {
+        let params = s.split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid signal `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match params.as_slice() {
+            &[rate, volatility, correlation, loading]
+                if rate >= 0.0 && volatility >= 0.0 && correlation >= -1.0 && correlation <= 1.0 =>
+                Ok(Signal::new(rate, volatility, correlation, loading)),
+            _ => Err(format!(
+                "Signal `{}` must be of the form rate,volatility,correlation,loading with non-negative \
+                 rate and volatility and correlation in [-1, 1].", s
+            )),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Value iteration for the nominal market making problem.
+//!
+//! With unit orders, a risk-neutral trader and an execution model that does
+//! not depend on the trader's past orders, the environment's reward only
+//! depends on the time and the inventory in expectation: the inventory earns
+//! the drift of the midprice, each fill earns its offset, and the quadratic
+//! penalties are charged on the inventory held after the fills, together
+//! with the expected cost of liquidating it at the horizon. The optimal
+//! quotes can then be found by backward induction on a grid of inventories,
+//! which gives an upper bound on what the learned traders can achieve, and
+//! value surfaces against which to check their critics.
+//!
+//! The robust counterpart lets an adversary pick the drift from a bounded
+//! set at every step, as in adversarial training. The drift only enters the
+//! reward through the inventory carried over the step, before any fills, so
+//! the adversary's best response is to drift against the inventory at the
+//! bound, whatever the quotes, and the minimax backup has the same form as
+//! the nominal one.
+use crate::env::{
+    INV_BOUNDS,
+    dynamics::{ASDynamics, execution::{ExecutionDynamics, Side}, price::PriceDynamics},
+    liquidation::Liquidation,
+    penalty::InventoryPenalty,
+};
+
+/// Largest drift that the adversaries may choose, as in their training.
+pub const DRIFT_BOUND: f64 = 5.0;
+
+/// Optimal value and quotes at a point of the grid, and the drift faced
+/// there, as logged to a results file.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct SolutionRecord {
+    pub time: f64,
+    pub inv: f64,
+    pub value: f64,
+    pub ask: f64,
+    pub bid: f64,
+    pub drift: f64,
+}
+
+/// Optimal value and quotes for every step and integer inventory.
+#[derive(Clone, Debug)]
+pub struct Solution {
+    pub dt: f64,
+    pub invs: Vec<f64>,
+
+    /// Expected return from the start of each step by inventory, with a last
+    /// row for the horizon.
+    pub values: Vec<Vec<f64>>,
+
+    /// Optimal `[ask, bid]` offsets at each step by inventory; NaN on a side
+    /// that cannot trade at that inventory.
+    pub quotes: Vec<Vec<[f64; 2]>>,
+
+    /// Drift faced at each inventory: the nominal drift, or the adversary's
+    /// worst case.
+    pub drifts: Vec<f64>,
+}
+
+impl Solution {
+    pub fn n_steps(&self) -> usize { self.quotes.len() }
+
+    fn step_index(&self, time: f64) -> usize {
+        ((time / self.dt + 1e-9).floor().max(0.0) as usize).min(self.n_steps())
+    }
+
+    fn inv_index(&self, inv: f64) -> usize {
+        ((inv - self.invs[0]).round().max(0.0) as usize).min(self.invs.len() - 1)
+    }
+
+    /// Optimal expected return from `time` onwards holding `inv`, at the
+    /// nearest point of the grid.
+    pub fn value(&self, time: f64, inv: f64) -> f64 {
+        self.values[self.step_index(time)][self.inv_index(inv)]
+    }
+
+    /// Optimal quotes at `time` holding `inv`, at the nearest point of the
+    /// grid; the last step's quotes are used at the horizon.
+    pub fn quotes(&self, time: f64, inv: f64) -> [f64; 2] {
+        self.quotes[self.step_index(time).min(self.n_steps() - 1)][self.inv_index(inv)]
+    }
+
+    /// Flatten the solution with a record per step and inventory.
+    pub fn records(&self) -> Vec<SolutionRecord> {
+        let mut records = vec![];
+
+        for (n, (values, quotes)) in self.values.iter().zip(self.quotes.iter()).enumerate() {
+            let points = self.invs.iter().zip(values.iter()).zip(quotes.iter()).zip(self.drifts.iter());
+
+            for (((&inv, &value), q), &drift) in points {
+                records.push(SolutionRecord { time: n as f64 * self.dt, inv, value, ask: q[0], bid: q[1], drift, });
+            }
+        }
+
+        records
+    }
+}
+
+/// Backward induction over the steps of an episode, searching a grid of
+/// offsets on each side.
+///
+/// Quotes are searched jointly, since a round trip within a step leaves the
+/// inventory unchanged, so each step costs `O(n_invs * n_offsets^2)`.
+#[derive(Clone, Debug)]
+pub struct ValueIteration<E> {
+    pub execution_dynamics: E,
+    pub dt: f64,
+
+    /// Drift of the midprice per unit time.
+    pub drift: f64,
+    pub penalty: InventoryPenalty,
+    pub liquidation: Liquidation,
+
+    /// Offsets from the midprice considered on each side.
+    pub offsets: Vec<f64>,
+}
+
+impl<E: ExecutionDynamics> ValueIteration<E> {
+    pub fn new(execution_dynamics: E, dt: f64, offsets: Vec<f64>) -> ValueIteration<E> {
+        ValueIteration {
+            execution_dynamics, dt,
+
+            drift: 0.0,
+            penalty: InventoryPenalty::default(),
+            liquidation: Liquidation::default(),
+
+            offsets,
+        }
+    }
+
+    /// Solver for the nominal model of `dynamics`, i.e. with the drift of its
+    /// price model held fixed, over `n_offsets` offsets evenly spaced in
+    /// _[0, max_offset]_.
+    pub fn for_dynamics<P>(dynamics: &ASDynamics<P, E>, max_offset: f64, n_offsets: usize) -> ValueIteration<E>
+    where
+        P: PriceDynamics,
+        E: Clone,
+    {
+        let n = n_offsets.max(2);
+        let offsets = (0..n).map(|i| max_offset * i as f64 / (n - 1) as f64).collect();
+
+        ValueIteration::new(dynamics.execution_dynamics.clone(), dynamics.dt, offsets)
+            .with_drift(dynamics.price_dynamics.drift())
+    }
+
+    pub fn with_drift(self, drift: f64) -> ValueIteration<E> {
+        ValueIteration { drift, ..self }
+    }
+
+    pub fn with_penalty(self, penalty: InventoryPenalty) -> ValueIteration<E> {
+        ValueIteration { penalty, ..self }
+    }
+
+    pub fn with_liquidation(self, liquidation: Liquidation) -> ValueIteration<E> {
+        ValueIteration { liquidation, ..self }
+    }
+
+    pub fn n_steps(&self) -> usize { (1.0 / self.dt).round() as usize }
+
+    /// Solve the nominal problem, at the solver's drift.
+    pub fn solve(&self) -> Solution {
+        self.solve_with(|_| self.drift)
+    }
+
+    /// Solve the robust problem, against an adversary choosing the drift from
+    /// _[-drift_bound, drift_bound]_ at every step.
+    pub fn solve_robust(&self, drift_bound: f64) -> Solution {
+        // Any drift is a best response when flat; zero is reported:
+        self.solve_with(|inv| if inv == 0.0 { 0.0 } else { -drift_bound * inv.signum() })
+    }
+
+    // Backward induction with the drift at each inventory given by `drift`.
+    fn solve_with(&self, drift: impl Fn(f64) -> f64) -> Solution {
+        let n_steps = self.n_steps();
+        let invs: Vec<f64> = (INV_BOUNDS[0] as i64..=INV_BOUNDS[1] as i64).map(|q| q as f64).collect();
+        let drifts: Vec<f64> = invs.iter().map(|&q| drift(q)).collect();
+        let n_invs = invs.len();
+
+        let probs = |side| -> Vec<f64> {
+            self.offsets.iter().map(|&o| self.execution_dynamics.side_match_prob(side, o)).collect()
+        };
+        let (ask_probs, bid_probs) = (probs(Side::Ask), probs(Side::Bid));
+
+        let mut values = vec![vec![0.0; n_invs]; n_steps + 1];
+        let mut quotes = vec![vec![[::std::f64::NAN; 2]; n_invs]; n_steps];
+
+        values[n_steps] = invs.iter().map(|&q| {
+            -self.penalty.terminal * q * q - self.liquidation.expected_cost(q)
+        }).collect();
+
+        for n in (0..n_steps).rev() {
+            // Value of the inventory held after the fills, net of the running
+            // penalty on it:
+            let cont: Vec<f64> = invs.iter().zip(values[n + 1].iter()).map(|(q, v)| {
+                v - self.penalty.running * q * q * self.dt
+            }).collect();
+
+            for i in 0..n_invs {
+                let c0 = cont[i];
+                let can_ask = i > 0;
+                let can_bid = i + 1 < n_invs;
+
+                // Gains from an ask or a bid fill alone, and the correction
+                // when both sides fill:
+                let down = if can_ask { cont[i - 1] - c0 } else { 0.0 };
+                let up = if can_bid { cont[i + 1] - c0 } else { 0.0 };
+                let both = -(down + up);
+
+                let mut best = (::std::f64::NEG_INFINITY, [::std::f64::NAN; 2]);
+
+                for (ka, &ask) in self.offsets.iter().enumerate() {
+                    let pa = if can_ask { ask_probs[ka] } else { 0.0 };
+                    let gain_a = pa * (ask + down);
+
+                    for (kb, &bid) in self.offsets.iter().enumerate() {
+                        let pb = if can_bid { bid_probs[kb] } else { 0.0 };
+                        let gain = gain_a + pb * (bid + up) + pa * pb * both;
+
+                        if gain > best.0 {
+                            best = (gain, [ask, bid]);
+                        }
+
+                        if !can_bid { break; }
+                    }
+
+                    if !can_ask { break; }
+                }
+
+                values[n][i] = invs[i] * drifts[i] * self.dt + c0 + best.0;
+                quotes[n][i] = [
+                    if can_ask { best.1[0] } else { ::std::f64::NAN },
+                    if can_bid { best.1[1] } else { ::std::f64::NAN },
+                ];
+            }
+        }
+
+        Solution { dt: self.dt, invs, values, quotes, drifts, }
+    }
+}
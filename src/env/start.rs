@@ -0,0 +1,73 @@
+//! Randomised initial states for training episodes.
+//!
+//! Episodes that always start at _(t = 0, inv = 0)_ leave the critic to
+//! extrapolate to the states reached late in an episode or with a large
+//! position. Drawing the initial inventory and time uniformly at random
+//! improves its coverage of the state space.
+use crate::env::INV_BOUNDS;
+use std::str::FromStr;
+
+/// Closed interval from which a component of the initial state is drawn
+/// uniformly.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds(pub f64, pub f64);
+
+impl Bounds {
+    /// The single point `x`.
+    pub fn point(x: f64) -> Bounds { Bounds(x, x) }
+
+    pub fn is_point(&self) -> bool { self.0 == self.1 }
+}
+
+impl FromStr for Bounds {
+    type Err = String;
+
+    /// Parse bounds of the form `lower,upper`, or a single point.
+    fn from_str(s: &str) -> Result<Bounds, String> {
+        let params = s.split(',')
+            .map(|x| x.trim().parse::<f64>().map_err(|e| format!("Invalid bounds `{}`: {}.", s, e)))
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match params.as_slice() {
+            &[x] => Ok(Bounds::point(x)),
+            &[lower, upper] if lower <= upper => Ok(Bounds(lower, upper)),
+            _ => Err(format!("Bounds `{}` must be of the form lower,upper with lower <= upper.", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StartDistribution {
+    pub inv: Bounds,
+
+    /// Bounds on the initial time; draws are rounded down to a whole number
+    /// of steps, so that episodes still end at the horizon.
+    pub time: Bounds,
+}
+
+impl StartDistribution {
+    /// Check that the inventory lies within `INV_BOUNDS` and the time within
+    /// _[0, 1)_.
+    pub fn new(inv: Bounds, time: Bounds) -> Result<StartDistribution, String> {
+        if inv.0 < INV_BOUNDS[0] || inv.1 > INV_BOUNDS[1] {
+            return Err(format!(
+                "Initial inventory must lie within [{}, {}].", INV_BOUNDS[0], INV_BOUNDS[1]
+            ));
+        }
+
+        if time.0 < 0.0 || time.1 >= 1.0 {
+            return Err("Initial time must lie within [0, 1).".to_owned());
+        }
+
+        Ok(StartDistribution { inv, time, })
+    }
+
+    /// True if every episode starts at _(t = 0, inv = 0)_.
+    pub fn is_default(&self) -> bool { *self == StartDistribution::default() }
+}
+
+impl Default for StartDistribution {
+    fn default() -> StartDistribution {
+        StartDistribution { inv: Bounds::point(0.0), time: Bounds::point(0.0), }
+    }
+}
@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 #[derive(Debug)]
 pub struct LinearUtilityStrategy {
     k: f64,
@@ -53,3 +55,35 @@ impl ExponentialUtilityStrategy {
         [rp + sp / 2.0 - price, price - (rp - sp / 2.0)]
     }
 }
+
+/// One of the closed-form strategies above, selectable by name with the
+/// parameters used throughout the experiments.
+#[derive(Debug)]
+pub enum Baseline {
+    Linear(LinearUtilityStrategy),
+    LinearPenalty(LinearUtilityTerminalPenaltyStrategy),
+    Exponential(ExponentialUtilityStrategy),
+}
+
+impl Baseline {
+    pub fn compute(&self, time: f64, price: f64, inventory: f64) -> [f64; 2] {
+        match self {
+            Baseline::Linear(s) => s.compute(time, price, inventory),
+            Baseline::LinearPenalty(s) => s.compute(time, price, inventory),
+            Baseline::Exponential(s) => s.compute(time, price, inventory),
+        }
+    }
+}
+
+impl FromStr for Baseline {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Baseline, String> {
+        match s {
+            "linear" => Ok(Baseline::Linear(LinearUtilityStrategy::new(1.5))),
+            "linear_penalty" => Ok(Baseline::LinearPenalty(LinearUtilityTerminalPenaltyStrategy::new(1.5, 0.1))),
+            "exponential" => Ok(Baseline::Exponential(ExponentialUtilityStrategy::new(1.5, 0.1, 2.0))),
+            _ => Err(format!("Unknown baseline `{}`; expected linear, linear_penalty or exponential.", s)),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Error type shared by the fallible library APIs.
+use bincode;
+use std::{error, fmt, io, result};
+
+#[derive(Debug)]
+pub enum Error {
+    /// Failure reading from or writing to a file or stream.
+    Io(io::Error),
+
+    /// Failure (de)serialising an agent.
+    Serialisation(bincode::Error),
+
+    /// A model parameter outside of its valid domain.
+    InvalidParameter {
+        name: &'static str,
+        value: f64,
+    },
+
+    /// An agent whose basis was built for states of a different dimension.
+    StateDimension {
+        expected: usize,
+        found: usize,
+    },
+
+    /// Weight matrices of two agents that cannot be compared.
+    IncompatibleShapes {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+
+    /// A scenario bundle written in an unsupported format version.
+    BundleVersion {
+        expected: u32,
+        found: u32,
+    },
+
+    /// A compressed checkpoint written in an unsupported format version.
+    CheckpointVersion {
+        expected: u32,
+        found: u32,
+    },
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Serialisation(e) => write!(f, "serialisation error: {}", e),
+            Error::InvalidParameter { name, value } =>
+                write!(f, "invalid value for parameter `{}`: {}", name, value),
+            Error::StateDimension { expected, found } =>
+                write!(f, "state dimension mismatch: expected {}, found basis over {}", expected, found),
+            Error::IncompatibleShapes { expected, found } =>
+                write!(f, "incompatible weight shapes: expected {:?}, found {:?}", expected, found),
+            Error::BundleVersion { expected, found } =>
+                write!(f, "unsupported scenario bundle version: expected {}, found {}", expected, found),
+            Error::CheckpointVersion { expected, found } =>
+                write!(f, "unsupported checkpoint version: expected {}, found {}", expected, found),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Serialisation(e) => Some(e),
+            Error::InvalidParameter { .. }
+            | Error::StateDimension { .. }
+            | Error::IncompatibleShapes { .. }
+            | Error::BundleVersion { .. }
+            | Error::CheckpointVersion { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error { Error::Io(e) }
+}
+
+impl From<bincode::Error> for Error {
+    fn from(e: bincode::Error) -> Error { Error::Serialisation(e) }
+}
@@ -0,0 +1,101 @@
+//! C-compatible interface for policy inference.
+//!
+//! The functions below are exported from the `cdylib` build of the crate and
+//! allow a saved trader to be embedded into existing C/C++ infrastructure. See
+//! `include/algo_hft.h` for the corresponding declarations.
+use crate::agents::{load_trader, state_dim, tta, Trader};
+use rsrl::{geometry::Vector, policies::Policy};
+use std::{
+    ffi::CStr,
+    os::raw::{c_char, c_double, c_int},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+    slice,
+};
+
+pub const ALGO_HFT_OK: c_int = 0;
+pub const ALGO_HFT_NULL_POINTER: c_int = -1;
+pub const ALGO_HFT_PANIC: c_int = -2;
+pub const ALGO_HFT_BAD_STATE: c_int = -3;
+
+/// Load a serialised trader from `path`.
+///
+/// Returns a null pointer if the path is invalid or the trader could not be
+/// deserialised. The returned handle must be released with
+/// `algo_hft_trader_free`.
+///
+/// # Safety
+/// `path` must be null or point to a NUL-terminated string that stays valid
+/// for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn algo_hft_trader_load(path: *const c_char) -> *mut Trader {
+    if path.is_null() {
+        return ptr::null_mut();
+    }
+
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(path) => path.to_owned(),
+        Err(_) => return ptr::null_mut(),
+    };
+
+    match catch_unwind(|| load_trader(path)) {
+        Ok(Ok(trader)) => Box::into_raw(Box::new(trader)),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Compute the (ask, bid) quote offsets of the trader's greedy policy.
+///
+/// The state is read from `state[0..state_len]` and must follow the layout
+/// emitted by `Env`, i.e. `[time, inventory]`. The ask and bid offsets
+/// relative to the midprice are written to `quotes[0]` and `quotes[1]`,
+/// respectively. Returns `ALGO_HFT_BAD_STATE` if `state_len` does not match
+/// the dimension of the trader's basis.
+///
+/// # Safety
+/// `trader` must be null or a live handle from `algo_hft_trader_load` that is
+/// not in use by another thread, `state` must be null or valid for reads of
+/// `state_len` values, and `quotes` must be null or valid for writes of two
+/// values.
+#[no_mangle]
+pub unsafe extern "C" fn algo_hft_trader_quotes(
+    trader: *mut Trader,
+    state: *const c_double,
+    state_len: usize,
+    quotes: *mut c_double,
+) -> c_int
+{
+    if trader.is_null() || state.is_null() || quotes.is_null() {
+        return ALGO_HFT_NULL_POINTER;
+    }
+
+    let trader = &mut *trader;
+
+    if state_len != state_dim(trader) {
+        return ALGO_HFT_BAD_STATE;
+    }
+
+    let state = Vector::from_vec(slice::from_raw_parts(state, state_len).to_vec());
+
+    match catch_unwind(AssertUnwindSafe(|| tta(trader.policy.mpa(&state)))) {
+        Ok([ask, bid]) => {
+            *quotes = ask;
+            *quotes.offset(1) = bid;
+
+            ALGO_HFT_OK
+        },
+        Err(_) => ALGO_HFT_PANIC,
+    }
+}
+
+/// Release a trader previously returned by `algo_hft_trader_load`.
+///
+/// # Safety
+/// `trader` must be null or a handle from `algo_hft_trader_load` that has not
+/// already been released, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn algo_hft_trader_free(trader: *mut Trader) {
+    if !trader.is_null() {
+        drop(Box::from_raw(trader));
+    }
+}
@@ -1,9 +1,35 @@
+extern crate bincode;
+#[cfg(feature = "training")]
 extern crate rand;
+#[cfg(feature = "training")]
+extern crate rayon;
 extern crate rsrl;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[cfg(feature = "training")]
+extern crate serde_json;
+extern crate special_fun;
+#[cfg(feature = "cli")]
+extern crate csv;
+#[cfg(feature = "toml-dynamics")]
+extern crate toml;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+#[cfg(feature = "compressed-checkpoints")]
+extern crate zstd;
 
 pub mod agents;
+#[cfg(feature = "training")]
 pub mod env;
+pub mod error;
+#[cfg(feature = "fs")]
+pub mod ffi;
+#[cfg(feature = "training")]
+pub mod metrics;
+#[cfg(feature = "cli")]
+pub mod output;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod stats;
 pub mod utils;
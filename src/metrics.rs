@@ -0,0 +1,146 @@
+//! Prometheus/OpenMetrics exposition of training progress.
+//!
+//! A `Metrics` handle is cheap to clone and can be updated from the training
+//! loop while a background thread serves the current values in the
+//! Prometheus text format on `GET /metrics` (any path is accepted).
+use std::{
+    collections::VecDeque,
+    fmt::Write as FmtWrite,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Instant,
+};
+
+#[derive(Debug)]
+struct State {
+    started: Instant,
+
+    episodes: u64,
+    steps: u64,
+
+    window: usize,
+    rewards: VecDeque<f64>,
+
+    learning_rates: Vec<(String, f64)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Metrics(Arc<Mutex<State>>);
+
+impl Metrics {
+    /// Create a new handle, averaging rewards over the last `window` episodes.
+    pub fn new(window: usize) -> Metrics {
+        Metrics(Arc::new(Mutex::new(State {
+            started: Instant::now(),
+
+            episodes: 0,
+            steps: 0,
+
+            window: window.max(1),
+            rewards: VecDeque::with_capacity(window.max(1)),
+
+            learning_rates: vec![],
+        })))
+    }
+
+    /// Spawn a background thread serving the metrics on `0.0.0.0:port`.
+    pub fn serve(&self, port: u16) -> io::Result<()> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let metrics = self.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if let Ok(stream) = stream {
+                    metrics.respond(stream).ok();
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    fn respond(&self, mut stream: TcpStream) -> io::Result<()> {
+        // The request itself is irrelevant; drain what is available.
+        let mut buffer = [0u8; 1024];
+        let _ = stream.read(&mut buffer)?;
+
+        let body = self.render();
+
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(), body
+        )?;
+
+        stream.flush()
+    }
+
+    /// Record the completion of a training episode.
+    pub fn record_episode(&self, steps: u64, reward: f64) {
+        let mut state = self.0.lock().unwrap();
+
+        state.episodes += 1;
+        state.steps += steps;
+
+        if state.rewards.len() >= state.window {
+            state.rewards.pop_front();
+        }
+
+        state.rewards.push_back(reward);
+    }
+
+    /// Set the current value of a named learning rate.
+    pub fn set_learning_rate(&self, component: &str, value: f64) {
+        let mut state = self.0.lock().unwrap();
+        let position = state.learning_rates.iter().position(|lr| lr.0 == component);
+
+        match position {
+            Some(i) => state.learning_rates[i].1 = value,
+            None => state.learning_rates.push((component.to_owned(), value)),
+        }
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let state = self.0.lock().unwrap();
+
+        let elapsed = state.started.elapsed();
+        let elapsed = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) * 1e-9;
+
+        let reward_mean = if state.rewards.is_empty() {
+            0.0
+        } else {
+            state.rewards.iter().sum::<f64>() / state.rewards.len() as f64
+        };
+
+        let mut out = String::new();
+
+        writeln!(out, "# HELP algo_hft_episodes_total Number of completed training episodes.").ok();
+        writeln!(out, "# TYPE algo_hft_episodes_total counter").ok();
+        writeln!(out, "algo_hft_episodes_total {}", state.episodes).ok();
+
+        writeln!(out, "# HELP algo_hft_steps_total Number of environment steps taken.").ok();
+        writeln!(out, "# TYPE algo_hft_steps_total counter").ok();
+        writeln!(out, "algo_hft_steps_total {}", state.steps).ok();
+
+        writeln!(out, "# HELP algo_hft_steps_per_second Mean environment steps per second.").ok();
+        writeln!(out, "# TYPE algo_hft_steps_per_second gauge").ok();
+        writeln!(out, "algo_hft_steps_per_second {}", state.steps as f64 / elapsed.max(1e-9)).ok();
+
+        writeln!(out, "# HELP algo_hft_reward_mean Mean episode reward over the recent window.").ok();
+        writeln!(out, "# TYPE algo_hft_reward_mean gauge").ok();
+        writeln!(out, "algo_hft_reward_mean {}", reward_mean).ok();
+
+        writeln!(out, "# HELP algo_hft_learning_rate Current learning rate of each component.").ok();
+        writeln!(out, "# TYPE algo_hft_learning_rate gauge").ok();
+
+        for &(ref component, value) in state.learning_rates.iter() {
+            writeln!(out, "algo_hft_learning_rate{{component=\"{}\"}} {}", component, value).ok();
+        }
+
+        out
+    }
+}
@@ -0,0 +1,491 @@
+//! Output sinks for tabular experiment results.
+//!
+//! All binaries write their records through an `Output`, which mirrors the
+//! `serialize`/`flush` interface of `csv::Writer` but can alternatively emit
+//! Apache Parquet files (with the `parquet-output` feature enabled).
+//!
+//! Records are flattened through `serde_json`, so they may contain a
+//! variable set of named fields (e.g. one column per evaluation probe), as
+//! long as that set is the same across all records.
+//!
+//! CSV results can also be appended to, e.g. when resuming a run from a
+//! checkpoint; records are then keyed on one of their fields, and records
+//! whose key is already present in the file are dropped.
+use serde::Serialize;
+use serde_json::{self, Value};
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{self, stdout, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Csv,
+    Parquet,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Parquet => "parquet",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Format::Csv),
+            "parquet" => Ok(Format::Parquet),
+            _ => Err(format!("Unknown output format: {}.", s)),
+        }
+    }
+}
+
+pub enum Output {
+    Csv(CsvSink),
+
+    #[cfg(feature = "parquet-output")]
+    Parquet(columnar::ParquetSink),
+}
+
+impl Output {
+    /// Create an output writing to `{stem}.{extension}`.
+    pub fn to_path<P: AsRef<Path>>(stem: P, format: Format) -> io::Result<Output> {
+        let path = stem.as_ref().with_extension(format.extension());
+
+        match format {
+            Format::Csv => {
+                let file: Box<dyn Write + Send> = Box::new(File::create(path)?);
+
+                Ok(Output::Csv(CsvSink::new(file)))
+            },
+            Format::Parquet => Output::parquet(Some(path)),
+        }
+    }
+
+    /// Create an output appending to `{stem}.csv`, or creating it if it does
+    /// not exist yet.
+    ///
+    /// Records must have the same columns as those already in the file, and
+    /// are skipped if their `key` field matches that of an existing record.
+    pub fn append_to_path<P: AsRef<Path>>(stem: P, format: Format, key: &str) -> io::Result<Output> {
+        if format != Format::Csv {
+            return Err(io::Error::new(io::ErrorKind::Other, "Appending requires CSV output."));
+        }
+
+        let path = stem.as_ref().with_extension(format.extension());
+
+        let (columns, seen) = if path.exists() {
+            let mut reader = ::csv::Reader::from_path(&path)?;
+            let columns: Vec<String> = reader.headers()?.iter().map(|c| c.to_owned()).collect();
+            let index = columns.iter().position(|c| c == key);
+
+            let mut seen = HashSet::new();
+
+            for row in reader.records() {
+                let row = row?;
+
+                if let Some(value) = index.and_then(|i| row.get(i)) {
+                    seen.insert(value.to_owned());
+                }
+            }
+
+            (if columns.is_empty() { None } else { Some(columns) }, seen)
+        } else {
+            (None, HashSet::new())
+        };
+
+        let file: Box<dyn Write + Send> = Box::new(OpenOptions::new().create(true).append(true).open(path)?);
+        let mut sink = CsvSink::new(file);
+
+        sink.header = columns.is_some();
+        sink.columns = columns;
+        sink.dedup = Some((key.to_owned(), seen));
+
+        Ok(Output::Csv(sink))
+    }
+
+    /// Create an output writing to the standard output stream.
+    pub fn to_stdout(format: Format) -> io::Result<Output> {
+        match format {
+            Format::Csv => {
+                let out: Box<dyn Write + Send> = Box::new(stdout());
+
+                Ok(Output::Csv(CsvSink::new(out)))
+            },
+            Format::Parquet => Output::parquet(None),
+        }
+    }
+
+    #[cfg(feature = "parquet-output")]
+    fn parquet(path: Option<PathBuf>) -> io::Result<Output> {
+        Ok(Output::Parquet(columnar::ParquetSink::new(path)))
+    }
+
+    #[cfg(not(feature = "parquet-output"))]
+    fn parquet(_: Option<PathBuf>) -> io::Result<Output> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "Parquet output requires the `parquet-output` feature."
+        ))
+    }
+
+    pub fn serialize<T: Serialize>(&mut self, record: T) -> io::Result<()> {
+        match self {
+            Output::Csv(sink) => sink.serialize(record),
+
+            #[cfg(feature = "parquet-output")]
+            Output::Parquet(sink) => sink.serialize(record),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Csv(sink) => sink.flush(),
+
+            #[cfg(feature = "parquet-output")]
+            Output::Parquet(sink) => sink.flush(),
+        }
+    }
+}
+
+pub struct CsvSink {
+    writer: ::csv::Writer<Box<dyn Write + Send>>,
+    header: bool,
+
+    // Columns of the file being appended to, checked against the first record.
+    columns: Option<Vec<String>>,
+
+    // Key field and the keys written so far.
+    dedup: Option<(String, HashSet<String>)>,
+}
+
+impl CsvSink {
+    fn new(writer: Box<dyn Write + Send>) -> CsvSink {
+        CsvSink {
+            writer: ::csv::WriterBuilder::new().has_headers(false).from_writer(writer),
+            header: false,
+
+            columns: None,
+            dedup: None,
+        }
+    }
+
+    fn cell(value: Value) -> String {
+        match value {
+            // Non-finite floats are serialised as null by serde_json.
+            Value::Null => String::new(),
+            Value::String(s) => s,
+            v => v.to_string(),
+        }
+    }
+
+    pub fn serialize<T: Serialize>(&mut self, record: T) -> io::Result<()> {
+        let fields = match serde_json::to_value(record)? {
+            Value::Object(map) => map.into_iter().collect::<Vec<_>>(),
+            value => vec![("value".to_owned(), value)],
+        };
+
+        if let Some(columns) = self.columns.take() {
+            if !fields.iter().map(|f| &f.0).eq(columns.iter()) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Record columns differ from those of the file being appended to.",
+                ));
+            }
+        }
+
+        if let Some((ref key, ref mut seen)) = self.dedup {
+            let value = fields.iter()
+                .find(|f| &f.0 == key)
+                .map(|f| CsvSink::cell(f.1.clone()))
+                .ok_or_else(|| io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Record has no `{}` field.", key),
+                ))?;
+
+            if !seen.insert(value) {
+                return Ok(());
+            }
+        }
+
+        if !self.header {
+            self.writer.write_record(fields.iter().map(|f| f.0.as_str()))?;
+            self.header = true;
+        }
+
+        Ok(self.writer.write_record(fields.into_iter().map(|f| CsvSink::cell(f.1)))?)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> { self.writer.flush() }
+}
+
+/// Read every value of `column` from a CSV file.
+pub fn read_column<P: AsRef<Path>>(path: P, column: &str) -> io::Result<Vec<String>> {
+    let mut reader = ::csv::Reader::from_path(path)?;
+    let index = reader.headers()?.iter().position(|c| c == column).ok_or_else(|| io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("No `{}` column.", column),
+    ))?;
+
+    reader.records().map(|row| Ok(row?.get(index).unwrap_or("").to_owned())).collect()
+}
+
+/// Merge CSV files with identical columns into `output`, keeping the first
+/// record for each value of `key`, and return the number of records written.
+///
+/// Records are sorted by key, numerically if every key is a number.
+pub fn merge_csv<P: AsRef<Path>, Q: AsRef<Path>>(inputs: &[P], output: Q, key: &str) -> io::Result<usize> {
+    let mut columns: Option<::csv::StringRecord> = None;
+    let mut seen = HashSet::new();
+    let mut rows = vec![];
+
+    for input in inputs {
+        let mut reader = ::csv::Reader::from_path(input)?;
+        let headers = reader.headers()?.clone();
+
+        match columns {
+            Some(ref columns) if *columns != headers => return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Columns of {} differ from those of the first input.", input.as_ref().display()),
+            )),
+            Some(_) => {},
+            None => columns = Some(headers.clone()),
+        }
+
+        let index = headers.iter().position(|c| c == key).ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} has no `{}` column.", input.as_ref().display(), key),
+        ))?;
+
+        for row in reader.records() {
+            let row = row?;
+            let value = row.get(index).unwrap_or("").to_owned();
+
+            if seen.insert(value.clone()) {
+                rows.push((value, row));
+            }
+        }
+    }
+
+    if rows.iter().all(|r| r.0.parse::<f64>().is_ok()) {
+        rows.sort_by(|a, b| {
+            let (x, y) = (a.0.parse::<f64>().unwrap(), b.0.parse::<f64>().unwrap());
+
+            x.total_cmp(&y)
+        });
+    } else {
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    let mut writer = ::csv::Writer::from_path(output)?;
+
+    if let Some(columns) = columns {
+        writer.write_record(&columns)?;
+    }
+
+    for (_, row) in rows.iter() {
+        writer.write_record(row)?;
+    }
+
+    writer.flush()?;
+
+    Ok(rows.len())
+}
+
+#[cfg(feature = "parquet-output")]
+mod columnar {
+    extern crate arrow_array;
+    extern crate arrow_schema;
+    extern crate parquet;
+
+    use self::arrow_array::{
+        ArrayRef,
+        BooleanArray,
+        Float64Array,
+        Int64Array,
+        RecordBatch,
+        StringArray,
+        UInt64Array,
+    };
+    use self::arrow_schema::{DataType, Field, Schema};
+    use self::parquet::arrow::ArrowWriter;
+    use serde::Serialize;
+    use serde_json::{self, Value};
+    use std::{
+        fs::{rename, File},
+        io::{self, stdout, Write},
+        path::PathBuf,
+        sync::Arc,
+    };
+
+    #[derive(Debug)]
+    enum Column {
+        Float(Vec<f64>),
+        Int(Vec<i64>),
+        UInt(Vec<u64>),
+        Bool(Vec<bool>),
+        Str(Vec<String>),
+    }
+
+    impl Column {
+        fn from_value(value: &Value) -> Column {
+            match value {
+                Value::Bool(_) => Column::Bool(vec![]),
+                Value::Number(n) if n.is_u64() => Column::UInt(vec![]),
+                Value::Number(n) if n.is_i64() => Column::Int(vec![]),
+                Value::Number(_) | Value::Null => Column::Float(vec![]),
+                _ => Column::Str(vec![]),
+            }
+        }
+
+        fn push(&mut self, value: Value) {
+            match self {
+                // Non-finite floats are serialised as null by serde_json.
+                Column::Float(c) => c.push(value.as_f64().unwrap_or(::std::f64::NAN)),
+                Column::Int(c) => c.push(value.as_i64().unwrap_or(0)),
+                Column::UInt(c) => c.push(value.as_u64().unwrap_or(0)),
+                Column::Bool(c) => c.push(value.as_bool().unwrap_or(false)),
+                Column::Str(c) => c.push(match value {
+                    Value::String(s) => s,
+                    v => v.to_string(),
+                }),
+            }
+        }
+
+        fn data_type(&self) -> DataType {
+            match self {
+                Column::Float(_) => DataType::Float64,
+                Column::Int(_) => DataType::Int64,
+                Column::UInt(_) => DataType::UInt64,
+                Column::Bool(_) => DataType::Boolean,
+                Column::Str(_) => DataType::Utf8,
+            }
+        }
+
+        fn to_array(&self) -> ArrayRef {
+            match self {
+                Column::Float(c) => Arc::new(Float64Array::from(c.clone())),
+                Column::Int(c) => Arc::new(Int64Array::from(c.clone())),
+                Column::UInt(c) => Arc::new(UInt64Array::from(c.clone())),
+                Column::Bool(c) => Arc::new(BooleanArray::from(c.clone())),
+                Column::Str(c) => Arc::new(StringArray::from(c.clone())),
+            }
+        }
+    }
+
+    /// Column-buffered Parquet writer.
+    ///
+    /// When backed by a file, every `flush` rewrites the complete file so that
+    /// it is always readable while an experiment is still running. When backed
+    /// by the standard output, rows are written once the sink is dropped.
+    pub struct ParquetSink {
+        path: Option<PathBuf>,
+
+        names: Vec<String>,
+        columns: Vec<Column>,
+
+        n_written: usize,
+    }
+
+    impl ParquetSink {
+        pub fn new(path: Option<PathBuf>) -> ParquetSink {
+            ParquetSink {
+                path,
+
+                names: vec![],
+                columns: vec![],
+
+                n_written: 0,
+            }
+        }
+
+        fn n_rows(&self) -> usize {
+            self.columns.first().map_or(0, |c| match c {
+                Column::Float(c) => c.len(),
+                Column::Int(c) => c.len(),
+                Column::UInt(c) => c.len(),
+                Column::Bool(c) => c.len(),
+                Column::Str(c) => c.len(),
+            })
+        }
+
+        pub fn serialize<T: Serialize>(&mut self, record: T) -> io::Result<()> {
+            let fields = match serde_json::to_value(record)? {
+                Value::Object(map) => map.into_iter().collect::<Vec<_>>(),
+                value => vec![("value".to_owned(), value)],
+            };
+
+            if self.columns.is_empty() {
+                self.names = fields.iter().map(|f| f.0.clone()).collect();
+                self.columns = fields.iter().map(|f| Column::from_value(&f.1)).collect();
+            } else if fields.len() != self.columns.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Inconsistent number of fields across records."
+                ));
+            }
+
+            for (column, (_, value)) in self.columns.iter_mut().zip(fields.into_iter()) {
+                column.push(value);
+            }
+
+            Ok(())
+        }
+
+        fn write_to<W: Write + Send>(&self, writer: W) -> io::Result<()> {
+            let schema = Arc::new(Schema::new(
+                self.names.iter().zip(self.columns.iter())
+                    .map(|(n, c)| Field::new(n.as_str(), c.data_type(), false))
+                    .collect::<Vec<_>>()
+            ));
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                self.columns.iter().map(|c| c.to_array()).collect()
+            ).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+            let mut writer = ArrowWriter::try_new(writer, schema, None)?;
+
+            writer.write(&batch)?;
+            writer.close()?;
+
+            Ok(())
+        }
+
+        pub fn flush(&mut self) -> io::Result<()> {
+            let n_rows = self.n_rows();
+
+            if n_rows == 0 || n_rows == self.n_written {
+                return Ok(());
+            }
+
+            if let Some(ref path) = self.path {
+                let tmp_path = path.with_extension("parquet.tmp");
+
+                self.write_to(File::create(&tmp_path)?)?;
+                rename(&tmp_path, path)?;
+
+                self.n_written = n_rows;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl Drop for ParquetSink {
+        fn drop(&mut self) {
+            if self.path.is_some() {
+                self.flush().ok();
+            } else if self.n_rows() > 0 {
+                self.write_to(stdout()).ok();
+            }
+        }
+    }
+}
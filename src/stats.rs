@@ -0,0 +1,307 @@
+//! Two-sample tests for comparing the results of independent training runs.
+//!
+//! Each run contributes a single number per metric, e.g. its final mean
+//! wealth, so samples are small and the tests are chosen accordingly: Welch's
+//! t-test does not assume equal variances, and the Mann–Whitney U test does
+//! not assume normality. Effect sizes are reported alongside the p-values,
+//! since with few seeds the latter alone say little.
+use special_fun::FloatSpecial;
+use std::{f64, fmt::Write};
+
+fn sample_mean_var(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let ss = values.iter().fold(0.0, |acc, v| acc + (v - mean) * (v - mean));
+
+    (mean, ss / (n - 1.0))
+}
+
+/// Two-sided tail probability of Student's t distribution.
+fn student_t_p_value(t: f64, dof: f64) -> f64 { (dof / (dof + t * t)).betainc(dof / 2.0, 0.5) }
+
+/// Two-sided tail probability of the standard normal distribution.
+fn normal_p_value(z: f64) -> f64 { FloatSpecial::erfc(z.abs() / f64::consts::SQRT_2) }
+
+/// Largest sample size for which the Mann–Whitney test uses the exact
+/// distribution of U.
+const EXACT_MAX_N: usize = 20;
+
+/// Number of orderings of `m` values from `a` and `n` from `b`, without ties,
+/// giving each value of U from zero to `m * n`.
+fn u_counts(m: usize, n: usize) -> Vec<f64> {
+    // The largest value either comes from `a`, adding nothing to U, or from
+    // `b`, beating all values from `a`:
+    let mut counts = vec![vec![vec![1.0]; n + 1]; m + 1];
+
+    for i in 1..=m {
+        for j in 1..=n {
+            let mut c = vec![0.0; i * j + 1];
+
+            for (k, &x) in counts[i - 1][j].iter().enumerate() { c[k] += x; }
+            for (k, &x) in counts[i][j - 1].iter().enumerate() { c[k + i] += x; }
+
+            counts[i][j] = c;
+        }
+    }
+
+    counts[m][n].clone()
+}
+
+/// Two-sided tail probability of U under the null, from its exact
+/// distribution.
+fn exact_u_p_value(u: f64, m: usize, n: usize) -> f64 {
+    let counts = u_counts(m, n);
+    let total: f64 = counts.iter().sum();
+    let u = u as usize;
+
+    let lower: f64 = counts[..=u].iter().sum();
+    let upper: f64 = counts[u..].iter().sum();
+
+    (2.0 * lower.min(upper) / total).min(1.0)
+}
+
+/// Copy the finite values of a sample, dropping e.g. those of diverged runs.
+fn finite(values: &[f64]) -> Vec<f64> { values.iter().cloned().filter(|v| v.is_finite()).collect() }
+
+#[derive(Clone, Copy, Debug)]
+pub struct WelchTest {
+    pub t: f64,
+    pub dof: f64,
+    pub p_value: f64,
+}
+
+/// Welch's t-test of equal means; positive `t` if `b` has the larger mean.
+/// Non-finite values are ignored.
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> WelchTest {
+    let (a, b) = (&finite(a)[..], &finite(b)[..]);
+    let (mean_a, var_a) = sample_mean_var(a);
+    let (mean_b, var_b) = sample_mean_var(b);
+
+    let se_a = var_a / a.len() as f64;
+    let se_b = var_b / b.len() as f64;
+
+    let t = (mean_b - mean_a) / (se_a + se_b).sqrt();
+    let dof = (se_a + se_b).powi(2) /
+        (se_a * se_a / (a.len() as f64 - 1.0) + se_b * se_b / (b.len() as f64 - 1.0));
+
+    WelchTest { t, dof, p_value: if t.is_finite() { student_t_p_value(t, dof) } else { f64::NAN }, }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MannWhitneyTest {
+    /// Number of pairs in which the value from `b` exceeds that from `a`,
+    /// ties counting one half.
+    pub u: f64,
+    pub z: f64,
+    pub p_value: f64,
+
+    /// Whether `p_value` is from the exact distribution of U rather than the
+    /// normal approximation.
+    pub exact: bool,
+}
+
+/// Mann–Whitney U test. Non-finite values are ignored.
+///
+/// The p-value is exact if neither sample has more than `EXACT_MAX_N` values
+/// and there are no ties, and from the normal approximation with tie and
+/// continuity corrections otherwise; `z` is reported in either case.
+pub fn mann_whitney_test(a: &[f64], b: &[f64]) -> MannWhitneyTest {
+    let (a, b) = (&finite(a)[..], &finite(b)[..]);
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let n = n_a + n_b;
+
+    let mut pooled: Vec<(f64, bool)> = a.iter().map(|&v| (v, false)).chain(b.iter().map(|&v| (v, true))).collect();
+    pooled.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap());
+
+    let mut rank_sum_b = 0.0;
+    let mut ties = 0.0;
+    let mut i = 0;
+
+    while i < pooled.len() {
+        let mut j = i;
+
+        while j + 1 < pooled.len() && pooled[j + 1].0 == pooled[i].0 { j += 1; }
+
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        let count = (j - i + 1) as f64;
+
+        rank_sum_b += rank * pooled[i..=j].iter().filter(|x| x.1).count() as f64;
+        ties += count * count * count - count;
+        i = j + 1;
+    }
+
+    let u = rank_sum_b - n_b * (n_b + 1.0) / 2.0;
+    let mean = n_a * n_b / 2.0;
+    let sd = (n_a * n_b / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)))).sqrt();
+
+    let diff = u - mean;
+    let z = if sd > 0.0 { (diff - 0.5 * diff.signum()) / sd } else { 0.0 };
+
+    let exact = ties == 0.0 && a.len().max(b.len()) <= EXACT_MAX_N;
+    let p_value = if exact {
+        exact_u_p_value(u, a.len(), b.len())
+    } else if sd > 0.0 {
+        normal_p_value(z)
+    } else {
+        1.0
+    };
+
+    MannWhitneyTest { u, z, p_value, exact, }
+}
+
+/// Cohen's d with the pooled sample standard deviation; positive if `b` has
+/// the larger mean. Non-finite values are ignored.
+pub fn cohens_d(a: &[f64], b: &[f64]) -> f64 {
+    let (a, b) = (&finite(a)[..], &finite(b)[..]);
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let (mean_a, var_a) = sample_mean_var(a);
+    let (mean_b, var_b) = sample_mean_var(b);
+
+    (mean_b - mean_a) / (((n_a - 1.0) * var_a + (n_b - 1.0) * var_b) / (n_a + n_b - 2.0)).sqrt()
+}
+
+/// Comparison of one metric between two groups of runs.
+#[derive(Clone, Debug, Serialize)]
+pub struct Comparison {
+    pub metric: String,
+
+    pub n_a: usize,
+    pub mean_a: f64,
+    pub stddev_a: f64,
+
+    pub n_b: usize,
+    pub mean_b: f64,
+    pub stddev_b: f64,
+
+    pub welch_t: f64,
+    pub welch_dof: f64,
+    pub welch_p: f64,
+
+    pub mann_whitney_u: f64,
+    pub mann_whitney_p: f64,
+
+    pub cohens_d: f64,
+
+    /// Probability that a run from `b` beats one from `a`, rescaled to
+    /// _[-1, 1]_.
+    pub rank_biserial: f64,
+
+    /// `higher` or `lower` for `b` relative to `a` if both tests reject at
+    /// the chosen level, `inconclusive` otherwise.
+    pub verdict: &'static str,
+}
+
+impl Comparison {
+    /// Compare `metric` between the groups; each needs at least two runs
+    /// with finite values, and runs with non-finite values are left out.
+    pub fn new(metric: &str, a: &[f64], b: &[f64], alpha: f64) -> Comparison {
+        let (a, b) = (&finite(a)[..], &finite(b)[..]);
+        let (mean_a, var_a) = sample_mean_var(a);
+        let (mean_b, var_b) = sample_mean_var(b);
+
+        let welch = welch_t_test(a, b);
+        let mw = mann_whitney_test(a, b);
+
+        let verdict = if welch.p_value < alpha && mw.p_value < alpha {
+            if mean_b > mean_a { "higher" } else { "lower" }
+        } else {
+            "inconclusive"
+        };
+
+        Comparison {
+            metric: metric.to_owned(),
+
+            n_a: a.len(),
+            mean_a,
+            stddev_a: var_a.sqrt(),
+
+            n_b: b.len(),
+            mean_b,
+            stddev_b: var_b.sqrt(),
+
+            welch_t: welch.t,
+            welch_dof: welch.dof,
+            welch_p: welch.p_value,
+
+            mann_whitney_u: mw.u,
+            mann_whitney_p: mw.p_value,
+
+            cohens_d: cohens_d(a, b),
+            rank_biserial: 2.0 * mw.u / (a.len() * b.len()) as f64 - 1.0,
+
+            verdict,
+        }
+    }
+}
+
+/// Markdown table of the comparisons, with `a` and `b` labelled as given.
+pub fn verdict_table(comparisons: &[Comparison], label_a: &str, label_b: &str) -> String {
+    let mut md = format!("# {} vs. {}\n\n", label_b, label_a);
+
+    writeln!(md, "| Metric | {} | {} | Welch p | Mann–Whitney p | Cohen's d | Rank-biserial | Verdict |", label_a, label_b).ok();
+    md.push_str("|---|---|---|---|---|---|---|---|\n");
+
+    for c in comparisons {
+        writeln!(
+            md, "| {} | {:.3} ± {:.3} (n={}) | {:.3} ± {:.3} (n={}) | {:.4} | {:.4} | {:.3} | {:.3} | {} |",
+            c.metric, c.mean_a, c.stddev_a, c.n_a, c.mean_b, c.stddev_b, c.n_b,
+            c.welch_p, c.mann_whitney_p, c.cohens_d, c.rank_biserial, c.verdict,
+        ).ok();
+    }
+
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mann_whitney_test, welch_t_test, Comparison};
+
+    // Welch's first example on Wikipedia, which has ties across the groups:
+    const A: [f64; 15] = [27.5, 21.0, 19.0, 23.6, 17.0, 17.9, 16.9, 20.1, 21.9, 22.6, 23.1, 19.6, 19.0, 21.7, 21.4];
+    const B: [f64; 15] = [27.1, 22.0, 20.8, 23.4, 23.4, 23.5, 25.8, 22.0, 24.8, 20.2, 21.9, 22.1, 22.9, 20.5, 24.4];
+
+    #[test]
+    fn test_welch_t_test() {
+        let w = welch_t_test(&A, &B);
+
+        assert!((w.t - 2.455356).abs() < 1e-6);
+        assert!((w.dof - 24.988529).abs() < 1e-6);
+        assert!((w.p_value - 0.021378).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mann_whitney_exact() {
+        let mw = mann_whitney_test(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]);
+
+        assert!(mw.exact);
+        assert_eq!(mw.u, 9.0);
+        assert!((mw.p_value - 0.1).abs() < 1e-12);
+
+        let mw = mann_whitney_test(&[0.3, 1.2, 2.5, 0.9, 1.7], &[2.2, 3.1, 1.4, 2.8, 3.6, 2.0]);
+
+        assert!(mw.exact);
+        assert_eq!(mw.u, 26.0);
+        assert!((mw.p_value - 24.0 / 462.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_mann_whitney_normal() {
+        let mw = mann_whitney_test(&A, &B);
+
+        assert!(!mw.exact);
+        assert_eq!(mw.u, 171.5);
+        assert!((mw.z - 2.427547).abs() < 1e-6);
+        assert!((mw.p_value - 0.015201).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_non_finite_ignored() {
+        let a = [1.0, f64::NAN, 2.0, 3.0, f64::INFINITY];
+        let c = Comparison::new("wealth", &a, &[4.0, 5.0, 6.0], 0.05);
+
+        assert_eq!(c.n_a, 3);
+        assert_eq!(c.mann_whitney_u, 9.0);
+        assert!((c.mann_whitney_p - 0.1).abs() < 1e-12);
+        assert!(c.welch_p.is_finite());
+    }
+}
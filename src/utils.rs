@@ -1,5 +1,8 @@
+#[cfg(feature = "logging")]
 extern crate slog;
 
+use std::cmp::Ordering;
+
 pub fn mean_var(values: &[f64]) -> [f64; 2] {
     let n = values.len() as f64;
 
@@ -12,6 +15,14 @@ pub fn mean_var(values: &[f64]) -> [f64; 2] {
     [mean, var]
 }
 
+/// Order rewards from worst to best, ranking NaN, e.g. from a diverged agent,
+/// as the worst.
+pub fn cmp_rewards(a: f64, b: f64) -> Ordering {
+    let key = |x: f64| if x.is_nan() { ::std::f64::NEG_INFINITY } else { x };
+
+    key(a).total_cmp(&key(b))
+}
+
 pub fn median_quantiles(values: &[f64]) -> [f64; 3] {
     let pivot = values.len() / 4;
 
@@ -29,6 +40,7 @@ impl Estimate {
     }
 }
 
+#[cfg(feature = "logging")]
 impl slog::Value for Estimate {
     fn serialize(&self, _rec: &slog::Record, key: slog::Key, serializer: &mut slog::Serializer) -> slog::Result {
         serializer.emit_arguments(key, &format_args!("{} ± {}", self.0, self.1))
@@ -0,0 +1,104 @@
+//! Browser bindings for an interactive market-making demo.
+//!
+//! The simulation is seeded explicitly since `wasm32` targets have no OS
+//! entropy source to back `FromEntropy`. Quotes are produced either by a
+//! trader supplied as serialised bytes or, failing that, by the
+//! Avellaneda-Stoikov exponential utility strategy.
+use crate::{
+    agents::{check_state_dim, trader_from_bytes, tta, Trader},
+    env::{
+        dynamics::{execution::PoissonRate, price::BrownianMotion, ASDynamics},
+        strategies::ExponentialUtilityStrategy,
+        Env,
+    },
+    error::Error,
+};
+use rand::{rngs::SmallRng, SeedableRng};
+use rsrl::{domains::Domain, geometry::Surjection, policies::Policy};
+use wasm_bindgen::prelude::*;
+
+const DT: f64 = 0.005;
+
+fn build_env(seed: u32) -> Result<Env<BrownianMotion, PoissonRate>, JsValue> {
+    ASDynamics::new(
+        DT, 100.0, SmallRng::seed_from_u64(seed as u64),
+        BrownianMotion::new(DT, 2.0),
+        PoissonRate::default()
+    ).map(Env::new).map_err(to_js)
+}
+
+fn to_js(e: Error) -> JsValue { JsValue::from_str(&e.to_string()) }
+
+#[wasm_bindgen]
+pub struct Simulation {
+    env: Env<BrownianMotion, PoissonRate>,
+    trader: Option<Trader>,
+    baseline: ExponentialUtilityStrategy,
+
+    quotes: [f64; 2],
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u32) -> Result<Simulation, JsValue> {
+        Ok(Simulation {
+            env: build_env(seed)?,
+            trader: None,
+            baseline: ExponentialUtilityStrategy::new(1.5, 0.1, 2.0),
+
+            quotes: [0.0, 0.0],
+        })
+    }
+
+    /// Use a serialised trader (as written by `save_trader`) to set quotes.
+    pub fn load_trader(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        let trader = trader_from_bytes(bytes).map_err(to_js)?;
+
+        check_state_dim(&trader, &self.env.state_space()).map_err(to_js)?;
+
+        self.trader = Some(trader);
+
+        Ok(())
+    }
+
+    /// Start a new episode with the given seed.
+    pub fn reset(&mut self, seed: u32) -> Result<(), JsValue> {
+        self.env = build_env(seed)?;
+
+        Ok(())
+    }
+
+    /// Advance the simulation by one step, returning true if the episode has
+    /// terminated.
+    pub fn step(&mut self) -> bool {
+        let quotes = match self.trader {
+            Some(ref mut trader) => tta(trader.policy.mpa(self.env.state())),
+            None => self.baseline.compute(
+                self.env.dynamics.time,
+                self.env.dynamics.price,
+                self.env.inv,
+            ),
+        };
+
+        self.quotes = self.env.quote_space.map(quotes);
+
+        self.env.step(self.quotes).terminated()
+    }
+
+    pub fn time(&self) -> f64 { self.env.dynamics.time }
+
+    pub fn midprice(&self) -> f64 { self.env.dynamics.price }
+
+    pub fn ask_price(&self) -> f64 { self.env.dynamics.price + self.quotes[0] }
+
+    pub fn bid_price(&self) -> f64 { self.env.dynamics.price - self.quotes[1] }
+
+    pub fn ask_executed(&self) -> bool { self.env.ask_executed }
+
+    pub fn bid_executed(&self) -> bool { self.env.bid_executed }
+
+    pub fn inventory(&self) -> f64 { self.env.inv }
+
+    pub fn wealth(&self) -> f64 { self.env.wealth }
+}